@@ -1,15 +1,22 @@
 use serde::{Deserialize, Serialize};
 use shard::accounts::{Account, Accounts, delete_account_tokens, load_accounts, remove_account, save_accounts, set_active};
 use shard::auth::{DeviceCode, request_device_code};
-use shard::config::{Config, load_config, save_config};
+use shard::config::{Config, TitlebarMode, load_config, save_config};
 use shard::content_store::{ContentStore, ContentType, Platform, SearchOptions, ContentItem, ContentVersion};
-use shard::java::{JavaInstallation, JavaValidation, AdoptiumRelease, detect_installations, validate_java_path, get_required_java_version, is_java_compatible, fetch_adoptium_release, download_and_install_java, find_compatible_java, get_managed_java, list_managed_runtimes};
+use shard::http::{DownloadLimits, ProxyCredentials, ServiceStatus, connectivity_check, is_download_cancelled, is_proxy_auth_required, save_proxy_credentials, set_download_limits, set_mirrors, set_proxy_credentials, set_proxy_settings};
+use shard::java::{JavaGroup, JavaInstallation, JavaValidation, AdoptiumRelease, JavaRelease, JavaVendor, ImageType, detect_installations_async, detect_installations_min_async, detect_installations_with_diagnostics_async, JavaDetection, grouped_installations, validate_java_path, get_required_java_version, is_java_compatible_with_loader, fetch_adoptium_release_cached, fetch_adoptium_version, fetch_release, download_and_install_release_with_progress, DownloadProgress, ProgressCallback2, InstallPhase, PhaseCallback, DEFAULT_DOWNLOAD_CONNECTIONS, find_compatible_java, find_compatible_java_detailed, JavaResolution, get_managed_java, managed_runtime_dir, uninstall_managed_java, repair_managed_java, ProgressCallback, list_managed_runtimes, all_java_installations, explain_java_requirement, JavaRequirementExplanation, JavaRequirement, matched_requirement, benchmark_runtimes, recommend_max_heap_mb, system_memory_mb, McVersion};
 use shard::library::{Library, LibraryItem, LibraryFilter, LibraryItemInput, LibraryContentType, LibraryStats, Tag, ImportResult, UnusedItemsSummary, PurgeResult};
-use shard::logs::{LogEntry, LogFile, LogWatcher, list_log_files, list_crash_reports, read_log_file, read_log_tail};
-use shard::minecraft::{LaunchPlan, prepare};
+use shard::instance::{SafeModeGuard, duplicate_instance};
+use shard::instance_archive::{InstanceImport, InstanceManifest, export_instance, import_instance_archive};
+use shard::logs::{CrashKind, LogEntry, diagnose_exit, LogFile, LogWatcher, list_log_files, list_crash_reports, read_log_file, read_log_tail, suggest_suspect_mods_from_latest_crash};
+use shard::minecraft::{LaunchOptions, LaunchPlan, LaunchPreview, QuickPlay, StartupEvent, VerifySummary, apply_aikar_flags, detect_java_env_overrides, parse_memory_mb, prepare, prepare_with_options, run_with_watchdog, verify_instance_integrity};
+use shard::mod_scan::{ModConflictKind, ModScan, keep_newest, scan_mod_conflicts};
+use shard::curseforge::CurseForgeClient;
+use shard::modpack::{CurseForgeImport, import_curseforge_zip};
+use shard::modrinth::{InstallPlan, ModrinthClient};
 use shard::ops::{finish_device_code_flow, parse_loader, resolve_input, resolve_launch_account, ensure_fresh_account};
 use shard::paths::Paths;
-use shard::profile::{ContentRef, Loader, Profile, Runtime, clone_profile, create_profile, delete_profile, diff_profiles, list_profiles, load_profile, remove_mod, remove_resourcepack, remove_shaderpack, rename_profile, save_profile, upsert_mod, upsert_resourcepack, upsert_shaderpack};
+use shard::profile::{ContentRef, GcPreset, Loader, Profile, Runtime, clone_profile, create_profile, delete_profile, diff_profiles, list_profiles, load_profile, remove_mod, remove_resourcepack, remove_shaderpack, rename_profile, save_profile, validate_jvm_args, upsert_mod, upsert_resourcepack, upsert_shaderpack};
 use shard::skin::{
     MinecraftProfile,
     get_profile as get_mc_profile,
@@ -30,10 +37,12 @@ use shard::skin::{
 };
 use shard::store::{ContentKind, store_content};
 use shard::template::{Template, list_templates, load_template, init_builtin_templates};
-use shard::updates::{StorageStats, UpdateCheckResult, get_storage_stats, check_all_updates, check_profile_updates, set_content_pinned, set_content_enabled, apply_update};
+use shard::updates::{ModUpdate, StorageStats, UpdateCheckResult, get_storage_stats, check_all_updates, check_mod_updates, check_profile_updates, set_content_pinned, set_content_enabled, apply_update};
+use std::collections::HashMap;
 use std::path::PathBuf;
-use std::process::Command;
-use tauri::{AppHandle, Emitter};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::{AppHandle, Emitter, Manager};
 
 #[derive(Serialize)]
 pub struct DiffResult {
@@ -45,17 +54,22 @@ pub struct DiffResult {
 #[derive(Serialize)]
 pub struct LaunchPlanDto {
     pub instance_dir: String,
+    pub wrapper: Vec<String>,
     pub java_exec: String,
     pub jvm_args: Vec<String>,
     pub classpath: String,
     pub main_class: String,
     pub game_args: Vec<String>,
+    pub verification: VerifySummary,
+    pub warnings: Vec<String>,
 }
 
 #[derive(Clone, Serialize)]
 pub struct LaunchEvent {
     pub stage: String,
     pub message: Option<String>,
+    /// Profile being launched, e.g. to stop the right game.
+    pub profile_id: String,
 }
 
 #[derive(Deserialize)]
@@ -98,6 +112,8 @@ pub struct StoreInstallInput {
     pub platform: String,
     pub version_id: Option<String>,
     pub content_type: Option<String>,
+    /// Also install the required dependencies of a Modrinth mod.
+    pub install_dependencies: Option<bool>,
 }
 
 fn load_paths() -> Result<Paths, String> {
@@ -160,18 +176,182 @@ pub fn create_profile_cmd(input: CreateProfileInput) -> Result<Profile, String>
         java: input.java.filter(|v| !v.trim().is_empty()),
         memory: input.memory.filter(|v| !v.trim().is_empty()),
         args,
+        wrapper_command: Vec::new(),
+        pre_launch: None,
+        post_exit: None,
+        env: HashMap::new(),
+        width: None,
+        height: None,
+        fullscreen: false,
+        game_dir: None,
+        gc_preset: None,
     };
 
     create_profile(&paths, &input.id, &input.mc_version, loader, runtime)
         .map_err(|e| e.to_string())
 }
 
+/// Replace a profile's extra JVM arguments. Classpath/main-class options are rejected.
+#[tauri::command]
+pub fn set_profile_jvm_args_cmd(profile_id: String, args: Vec<String>) -> Result<Profile, String> {
+    let paths = load_paths()?;
+    validate_jvm_args(&args).map_err(|e| e.to_string())?;
+    let mut profile = load_profile(&paths, &profile_id).map_err(|e| e.to_string())?;
+    profile.runtime.args = args;
+    save_profile(&paths, &profile).map_err(|e| e.to_string())?;
+    Ok(profile)
+}
+
+/// Set the command the profile's JVM runs under (e.g. "gamemoderun"); empty clears it.
+#[tauri::command]
+pub fn set_profile_wrapper_cmd(profile_id: String, wrapper: String) -> Result<Profile, String> {
+    let paths = load_paths()?;
+    let mut profile = load_profile(&paths, &profile_id).map_err(|e| e.to_string())?;
+    profile.runtime.wrapper_command = wrapper.split_whitespace().map(String::from).collect();
+    save_profile(&paths, &profile).map_err(|e| e.to_string())?;
+    Ok(profile)
+}
+
+/// Set the shell commands run before launch and after exit; blank clears a hook.
+#[tauri::command]
+pub fn set_profile_hooks_cmd(
+    profile_id: String,
+    pre_launch: Option<String>,
+    post_exit: Option<String>,
+) -> Result<Profile, String> {
+    let paths = load_paths()?;
+    let mut profile = load_profile(&paths, &profile_id).map_err(|e| e.to_string())?;
+    let clean = |hook: Option<String>| hook.map(|h| h.trim().to_string()).filter(|h| !h.is_empty());
+    profile.runtime.pre_launch = clean(pre_launch);
+    profile.runtime.post_exit = clean(post_exit);
+    save_profile(&paths, &profile).map_err(|e| e.to_string())?;
+    Ok(profile)
+}
+
+/// Set the game window size and fullscreen mode; `None` leaves a dimension
+/// to the game's default.
+#[tauri::command]
+pub fn set_profile_window_cmd(
+    profile_id: String,
+    width: Option<u32>,
+    height: Option<u32>,
+    fullscreen: bool,
+) -> Result<Profile, String> {
+    if width == Some(0) || height == Some(0) {
+        return Err("window size must be positive".to_string());
+    }
+    let paths = load_paths()?;
+    let mut profile = load_profile(&paths, &profile_id).map_err(|e| e.to_string())?;
+    profile.runtime.width = width;
+    profile.runtime.height = height;
+    profile.runtime.fullscreen = fullscreen;
+    save_profile(&paths, &profile).map_err(|e| e.to_string())?;
+    Ok(profile)
+}
+
+/// Point the profile's game directory elsewhere (e.g. a folder shared with
+/// other instances); `None` or an empty path uses the instance folder again.
+#[tauri::command]
+pub fn set_profile_game_dir_cmd(profile_id: String, game_dir: Option<String>) -> Result<Profile, String> {
+    let paths = load_paths()?;
+    let mut profile = load_profile(&paths, &profile_id).map_err(|e| e.to_string())?;
+    profile.runtime.game_dir = game_dir.map(|dir| dir.trim().to_string()).filter(|dir| !dir.is_empty());
+    save_profile(&paths, &profile).map_err(|e| e.to_string())?;
+    Ok(profile)
+}
+
+/// Choose the garbage collector preset appended at launch; `None` keeps the
+/// JVM defaults.
+#[tauri::command]
+pub fn set_profile_gc_preset_cmd(profile_id: String, preset: Option<GcPreset>) -> Result<Profile, String> {
+    let paths = load_paths()?;
+    let mut profile = load_profile(&paths, &profile_id).map_err(|e| e.to_string())?;
+    profile.runtime.gc_preset = preset;
+    save_profile(&paths, &profile).map_err(|e| e.to_string())?;
+    Ok(profile)
+}
+
+/// Replace the profile's game environment variables. An empty value unsets
+/// the variable for the game instead of inheriting it.
+#[tauri::command]
+pub fn set_profile_env_cmd(profile_id: String, env: HashMap<String, String>) -> Result<Profile, String> {
+    let paths = load_paths()?;
+    let mut profile = load_profile(&paths, &profile_id).map_err(|e| e.to_string())?;
+    if let Some(name) = env.keys().find(|name| name.is_empty() || name.contains('=')) {
+        return Err(format!("invalid environment variable name: {name:?}"));
+    }
+    profile.runtime.env = env;
+    save_profile(&paths, &profile).map_err(|e| e.to_string())?;
+    Ok(profile)
+}
+
+/// Apply Aikar's GC flags sized for the profile's memory setting (or the
+/// recommended heap when none is set).
+#[tauri::command]
+pub fn apply_aikar_flags_cmd(profile_id: String) -> Result<Profile, String> {
+    let paths = load_paths()?;
+    let mut profile = load_profile(&paths, &profile_id).map_err(|e| e.to_string())?;
+    let heap_mb = profile
+        .runtime
+        .memory
+        .as_deref()
+        .and_then(parse_memory_mb)
+        .unwrap_or_else(|| recommend_max_heap_mb(&profile.mc_version, profile.mods.len()));
+    profile.runtime.args = apply_aikar_flags(&profile.runtime.args, heap_mb);
+    save_profile(&paths, &profile).map_err(|e| e.to_string())?;
+    Ok(profile)
+}
+
 #[tauri::command]
 pub fn clone_profile_cmd(src: String, dst: String) -> Result<Profile, String> {
     let paths = load_paths()?;
     clone_profile(&paths, &src, &dst).map_err(|e| e.to_string())
 }
 
+/// Copy a profile and its instance files; the id gets " (copy)" appended when taken.
+#[tauri::command]
+pub fn duplicate_instance_cmd(src: String, new_name: Option<String>, include_saves: bool) -> Result<Profile, String> {
+    let paths = load_paths()?;
+    duplicate_instance(&paths, &src, new_name.as_deref(), include_saves).map_err(|e| e.to_string())
+}
+
+/// Re-check a profile's libraries, assets and client jars against their sha1,
+/// re-downloading corrupt or missing files ("repair installation").
+#[tauri::command]
+pub fn verify_instance_integrity_cmd(profile_id: String) -> Result<VerifySummary, String> {
+    let paths = load_paths()?;
+    let profile = load_profile(&paths, &profile_id).map_err(|e| e.to_string())?;
+    verify_instance_integrity(&paths, &profile).map_err(|e| e.to_string())
+}
+
+/// Export a profile as a portable zip. The returned manifest lists the
+/// downloads referenced and the files bundled.
+#[tauri::command]
+pub fn export_instance_cmd(profile_id: String, out_path: String, include_worlds: bool) -> Result<InstanceManifest, String> {
+    let paths = load_paths()?;
+    let profile = load_profile(&paths, &profile_id).map_err(|e| e.to_string())?;
+    export_instance(&paths, &profile, &PathBuf::from(out_path), include_worlds).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn import_instance_archive_cmd(path: String, profile_id: Option<String>) -> Result<InstanceImport, String> {
+    let paths = load_paths()?;
+    import_instance_archive(&paths, &PathBuf::from(path), profile_id.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Import a CurseForge modpack zip. Files the author only allows on
+/// curseforge.com come back in `blocked`, for the user to download by hand.
+#[tauri::command]
+pub fn import_curseforge_zip_cmd(path: String, profile_id: Option<String>) -> Result<CurseForgeImport, String> {
+    let paths = load_paths()?;
+    let config = load_config(&paths).map_err(|e| e.to_string())?;
+    let api_key = config
+        .curseforge_api_key
+        .ok_or("CurseForge modpacks need a CurseForge API key")?;
+    let client = CurseForgeClient::new(&api_key).map_err(|e| e.to_string())?;
+    import_curseforge_zip(&paths, &client, &PathBuf::from(path), profile_id.as_deref()).map_err(|e| format!("{e:#}"))
+}
+
 #[tauri::command]
 pub fn delete_profile_cmd(id: String) -> Result<(), String> {
     let paths = load_paths()?;
@@ -329,6 +509,29 @@ pub fn remove_shaderpack_cmd(profile_id: String, target: String) -> Result<bool,
     remove_content(&profile_id, &target, ContentKind::ShaderPack)
 }
 
+#[tauri::command]
+pub fn scan_mod_conflicts_cmd(profile_id: String) -> Result<ModScan, String> {
+    let paths = load_paths()?;
+    let profile = load_profile(&paths, &profile_id).map_err(|e| e.to_string())?;
+    Ok(scan_mod_conflicts(&paths, &profile))
+}
+
+/// Keep the newest copy of a duplicated mod and remove the others.
+#[tauri::command]
+pub fn keep_newest_mod_cmd(profile_id: String, mod_id: String) -> Result<Profile, String> {
+    let paths = load_paths()?;
+    let mut profile = load_profile(&paths, &profile_id).map_err(|e| e.to_string())?;
+    let scan = scan_mod_conflicts(&paths, &profile);
+    let conflict = scan
+        .conflicts
+        .iter()
+        .find(|c| c.kind == ModConflictKind::Duplicate && c.jars.iter().any(|j| j.mod_id == mod_id))
+        .ok_or_else(|| format!("{mod_id} is not installed more than once"))?;
+    keep_newest(&mut profile, conflict).map_err(|e| e.to_string())?;
+    save_profile(&paths, &profile).map_err(|e| e.to_string())?;
+    Ok(profile)
+}
+
 #[tauri::command]
 pub fn list_accounts_cmd() -> Result<Accounts, String> {
     let paths = load_paths()?;
@@ -380,6 +583,25 @@ pub fn save_config_cmd(client_id: Option<String>, client_secret: Option<String>)
     Ok(config)
 }
 
+/// Supply credentials for an authenticating proxy. Kept in memory unless `remember` is set,
+/// in which case they are also stored in the system keychain.
+#[tauri::command]
+pub fn set_proxy_credentials_cmd(username: String, password: String, remember: bool) -> Result<(), String> {
+    let credentials = ProxyCredentials { username, password };
+    if remember {
+        save_proxy_credentials(Some(&credentials)).map_err(|e| e.to_string())?;
+    }
+    set_proxy_credentials(Some(credentials));
+    Ok(())
+}
+
+/// Forget proxy credentials, both in memory and in the keychain.
+#[tauri::command]
+pub fn clear_proxy_credentials_cmd() -> Result<(), String> {
+    set_proxy_credentials(None);
+    save_proxy_credentials(None).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn request_device_code_cmd(client_id: Option<String>, client_secret: Option<String>) -> Result<DeviceCode, String> {
     let paths = load_paths()?;
@@ -407,24 +629,76 @@ pub fn prepare_profile_cmd(profile_id: String, account_id: Option<String>) -> Re
     Ok(LaunchPlanDto::from(plan))
 }
 
+/// Prepare a launch and return the exact command it would run, without
+/// starting the game. The access token is redacted.
 #[tauri::command]
-pub fn launch_profile_cmd(app: AppHandle, profile_id: String, account_id: Option<String>) -> Result<(), String> {
+pub fn preview_launch_cmd(
+    profile_id: String,
+    account_id: Option<String>,
+    clear_java_env: Option<bool>,
+    show_console: Option<bool>,
+) -> Result<LaunchPreview, String> {
+    let paths = load_paths()?;
+    let profile = load_profile(&paths, &profile_id).map_err(|e| e.to_string())?;
+    let account = resolve_launch_account(&paths, account_id).map_err(|e| e.to_string())?;
+    let options = LaunchOptions {
+        clear_java_env: clear_java_env.unwrap_or(false),
+        show_console: show_console.unwrap_or(false),
+        ..LaunchOptions::default()
+    };
+    let plan = prepare_with_options(&paths, &profile, &account, &options).map_err(|e| e.to_string())?;
+    Ok(plan.preview(&options))
+}
+
+#[tauri::command]
+pub fn launch_profile_cmd(
+    app: AppHandle,
+    profile_id: String,
+    account_id: Option<String>,
+    full_verify: Option<bool>,
+    clear_java_env: Option<bool>,
+    safe_mode: Option<bool>,
+    copy_instead_of_link: Option<bool>,
+    quick_play: Option<QuickPlay>,
+    show_console: Option<bool>,
+) -> Result<(), String> {
     let app_handle = app.clone();
+    let screen_size = app
+        .get_webview_window("main")
+        .and_then(|window| window.primary_monitor().ok().flatten())
+        .map(|monitor| (monitor.size().width, monitor.size().height));
 
     // Emit initial status immediately before spawning thread
     let _ = app.emit("launch-status", LaunchEvent {
         stage: "queued".to_string(),
         message: Some("Starting launch...".to_string()),
+        profile_id: profile_id.clone(),
     });
 
     // Use spawn_blocking for blocking I/O operations (HTTP requests, file I/O)
     tauri::async_runtime::spawn_blocking(move || {
-        match run_launch(app_handle.clone(), profile_id.clone(), account_id) {
+        let config = load_paths()
+            .and_then(|paths| load_config(&paths).map_err(|e| e.to_string()))
+            .ok();
+        let options = LaunchOptions {
+            full_verify: full_verify.unwrap_or(false),
+            fast_verify: false,
+            clear_java_env: clear_java_env.unwrap_or(false),
+            safe_mode: safe_mode.unwrap_or(false),
+            copy_instead_of_link: copy_instead_of_link.unwrap_or(false),
+            show_console: show_console
+                .unwrap_or_else(|| config.as_ref().is_some_and(|config| config.show_game_console)),
+            quick_play,
+            screen_size,
+            startup_timeout: config.as_ref().and_then(|config| config.startup_timeout()),
+        };
+        match run_launch(app_handle.clone(), profile_id.clone(), account_id, options) {
             Ok(()) => {}
             Err(err) => {
                 let _ = app_handle.emit("launch-status", LaunchEvent {
                     stage: "error".to_string(),
                     message: Some(err),
+                    profile_id: profile_id.clone(),
                 });
             }
         }
@@ -432,52 +706,186 @@ pub fn launch_profile_cmd(app: AppHandle, profile_id: String, account_id: Option
     Ok(())
 }
 
+/// Kill flags for games started from the app, keyed by profile id.
+static RUNNING_GAMES: Mutex<Vec<(String, Arc<AtomicBool>)>> = Mutex::new(Vec::new());
+
+/// Stop a game started from the app, e.g. after it was reported as not
+/// responding. Without a profile id every running game is stopped.
+/// Returns false when no matching game is running.
+#[tauri::command]
+pub fn kill_game_cmd(profile_id: Option<String>) -> bool {
+    let Ok(games) = RUNNING_GAMES.lock() else {
+        return false;
+    };
+    let mut found = false;
+    for (_, flag) in games.iter().filter(|(id, _)| profile_id.as_ref().is_none_or(|wanted| wanted == id)) {
+        flag.store(true, Ordering::Relaxed);
+        found = true;
+    }
+    found
+}
+
+/// JVM option variables (e.g. `_JAVA_OPTIONS`) set in the launcher's environment.
+#[tauri::command]
+pub fn detect_java_env_overrides_cmd() -> Vec<(String, String)> {
+    detect_java_env_overrides()
+}
+
 #[tauri::command]
 pub fn instance_path_cmd(profile_id: String) -> Result<String, String> {
     let paths = load_paths()?;
-    Ok(paths.instance_dir(&profile_id).to_string_lossy().to_string())
+    Ok(paths.instance_game_dir(&profile_id).to_string_lossy().to_string())
 }
 
-fn run_launch(app: AppHandle, profile_id: String, account_id: Option<String>) -> Result<(), String> {
+fn run_launch(app: AppHandle, profile_id: String, account_id: Option<String>, options: LaunchOptions) -> Result<(), String> {
     let _ = app.emit("launch-status", LaunchEvent {
         stage: "preparing".to_string(),
         message: Some("Downloading game files...".to_string()),
+        profile_id: profile_id.clone(),
     });
 
     let paths = load_paths()?;
     let profile = load_profile(&paths, &profile_id).map_err(|e| format!("Failed to load profile: {}", e))?;
     let account = resolve_launch_account(&paths, account_id).map_err(|e| format!("Failed to resolve account: {}", e))?;
-    let plan = prepare(&paths, &profile, &account).map_err(|e| format!("Failed to prepare launch: {}", e))?;
+    let plan = prepare_with_options(&paths, &profile, &account, &options).map_err(|e| {
+        // Let the UI prompt for proxy credentials and retry the launch
+        if is_proxy_auth_required(&e) {
+            let _ = app.emit("launch-status", LaunchEvent {
+                stage: "proxy-auth-required".to_string(),
+                message: Some(e.to_string()),
+                profile_id: profile_id.clone(),
+            });
+        }
+        format!("Failed to prepare launch: {}", e)
+    })?;
+
+    if options.full_verify {
+        let _ = app.emit("launch-status", LaunchEvent {
+            stage: "verified".to_string(),
+            message: Some(format!(
+                "Verified {} files ({} repaired)",
+                plan.verification.checked, plan.verification.repaired
+            )),
+            profile_id: profile_id.clone(),
+        });
+    }
+
+    for warning in &plan.warnings {
+        let _ = app.emit("launch-status", LaunchEvent {
+            stage: "launch-warning".to_string(),
+            message: Some(warning.clone()),
+            profile_id: profile_id.clone(),
+        });
+    }
 
     let _ = app.emit("launch-status", LaunchEvent {
         stage: "launching".to_string(),
         message: Some("Starting Minecraft...".to_string()),
+        profile_id: profile_id.clone(),
     });
 
-    let mut child = Command::new(&plan.java_exec)
-        .args(&plan.jvm_args)
-        .arg("-cp")
-        .arg(&plan.classpath)
-        .arg(&plan.main_class)
-        .args(&plan.game_args)
-        .current_dir(&plan.instance_dir)
-        .spawn()
-        .map_err(|e| format!("Failed to start Java: {}", e))?;
+    if !options.clear_java_env {
+        for (name, value) in detect_java_env_overrides() {
+            let _ = app.emit("launch-status", LaunchEvent {
+                stage: "java-env-warning".to_string(),
+                message: Some(format!("{}={} is set and will affect the game's JVM options", name, value)),
+                profile_id: profile_id.clone(),
+            });
+        }
+    }
+
+    let safe_mode = if options.safe_mode {
+        Some(SafeModeGuard::engage(&plan.instance_dir).map_err(|e| format!("Failed to enter safe mode: {}", e))?)
+    } else {
+        None
+    };
 
     let _ = app.emit("launch-status", LaunchEvent {
         stage: "running".to_string(),
         message: Some("Minecraft is running".to_string()),
+        profile_id: profile_id.clone(),
     });
 
-    let status = child.wait().map_err(|e| format!("Failed to wait for process: {}", e))?;
+    let kill = Arc::new(AtomicBool::new(false));
+    if let Ok(mut games) = RUNNING_GAMES.lock() {
+        games.push((profile_id.clone(), kill.clone()));
+    }
+
+    // Forward game output to the UI's live log console, tagged with the profile
+    let output_app = app.clone();
+    let startup_app = app.clone();
+    let mut reported_hang = false;
+    let run = run_with_watchdog(
+        &plan,
+        &options,
+        Some(&kill),
+        |line| {
+            let mut payload = serde_json::to_value(line).unwrap_or_default();
+            payload["profile_id"] = serde_json::json!(profile_id);
+            let _ = output_app.emit("game-output", payload);
+        },
+        |event| match event {
+            StartupEvent::NotResponding { waited_secs } => {
+                reported_hang = true;
+                let _ = startup_app.emit("launch-status", LaunchEvent {
+                    stage: "not-responding".to_string(),
+                    message: Some(format!("No game window after {} seconds", waited_secs)),
+                    profile_id: profile_id.clone(),
+                });
+            }
+            // Only worth telling the UI when it was told about a hang
+            StartupEvent::Started if reported_hang => {
+                let _ = startup_app.emit("launch-status", LaunchEvent {
+                    stage: "running".to_string(),
+                    message: Some("Minecraft is running".to_string()),
+                    profile_id: profile_id.clone(),
+                });
+            }
+            StartupEvent::Started => {}
+        },
+    );
+    if let Ok(mut games) = RUNNING_GAMES.lock() {
+        games.retain(|(_, flag)| !Arc::ptr_eq(flag, &kill));
+    }
+    let run = run.map_err(|e| format!("Failed to run Minecraft: {}", e))?;
+    let status = run.status;
+
+    if let Some(guard) = safe_mode {
+        guard.restore().map_err(|e| format!("Failed to restore mods after safe mode: {}", e))?;
+    }
+
+    if run.killed {
+        let _ = app.emit("launch-status", LaunchEvent {
+            stage: "done".to_string(),
+            message: Some("Minecraft was stopped".to_string()),
+            profile_id: profile_id.clone(),
+        });
+        return Ok(());
+    }
 
     if !status.success() {
-        return Err(format!("Minecraft exited with status {}", status));
+        let lines: Vec<String> = run.output_tail.into_iter().map(|line| line.line).collect();
+        let diagnosis = diagnose_exit(&status, &lines, &profile.mc_version);
+        if diagnosis.kind == CrashKind::Unknown && !options.safe_mode && !profile.mods.is_empty() {
+            let suspects = suggest_suspect_mods_from_latest_crash(&paths, &profile).unwrap_or_default();
+            if !suspects.is_empty() {
+                return Err(format!(
+                    "Minecraft exited with status {} (crash report mentions: {})",
+                    status,
+                    suspects.join(", ")
+                ));
+            }
+        }
+        return Err(format!(
+            "Minecraft exited with status {}. {} {}",
+            status, diagnosis.explanation, diagnosis.suggestion
+        ));
     }
 
     let _ = app.emit("launch-status", LaunchEvent {
         stage: "done".to_string(),
         message: None,
+        profile_id: profile_id.clone(),
     });
 
     Ok(())
@@ -485,13 +893,17 @@ fn run_launch(app: AppHandle, profile_id: String, account_id: Option<String>) ->
 
 impl From<LaunchPlan> for LaunchPlanDto {
     fn from(plan: LaunchPlan) -> Self {
+        let game_args = plan.game_args.iter().map(|arg| plan.redact(arg)).collect();
         Self {
             instance_dir: plan.instance_dir.to_string_lossy().to_string(),
+            wrapper: plan.wrapper,
             java_exec: plan.java_exec,
             jvm_args: plan.jvm_args,
             classpath: plan.classpath,
             main_class: plan.main_class,
-            game_args: plan.game_args,
+            game_args,
+            verification: plan.verification,
+            warnings: plan.warnings,
         }
     }
 }
@@ -716,13 +1128,22 @@ pub fn create_profile_from_template_cmd(input: CreateProfileInput) -> Result<Pro
             } else {
                 template.runtime.args
             },
+            wrapper_command: Vec::new(),
+            pre_launch: None,
+            post_exit: None,
+            env: HashMap::new(),
+            width: None,
+            height: None,
+            fullscreen: false,
+            game_dir: None,
+            gc_preset: None,
         };
 
         let mut profile = create_profile(&paths, &input.id, &template.mc_version, loader.clone(), runtime)
             .map_err(|e| e.to_string())?;
 
         // Download content from template (mods, shaderpacks, resourcepacks)
-        let store = ContentStore::modrinth_only();
+        let store = ContentStore::modrinth_only().map_err(|e| e.to_string())?;
         let loader_type = loader.as_ref().map(|l| l.loader_type.as_str());
 
         for mod_content in &template.mods {
@@ -790,6 +1211,15 @@ pub fn create_profile_from_template_cmd(input: CreateProfileInput) -> Result<Pro
             java: input.java.filter(|v| !v.trim().is_empty()),
             memory: input.memory.filter(|v| !v.trim().is_empty()),
             args,
+            wrapper_command: Vec::new(),
+            pre_launch: None,
+            post_exit: None,
+            env: HashMap::new(),
+            width: None,
+            height: None,
+            fullscreen: false,
+            game_dir: None,
+            gc_preset: None,
         };
 
         create_profile(&paths, &input.id, &input.mc_version, loader, runtime)
@@ -822,7 +1252,7 @@ pub fn store_search_cmd(input: StoreSearchInput) -> Result<Vec<ContentItem>, Str
     let paths = load_paths()?;
     let config = load_config(&paths).map_err(|e| e.to_string())?;
     let has_cf_key = config.curseforge_api_key.is_some();
-    let store = ContentStore::new(config.curseforge_api_key.as_deref());
+    let store = ContentStore::new(config.curseforge_api_key.as_deref()).map_err(|e| e.to_string())?;
 
     let content_type = input.content_type.as_ref()
         .map(|s| parse_content_type(s))
@@ -853,7 +1283,7 @@ pub fn store_search_cmd(input: StoreSearchInput) -> Result<Vec<ContentItem>, Str
 pub fn store_get_project_cmd(project_id: String, platform: String) -> Result<ContentItem, String> {
     let paths = load_paths()?;
     let config = load_config(&paths).map_err(|e| e.to_string())?;
-    let store = ContentStore::new(config.curseforge_api_key.as_deref());
+    let store = ContentStore::new(config.curseforge_api_key.as_deref()).map_err(|e| e.to_string())?;
     let platform = parse_platform(&platform)?;
     store.get_project(platform, &project_id).map_err(|e| e.to_string())
 }
@@ -868,7 +1298,7 @@ pub fn store_get_versions_cmd(
 ) -> Result<Vec<ContentVersion>, String> {
     let paths = load_paths()?;
     let config = load_config(&paths).map_err(|e| e.to_string())?;
-    let store = ContentStore::new(config.curseforge_api_key.as_deref());
+    let store = ContentStore::new(config.curseforge_api_key.as_deref()).map_err(|e| e.to_string())?;
     let platform = parse_platform(&platform)?;
 
     // Fetch project to determine content type
@@ -900,7 +1330,7 @@ pub fn store_get_versions_cmd(
 pub fn store_install_cmd(input: StoreInstallInput) -> Result<Profile, String> {
     let paths = load_paths()?;
     let config = load_config(&paths).map_err(|e| e.to_string())?;
-    let store = ContentStore::new(config.curseforge_api_key.as_deref());
+    let store = ContentStore::new(config.curseforge_api_key.as_deref()).map_err(|e| e.to_string())?;
 
     let mut profile = load_profile(&paths, &input.profile_id).map_err(|e| e.to_string())?;
     let platform = parse_platform(&input.platform)?;
@@ -933,6 +1363,20 @@ pub fn store_install_cmd(input: StoreInstallInput) -> Result<Profile, String> {
             .map_err(|e| e.to_string())?
     };
 
+    let with_dependencies = input.install_dependencies.unwrap_or(false)
+        && platform == Platform::Modrinth
+        && ct == ContentType::Mod;
+    let plan = if with_dependencies {
+        let plan = plan_modrinth_install(&profile, &input.project_id, Some(&version.id))?;
+        if !plan.conflicts.is_empty() {
+            let messages: Vec<_> = plan.conflicts.iter().map(|c| c.message.as_str()).collect();
+            return Err(format!("dependency conflict: {}", messages.join("; ")));
+        }
+        Some(plan)
+    } else {
+        None
+    };
+
     // Download and store
     let mut content_ref = store.download_to_store(&paths, &version, ct).map_err(|e| e.to_string())?;
 
@@ -971,10 +1415,47 @@ pub fn store_install_cmd(input: StoreInstallInput) -> Result<Profile, String> {
         ContentType::ShaderPack => upsert_shaderpack(&mut profile, content_ref),
     };
 
+    for dep in plan.iter().flat_map(|plan| plan.dependencies.iter()) {
+        let dep_version = store
+            .get_versions(Platform::Modrinth, &dep.project_id, None, None)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|v| v.id == dep.version_id)
+            .ok_or_else(|| format!("version {} of {} not found", dep.version_number, dep.title))?;
+        let mut dep_ref = store.download_to_store(&paths, &dep_version, ContentType::Mod).map_err(|e| e.to_string())?;
+        dep_ref.platform = Some(input.platform.clone());
+        dep_ref.project_id = Some(dep.project_id.clone());
+        dep_ref.version_id = Some(dep.version_id.clone());
+        upsert_mod(&mut profile, dep_ref);
+    }
+
     save_profile(&paths, &profile).map_err(|e| e.to_string())?;
     Ok(profile)
 }
 
+/// Resolve the Modrinth dependency graph for a mod, leaving out projects the profile already has.
+fn plan_modrinth_install(profile: &Profile, project_id: &str, version_id: Option<&str>) -> Result<InstallPlan, String> {
+    let client = ModrinthClient::new().map_err(|e| e.to_string())?;
+    let loader = profile.loader.as_ref().map(|l| l.loader_type.as_str());
+    let mut plan = match version_id {
+        Some(id) => client
+            .get_version(id)
+            .and_then(|root| client.resolve_version_dependencies(root, &profile.mc_version, loader)),
+        None => client.resolve_dependencies(project_id, &profile.mc_version, loader),
+    }
+    .map_err(|e| e.to_string())?;
+    plan.skip_installed(profile.mods.iter().filter_map(|m| m.project_id.as_deref()));
+    Ok(plan)
+}
+
+/// Preview what installing a Modrinth mod would also pull in.
+#[tauri::command]
+pub fn store_plan_install_cmd(profile_id: String, project_id: String, version_id: Option<String>) -> Result<InstallPlan, String> {
+    let paths = load_paths()?;
+    let profile = load_profile(&paths, &profile_id).map_err(|e| e.to_string())?;
+    plan_modrinth_install(&profile, &project_id, version_id.as_deref())
+}
+
 // ==================== Logs Commands ====================
 
 #[tauri::command]
@@ -1108,7 +1589,7 @@ pub struct MinecraftVersionsResponse {
 
 #[tauri::command]
 pub fn fetch_minecraft_versions_cmd() -> Result<MinecraftVersionsResponse, String> {
-    let client = reqwest::blocking::Client::new();
+    let client = shard::http::client().map_err(|e| e.to_string())?;
     let resp = client
         .get("https://piston-meta.mojang.com/mc/game/version_manifest_v2.json")
         .send()
@@ -1129,6 +1610,14 @@ pub fn fetch_minecraft_versions_cmd() -> Result<MinecraftVersionsResponse, Strin
     })
 }
 
+/// Sort Minecraft versions newest first, with the ordering the launcher uses
+/// for Java requirements (snapshots and pre-releases just below their release).
+#[tauri::command]
+pub fn sort_mc_versions_cmd(mut versions: Vec<String>) -> Vec<String> {
+    versions.sort_by_cached_key(|version| std::cmp::Reverse(McVersion::parse(version)));
+    versions
+}
+
 /// Fabric loader version entry from the Fabric Meta API
 #[derive(Clone, Deserialize)]
 struct FabricLoaderEntry {
@@ -1137,7 +1626,7 @@ struct FabricLoaderEntry {
 
 #[tauri::command]
 pub fn fetch_fabric_versions_cmd() -> Result<Vec<String>, String> {
-    let client = reqwest::blocking::Client::new();
+    let client = shard::http::client().map_err(|e| e.to_string())?;
     let resp = client
         .get("https://meta.fabricmc.net/v2/versions/loader")
         .send()
@@ -1163,7 +1652,7 @@ struct QuiltLoaderEntry {
 
 #[tauri::command]
 pub fn fetch_quilt_versions_cmd() -> Result<Vec<String>, String> {
-    let client = reqwest::blocking::Client::new();
+    let client = shard::http::client().map_err(|e| e.to_string())?;
     let resp = client
         .get("https://meta.quiltmc.org/v3/versions/loader")
         .send()
@@ -1205,7 +1694,7 @@ fn extract_neoforge_version_filter(mc_version: &str) -> String {
 
 #[tauri::command]
 pub fn fetch_neoforge_versions_cmd(mc_version: Option<String>) -> Result<Vec<String>, String> {
-    let client = reqwest::blocking::Client::new();
+    let client = shard::http::client().map_err(|e| e.to_string())?;
 
     // NeoForge API returns versions for a specific MC version
     // NeoForge versions omit the leading "1." from MC versions (e.g., 1.20.1 -> 20.1)
@@ -1243,7 +1732,7 @@ struct ForgePromotionsResponse {
 
 #[tauri::command]
 pub fn fetch_forge_versions_cmd(mc_version: Option<String>) -> Result<Vec<String>, String> {
-    let client = reqwest::blocking::Client::new();
+    let client = shard::http::client().map_err(|e| e.to_string())?;
 
     // Forge uses a promotions endpoint that lists recommended/latest versions
     let resp = client
@@ -1332,13 +1821,39 @@ pub fn fetch_loader_versions_cmd(loader_type: String, mc_version: Option<String>
 // Java detection and validation commands
 // ============================================================================
 
-/// Detect all Java installations on the system.
+/// Detect Java installations on the system without blocking the UI thread;
+/// with `min_major`, only working installs of at least that version.
+#[tauri::command]
+pub async fn detect_java_installations_cmd(min_major: Option<u32>) -> Vec<JavaInstallation> {
+    match min_major {
+        Some(min_major) => detect_installations_min_async(min_major).await,
+        None => detect_installations_async().await,
+    }
+}
+
+/// Detect Java installations along with problems such as a broken `JAVA_HOME`.
+#[tauri::command]
+pub async fn detect_java_with_diagnostics_cmd() -> JavaDetection {
+    detect_installations_with_diagnostics_async().await
+}
+
+/// Probe the services a launch depends on, for the diagnostics panel.
+#[tauri::command]
+pub async fn connectivity_check_cmd() -> Result<Vec<ServiceStatus>, String> {
+    tauri::async_runtime::spawn_blocking(connectivity_check)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Detect Java installations grouped by major version for the Java dropdown.
 #[tauri::command]
-pub fn detect_java_installations_cmd() -> Vec<JavaInstallation> {
-    detect_installations()
+pub async fn grouped_java_installations_cmd() -> Result<Vec<JavaGroup>, String> {
+    tauri::async_runtime::spawn_blocking(grouped_installations)
+        .await
+        .map_err(|e| e.to_string())
 }
 
-/// Validate a specific Java path.
+/// Validate a specific Java path (or a Java home folder, see `resolved_path`).
 #[tauri::command]
 pub fn validate_java_path_cmd(path: String) -> JavaValidation {
     validate_java_path(&path)
@@ -1350,54 +1865,262 @@ pub fn get_required_java_version_cmd(mc_version: String) -> u32 {
     get_required_java_version(&mc_version)
 }
 
-/// Check if a Java version is compatible with a Minecraft version.
+/// The requirement rule for a Minecraft version (e.g. "1.20.5+ requires Java 21").
+#[tauri::command]
+pub fn matched_java_requirement_cmd(mc_version: String) -> JavaRequirement {
+    matched_requirement(&mc_version)
+}
+
+/// Explain which rule decides the required Java version for a Minecraft version.
 #[tauri::command]
-pub fn check_java_compatibility_cmd(java_major: u32, mc_version: String) -> bool {
-    is_java_compatible(java_major, &mc_version)
+pub fn explain_java_requirement_cmd(mc_version: String) -> JavaRequirementExplanation {
+    explain_java_requirement(&mc_version)
 }
 
-/// Fetch Adoptium release info for a Java version.
+/// Check if a Java version is compatible with a Minecraft version and loader.
 #[tauri::command]
-pub fn fetch_adoptium_release_cmd(java_major: u32) -> Result<AdoptiumRelease, String> {
-    fetch_adoptium_release(java_major).map_err(|e| e.to_string())
+pub fn check_java_compatibility_cmd(java_major: u32, mc_version: String, loader_type: Option<String>) -> bool {
+    is_java_compatible_with_loader(java_major, &mc_version, loader_type.as_deref())
 }
 
-/// Download and install Java from Adoptium.
+/// Fetch Adoptium release info for a Java version (a JRE unless `image_type`
+/// says otherwise), served from a short-lived on-disk cache unless
+/// `force_refresh` is set.
 #[tauri::command]
-pub fn download_java_cmd(app: AppHandle, java_major: u32) -> Result<String, String> {
+pub fn fetch_adoptium_release_cmd(
+    java_major: u32,
+    image_type: Option<ImageType>,
+    force_refresh: Option<bool>,
+) -> Result<AdoptiumRelease, String> {
+    let paths = Paths::new().map_err(|e| e.to_string())?;
+    fetch_adoptium_release_cached(
+        java_major,
+        image_type.unwrap_or_default(),
+        &paths.java_runtimes,
+        force_refresh.unwrap_or(false),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Fetch release info for a Java version from a specific vendor.
+#[tauri::command]
+pub fn fetch_java_release_cmd(
+    vendor: JavaVendor,
+    java_major: u32,
+    image_type: Option<ImageType>,
+) -> Result<JavaRelease, String> {
+    fetch_release(vendor, java_major, image_type.unwrap_or_default()).map_err(|e| e.to_string())
+}
+
+/// Cancellation flags for in-flight Java downloads, keyed by vendor, major
+/// version and image type so concurrent JRE and JDK downloads stay separate.
+static JAVA_DOWNLOADS: Mutex<Vec<((JavaVendor, u32, ImageType), Arc<AtomicBool>)>> = Mutex::new(Vec::new());
+
+/// Download and install Java (Adoptium unless another vendor is given).
+/// A pinned Adoptium `version` (e.g. "17.0.8+7") is installed instead of the latest.
+/// Installs a JRE unless `image_type` asks for a full JDK.
+/// Emits "java-download-progress" events tagged with the major version to every
+/// window, "java-download-phase" when it moves on to verifying, extracting or
+/// validating, then "java-download-done" or "java-download-error" when it finishes
+/// ("java-download-cancelled" when aborted with `cancel_java_download_cmd`).
+/// Every payload carries the Java version under `major`.
+#[tauri::command]
+pub fn download_java_cmd(
+    app: AppHandle,
+    java_major: u32,
+    vendor: Option<JavaVendor>,
+    version: Option<String>,
+    image_type: Option<ImageType>,
+) -> Result<String, String> {
     let paths = Paths::new().map_err(|e| e.to_string())?;
     paths.ensure().map_err(|e| e.to_string())?;
 
-    let install_dir = paths.java_runtimes.join(format!("temurin-{}", java_major));
+    let vendor = vendor.unwrap_or_default();
+    let image_type = image_type.unwrap_or_default();
+    let release = match version {
+        Some(version) if vendor == JavaVendor::Adoptium => fetch_adoptium_version(&version, image_type),
+        Some(_) => return Err("Pinned versions are only supported for Adoptium".to_string()),
+        None => fetch_release(vendor, java_major, image_type),
+    }
+    .map_err(|e| e.to_string())?;
+    let install_dir = managed_runtime_dir(&paths.java_runtimes, vendor, java_major);
+
+    let key = (vendor, java_major, image_type);
+    let cancel = Arc::new(AtomicBool::new(false));
+    if let Ok(mut downloads) = JAVA_DOWNLOADS.lock() {
+        downloads.retain(|(k, _)| *k != key);
+        downloads.push((key, cancel.clone()));
+    }
 
     // Create a progress callback that emits events
     let app_handle = app.clone();
-    let progress_callback = Some(Box::new(move |downloaded: u64, total: u64| {
+    let progress_callback = Some(Box::new(move |progress: DownloadProgress| {
+        let (downloaded, total) = (progress.downloaded, progress.total);
         let _ = app_handle.emit("java-download-progress", serde_json::json!({
+            "major": java_major,
             "downloaded": downloaded,
             "total": total,
-            "percentage": if total > 0 { (downloaded as f64 / total as f64 * 100.0) as u32 } else { 0 }
+            "percentage": if total > 0 { (downloaded as f64 / total as f64 * 100.0) as u32 } else { 0 },
+            "bytes_per_sec": progress.bytes_per_sec,
+            "eta_secs": progress.eta_secs
         }));
-    }) as Box<dyn Fn(u64, u64) + Send>);
+    }) as ProgressCallback2);
+    let phase_app = app.clone();
+    let phase_callback = Some(Box::new(move |phase: InstallPhase| {
+        // e.g. {"major": 21, "phase": "extracting", "done": 96, "total": 412}
+        let mut payload = serde_json::to_value(phase).unwrap_or_default();
+        payload["major"] = serde_json::json!(java_major);
+        let _ = phase_app.emit("java-download-phase", payload);
+    }) as PhaseCallback);
+
+    let result = download_and_install_release_with_progress(
+        &release,
+        &install_dir,
+        progress_callback,
+        phase_callback,
+        Some(cancel.clone()),
+        DEFAULT_DOWNLOAD_CONNECTIONS,
+    );
+    if let Ok(mut downloads) = JAVA_DOWNLOADS.lock() {
+        downloads.retain(|(_, flag)| !Arc::ptr_eq(flag, &cancel));
+    }
+    let java_path = result.map_err(|e| {
+        if is_download_cancelled(&e) {
+            let _ = app.emit("java-download-cancelled", serde_json::json!({ "major": java_major }));
+        } else {
+            let _ = app.emit("java-download-error", serde_json::json!({
+                "major": java_major,
+                "error": format!("{e:#}")
+            }));
+        }
+        e.to_string()
+    })?;
+
+    let java_path = java_path.to_string_lossy().to_string();
+    let _ = app.emit("java-download-done", serde_json::json!({
+        "major": java_major,
+        "java_path": java_path
+    }));
+    Ok(java_path)
+}
+
+/// Cancel an in-flight Java download, matched by vendor, major version and
+/// image type (Adoptium JRE by default). Returns false when none is running.
+#[tauri::command]
+pub fn cancel_java_download_cmd(
+    java_major: u32,
+    vendor: Option<JavaVendor>,
+    image_type: Option<ImageType>,
+) -> bool {
+    let Ok(downloads) = JAVA_DOWNLOADS.lock() else {
+        return false;
+    };
+    let key = (vendor.unwrap_or_default(), java_major, image_type.unwrap_or_default());
+    match downloads.iter().find(|(k, _)| *k == key) {
+        Some((_, flag)) => {
+            flag.store(true, Ordering::Relaxed);
+            true
+        }
+        None => false,
+    }
+}
 
-    let java_path = download_and_install_java(java_major, &install_dir, progress_callback)
-        .map_err(|e| e.to_string())?;
+/// Find a compatible Java for a Minecraft version (checks managed runtimes first).
+/// Native-arch installations are preferred unless `prefer_native_arch` is false,
+/// and only full JDKs are considered when `require_jdk` is set.
+/// Returns `None` when a download should be offered; with `offline` set, a
+/// missing Java is reported as an error instead since downloads are disabled.
+#[tauri::command]
+pub fn find_compatible_java_cmd(
+    mc_version: String,
+    loader_type: Option<String>,
+    prefer_native_arch: Option<bool>,
+    require_jdk: Option<bool>,
+    offline: Option<bool>,
+) -> Result<Option<String>, String> {
+    let paths = Paths::new().map_err(|e| e.to_string())?;
+    match find_compatible_java(
+        &mc_version,
+        loader_type.as_deref(),
+        &paths.java_runtimes,
+        prefer_native_arch.unwrap_or(true),
+        require_jdk.unwrap_or(false),
+        offline.unwrap_or(false),
+    ) {
+        Ok(path) => Ok(Some(path)),
+        Err(reason) if reason.downloads_disabled => Err(reason.to_string()),
+        Err(_) => Ok(None),
+    }
+}
 
-    Ok(java_path.to_string_lossy().to_string())
+/// Like `find_compatible_java_cmd`, but says why nothing was found: no Java
+/// at all, or only versions older than required.
+#[tauri::command]
+pub fn find_compatible_java_detailed_cmd(
+    mc_version: String,
+    loader_type: Option<String>,
+    prefer_native_arch: Option<bool>,
+    require_jdk: Option<bool>,
+    offline: Option<bool>,
+) -> Result<JavaResolution, String> {
+    let paths = Paths::new().map_err(|e| e.to_string())?;
+    Ok(find_compatible_java_detailed(
+        &mc_version,
+        loader_type.as_deref(),
+        &paths.java_runtimes,
+        prefer_native_arch.unwrap_or(true),
+        require_jdk.unwrap_or(false),
+        offline.unwrap_or(false),
+    ))
 }
 
-/// Find a compatible Java for a Minecraft version (checks managed runtimes first).
+/// Check if a managed Java runtime exists for a version, from `vendor` or any vendor.
 #[tauri::command]
-pub fn find_compatible_java_cmd(mc_version: String) -> Result<Option<String>, String> {
+pub fn get_managed_java_cmd(java_major: u32, vendor: Option<JavaVendor>) -> Result<Option<String>, String> {
     let paths = Paths::new().map_err(|e| e.to_string())?;
-    Ok(find_compatible_java(&mc_version, &paths.java_runtimes))
+    Ok(get_managed_java(&paths.java_runtimes, vendor, java_major).map(|p| p.to_string_lossy().to_string()))
 }
 
-/// Check if a managed Java runtime exists for a version.
+/// Remove a managed Java runtime, from `vendor` or every vendor. Errors with
+/// "nothing to remove" when it isn't installed.
 #[tauri::command]
-pub fn get_managed_java_cmd(java_major: u32) -> Result<Option<String>, String> {
+pub fn uninstall_managed_java_cmd(java_major: u32, vendor: Option<JavaVendor>) -> Result<(), String> {
     let paths = Paths::new().map_err(|e| e.to_string())?;
-    Ok(get_managed_java(&paths.java_runtimes, java_major).map(|p| p.to_string_lossy().to_string()))
+    uninstall_managed_java(&paths.java_runtimes, vendor, java_major).map_err(|e| e.to_string())
+}
+
+/// Reinstall a managed Java runtime whose files are broken. Emits the same
+/// "java-download-progress" / "java-download-done" / "java-download-error"
+/// events as `download_java_cmd`. Returns the new java path.
+#[tauri::command]
+pub fn repair_managed_java_cmd(app: AppHandle, java_major: u32) -> Result<String, String> {
+    let paths = Paths::new().map_err(|e| e.to_string())?;
+    paths.ensure().map_err(|e| e.to_string())?;
+
+    let app_handle = app.clone();
+    let progress_callback = Some(Box::new(move |downloaded: u64, total: u64| {
+        let _ = app_handle.emit("java-download-progress", serde_json::json!({
+            "major": java_major,
+            "downloaded": downloaded,
+            "total": total,
+            "percentage": if total > 0 { (downloaded as f64 / total as f64 * 100.0) as u32 } else { 0 }
+        }));
+    }) as ProgressCallback);
+
+    let java_path = repair_managed_java(&paths.java_runtimes, java_major, progress_callback).map_err(|e| {
+        let _ = app.emit("java-download-error", serde_json::json!({
+            "major": java_major,
+            "error": format!("{e:#}")
+        }));
+        format!("{e:#}")
+    })?;
+
+    let java_path = java_path.to_string_lossy().to_string();
+    let _ = app.emit("java-download-done", serde_json::json!({
+        "major": java_major,
+        "java_path": java_path
+    }));
+    Ok(java_path)
 }
 
 /// List all managed Java runtimes.
@@ -1407,6 +2130,52 @@ pub fn list_managed_runtimes_cmd() -> Result<Vec<JavaInstallation>, String> {
     Ok(list_managed_runtimes(&paths.java_runtimes))
 }
 
+/// List managed runtimes and system installations, merged and de-duplicated.
+#[tauri::command]
+pub fn list_all_java_installations_cmd() -> Result<Vec<JavaInstallation>, String> {
+    let paths = Paths::new().map_err(|e| e.to_string())?;
+    Ok(all_java_installations(&paths.java_runtimes))
+}
+
+#[derive(Serialize)]
+pub struct JavaBenchmark {
+    pub installation: JavaInstallation,
+    /// Median `java -version` startup time in milliseconds.
+    pub startup_ms: u64,
+}
+
+/// Physical memory and a suggested `-Xmx` (both in MB) for prefilling the memory slider.
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryRecommendation {
+    pub system_mb: u64,
+    pub recommended_mb: u64,
+}
+
+/// Suggest a default max heap for a Minecraft version and number of installed mods.
+#[tauri::command]
+pub fn recommend_max_heap_cmd(mc_version: String, installed_mods: usize) -> MemoryRecommendation {
+    MemoryRecommendation {
+        system_mb: system_memory_mb(),
+        recommended_mb: recommend_max_heap_mb(&mc_version, installed_mods),
+    }
+}
+
+/// Benchmark JVM startup for all known Java installations, fastest first.
+#[tauri::command]
+pub fn benchmark_java_runtimes_cmd() -> Result<Vec<JavaBenchmark>, String> {
+    let paths = Paths::new().map_err(|e| e.to_string())?;
+    let installations = all_java_installations(&paths.java_runtimes);
+    let mut results: Vec<JavaBenchmark> = benchmark_runtimes(&installations)
+        .into_iter()
+        .map(|(installation, duration)| JavaBenchmark {
+            installation,
+            startup_ms: duration.as_millis() as u64,
+        })
+        .collect();
+    results.sort_by_key(|b| b.startup_ms);
+    Ok(results)
+}
+
 // ============================================================================
 // Library commands
 // ============================================================================
@@ -1744,6 +2513,146 @@ pub fn set_auto_update_enabled_cmd(enabled: bool) -> Result<Config, String> {
     Ok(config)
 }
 
+#[tauri::command]
+pub fn get_debug_logging_cmd() -> Result<bool, String> {
+    let paths = load_paths()?;
+    let config = load_config(&paths).map_err(|e| e.to_string())?;
+    Ok(config.debug_logging)
+}
+
+#[tauri::command]
+pub fn get_download_mirrors_cmd() -> Result<Vec<String>, String> {
+    let paths = load_paths()?;
+    let config = load_config(&paths).map_err(|e| e.to_string())?;
+    Ok(config.download_mirrors)
+}
+
+/// Save download mirrors and apply them immediately.
+#[tauri::command]
+pub fn set_download_mirrors_cmd(mirrors: Vec<String>) -> Result<Config, String> {
+    let paths = load_paths()?;
+    let mut config = load_config(&paths).map_err(|e| e.to_string())?;
+    config.download_mirrors = mirrors.into_iter().filter(|m| !m.trim().is_empty()).collect();
+    save_config(&paths, &config).map_err(|e| e.to_string())?;
+    set_mirrors(config.download_mirrors.clone());
+    Ok(config)
+}
+
+/// Save the proxy override (or disable proxies) and apply it to new requests.
+#[tauri::command]
+pub fn set_proxy_settings_cmd(url: Option<String>, disabled: bool) -> Result<Config, String> {
+    let url = url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+    if let Some(url) = &url {
+        reqwest::Proxy::all(url.as_str()).map_err(|e| format!("invalid proxy url: {e}"))?;
+    }
+    let paths = load_paths()?;
+    let mut config = load_config(&paths).map_err(|e| e.to_string())?;
+    config.proxy_url = url;
+    config.disable_proxy = disabled;
+    save_config(&paths, &config).map_err(|e| e.to_string())?;
+    set_proxy_settings(config.proxy_settings());
+    Ok(config)
+}
+
+#[tauri::command]
+pub fn get_titlebar_mode_cmd() -> Result<TitlebarMode, String> {
+    let paths = load_paths()?;
+    let config = load_config(&paths).map_err(|e| e.to_string())?;
+    Ok(config.titlebar)
+}
+
+/// Whether the UI should draw its own title bar and window controls.
+#[tauri::command]
+pub fn custom_titlebar_cmd() -> Result<bool, String> {
+    let paths = load_paths()?;
+    let config = load_config(&paths).map_err(|e| e.to_string())?;
+    Ok(crate::use_custom_titlebar(config.titlebar))
+}
+
+/// Save the title bar mode and apply it to the window right away.
+/// Emits `titlebar-changed` with whether the custom title bar is now in use.
+#[tauri::command]
+pub fn set_titlebar_mode_cmd(window: tauri::WebviewWindow, mode: TitlebarMode) -> Result<bool, String> {
+    let paths = load_paths()?;
+    let mut config = load_config(&paths).map_err(|e| e.to_string())?;
+    config.titlebar = mode;
+    save_config(&paths, &config).map_err(|e| e.to_string())?;
+
+    let custom = crate::use_custom_titlebar(mode);
+    if cfg!(target_os = "linux") {
+        window.set_decorations(!custom).map_err(|e| e.to_string())?;
+    }
+    let _ = window.emit("titlebar-changed", custom);
+    Ok(custom)
+}
+
+/// Seconds to wait for the game window before reporting it as not responding
+/// (`None` when disabled).
+#[tauri::command]
+pub fn get_startup_timeout_cmd() -> Result<Option<u64>, String> {
+    let paths = load_paths()?;
+    let config = load_config(&paths).map_err(|e| e.to_string())?;
+    Ok(config.startup_timeout().map(|timeout| timeout.as_secs()))
+}
+
+/// Save the startup watchdog timeout; 0 disables it, `None` restores the default.
+/// Applies from the next launch.
+#[tauri::command]
+pub fn set_startup_timeout_cmd(secs: Option<u64>) -> Result<Config, String> {
+    let paths = load_paths()?;
+    let mut config = load_config(&paths).map_err(|e| e.to_string())?;
+    config.startup_timeout_secs = secs;
+    save_config(&paths, &config).map_err(|e| e.to_string())?;
+    Ok(config)
+}
+
+/// Global download limits shared by every transfer.
+#[tauri::command]
+pub fn get_download_limits_cmd() -> Result<DownloadLimits, String> {
+    let paths = load_paths()?;
+    let config = load_config(&paths).map_err(|e| e.to_string())?;
+    Ok(config.download_limits())
+}
+
+/// Save the global download limits; applies to downloads already running.
+#[tauri::command]
+pub fn set_download_limits_cmd(limits: DownloadLimits) -> Result<Config, String> {
+    let paths = load_paths()?;
+    let mut config = load_config(&paths).map_err(|e| e.to_string())?;
+    config.max_concurrent_downloads = limits.max_concurrent.filter(|max| *max > 0);
+    config.download_bytes_per_sec = limits.bytes_per_sec.filter(|rate| *rate > 0);
+    save_config(&paths, &config).map_err(|e| e.to_string())?;
+    set_download_limits(config.download_limits());
+    Ok(config)
+}
+
+#[tauri::command]
+pub fn get_show_game_console_cmd() -> Result<bool, String> {
+    let paths = load_paths()?;
+    let config = load_config(&paths).map_err(|e| e.to_string())?;
+    Ok(config.show_game_console)
+}
+
+/// Persist whether games keep a console window on Windows. Used by the next launch.
+#[tauri::command]
+pub fn set_show_game_console_cmd(enabled: bool) -> Result<Config, String> {
+    let paths = load_paths()?;
+    let mut config = load_config(&paths).map_err(|e| e.to_string())?;
+    config.show_game_console = enabled;
+    save_config(&paths, &config).map_err(|e| e.to_string())?;
+    Ok(config)
+}
+
+/// Persist the debug logging setting. Takes effect on next start.
+#[tauri::command]
+pub fn set_debug_logging_cmd(enabled: bool) -> Result<Config, String> {
+    let paths = load_paths()?;
+    let mut config = load_config(&paths).map_err(|e| e.to_string())?;
+    config.debug_logging = enabled;
+    save_config(&paths, &config).map_err(|e| e.to_string())?;
+    Ok(config)
+}
+
 // ============================================================================
 // Update Checking Commands
 // ============================================================================
@@ -1762,6 +2671,14 @@ pub fn check_profile_updates_cmd(profile_id: String) -> Result<UpdateCheckResult
     check_profile_updates(&paths, &profile_id, config.curseforge_api_key.as_deref()).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub fn check_mod_updates_cmd(profile_id: String) -> Result<Vec<ModUpdate>, String> {
+    let paths = load_paths()?;
+    let config = load_config(&paths).map_err(|e| e.to_string())?;
+    let profile = load_profile(&paths, &profile_id).map_err(|e| e.to_string())?;
+    check_mod_updates(&paths, &profile, config.curseforge_api_key.as_deref()).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub fn apply_content_update_cmd(
     profile_id: String,
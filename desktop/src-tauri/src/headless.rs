@@ -0,0 +1,141 @@
+//! Launch an instance without opening the window, by starting the desktop app
+//! with `--launch <instance> [--account <name>]`.
+//!
+//! The account must already be signed in (through the app or `shard account
+//! add`); its saved tokens are refreshed as needed, but no browser or device
+//! code flow is ever started from here. Game output is streamed to stdout and
+//! stderr, and the process exits with the game's exit code. On Windows release
+//! builds there is no console, so the output is only visible when redirected.
+
+use shard::accounts::{find_account_mut, load_accounts};
+use shard::minecraft::{prepare_with_options, run_with_output, LaunchOptions, OutputStream};
+use shard::ops::resolve_launch_account;
+use shard::paths::Paths;
+use shard::profile::load_profile;
+
+/// Exit code for bad arguments, an unknown instance or a missing sign-in.
+pub(crate) const EXIT_USAGE: i32 = 2;
+/// Exit code when the launch fails, or the game ends without one of its own.
+const EXIT_FAILED: i32 = 1;
+
+/// A `--launch` request from the command line.
+pub(crate) struct HeadlessLaunch {
+    instance: String,
+    account: Option<String>,
+}
+
+/// Parse `--launch <instance>` and `--account <name>` (also as `--flag=value`).
+/// Returns `None` without `--launch`, so the app starts normally; other
+/// arguments (deep links, macOS `-psn_*`) are left alone in that case.
+pub(crate) fn parse_args(args: &[String]) -> Result<Option<HeadlessLaunch>, String> {
+    if !args.iter().any(|arg| arg == "--launch" || arg.starts_with("--launch=")) {
+        return Ok(None);
+    }
+
+    let mut instance = None;
+    let mut account = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let (flag, inline) = match arg.split_once('=') {
+            Some((flag, value)) => (flag, Some(value.to_string())),
+            None => (arg.as_str(), None),
+        };
+        let slot = match flag {
+            "--launch" => &mut instance,
+            "--account" => &mut account,
+            _ => return Err(format!("unknown argument: {arg} (expected --launch <instance> [--account <name>])")),
+        };
+        let value = match inline {
+            Some(value) => value,
+            None => args.next().cloned().ok_or_else(|| format!("{flag} needs a value"))?,
+        };
+        *slot = Some(value);
+    }
+
+    let instance = instance.filter(|id| !id.trim().is_empty()).ok_or("--launch needs an instance id")?;
+    Ok(Some(HeadlessLaunch { instance, account }))
+}
+
+/// Launch the instance and wait for the game; returns the process exit code.
+pub(crate) fn run(launch: HeadlessLaunch) -> i32 {
+    let paths = match Paths::new().and_then(|paths| paths.ensure().map(|_| paths)) {
+        Ok(paths) => paths,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            return EXIT_FAILED;
+        }
+    };
+    let config = shard::config::load_config(&paths).unwrap_or_default();
+    let _ = shard::logging::init_logging(Some(&paths.logs), config.debug_logging);
+    shard::http::set_proxy_settings(config.proxy_settings());
+    shard::http::set_download_limits(config.download_limits());
+    shard::http::set_mirrors(config.download_mirrors);
+    shard::java::set_adoptium_base_url(config.adoptium_base_url);
+    shard::java::migrate_legacy_runtime_dirs(&paths);
+
+    if let Err(message) = check_signed_in(&paths, launch.account.as_deref()) {
+        eprintln!("{message}");
+        return EXIT_USAGE;
+    }
+
+    let profile = match load_profile(&paths, &launch.instance) {
+        Ok(profile) => profile,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            return EXIT_USAGE;
+        }
+    };
+    let account = match resolve_launch_account(&paths, launch.account) {
+        Ok(account) => account,
+        Err(err) => {
+            eprintln!("error: could not refresh the saved sign-in: {err:#}");
+            eprintln!("Sign in again in the Shard window (or with `shard account add`), then retry.");
+            return EXIT_FAILED;
+        }
+    };
+
+    let options = LaunchOptions::default();
+    let plan = match prepare_with_options(&paths, &profile, &account, &options) {
+        Ok(plan) => plan,
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            return EXIT_FAILED;
+        }
+    };
+    for warning in &plan.warnings {
+        eprintln!("warning: {warning}");
+    }
+
+    let run = run_with_output(&plan, &options, |line| match line.stream {
+        OutputStream::Stdout => println!("{}", line.line),
+        OutputStream::Stderr => eprintln!("{}", line.line),
+    });
+    match run {
+        Ok(run) => {
+            if let Some(report) = &run.crash_report {
+                eprintln!("crash report: {}", report.display());
+            }
+            // Killed by a signal: no code, but still a failure
+            run.status.code().unwrap_or(EXIT_FAILED)
+        }
+        Err(err) => {
+            eprintln!("error: {err:#}");
+            EXIT_FAILED
+        }
+    }
+}
+
+/// Make sure the chosen (or active) account has saved tokens, so launching
+/// never needs an interactive sign-in.
+fn check_signed_in(paths: &Paths, account: Option<&str>) -> Result<(), String> {
+    const SIGN_IN: &str = "Sign in once in the Shard window (or run `shard account add`), then retry.";
+    let mut accounts = load_accounts(paths).map_err(|e| format!("error: {e:#}"))?;
+    let Some(target) = account.map(str::to_string).or_else(|| accounts.active.clone()) else {
+        return Err(format!("No account is signed in. {SIGN_IN}"));
+    };
+    match find_account_mut(&mut accounts, &target) {
+        Some(found) if !found.msa.refresh_token.is_empty() => Ok(()),
+        Some(found) => Err(format!("The sign-in for {} has no saved token. {SIGN_IN}", found.username)),
+        None => Err(format!("No signed-in account matches {target:?}. {SIGN_IN}")),
+    }
+}
@@ -1,7 +1,16 @@
 mod commands;
+mod headless;
+mod window_state;
 
-#[cfg(target_os = "linux")]
-use tauri::Manager;
+use tauri::{Emitter, Manager};
+
+/// Arguments of a second launch, forwarded to the running instance as the
+/// `second-instance` event (e.g. a `shard://` deep link).
+#[derive(Clone, serde::Serialize)]
+struct SecondInstance {
+    args: Vec<String>,
+    cwd: String,
+}
 
 /// Check if the current Linux desktop environment uses client-side decorations (CSDs).
 /// Returns true for GNOME and GNOME-based environments, false otherwise.
@@ -28,26 +37,101 @@ fn is_csd_desktop() -> bool {
     false
 }
 
+/// Whether the window should hide native decorations and draw its own title bar
+/// and window controls.
+///
+/// `Auto` keeps native decorations on GNOME-like desktops, which draw them
+/// client-side, and uses the custom bar elsewhere (e.g. KDE Plasma) to avoid a
+/// doubled title bar.
+pub(crate) fn use_custom_titlebar(mode: shard::config::TitlebarMode) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        match mode {
+            shard::config::TitlebarMode::Auto => !is_csd_desktop(),
+            shard::config::TitlebarMode::Native => false,
+            shard::config::TitlebarMode::Custom => true,
+        }
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = mode;
+        false
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    // `--launch <instance>` runs the game without a window and exits with its
+    // code; checked before the single-instance plugin hands arguments off.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match headless::parse_args(&args) {
+        Ok(Some(launch)) => std::process::exit(headless::run(launch)),
+        Ok(None) => {}
+        Err(message) => {
+            eprintln!("error: {message}");
+            std::process::exit(headless::EXIT_USAGE);
+        }
+    }
+
+    let builder = tauri::Builder::default();
+
+    // Must be the first plugin: a second launch exits here after handing its
+    // arguments to the running instance. The lock is held by the OS (D-Bus name,
+    // named mutex, local socket) and released when the process exits, crash included.
+    #[cfg(desktop)]
+    let builder = builder.plugin(tauri_plugin_single_instance::init(|app, args, cwd| {
+        if let Some(window) = app.get_webview_window("main") {
+            let _ = window.unminimize();
+            let _ = window.show();
+            let _ = window.set_focus();
+        }
+        let _ = app.emit("second-instance", SecondInstance { args, cwd });
+    }));
+
+    builder
         .setup(|app| {
             #[cfg(desktop)]
             let _ = app.handle().plugin(tauri_plugin_updater::Builder::new().build());
 
-            // On Linux with non-GNOME desktops, disable native window decorations
-            // to prevent double title bars. The app has its own custom title bar.
+            // Launcher diagnostics go to stderr and <data>/logs/launcher.log
+            let mut titlebar = shard::config::TitlebarMode::default();
+            if let Ok(paths) = shard::paths::Paths::new() {
+                let config = shard::config::load_config(&paths).unwrap_or_default();
+                let _ = shard::logging::init_logging(Some(&paths.logs), config.debug_logging);
+                shard::http::set_proxy_settings(config.proxy_settings());
+                shard::http::set_download_limits(config.download_limits());
+                shard::http::set_mirrors(config.download_mirrors);
+                shard::java::migrate_legacy_runtime_dirs(&paths);
+                // Leftovers from a download interrupted by a crash; probing Java is slow
+                let java_runtimes = paths.java_runtimes.clone();
+                std::thread::spawn(move || shard::java::cleanup_incomplete_downloads(&java_runtimes));
+                shard::java::set_adoptium_base_url(config.adoptium_base_url);
+                titlebar = config.titlebar;
+            }
+
+            // Restore proxy credentials the user chose to remember
+            let _ = shard::http::restore_saved_proxy_credentials();
+
+            // On Linux, native decorations are swapped for the app's own title
+            // bar when the desktop doesn't draw them (see `use_custom_titlebar`).
             #[cfg(target_os = "linux")]
             {
-                if !is_csd_desktop() {
+                if use_custom_titlebar(titlebar) {
                     if let Some(window) = app.get_webview_window("main") {
                         let _ = window.set_decorations(false);
                     }
                 }
             }
+            #[cfg(not(target_os = "linux"))]
+            let _ = titlebar;
+
+            if let Some(window) = app.get_webview_window("main") {
+                window_state::restore(&window);
+            }
 
             Ok(())
         })
+        .on_window_event(window_state::on_window_event)
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_os::init())
@@ -57,6 +141,19 @@ pub fn run() {
             commands::load_profile_cmd,
             commands::create_profile_cmd,
             commands::clone_profile_cmd,
+            commands::set_profile_jvm_args_cmd,
+            commands::apply_aikar_flags_cmd,
+            commands::set_profile_wrapper_cmd,
+            commands::set_profile_hooks_cmd,
+            commands::set_profile_env_cmd,
+            commands::set_profile_game_dir_cmd,
+            commands::set_profile_gc_preset_cmd,
+            commands::set_profile_window_cmd,
+            commands::duplicate_instance_cmd,
+            commands::verify_instance_integrity_cmd,
+            commands::export_instance_cmd,
+            commands::import_instance_archive_cmd,
+            commands::import_curseforge_zip_cmd,
             commands::delete_profile_cmd,
             commands::rename_profile_cmd,
             commands::update_profile_version_cmd,
@@ -67,9 +164,14 @@ pub fn run() {
             commands::remove_mod_cmd,
             commands::remove_resourcepack_cmd,
             commands::remove_shaderpack_cmd,
+            commands::scan_mod_conflicts_cmd,
+            commands::keep_newest_mod_cmd,
             commands::prepare_profile_cmd,
+            commands::preview_launch_cmd,
             commands::launch_profile_cmd,
             commands::instance_path_cmd,
+            commands::kill_game_cmd,
+            commands::detect_java_env_overrides_cmd,
             // Account commands
             commands::list_accounts_cmd,
             commands::set_active_account_cmd,
@@ -87,6 +189,8 @@ pub fn run() {
             // Config commands
             commands::get_config_cmd,
             commands::save_config_cmd,
+            commands::set_proxy_credentials_cmd,
+            commands::clear_proxy_credentials_cmd,
             // Template commands
             commands::list_templates_cmd,
             commands::load_template_cmd,
@@ -96,6 +200,7 @@ pub fn run() {
             commands::store_get_project_cmd,
             commands::store_get_versions_cmd,
             commands::store_install_cmd,
+            commands::store_plan_install_cmd,
             // Logs commands
             commands::list_log_files_cmd,
             commands::read_logs_cmd,
@@ -104,6 +209,7 @@ pub fn run() {
             commands::start_log_watch,
             // Version fetching commands
             commands::fetch_minecraft_versions_cmd,
+            commands::sort_mc_versions_cmd,
             commands::fetch_fabric_versions_cmd,
             commands::fetch_quilt_versions_cmd,
             commands::fetch_neoforge_versions_cmd,
@@ -111,15 +217,27 @@ pub fn run() {
             commands::fetch_loader_versions_cmd,
             // Java detection commands
             commands::detect_java_installations_cmd,
+            commands::detect_java_with_diagnostics_cmd,
+            commands::grouped_java_installations_cmd,
             commands::validate_java_path_cmd,
             commands::get_required_java_version_cmd,
+            commands::explain_java_requirement_cmd,
+            commands::matched_java_requirement_cmd,
             commands::check_java_compatibility_cmd,
             // Java download commands
             commands::fetch_adoptium_release_cmd,
+            commands::fetch_java_release_cmd,
             commands::download_java_cmd,
+            commands::cancel_java_download_cmd,
             commands::find_compatible_java_cmd,
+            commands::find_compatible_java_detailed_cmd,
             commands::get_managed_java_cmd,
+            commands::uninstall_managed_java_cmd,
+            commands::repair_managed_java_cmd,
             commands::list_managed_runtimes_cmd,
+            commands::list_all_java_installations_cmd,
+            commands::benchmark_java_runtimes_cmd,
+            commands::recommend_max_heap_cmd,
             // Library commands
             commands::library_list_items_cmd,
             commands::library_get_item_cmd,
@@ -145,9 +263,25 @@ pub fn run() {
             commands::purge_unused_items_cmd,
             commands::get_auto_update_enabled_cmd,
             commands::set_auto_update_enabled_cmd,
+            commands::get_debug_logging_cmd,
+            commands::set_debug_logging_cmd,
+            commands::get_show_game_console_cmd,
+            commands::set_show_game_console_cmd,
+            commands::get_startup_timeout_cmd,
+            commands::set_startup_timeout_cmd,
+            commands::get_download_limits_cmd,
+            commands::connectivity_check_cmd,
+            commands::set_download_limits_cmd,
+            commands::get_titlebar_mode_cmd,
+            commands::set_titlebar_mode_cmd,
+            commands::custom_titlebar_cmd,
+            commands::get_download_mirrors_cmd,
+            commands::set_download_mirrors_cmd,
+            commands::set_proxy_settings_cmd,
             // Update checking commands
             commands::check_all_updates_cmd,
             commands::check_profile_updates_cmd,
+            commands::check_mod_updates_cmd,
             commands::apply_content_update_cmd,
             commands::set_content_pinned_cmd,
             commands::set_content_enabled_cmd,
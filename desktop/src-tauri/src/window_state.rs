@@ -0,0 +1,293 @@
+//! Persist the main window's size and position across launches.
+//!
+//! The state is written to `window-state.json` in the app config dir shortly
+//! after the window stops moving or resizing, and right away when it closes.
+//! It is applied once on startup. Saved
+//! bounds are clamped to the monitors that are connected now, so a window last
+//! used on a disconnected display comes back on a visible screen.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, Runtime, WebviewWindow, Window, WindowEvent};
+
+const STATE_FILE: &str = "window-state.json";
+/// How much of the title bar must stay on a monitor for saved bounds to be kept.
+const MIN_VISIBLE_PX: i32 = 100;
+/// Quiet period after the last move or resize before the state is written.
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// State waiting to be written once the window has been still for [`SAVE_DELAY`].
+struct PendingSave {
+    state: WindowState,
+    due: Instant,
+}
+
+static PENDING_SAVE: Mutex<Option<PendingSave>> = Mutex::new(None);
+
+/// Window position (outer) and size (inner) in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct WindowState {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    #[serde(default)]
+    maximized: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rect {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+impl Rect {
+    fn right(&self) -> i32 {
+        self.x.saturating_add(self.width as i32)
+    }
+
+    fn bottom(&self) -> i32 {
+        self.y.saturating_add(self.height as i32)
+    }
+}
+
+fn state_path<R: Runtime>(app: &AppHandle<R>) -> Option<PathBuf> {
+    app.path().app_config_dir().ok().map(|dir| dir.join(STATE_FILE))
+}
+
+fn load_state<R: Runtime>(app: &AppHandle<R>) -> Option<WindowState> {
+    let data = std::fs::read_to_string(state_path(app)?).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+fn write_state<R: Runtime>(app: &AppHandle<R>, state: &WindowState) {
+    let Some(path) = state_path(app) else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(data) = serde_json::to_string_pretty(state) {
+        let _ = std::fs::write(path, data);
+    }
+}
+
+/// Queue `state` to be written after [`SAVE_DELAY`], replacing any queued state.
+/// One background thread writes it once events stop arriving.
+fn schedule_write<R: Runtime>(app: &AppHandle<R>, state: WindowState) {
+    let Ok(mut pending) = PENDING_SAVE.lock() else {
+        return;
+    };
+    let writer_running = pending.is_some();
+    *pending = Some(PendingSave { state, due: Instant::now() + SAVE_DELAY });
+    if writer_running {
+        return;
+    }
+    let app = app.clone();
+    std::thread::spawn(move || loop {
+        let wait = {
+            let Ok(mut pending) = PENDING_SAVE.lock() else {
+                return;
+            };
+            match pending.as_ref().map(|p| p.due.checked_duration_since(Instant::now())) {
+                None => return,
+                Some(Some(wait)) => wait,
+                Some(None) => {
+                    let Some(save) = pending.take() else {
+                        return;
+                    };
+                    drop(pending);
+                    write_state(&app, &save.state);
+                    return;
+                }
+            }
+        };
+        std::thread::sleep(wait);
+    });
+}
+
+/// Drop a queued write, returning the state it would have written.
+fn take_pending() -> Option<WindowState> {
+    PENDING_SAVE.lock().ok()?.take().map(|save| save.state)
+}
+
+/// The most recent state, queued or saved.
+fn latest_state<R: Runtime>(app: &AppHandle<R>) -> Option<WindowState> {
+    let pending = PENDING_SAVE.lock().ok().and_then(|p| p.as_ref().map(|save| save.state));
+    pending.or_else(|| load_state(app))
+}
+
+/// Apply the saved state to `window`, clamped to the current monitor layout.
+pub(crate) fn restore<R: Runtime>(window: &WebviewWindow<R>) {
+    let Some(saved) = load_state(window.app_handle()) else {
+        return;
+    };
+    let monitors: Vec<Rect> = window
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .map(|m| Rect {
+            x: m.position().x,
+            y: m.position().y,
+            width: m.size().width,
+            height: m.size().height,
+        })
+        .collect();
+    let primary = window.primary_monitor().ok().flatten().map(|m| Rect {
+        x: m.position().x,
+        y: m.position().y,
+        width: m.size().width,
+        height: m.size().height,
+    });
+    let Some(state) = clamp_to_monitors(saved, &monitors, primary) else {
+        return;
+    };
+
+    let _ = window.set_size(PhysicalSize::new(state.width, state.height));
+    let _ = window.set_position(PhysicalPosition::new(state.x, state.y));
+    if state.maximized {
+        let _ = window.maximize();
+    }
+}
+
+/// Keep the saved bounds if enough of the title bar is on some monitor;
+/// otherwise centre the window on the primary monitor, shrunk to fit.
+fn clamp_to_monitors(saved: WindowState, monitors: &[Rect], primary: Option<Rect>) -> Option<WindowState> {
+    let title_bar = Rect {
+        x: saved.x,
+        y: saved.y,
+        width: saved.width,
+        height: MIN_VISIBLE_PX as u32,
+    };
+    let visible = monitors.iter().any(|m| {
+        let overlap_x = title_bar.right().min(m.right()) - title_bar.x.max(m.x);
+        let overlap_y = title_bar.bottom().min(m.bottom()) - title_bar.y.max(m.y);
+        overlap_x >= MIN_VISIBLE_PX && overlap_y > 0
+    });
+    if visible {
+        return Some(saved);
+    }
+
+    let target = primary.or_else(|| monitors.first().copied())?;
+    let width = saved.width.min(target.width);
+    let height = saved.height.min(target.height);
+    Some(WindowState {
+        x: target.x + ((target.width - width) / 2) as i32,
+        y: target.y + ((target.height - height) / 2) as i32,
+        width,
+        height,
+        maximized: saved.maximized,
+    })
+}
+
+/// Save the window's bounds once it settles after moving or resizing, and
+/// immediately when it closes.
+pub(crate) fn on_window_event<R: Runtime>(window: &Window<R>, event: &WindowEvent) {
+    if window.label() != "main" {
+        return;
+    }
+    let closing = match event {
+        WindowEvent::Moved(_) | WindowEvent::Resized(_) => false,
+        WindowEvent::CloseRequested { .. } => true,
+        _ => return,
+    };
+    let app = window.app_handle();
+    if window.is_minimized().unwrap_or(false) {
+        // Keep the bounds from before minimizing; a queued save still holds them
+        if closing {
+            if let Some(state) = take_pending() {
+                write_state(app, &state);
+            }
+        }
+        return;
+    }
+
+    let maximized = window.is_maximized().unwrap_or(false);
+    // While maximized, keep the last normal bounds so un-maximizing after a
+    // restart returns to them.
+    let state = match (maximized, latest_state(app)) {
+        (true, Some(previous)) => WindowState { maximized: true, ..previous },
+        _ => {
+            // Inner size, because that is what `set_size` restores.
+            let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else {
+                return;
+            };
+            WindowState {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+                maximized,
+            }
+        }
+    };
+    if closing {
+        take_pending();
+        write_state(app, &state);
+    } else {
+        schedule_write(app, state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PRIMARY: Rect = Rect { x: 0, y: 0, width: 1920, height: 1080 };
+    const SECONDARY: Rect = Rect { x: 1920, y: 0, width: 1280, height: 1024 };
+
+    fn state(x: i32, y: i32, width: u32, height: u32) -> WindowState {
+        WindowState { x, y, width, height, maximized: false }
+    }
+
+    #[test]
+    fn visible_bounds_are_kept() {
+        let saved = state(100, 100, 1200, 800);
+        assert_eq!(clamp_to_monitors(saved, &[PRIMARY], Some(PRIMARY)), Some(saved));
+
+        // On the second monitor, and straddling both
+        let saved = state(2000, 50, 1000, 700);
+        assert_eq!(clamp_to_monitors(saved, &[PRIMARY, SECONDARY], Some(PRIMARY)), Some(saved));
+        let saved = state(1800, 0, 1000, 700);
+        assert_eq!(clamp_to_monitors(saved, &[PRIMARY, SECONDARY], Some(PRIMARY)), Some(saved));
+    }
+
+    #[test]
+    fn bounds_on_a_disconnected_monitor_are_centred_on_the_primary() {
+        let saved = WindowState { maximized: true, ..state(2000, 50, 1000, 700) };
+        assert_eq!(
+            clamp_to_monitors(saved, &[PRIMARY], Some(PRIMARY)),
+            Some(WindowState { maximized: true, ..state(460, 190, 1000, 700) })
+        );
+    }
+
+    #[test]
+    fn oversized_bounds_are_shrunk_to_fit() {
+        let saved = state(-5000, -5000, 2560, 1440);
+        assert_eq!(clamp_to_monitors(saved, &[PRIMARY], Some(PRIMARY)), Some(state(0, 0, 1920, 1080)));
+    }
+
+    #[test]
+    fn a_barely_visible_title_bar_is_moved() {
+        // Only 50px of the title bar reaches the monitor
+        let saved = state(1870, 100, 800, 600);
+        assert_eq!(clamp_to_monitors(saved, &[PRIMARY], Some(PRIMARY)), Some(state(560, 240, 800, 600)));
+        // Title bar above the top edge, even though the rest of the window shows
+        let saved = state(100, -150, 800, 600);
+        assert_eq!(clamp_to_monitors(saved, &[PRIMARY], Some(PRIMARY)), Some(state(560, 240, 800, 600)));
+    }
+
+    #[test]
+    fn falls_back_to_the_first_monitor_without_a_primary() {
+        let saved = state(-3000, 0, 800, 600);
+        assert_eq!(
+            clamp_to_monitors(saved, &[SECONDARY, PRIMARY], None),
+            Some(state(2160, 212, 800, 600))
+        );
+        assert_eq!(clamp_to_monitors(saved, &[], None), None);
+    }
+}
@@ -9,7 +9,7 @@ import { platform } from "@tauri-apps/plugin-os";
 
 import { useAppStore } from "./store";
 import { useOnline } from "./hooks";
-import type { LaunchEvent, ContentRef, ContentTab, Profile, LibraryItem } from "./types";
+import type { LaunchEvent, GameOutputLine, ContentRef, ContentTab, Profile, LibraryItem, JavaResolution, JavaValidation } from "./types";
 import {
   ErrorBoundary,
   Sidebar,
@@ -24,6 +24,7 @@ import {
   DeviceCodeModal,
   ProfileJsonModal,
   JavaDownloadModal,
+  ProxyAuthModal,
   WindowControls,
 } from "./components";
 import { formatContentName } from "./utils";
@@ -61,6 +62,8 @@ function App() {
     toast,
     launchStatus,
     setLaunchStatus,
+    appendGameOutput,
+    clearGameOutput,
     confirmState,
     setConfirmState,
     debugDrag,
@@ -79,6 +82,7 @@ function App() {
   const isOnline = useOnline();
   const [launchHidden, setLaunchHidden] = useState(false);
   const [currentPlatform, setCurrentPlatform] = useState<string>("");
+  const [customTitlebar, setCustomTitlebar] = useState(false);
   const hideTimerRef = useRef<ReturnType<typeof setTimeout> | null>(null);
   const clearTimerRef = useRef<ReturnType<typeof setTimeout> | null>(null);
   const updateCheckRef = useRef(false);
@@ -87,13 +91,28 @@ function App() {
   const [javaDownloadState, setJavaDownloadState] = useState<{
     javaMajor: number;
     mcVersion: string;
+    installedMajor?: number | null;
   } | null>(null);
 
+  // Message shown when a proxy asks for credentials
+  const [proxyAuthMessage, setProxyAuthMessage] = useState<string | null>(null);
+
   // Detect platform for platform-specific styling
   useEffect(() => {
     setCurrentPlatform(platform());
   }, []);
 
+  // Linux desktops without native decorations get our own title bar
+  useEffect(() => {
+    invoke<boolean>("custom_titlebar_cmd")
+      .then(setCustomTitlebar)
+      .catch(() => setCustomTitlebar(false));
+    const unlisten = listen<boolean>("titlebar-changed", (event) => setCustomTitlebar(event.payload));
+    return () => {
+      void unlisten.then((fn) => fn());
+    };
+  }, []);
+
   // Content modal state
   const contentKind = useAppStore((s) => s.activeTab);
 
@@ -128,14 +147,33 @@ function App() {
   useEffect(() => {
     const unlisten = listen<LaunchEvent>("launch-status", (event) => {
       setLaunchStatus(event.payload);
+      if (event.payload.stage === "running" && event.payload.profile_id) {
+        // A new run starts with an empty console
+        clearGameOutput(event.payload.profile_id);
+      }
       if (event.payload.stage === "error") {
         notify("Launch failed", event.payload.message ?? "Unknown error");
+      } else if (event.payload.stage === "launch-warning") {
+        notify("Launch warning", event.payload.message ?? "");
+      } else if (event.payload.stage === "not-responding") {
+        notify("Minecraft is not responding", event.payload.message ?? "");
+      } else if (event.payload.stage === "proxy-auth-required") {
+        setProxyAuthMessage(event.payload.message ?? null);
+        setActiveModal("proxy-auth");
       }
     });
     return () => {
       void unlisten.then((fn) => fn());
     };
-  }, [setLaunchStatus, notify]);
+  }, [setLaunchStatus, clearGameOutput, notify, setActiveModal]);
+
+  // Game output for the Logs console, collected even while another view is open
+  useEffect(() => {
+    const unlisten = listen<GameOutputLine>("game-output", (event) => appendGameOutput(event.payload));
+    return () => {
+      void unlisten.then((fn) => fn());
+    };
+  }, [appendGameOutput]);
 
   // Background app update check (non-blocking)
   useEffect(() => {
@@ -260,12 +298,15 @@ function App() {
   // Handlers
   const handleCreateProfile = useCallback(async (form: CreateProfileForm) => {
     await runAction(async () => {
+      // A Java home folder is saved as the executable found inside it
+      const java = form.java.trim();
+      const validation = java ? await invoke<JavaValidation>("validate_java_path_cmd", { path: java }) : null;
       const payload = {
         id: form.id.trim(),
         mc_version: form.mcVersion.trim(),
         loader_type: form.loaderType.trim() || null,
         loader_version: form.loaderVersion.trim() || null,
-        java: form.java.trim() || null,
+        java: validation?.resolved_path ?? (java || null),
         memory: form.memory.trim() || null,
         args: form.args.trim() || null,
       };
@@ -278,9 +319,9 @@ function App() {
 
   const handleCloneProfile = useCallback(async (src: string, dst: string) => {
     await runAction(async () => {
-      await invoke("clone_profile_cmd", { src, dst });
+      const copy = await invoke<Profile>("duplicate_instance_cmd", { src, newName: dst, includeSaves: false });
       await loadProfiles();
-      setSelectedProfileId(dst);
+      setSelectedProfileId(copy.id);
       setActiveModal(null);
     });
   }, [runAction, loadProfiles, setSelectedProfileId, setActiveModal]);
@@ -338,12 +379,33 @@ function App() {
 
     // Check if compatible Java is available
     const mcVersion = currentProfile.mcVersion;
-    const compatibleJava = await invoke<string | null>("find_compatible_java_cmd", { mcVersion });
+    const loaderType = currentProfile.loader?.type ?? null;
+    const java = await invoke<JavaResolution>("find_compatible_java_detailed_cmd", { mcVersion, loaderType });
+
+    if (java.kind === "none_installed" || java.kind === "too_old") {
+      // No compatible Java found - show download modal for the required version
+      setJavaDownloadState({
+        javaMajor: java.required_major,
+        mcVersion,
+        installedMajor: java.kind === "too_old" ? java.installed_major : null,
+      });
+      return;
+    }
 
-    if (!compatibleJava) {
-      // No compatible Java found - get required version and show download modal
-      const requiredJava = await invoke<number>("get_required_java_version_cmd", { mcVersion });
-      setJavaDownloadState({ javaMajor: requiredJava, mcVersion });
+    if (java.kind === "managed_broken") {
+      // The downloaded runtime is damaged - offer to reinstall it
+      setConfirmState({
+        title: `Repair Java ${java.required_major}?`,
+        message: "The Java runtime Shard downloaded for this version no longer works. Repairing deletes it and downloads it again.",
+        confirmLabel: "Repair",
+        onConfirm: async () => {
+          setConfirmState(null);
+          await runAction(async () => {
+            await invoke("repair_managed_java_cmd", { javaMajor: java.required_major });
+            notify("Java repaired", `Java ${java.required_major} was reinstalled.`);
+          });
+        },
+      });
       return;
     }
 
@@ -360,7 +422,7 @@ function App() {
       notify("Launch failed", String(err));
       setLaunchStatus(null);
     }
-  }, [selectedProfileId, getActiveAccount, notify, setLaunchStatus]);
+  }, [selectedProfileId, getActiveAccount, notify, setLaunchStatus, setConfirmState, runAction]);
 
   const handleOpenInstance = useCallback(async () => {
     if (!selectedProfileId) return;
@@ -416,7 +478,7 @@ function App() {
       <div className={clsx("app-root", debugDrag && "debug-drag")} data-platform={currentPlatform}>
         <div className="titlebar-drag-region" />
         <div className="sidebar-titlebar-bg" />
-        <WindowControls />
+        <WindowControls customTitlebar={customTitlebar} />
 
         {!isOnline && (
           <div
@@ -434,7 +496,8 @@ function App() {
           />
         )}
 
-        <div className="app-layout drag-region">
+        {/* With a custom title bar only the bar itself drags the window */}
+        <div className={clsx("app-layout", !customTitlebar && "drag-region")}>
           <Sidebar
             onCreateProfile={() => setActiveModal("create")}
             onCloneProfile={() => setActiveModal("clone")}
@@ -504,6 +567,14 @@ function App() {
                 {launchStatus.stage.charAt(0).toUpperCase() + launchStatus.stage.slice(1)}
                 {launchStatus.message && `: ${launchStatus.message}`}
               </div>
+              {launchStatus.stage === "not-responding" && (
+                <button
+                  className="btn btn-secondary btn-sm launch-status-action"
+                  onClick={() => void invoke<boolean>("kill_game_cmd", { profileId: launchStatus.profile_id })}
+                >
+                  Stop game
+                </button>
+              )}
             </div>
           )}
         </div>
@@ -550,6 +621,7 @@ function App() {
           onClose={() => setJavaDownloadState(null)}
           javaMajor={javaDownloadState?.javaMajor ?? 21}
           mcVersion={javaDownloadState?.mcVersion ?? ""}
+          installedMajor={javaDownloadState?.installedMajor}
           onSuccess={() => {
             setJavaDownloadState(null);
             // Retry launch after Java is installed
@@ -557,6 +629,18 @@ function App() {
           }}
         />
 
+        <ProxyAuthModal
+          open={activeModal === "proxy-auth"}
+          message={proxyAuthMessage ?? undefined}
+          onClose={() => setActiveModal(null)}
+          onSuccess={() => {
+            setActiveModal(null);
+            // Retry the launch with the credentials attached
+            setLaunchStatus(null);
+            handleLaunch();
+          }}
+        />
+
         {confirmState && (
           <ConfirmDialog state={confirmState} onClose={() => setConfirmState(null)} />
         )}
@@ -3,10 +3,10 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen, UnlistenFn } from "@tauri-apps/api/event";
 import clsx from "clsx";
 import { useAppStore } from "../store";
-import type { LogEntry, LogFile, LogLevel } from "../types";
+import type { GameOutputLine, LogEntry, LogFile, LogLevel } from "../types";
 import { formatFileSize, formatTimeAgo } from "../utils";
 
-type LogTab = "latest" | "history" | "crashes";
+type LogTab = "latest" | "console" | "history" | "crashes";
 
 const LEVEL_COLORS: Record<LogLevel, string> = {
   debug: "var(--text-muted)",
@@ -19,8 +19,18 @@ const LEVEL_COLORS: Record<LogLevel, string> = {
 
 const MAX_LOG_ENTRIES = 2000;
 
+// Raw game output has no levels; stderr is shown as errors
+const toLogEntry = (output: GameOutputLine, index: number): LogEntry => ({
+  timestamp: null,
+  level: output.stream === "stderr" ? "error" : "unknown",
+  thread: null,
+  message: output.line,
+  raw: output.line,
+  line_number: index + 1,
+});
+
 export function LogsView() {
-  const { selectedProfileId, notify, launchStatus } = useAppStore();
+  const { selectedProfileId, notify, launchStatus, gameOutput } = useAppStore();
   const [tab, setTab] = useState<LogTab>("latest");
   const [logs, setLogs] = useState<LogEntry[]>([]);
   const [logFiles, setLogFiles] = useState<LogFile[]>([]);
@@ -199,9 +209,13 @@ export function LogsView() {
       const container = logsContainerRef.current;
       container.scrollTop = container.scrollHeight;
     }
-  }, [logs, autoScroll]);
+  }, [logs, gameOutput, autoScroll]);
+
+  const visibleLogs = tab === "console"
+    ? gameOutput.filter((output) => output.profile_id === selectedProfileId).map(toLogEntry)
+    : logs;
 
-  const filteredLogs = logs.filter((entry) => {
+  const filteredLogs = visibleLogs.filter((entry) => {
     const levelPriority: Record<LogLevel, number> = {
       debug: 0, info: 1, warn: 2, error: 3, fatal: 4, unknown: 1,
     };
@@ -240,6 +254,16 @@ export function LogsView() {
             Live
             {isGameRunning && <span className="logs-live-dot" />}
           </button>
+          <button
+            className={clsx("logs-tab", tab === "console" && "active")}
+            onClick={() => { setTab("console"); setSelectedFile(null); }}
+          >
+            <svg width="14" height="14" viewBox="0 0 14 14" fill="none">
+              <rect x="1.5" y="2.5" width="11" height="9" rx="1.5" stroke="currentColor" strokeWidth="1.5" />
+              <path d="M4 6l2 1.5L4 9M7.5 9H10" stroke="currentColor" strokeWidth="1.5" strokeLinecap="round" strokeLinejoin="round" />
+            </svg>
+            Console
+          </button>
           <button
             className={clsx("logs-tab", tab === "history" && "active")}
             onClick={() => { setTab("history"); setSelectedFile(null); }}
@@ -267,7 +291,7 @@ export function LogsView() {
         </div>
 
         {/* Controls - only show when viewing logs */}
-        {(tab === "latest" || selectedFile) && (
+        {(tab === "latest" || tab === "console" || selectedFile) && (
           <div className="logs-controls">
             <div className="logs-filter">
               <svg width="14" height="14" viewBox="0 0 14 14" fill="none" style={{ opacity: 0.4 }}>
@@ -291,7 +315,7 @@ export function LogsView() {
               <option value="warn">Warn+</option>
               <option value="error">Errors</option>
             </select>
-            {tab === "latest" && (
+            {(tab === "latest" || tab === "console") && (
               <label className="logs-checkbox">
                 <input
                   type="checkbox"
@@ -364,7 +388,7 @@ export function LogsView() {
       )}
 
       {/* Log viewer */}
-      {(tab === "latest" || selectedFile) && (
+      {(tab === "latest" || tab === "console" || selectedFile) && (
         <div className="logs-viewer">
           {selectedFile && (
             <div className="logs-breadcrumb">
@@ -393,9 +417,9 @@ export function LogsView() {
                   <circle cx="24" cy="24" r="16" stroke="currentColor" strokeWidth="2" />
                   <path d="M24 16v8l5 3" stroke="currentColor" strokeWidth="2" strokeLinecap="round" />
                 </svg>
-                <h3>{logs.length === 0 ? "No logs yet" : "No matching logs"}</h3>
+                <h3>{visibleLogs.length === 0 ? "No logs yet" : "No matching logs"}</h3>
                 <p style={{ marginBottom: 0 }}>
-                  {logs.length === 0
+                  {visibleLogs.length === 0
                     ? "Launch the game to see live output"
                     : "Try adjusting your filter settings"}
                 </p>
@@ -424,8 +448,8 @@ export function LogsView() {
               </div>
 
               <div className="logs-status">
-                <span>{filteredLogs.length} of {logs.length} entries</span>
-                {tab === "latest" && isGameRunning && (
+                <span>{filteredLogs.length} of {visibleLogs.length} entries</span>
+                {(tab === "latest" || tab === "console") && isGameRunning && (
                   <span className="logs-status-live">
                     <span className="logs-live-dot" />
                     Live
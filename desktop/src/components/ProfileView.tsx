@@ -2,7 +2,7 @@ import { useState, useRef, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import clsx from "clsx";
 import { useAppStore } from "../store";
-import type { ContentRef, ContentTab, Profile } from "../types";
+import type { ContentRef, ContentTab, ModUpdate, Profile } from "../types";
 import { getContentTypeLabel, getContentTypeLabelPlural } from "../utils";
 import { ContentItemRow } from "./ContentItemRow";
 import type { Platform } from "./PlatformIcon";
@@ -44,6 +44,7 @@ export function ProfileView({
   const activeAccount = getActiveAccount();
   const [togglingPin, setTogglingPin] = useState<string | null>(null);
   const [togglingEnabled, setTogglingEnabled] = useState<string | null>(null);
+  const [modUpdates, setModUpdates] = useState<ModUpdate[]>([]);
 
   // Inline version/loader editing state
   const [expandedDropdown, setExpandedDropdown] = useState<ExpandedDropdown>(null);
@@ -276,6 +277,22 @@ export function ProfileView({
     }
   }, [hasModLoader, activeTab, setActiveTab]);
 
+  // Look for mod updates in the background; failures (e.g. offline) just hide the badge
+  const modCount = profile.mods.length;
+  useEffect(() => {
+    setModUpdates([]);
+    if (!hasModLoader || modCount === 0) return;
+    let cancelled = false;
+    invoke<ModUpdate[]>("check_mod_updates_cmd", { profileId: profile.id })
+      .then((updates) => {
+        if (!cancelled) setModUpdates(updates);
+      })
+      .catch(() => {});
+    return () => {
+      cancelled = true;
+    };
+  }, [profile.id, profile.mcVersion, hasModLoader, modCount]);
+
   const filteredVersions = showSnapshots
     ? mcVersions
     : mcVersions.filter((v) => v.type === "release");
@@ -455,6 +472,14 @@ export function ProfileView({
                 onClick={() => hasModLoader && setActiveTab("mods")}
               >
                 Mods<span className="count">{contentCounts.mods}</span>
+                {modUpdates.length > 0 && (
+                  <span
+                    className="update-count"
+                    title={`${modUpdates.length} update${modUpdates.length === 1 ? "" : "s"} available`}
+                  >
+                    {modUpdates.length}
+                  </span>
+                )}
               </button>
             </div>
           </div>
@@ -5,7 +5,7 @@ import { revealItemInDir, openUrl } from "@tauri-apps/plugin-opener";
 import { check } from "@tauri-apps/plugin-updater";
 import { platform, arch, version as osVersion } from "@tauri-apps/plugin-os";
 import { useAppStore } from "../store";
-import type { StorageStats, UpdateCheckResult, ContentUpdate, JavaInstallation, PurgeResult } from "../types";
+import type { StorageStats, UpdateCheckResult, ContentUpdate, JavaInstallation, PurgeResult, TitlebarMode } from "../types";
 import { formatFileSize } from "../utils";
 import { PurgeStorageModal } from "./modals/PurgeStorageModal";
 
@@ -23,6 +23,9 @@ export function SettingsView() {
   const [activeSection, setActiveSection] = useState<SettingsSection>("general");
   const [stats, setStats] = useState<StorageStats | null>(null);
   const [autoUpdate, setAutoUpdate] = useState(true);
+  const [debugLogging, setDebugLogging] = useState(false);
+  const [showGameConsole, setShowGameConsole] = useState(false);
+  const [titlebarMode, setTitlebarMode] = useState<TitlebarMode>("auto");
   const [loading, setLoading] = useState(true);
   const [checkingUpdates, setCheckingUpdates] = useState(false);
   const [updateResult, setUpdateResult] = useState<UpdateCheckResult | null>(null);
@@ -60,6 +63,14 @@ export function SettingsView() {
     }
   }, []);
 
+  const loadDebugLogging = useCallback(async () => {
+    try {
+      setDebugLogging(await invoke<boolean>("get_debug_logging_cmd"));
+    } catch {
+      setDebugLogging(false);
+    }
+  }, []);
+
   const loadJavaInstallations = useCallback(async () => {
     setDetectingJava(true);
     try {
@@ -71,14 +82,23 @@ export function SettingsView() {
     setDetectingJava(false);
   }, [notify]);
 
+  useEffect(() => {
+    invoke<TitlebarMode>("get_titlebar_mode_cmd")
+      .then(setTitlebarMode)
+      .catch(() => setTitlebarMode("auto"));
+    invoke<boolean>("get_show_game_console_cmd")
+      .then(setShowGameConsole)
+      .catch(() => setShowGameConsole(false));
+  }, []);
+
   useEffect(() => {
     const load = async () => {
       setLoading(true);
-      await Promise.all([loadStats(), loadAutoUpdate()]);
+      await Promise.all([loadStats(), loadAutoUpdate(), loadDebugLogging()]);
       setLoading(false);
     };
     load();
-  }, [loadStats, loadAutoUpdate]);
+  }, [loadStats, loadAutoUpdate, loadDebugLogging]);
 
   useEffect(() => {
     getVersion()
@@ -115,6 +135,37 @@ export function SettingsView() {
     }
   };
 
+  const handleDebugLoggingToggle = async () => {
+    const newValue = !debugLogging;
+    try {
+      await invoke("set_debug_logging_cmd", { enabled: newValue });
+      setDebugLogging(newValue);
+      notify("Settings saved", `Debug logging ${newValue ? "enabled" : "disabled"}; restart the launcher to apply`);
+    } catch (err) {
+      notify("Failed to save settings", String(err));
+    }
+  };
+
+  const handleShowGameConsoleToggle = async () => {
+    const newValue = !showGameConsole;
+    try {
+      await invoke("set_show_game_console_cmd", { enabled: newValue });
+      setShowGameConsole(newValue);
+      notify("Settings saved", `Game console ${newValue ? "shown" : "hidden"} from the next launch`);
+    } catch (err) {
+      notify("Failed to save settings", String(err));
+    }
+  };
+
+  const handleTitlebarModeChange = async (mode: TitlebarMode) => {
+    try {
+      await invoke("set_titlebar_mode_cmd", { mode });
+      setTitlebarMode(mode);
+    } catch (err) {
+      notify("Failed to save settings", String(err));
+    }
+  };
+
   const handleCheckUpdates = async () => {
     setCheckingUpdates(true);
     setUpdateResult(null);
@@ -337,6 +388,44 @@ export function SettingsView() {
                   <span className="toggle-switch-thumb" />
                 </button>
               </div>
+
+              {osInfo?.platform === "linux" && (
+                <div className="settings-row">
+                  <div className="settings-row-content">
+                    <div className="settings-row-title">Title bar</div>
+                    <div className="settings-row-description">
+                      Use the launcher's own title bar when your desktop shows no window buttons
+                    </div>
+                  </div>
+                  <select
+                    className="select select-sm"
+                    value={titlebarMode}
+                    onChange={(e) => handleTitlebarModeChange(e.target.value as TitlebarMode)}
+                  >
+                    <option value="auto">Automatic</option>
+                    <option value="native">System</option>
+                    <option value="custom">Launcher</option>
+                  </select>
+                </div>
+              )}
+
+              {osInfo?.platform === "windows" && (
+                <div className="settings-row">
+                  <div className="settings-row-content">
+                    <div className="settings-row-title">Game console</div>
+                    <div className="settings-row-description">
+                      Open a console window next to the game that shows its output
+                    </div>
+                  </div>
+                  <button
+                    className="toggle-switch"
+                    data-active={showGameConsole}
+                    onClick={handleShowGameConsoleToggle}
+                  >
+                    <span className="toggle-switch-thumb" />
+                  </button>
+                </div>
+              )}
             </section>
 
             <section className="settings-card" style={{ marginBottom: 24 }}>
@@ -357,6 +446,20 @@ export function SettingsView() {
                   Open
                 </button>
               </div>
+
+              <div className="settings-row">
+                <div className="settings-row-content">
+                  <div className="settings-row-title">Debug logging</div>
+                  <div className="settings-row-description">Write detailed diagnostics to logs/launcher.log, for bug reports</div>
+                </div>
+                <button
+                  className="toggle-switch"
+                  data-active={debugLogging}
+                  onClick={handleDebugLoggingToggle}
+                >
+                  <span className="toggle-switch-thumb" />
+                </button>
+              </div>
             </section>
 
             <section className="settings-card settings-card-muted">
@@ -522,7 +625,7 @@ export function SettingsView() {
                         {java.is_valid ? (
                           <span className="badge badge-success">Valid</span>
                         ) : (
-                          <span className="badge badge-warning">Invalid</span>
+                          <span className="badge badge-warning">Unverified</span>
                         )}
                       </div>
                     </div>
@@ -3,7 +3,7 @@ import { invoke } from "@tauri-apps/api/core";
 import { openUrl } from "@tauri-apps/plugin-opener";
 import clsx from "clsx";
 import { useAppStore } from "../store";
-import type { StoreProject, StoreVersion } from "../types";
+import type { InstallPlan, StoreProject, StoreVersion } from "../types";
 import { formatDownloads, formatFileSize } from "../utils";
 
 type StoreCategory = "mods" | "resourcepacks" | "shaderpacks";
@@ -54,7 +54,7 @@ const cacheLoading: Record<StoreCategory, boolean> = {
 };
 
 export function StoreView() {
-  const { profile, selectedProfileId, loadProfile, notify } = useAppStore();
+  const { profile, selectedProfileId, loadProfile, notify, setConfirmState } = useAppStore();
   const [query, setQuery] = useState("");
   const [category, setCategory] = useState<StoreCategory>("mods");
   const [platform, setPlatform] = useState<StorePlatform>("all");
@@ -182,27 +182,72 @@ export function StoreView() {
     }
   }, [profile, selectedProfileId, notify]);
 
-  const handleInstall = useCallback(async (version: StoreVersion) => {
-    if (!selectedProfileId || !selectedProject) return;
+  // Install a version, first asking to confirm any Modrinth dependencies it pulls in
+  const installVersion = useCallback(async (project: StoreProject, version: StoreVersion) => {
+    if (!selectedProfileId) return;
 
-    setInstalling(version.id);
-    try {
+    const install = async (installDependencies: boolean, extra: string[]) => {
       const input = {
         profile_id: selectedProfileId,
-        platform: selectedProject.platform,
-        project_id: selectedProject.id,
+        platform: project.platform,
+        project_id: project.id,
         version_id: version.id,
         content_type: CATEGORY_TO_CONTENT_TYPE[category],
+        install_dependencies: installDependencies,
       };
       await invoke("store_install_cmd", { input });
       await loadProfile(selectedProfileId);
-      notify("Installed", `${selectedProject.name} v${version.version}`);
+      const also = extra.length > 0 ? ` (with ${extra.join(", ")})` : "";
+      notify("Installed", `${project.name} v${version.version}${also}`);
+    };
+
+    if (project.platform !== "modrinth" || category !== "mods") {
+      await install(false, []);
+      return;
+    }
+
+    const plan = await invoke<InstallPlan>("store_plan_install_cmd", {
+      profileId: selectedProfileId,
+      projectId: project.id,
+      versionId: version.id,
+    });
+    if (plan.conflicts.length > 0) {
+      notify("Dependency conflict", plan.conflicts.map((c) => c.message).join("\n"));
+      return;
+    }
+    if (plan.dependencies.length === 0) {
+      await install(false, []);
+      return;
+    }
+
+    const names = plan.dependencies.map((d) => `${d.title} ${d.version_number}`);
+    setConfirmState({
+      title: `Install ${project.name}?`,
+      message: `Will also install: ${names.join(", ")}`,
+      confirmLabel: "Install",
+      onConfirm: async () => {
+        setConfirmState(null);
+        try {
+          await install(true, names);
+        } catch (err) {
+          notify("Install failed", String(err));
+        }
+      },
+    });
+  }, [selectedProfileId, category, loadProfile, notify, setConfirmState]);
+
+  const handleInstall = useCallback(async (version: StoreVersion) => {
+    if (!selectedProfileId || !selectedProject) return;
+
+    setInstalling(version.id);
+    try {
+      await installVersion(selectedProject, version);
     } catch (err) {
       notify("Install failed", String(err));
     } finally {
       setInstalling(null);
     }
-  }, [selectedProfileId, selectedProject, category, loadProfile, notify]);
+  }, [selectedProfileId, selectedProject, installVersion, notify]);
 
   // Quick install - fetch latest version and install directly
   const handleQuickInstall = useCallback(async (project: StoreProject, e: React.MouseEvent) => {
@@ -227,23 +272,13 @@ export function StoreView() {
       }
 
       // Install the first (latest) version
-      const latestVersion = versions[0];
-      const input = {
-        profile_id: selectedProfileId,
-        platform: project.platform,
-        project_id: project.id,
-        version_id: latestVersion.id,
-        content_type: CATEGORY_TO_CONTENT_TYPE[category],
-      };
-      await invoke("store_install_cmd", { input });
-      await loadProfile(selectedProfileId);
-      notify("Installed", `${project.name} v${latestVersion.version}`);
+      await installVersion(project, versions[0]);
     } catch (err) {
       notify("Install failed", String(err));
     } finally {
       setQuickInstalling(null);
     }
-  }, [selectedProfileId, profile, category, loadProfile, notify]);
+  }, [selectedProfileId, profile, installVersion, notify]);
 
   return (
     <div className="view-transition">
@@ -2,7 +2,12 @@ import { useEffect, useState } from "react";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import { platform } from "@tauri-apps/plugin-os";
 
-export function WindowControls() {
+type WindowControlsProps = {
+  /** Linux without native decorations: the app draws its own title bar. */
+  customTitlebar?: boolean;
+};
+
+export function WindowControls({ customTitlebar = false }: WindowControlsProps) {
   const [isWindows, setIsWindows] = useState(false);
   const [isMaximized, setIsMaximized] = useState(false);
 
@@ -10,8 +15,10 @@ export function WindowControls() {
     setIsWindows(platform() === "windows");
   }, []);
 
+  const visible = isWindows || customTitlebar;
+
   useEffect(() => {
-    if (!isWindows) return;
+    if (!visible) return;
 
     const win = getCurrentWindow();
 
@@ -26,9 +33,9 @@ export function WindowControls() {
     return () => {
       unlisten.then((fn) => fn());
     };
-  }, [isWindows]);
+  }, [visible]);
 
-  if (!isWindows) return null;
+  if (!visible) return null;
 
   const win = getCurrentWindow();
 
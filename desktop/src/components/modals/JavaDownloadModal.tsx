@@ -3,12 +3,15 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { Modal } from "../Modal";
 import { useAppStore } from "../../store";
+import type { JavaDownloadPhase, JavaDownloadProgress } from "../../types";
 
 interface JavaDownloadModalProps {
   open: boolean;
   onClose: () => void;
   javaMajor: number;
   mcVersion: string;
+  /** Newest Java found when it is too old for this version. */
+  installedMajor?: number | null;
   onSuccess: (javaPath: string) => void;
 }
 
@@ -21,47 +24,58 @@ interface AdoptiumRelease {
   checksum: string | null;
 }
 
-interface DownloadProgress {
-  downloaded: number;
-  total: number;
-  percentage: number;
-}
-
-export function JavaDownloadModal({ open, onClose, javaMajor, mcVersion, onSuccess }: JavaDownloadModalProps) {
+export function JavaDownloadModal({ open, onClose, javaMajor, mcVersion, installedMajor, onSuccess }: JavaDownloadModalProps) {
   const { notify } = useAppStore();
   const [stage, setStage] = useState<"confirm" | "downloading" | "extracting" | "done">("confirm");
   const [releaseInfo, setReleaseInfo] = useState<AdoptiumRelease | null>(null);
-  const [progress, setProgress] = useState<DownloadProgress>({ downloaded: 0, total: 0, percentage: 0 });
+  const [progress, setProgress] = useState<JavaDownloadProgress>({ major: javaMajor, downloaded: 0, total: 0, percentage: 0 });
+  const [phase, setPhase] = useState<JavaDownloadPhase | null>(null);
   const [error, setError] = useState<string | null>(null);
+  // Players only need the runtime; the JDK adds javac for mod development
+  const [imageType, setImageType] = useState<"jre" | "jdk">("jre");
 
   // Reset state when modal opens
   useEffect(() => {
     if (open) {
       setStage("confirm");
       setReleaseInfo(null);
-      setProgress({ downloaded: 0, total: 0, percentage: 0 });
+      setProgress({ major: javaMajor, downloaded: 0, total: 0, percentage: 0 });
+      setPhase(null);
       setError(null);
-      // Fetch release info
-      fetchReleaseInfo();
     }
   }, [open, javaMajor]);
 
+  useEffect(() => {
+    if (open) {
+      setReleaseInfo(null);
+      fetchReleaseInfo();
+    }
+  }, [open, javaMajor, imageType]);
+
   // Listen for progress events
   useEffect(() => {
     if (!open) return;
 
-    const unlisten = listen<DownloadProgress>("java-download-progress", (event) => {
+    const unlisten = listen<JavaDownloadProgress>("java-download-progress", (event) => {
+      // Other windows may be downloading a different major at the same time
+      if (event.payload.major !== javaMajor) return;
       setProgress(event.payload);
     });
+    const unlistenPhase = listen<JavaDownloadPhase>("java-download-phase", (event) => {
+      if (event.payload.major !== javaMajor) return;
+      setPhase(event.payload);
+      setStage(event.payload.phase === "downloading" ? "downloading" : "extracting");
+    });
 
     return () => {
       unlisten.then((fn) => fn());
+      unlistenPhase.then((fn) => fn());
     };
-  }, [open]);
+  }, [open, javaMajor]);
 
   const fetchReleaseInfo = async () => {
     try {
-      const info = await invoke<AdoptiumRelease>("fetch_adoptium_release_cmd", { javaMajor });
+      const info = await invoke<AdoptiumRelease>("fetch_adoptium_release_cmd", { javaMajor, imageType });
       setReleaseInfo(info);
     } catch (err) {
       setError(`Failed to fetch Java info: ${err}`);
@@ -73,7 +87,7 @@ export function JavaDownloadModal({ open, onClose, javaMajor, mcVersion, onSucce
     setError(null);
 
     try {
-      const javaPath = await invoke<string>("download_java_cmd", { javaMajor });
+      const javaPath = await invoke<string>("download_java_cmd", { javaMajor, imageType });
       setStage("done");
       setTimeout(() => {
         onSuccess(javaPath);
@@ -92,8 +106,28 @@ export function JavaDownloadModal({ open, onClose, javaMajor, mcVersion, onSucce
     return `${(bytes / (1024 * 1024 * 1024)).toFixed(2)} GB`;
   };
 
-  // Don't allow closing during download
-  const handleClose = stage === "downloading" ? () => {} : onClose;
+  const formatEta = (secs: number): string => {
+    if (secs < 60) return `${secs}s`;
+    return `${Math.floor(secs / 60)}m ${secs % 60}s`;
+  };
+
+  const phaseLabel = (): string => {
+    switch (phase?.phase) {
+      case "verifying":
+        return `Verifying the Java ${javaMajor} download...`;
+      case "extracting":
+        return phase.total > 0
+          ? `Extracting Java ${javaMajor}... (${phase.done} / ${phase.total} files)`
+          : `Extracting Java ${javaMajor}... (${phase.done} files)`;
+      case "validating":
+        return `Checking that Java ${javaMajor} runs...`;
+      default:
+        return `Extracting Java ${javaMajor}...`;
+    }
+  };
+
+  // Don't allow closing during download or install
+  const handleClose = stage === "downloading" || stage === "extracting" ? () => {} : onClose;
 
   return (
     <Modal open={open} onClose={handleClose} title="Java Required">
@@ -107,7 +141,9 @@ export function JavaDownloadModal({ open, onClose, javaMajor, mcVersion, onSucce
         {stage === "confirm" && (
           <>
             <p className="java-download-desc">
-              Minecraft {mcVersion} requires <strong>Java {javaMajor}</strong> which is not installed on your system.
+              {installedMajor
+                ? <>Minecraft {mcVersion} requires <strong>Java {javaMajor}</strong>, but the newest Java on your system is Java {installedMajor}.</>
+                : <>Minecraft {mcVersion} requires <strong>Java {javaMajor}</strong> which is not installed on your system.</>}
             </p>
             <p className="java-download-desc">
               Would you like to download and install it automatically from Eclipse Adoptium (Temurin)?
@@ -126,6 +162,15 @@ export function JavaDownloadModal({ open, onClose, javaMajor, mcVersion, onSucce
               </div>
             )}
 
+            <label className="checkbox-label">
+              <input
+                type="checkbox"
+                checked={imageType === "jdk"}
+                onChange={(e) => setImageType(e.target.checked ? "jdk" : "jre")}
+              />
+              Full JDK (includes javac, for mod development)
+            </label>
+
             <div className="java-download-actions">
               <button className="btn btn-secondary" onClick={onClose}>
                 Cancel
@@ -156,6 +201,8 @@ export function JavaDownloadModal({ open, onClose, javaMajor, mcVersion, onSucce
               </div>
               <div className="java-download-progress-text">
                 {formatSize(progress.downloaded)} / {formatSize(progress.total)} ({progress.percentage}%)
+                {progress.bytes_per_sec ? ` · ${formatSize(progress.bytes_per_sec)}/s` : ""}
+                {progress.eta_secs != null ? ` · ${formatEta(progress.eta_secs)} left` : ""}
               </div>
             </div>
 
@@ -168,7 +215,7 @@ export function JavaDownloadModal({ open, onClose, javaMajor, mcVersion, onSucce
         {stage === "extracting" && (
           <>
             <p className="java-download-desc">
-              Extracting Java {javaMajor}...
+              {phaseLabel()}
             </p>
             <div className="java-download-spinner" />
           </>
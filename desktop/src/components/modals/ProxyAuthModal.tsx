@@ -0,0 +1,68 @@
+import { useState, useEffect } from "react";
+import clsx from "clsx";
+import { invoke } from "@tauri-apps/api/core";
+import { Modal } from "../Modal";
+import { ModalFooter } from "../ModalFooter";
+import { Field } from "../Field";
+import { useAppStore } from "../../store";
+
+interface ProxyAuthModalProps {
+  open: boolean;
+  message?: string;
+  onClose: () => void;
+  onSuccess: () => void;
+}
+
+export function ProxyAuthModal({ open, message, onClose, onSuccess }: ProxyAuthModalProps) {
+  const { runAction } = useAppStore();
+
+  const [form, setForm] = useState({ username: "", password: "", remember: false });
+  const [error, setError] = useState<string | null>(null);
+
+  useEffect(() => {
+    if (open) {
+      setForm({ username: "", password: "", remember: false });
+      setError(null);
+    }
+  }, [open]);
+
+  const handleSubmit = async () => {
+    if (!form.username.trim()) {
+      setError("Required");
+      return;
+    }
+    await runAction(async () => {
+      await invoke("set_proxy_credentials_cmd", {
+        username: form.username.trim(),
+        password: form.password,
+        remember: form.remember,
+      });
+      onSuccess();
+    });
+  };
+
+  return (
+    <Modal open={open} onClose={onClose} title="Proxy sign-in">
+      <div style={{ display: "flex", flexDirection: "column", gap: 16 }}>
+        <p style={{ margin: 0, fontSize: 14, color: "rgba(255,255,255,0.6)" }}>
+          {message ?? "Your proxy requires authentication."} Enter your proxy credentials to retry.
+        </p>
+        <Field label="Username" error={error ?? undefined}>
+          <input className={clsx("input", error && "input-error")} value={form.username} onChange={(e) => setForm({ ...form, username: e.target.value })} autoComplete="username" />
+        </Field>
+        <Field label="Password">
+          <input className="input" type="password" value={form.password} onChange={(e) => setForm({ ...form, password: e.target.value })} autoComplete="current-password" />
+        </Field>
+        <label className="checkbox-label">
+          <input
+            type="checkbox"
+            checked={form.remember}
+            onChange={(e) => setForm({ ...form, remember: e.target.checked })}
+          />
+          <span>Remember in the system keychain</span>
+        </label>
+        <ModalFooter onCancel={onClose} onSubmit={handleSubmit} submitLabel="Retry" />
+      </div>
+    </Modal>
+  );
+}
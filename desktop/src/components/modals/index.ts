@@ -8,3 +8,4 @@ export { ProfileJsonModal } from "./ProfileJsonModal";
 export { AccountDetailsModal } from "./AccountDetailsModal";
 export { EditVersionModal } from "./EditVersionModal";
 export { JavaDownloadModal } from "./JavaDownloadModal";
+export { ProxyAuthModal } from "./ProxyAuthModal";
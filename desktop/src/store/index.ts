@@ -10,6 +10,7 @@ import type {
   ConfirmState,
   Toast,
   LaunchEvent,
+  GameOutputLine,
   DeviceCode,
   DiffResult,
   ManifestVersion,
@@ -18,6 +19,9 @@ import type {
   AccountInfo,
 } from "../types";
 
+// Lines of game output kept for the Console tab, across all running games
+const MAX_GAME_OUTPUT_LINES = 5000;
+
 // Helper to persist profile organization to disk via Tauri
 const saveProfileOrg = (org: ProfileOrganization) => {
   invoke("save_profile_organization_cmd", { organization: org }).catch((err) => {
@@ -42,6 +46,7 @@ interface AppState {
   activeModal: ModalType | null;
   toast: Toast | null;
   launchStatus: LaunchEvent | null;
+  gameOutput: GameOutputLine[];
   isWorking: boolean;
   confirmState: ConfirmState | null;
   debugDrag: boolean;
@@ -75,6 +80,8 @@ interface AppState {
   setActiveModal: (modal: ModalType | null) => void;
   setToast: (toast: Toast | null) => void;
   setLaunchStatus: (status: LaunchEvent | null) => void;
+  appendGameOutput: (line: GameOutputLine) => void;
+  clearGameOutput: (profileId: string) => void;
   setIsWorking: (working: boolean) => void;
   setConfirmState: (state: ConfirmState | null) => void;
   setDebugDrag: (debug: boolean) => void;
@@ -129,6 +136,7 @@ export const useAppStore = create<AppState>((set, get) => ({
   activeModal: null,
   toast: null,
   launchStatus: null,
+  gameOutput: [],
   isWorking: false,
   confirmState: null,
   debugDrag: false,
@@ -156,6 +164,12 @@ export const useAppStore = create<AppState>((set, get) => ({
   setActiveModal: (activeModal) => set({ activeModal }),
   setToast: (toast) => set({ toast }),
   setLaunchStatus: (launchStatus) => set({ launchStatus }),
+  appendGameOutput: (line) => set((state) => ({
+    gameOutput: [...state.gameOutput, line].slice(-MAX_GAME_OUTPUT_LINES),
+  })),
+  clearGameOutput: (profileId) => set((state) => ({
+    gameOutput: state.gameOutput.filter((line) => line.profile_id !== profileId),
+  })),
   setIsWorking: (isWorking) => set({ isWorking }),
   setConfirmState: (confirmState) => set({ confirmState }),
   setDebugDrag: (debugDrag) => set({ debugDrag }),
@@ -20,7 +20,22 @@ export type Runtime = {
   java?: string | null;
   memory?: string | null;
   args: string[];
-};
+  wrapper_command?: string[];
+  pre_launch?: string | null;
+  post_exit?: string | null;
+  /** Overrides the inherited environment; an empty value unsets the variable. */
+  env?: Record<string, string>;
+  width?: number | null;
+  height?: number | null;
+  fullscreen?: boolean;
+  /** Game directory instead of the instance folder; relative to it when not absolute. */
+  game_dir?: string | null;
+  /** GC flags appended at launch; unset keeps the JVM defaults. */
+  gc_preset?: GcPreset | null;
+};
+
+/** "zgc" needs Java 15+; the launch falls back to G1 on older Java. */
+export type GcPreset = "g1" | "zgc" | "aikar";
 
 export type Profile = {
   id: string;
@@ -32,6 +47,45 @@ export type Profile = {
   runtime: Runtime;
 };
 
+export type ArchivedDownload = {
+  kind: "mod" | "resourcepack" | "shaderpack";
+  hash: string;
+  url: string;
+  platform?: string | null;
+  project_id?: string | null;
+  version_id?: string | null;
+};
+
+export type InstanceManifest = {
+  format_version: number;
+  mc_version: string;
+  loader?: Loader | null;
+  profile: Profile;
+  downloads: ArchivedDownload[];
+  bundled: string[];
+  include_worlds: boolean;
+};
+
+export type InstanceImport = {
+  profile: Profile;
+  failed: { path: string; optional: boolean; error: string }[];
+};
+
+// A CurseForge pack file the author only allows on curseforge.com
+export type BlockedFile = {
+  project_id: number;
+  file_id: number;
+  name: string;
+  file_name: string;
+  /** Page to download the file from by hand. */
+  url: string;
+  required: boolean;
+};
+
+export type CurseForgeImport = InstanceImport & {
+  blocked: BlockedFile[];
+};
+
 export type Account = {
   uuid: string;
   username: string;
@@ -47,6 +101,22 @@ export type Config = {
   msa_client_id?: string | null;
   msa_client_secret?: string | null;
   auto_update_enabled?: boolean;
+  debug_logging?: boolean;
+  show_game_console?: boolean;
+};
+
+export type DownloadLimits = {
+  max_concurrent: number | null;
+  bytes_per_sec: number | null;
+};
+
+export type ServiceStatus = {
+  name: string;
+  url: string;
+  reachable: boolean;
+  latency_ms: number | null;
+  status: number | null;
+  error: string | null;
 };
 
 export type DeviceCode = {
@@ -58,13 +128,30 @@ export type DeviceCode = {
   interval: number;
 };
 
+// Quick Play target passed to launch_profile_cmd (Minecraft 1.20+)
+export type QuickPlay =
+  | { type: "multiplayer"; target: string }
+  | { type: "singleplayer"; target: string }
+  | { type: "realms"; target: string };
+
 export type LaunchPlan = {
   instance_dir: string;
+  wrapper: string[];
   java_exec: string;
   jvm_args: string[];
   classpath: string;
   main_class: string;
   game_args: string[];
+  warnings: string[];
+};
+
+/** Command a launch would run; the access token is redacted. */
+export type LaunchPreview = {
+  program: string;
+  args: string[];
+  working_dir: string;
+  /** Changes to the inherited environment; null removes the variable. */
+  env: Record<string, string | null>;
 };
 
 export type DiffResult = {
@@ -76,6 +163,14 @@ export type DiffResult = {
 export type LaunchEvent = {
   stage: string;
   message?: string | null;
+  profile_id?: string;
+};
+
+// Payload of the "game-output" event, one line of a running game's output
+export type GameOutputLine = {
+  profile_id: string;
+  stream: "stdout" | "stderr";
+  line: string;
 };
 
 export type ManifestVersion = {
@@ -102,7 +197,8 @@ export type ModalType =
   | "account-details"
   | "skin-upload"
   | "logs"
-  | "store";
+  | "store"
+  | "proxy-auth";
 
 export type SidebarView = "profiles" | "accounts" | "store" | "logs" | "library" | "settings";
 
@@ -198,15 +294,65 @@ export type JavaInstallation = {
   is_valid: boolean;
 };
 
+export type JavaDetection = {
+  installations: JavaInstallation[];
+  /** Problems such as a JAVA_HOME that does not lead to a working Java. */
+  diagnostics: string[];
+};
+
+// Result of find_compatible_java_detailed_cmd
+export type JavaResolution =
+  | { kind: "system"; path: string; major: number }
+  | { kind: "managed"; path: string; major: number }
+  | { kind: "none_installed"; required_major: number; downloads_disabled: boolean }
+  | { kind: "too_old"; installed_major: number; required_major: number; downloads_disabled: boolean }
+  | { kind: "managed_broken"; required_major: number; downloads_disabled: boolean };
+
+export type JavaGroup = {
+  major: number;
+  label: string;
+  preferred: JavaInstallation;
+  alternates: JavaInstallation[];
+  /** Set when the preferred Java is 32-bit on a 64-bit machine. */
+  warning?: string | null;
+};
+
 export type JavaValidation = {
   is_valid: boolean;
   version?: string | null;
   major?: number | null;
   vendor?: string | null;
   arch?: string | null;
+  /** False for a JRE; some features need a full JDK. */
+  is_jdk: boolean;
+  /** 32 or 64 when the architecture is known. */
+  bits?: number | null;
+  /** Risk worth explaining for a working Java, e.g. 32-bit on a 64-bit machine. */
+  warning?: string | null;
   error?: string | null;
+  /** The java executable found inside, when a folder was validated. */
+  resolved_path?: string | null;
+};
+
+// Java download events; every payload carries the Java major it belongs to
+export type JavaDownloadProgress = {
+  major: number;
+  downloaded: number;
+  total: number;
+  percentage: number;
+  bytes_per_sec?: number;
+  eta_secs?: number | null;
 };
 
+/** Install step after the download; extraction counts archive entries (total 0 when unknown). */
+export type JavaDownloadPhase =
+  | { major: number; phase: "downloading" | "verifying" | "validating" }
+  | { major: number; phase: "extracting"; done: number; total: number };
+
+export type JavaDownloadDone = { major: number; java_path: string };
+export type JavaDownloadError = { major: number; error: string };
+export type JavaDownloadCancelled = { major: number };
+
 // Content store types - matches Rust ContentItem
 export type StoreProject = {
   id: string;
@@ -241,6 +387,21 @@ export type StoreVersion = {
   release_type: string;
 };
 
+// Modrinth install plan - matches Rust InstallPlan
+export type PlannedVersion = {
+  project_id: string;
+  title: string;
+  version_id: string;
+  version_number: string;
+  required_by?: string | null;
+};
+
+export type InstallPlan = {
+  root: PlannedVersion;
+  dependencies: PlannedVersion[];
+  conflicts: { project_id: string; message: string }[];
+};
+
 // Logs types
 export type LogLevel = "debug" | "info" | "warn" | "error" | "fatal" | "unknown";
 
@@ -364,6 +525,26 @@ export type PurgeResult = {
   errors: string[];
 };
 
+// Mod conflict scan - matches Rust ModScan
+export type ModJar = {
+  content_name?: string | null;
+  file_name: string;
+  path: string;
+};
+
+export type ModConflict = {
+  kind: "duplicate" | "incompatible";
+  message: string;
+  jars: { jar: ModJar; mod_id: string; version: string }[];
+  keep?: ModJar | null;
+};
+
+export type ModScan = {
+  conflicts: ModConflict[];
+  not_mods: ModJar[];
+  unreadable: [ModJar, string][];
+};
+
 // Update checking types
 export type ContentUpdate = {
   profile_id: string;
@@ -375,9 +556,18 @@ export type ContentUpdate = {
   changelog?: string | null;
 };
 
+// Matches Rust ModUpdate
+export type ModUpdate = {
+  mod: ContentRef;
+  current?: string | null;
+  latest: StoreVersion;
+};
+
 export type UpdateCheckResult = {
   updates: ContentUpdate[];
   checked: number;
   skipped: number;
   errors: string[];
 };
+
+export type TitlebarMode = "auto" | "native" | "custom";
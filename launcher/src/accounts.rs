@@ -65,15 +65,19 @@ struct StoredAccount {
     pub xuid: Option<String>,
 }
 
+/// Tokens are refreshed once they are this close to expiring, so a launch never
+/// starts with a token that runs out mid-session handshake.
+pub const TOKEN_REFRESH_MARGIN_SECS: u64 = 5 * 60;
+
 impl MsaTokens {
     pub fn is_expired(&self) -> bool {
-        now_epoch_secs() + 60 >= self.expires_at
+        now_epoch_secs() + TOKEN_REFRESH_MARGIN_SECS >= self.expires_at
     }
 }
 
 impl MinecraftTokens {
     pub fn is_expired(&self) -> bool {
-        now_epoch_secs() + 60 >= self.expires_at
+        now_epoch_secs() + TOKEN_REFRESH_MARGIN_SECS >= self.expires_at
     }
 }
 
@@ -314,6 +318,7 @@ pub fn load_accounts(paths: &Paths) -> Result<Accounts> {
             minecraft: tokens.minecraft,
         });
     }
+    repair_active(&mut accounts);
     Ok(accounts)
 }
 
@@ -342,6 +347,7 @@ pub fn find_account_mut<'a>(accounts: &'a mut Accounts, id: &str) -> Option<&'a
         .find(|account| matches_account(account, id, &id_lower))
 }
 
+/// Add or replace an account by UUID. The first account added becomes active.
 pub fn upsert_account(accounts: &mut Accounts, account: Account) {
     if let Some(existing) = accounts
         .accounts
@@ -352,10 +358,14 @@ pub fn upsert_account(accounts: &mut Accounts, account: Account) {
     } else {
         accounts.accounts.push(account);
     }
+    repair_active(accounts);
 }
 
 /// Removes accounts matching the given ID (UUID or username) and returns their UUIDs.
 /// Returns an empty vector if no accounts were found.
+///
+/// If the active account is removed, the first remaining account becomes active,
+/// or no account is active when none are left.
 pub fn remove_account(accounts: &mut Accounts, id: &str) -> Vec<String> {
     let id_lower = id.to_lowercase();
     let removed_uuids: Vec<String> = accounts
@@ -368,14 +378,22 @@ pub fn remove_account(accounts: &mut Accounts, id: &str) -> Vec<String> {
     accounts
         .accounts
         .retain(|account| !removed_uuids.contains(&account.uuid));
-    if let Some(active) = accounts.active.as_deref()
-        && removed_uuids.iter().any(|uuid| uuid == active)
-    {
-        accounts.active = None;
-    }
+    repair_active(accounts);
     removed_uuids
 }
 
+/// Point `active` at an existing account: a missing or dangling id falls back
+/// to the first account, and is cleared when there are no accounts.
+fn repair_active(accounts: &mut Accounts) {
+    let valid = accounts
+        .active
+        .as_deref()
+        .is_some_and(|active| accounts.accounts.iter().any(|account| account.uuid == active));
+    if !valid {
+        accounts.active = accounts.accounts.first().map(|account| account.uuid.clone());
+    }
+}
+
 pub fn set_active(accounts: &mut Accounts, id: &str) -> bool {
     let id_lower = id.to_lowercase();
     if let Some(uuid) = accounts
@@ -389,3 +407,45 @@ pub fn set_active(accounts: &mut Accounts, id: &str) -> bool {
     }
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(uuid: &str, username: &str) -> Account {
+        Account {
+            uuid: uuid.to_string(),
+            username: username.to_string(),
+            xuid: None,
+            msa: MsaTokens {
+                access_token: String::new(),
+                refresh_token: String::new(),
+                expires_at: 0,
+            },
+            minecraft: MinecraftTokens {
+                access_token: String::new(),
+                expires_at: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_removing_the_active_account_promotes_another() {
+        let mut accounts = Accounts::default();
+        upsert_account(&mut accounts, account("a", "Alice"));
+        upsert_account(&mut accounts, account("b", "Bob"));
+        assert_eq!(accounts.active.as_deref(), Some("a"));
+
+        assert!(set_active(&mut accounts, "bob"));
+        assert_eq!(remove_account(&mut accounts, "b"), vec!["b".to_string()]);
+        assert_eq!(accounts.active.as_deref(), Some("a"));
+
+        // Removing a non-active account leaves the selection alone
+        upsert_account(&mut accounts, account("c", "Carol"));
+        remove_account(&mut accounts, "Carol");
+        assert_eq!(accounts.active.as_deref(), Some("a"));
+
+        remove_account(&mut accounts, "a");
+        assert_eq!(accounts.active, None);
+    }
+}
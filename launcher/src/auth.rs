@@ -13,6 +13,67 @@ const XBL_AUTH_URL: &str = "https://user.auth.xboxlive.com/user/authenticate";
 const XSTS_AUTH_URL: &str = "https://xsts.auth.xboxlive.com/xsts/authorize";
 const MC_LOGIN_URL: &str = "https://api.minecraftservices.com/authentication/login_with_xbox";
 const MC_PROFILE_URL: &str = "https://api.minecraftservices.com/minecraft/profile";
+const MC_ENTITLEMENTS_URL: &str = "https://api.minecraftservices.com/entitlements/mcstore";
+
+/// Account states that block sign-in and need action from the user.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthProblem {
+    /// The Microsoft account has never signed in to Xbox.
+    NoXboxAccount,
+    /// Xbox Live is not available in the account's country.
+    RegionUnavailable,
+    /// Adult verification is required (South Korea).
+    AdultVerificationRequired,
+    /// A child account that must be added to a Microsoft family by an adult.
+    ChildAccount,
+    /// The account does not own Minecraft: Java Edition.
+    NoEntitlement,
+    /// The game is owned but no Minecraft profile (username) was created yet.
+    NoProfile,
+}
+
+impl AuthProblem {
+    fn from_xerr(xerr: i64) -> Option<Self> {
+        match xerr {
+            2148916233 => Some(Self::NoXboxAccount),
+            2148916235 => Some(Self::RegionUnavailable),
+            2148916236 | 2148916237 => Some(Self::AdultVerificationRequired),
+            2148916238 => Some(Self::ChildAccount),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for AuthProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::NoXboxAccount => {
+                "This Microsoft account has no Xbox profile. Sign in once at https://www.xbox.com to create one, then retry."
+            }
+            Self::RegionUnavailable => "Xbox Live is not available in this account's country or region.",
+            Self::AdultVerificationRequired => {
+                "This account needs adult verification on the Xbox website before it can sign in."
+            }
+            Self::ChildAccount => {
+                "This is a child account. An adult must add it to a Microsoft family at https://account.microsoft.com/family, then retry."
+            }
+            Self::NoEntitlement => {
+                "This Microsoft account does not own Minecraft: Java Edition. Buy it at https://www.minecraft.net or sign in with the account that owns it."
+            }
+            Self::NoProfile => {
+                "Minecraft is owned but has no profile yet. Pick a username at https://www.minecraft.net/msaprofile, then retry."
+            }
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for AuthProblem {}
+
+/// The [`AuthProblem`] behind a sign-in error, if any.
+pub fn auth_problem(err: &anyhow::Error) -> Option<AuthProblem> {
+    err.chain().find_map(|cause| cause.downcast_ref::<AuthProblem>().copied())
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DeviceCode {
@@ -144,7 +205,7 @@ struct McProfile {
 }
 
 pub fn request_device_code(client_id: &str, client_secret: Option<&str>) -> Result<DeviceCode> {
-    let client = Client::new();
+    let client = crate::http::client()?;
     let scope = "XboxLive.signin offline_access";
     let mut params = vec![("client_id", client_id), ("scope", scope)];
     if let Some(secret) = client_secret {
@@ -179,7 +240,7 @@ pub fn poll_device_code(
     client_secret: Option<&str>,
     device: &DeviceCode,
 ) -> Result<OAuthToken> {
-    let client = Client::new();
+    let client = crate::http::client()?;
     let mut interval = device.interval;
     let deadline = now_epoch_secs() + device.expires_in;
 
@@ -245,7 +306,7 @@ pub fn refresh_msa_token(
     client_secret: Option<&str>,
     refresh_token: &str,
 ) -> Result<OAuthToken> {
-    let client = Client::new();
+    let client = crate::http::client()?;
     let mut params = vec![
         ("grant_type", "refresh_token"),
         ("client_id", client_id),
@@ -301,7 +362,7 @@ pub fn exchange_for_minecraft(ms_access_token: &str) -> Result<MinecraftAuth> {
 }
 
 fn xbox_live_auth(ms_access_token: &str) -> Result<(String, String, Option<String>)> {
-    let client = Client::new();
+    let client = crate::http::client()?;
     let body = XblRequest {
         properties: XblProperties {
             auth_method: "RPS",
@@ -334,7 +395,7 @@ fn xbox_live_auth(ms_access_token: &str) -> Result<(String, String, Option<Strin
 }
 
 fn xsts_auth(xbl_token: &str) -> Result<(String, String, Option<String>)> {
-    let client = Client::new();
+    let client = crate::http::client()?;
     let body = XstsRequest {
         properties: XstsProperties {
             sandbox_id: "RETAIL",
@@ -366,7 +427,7 @@ fn xsts_auth(xbl_token: &str) -> Result<(String, String, Option<String>)> {
 }
 
 fn minecraft_login(xsts_token: &str, user_hash: &str) -> Result<MinecraftToken> {
-    let client = Client::new();
+    let client = crate::http::client()?;
     let identity_token = format!("XBL3.0 x={user_hash};{xsts_token}");
     let body = McLoginRequest {
         identity_token,
@@ -396,18 +457,59 @@ fn minecraft_login(xsts_token: &str, user_hash: &str) -> Result<MinecraftToken>
 }
 
 fn minecraft_profile(access_token: &str) -> Result<McProfile> {
-    let client = Client::new();
+    let client = crate::http::client()?;
     let resp = client
         .get(MC_PROFILE_URL)
         .bearer_auth(access_token)
         .send()
-        .context("failed minecraft profile request")?
+        .context("failed minecraft profile request")?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        // No profile: either the game isn't owned, or no username was chosen yet
+        return Err(if owns_minecraft(&client, access_token)? {
+            AuthProblem::NoProfile.into()
+        } else {
+            AuthProblem::NoEntitlement.into()
+        });
+    }
+    let resp = resp
         .error_for_status()
-        .context("minecraft profile request failed (does the account own Minecraft?)")?;
+        .context("minecraft profile request failed")?;
     let profile: McProfile = resp.json().context("failed to parse minecraft profile")?;
     Ok(profile)
 }
 
+#[derive(Debug, Deserialize)]
+struct Entitlements {
+    #[serde(default)]
+    items: Vec<EntitlementItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EntitlementItem {
+    name: String,
+}
+
+/// Whether the account's store entitlements include Minecraft: Java Edition.
+fn owns_minecraft(client: &Client, access_token: &str) -> Result<bool> {
+    let entitlements: Entitlements = client
+        .get(MC_ENTITLEMENTS_URL)
+        .bearer_auth(access_token)
+        .send()
+        .context("failed minecraft entitlements request")?
+        .error_for_status()
+        .context("minecraft entitlements request failed")?
+        .json()
+        .context("failed to parse minecraft entitlements")?;
+    Ok(has_java_entitlement(&entitlements))
+}
+
+fn has_java_entitlement(entitlements: &Entitlements) -> bool {
+    entitlements
+        .items
+        .iter()
+        .any(|item| item.name == "game_minecraft" || item.name == "product_minecraft")
+}
+
 struct MinecraftToken {
     access_token: String,
     expires_at: u64,
@@ -445,22 +547,38 @@ fn format_xsts_error(prefix: &str, resp: reqwest::blocking::Response) -> anyhow:
         .and_then(|v| v.as_str())
         .unwrap_or("unknown error");
     let xerr = body.get("XErr").and_then(|v| v.as_i64());
-    let hint = match xerr {
-        Some(2148916233) => Some(
-            "This account has no Xbox Live account. Sign in at https://xbox.com and accept terms, then retry.",
-        ),
-        Some(2148916235) => Some(
-            "This account is in a family or underage. Update Xbox privacy/family settings, then retry.",
-        ),
-        Some(2148916236) => {
-            Some("This account is blocked by region. Check account region settings, then retry.")
+
+    match xerr.and_then(AuthProblem::from_xerr) {
+        Some(problem) => {
+            // The problem itself is the user-facing message; keep the raw reply in the log
+            tracing::warn!("{prefix}: {status} {message} (XErr={xerr:?})");
+            problem.into()
         }
-        _ => None,
-    };
+        None => anyhow::anyhow!("{prefix}: {status} {message} (XErr={xerr:?})"),
+    }
+}
 
-    if let Some(hint) = hint {
-        anyhow::anyhow!("{prefix}: {status} {message} (XErr={xerr:?}). {hint}")
-    } else {
-        anyhow::anyhow!("{prefix}: {status} {message} (XErr={xerr:?})")
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xsts_errors_map_to_actionable_problems() {
+        assert_eq!(AuthProblem::from_xerr(2148916238), Some(AuthProblem::ChildAccount));
+        assert_eq!(AuthProblem::from_xerr(2148916233), Some(AuthProblem::NoXboxAccount));
+        assert_eq!(AuthProblem::from_xerr(1), None);
+
+        let err = anyhow::Error::new(AuthProblem::ChildAccount).context("sign-in failed");
+        assert_eq!(auth_problem(&err), Some(AuthProblem::ChildAccount));
+        assert!(auth_problem(&anyhow::anyhow!("network down")).is_none());
+    }
+
+    #[test]
+    fn test_entitlements_recognize_java_edition() {
+        let owned: Entitlements =
+            serde_json::from_str(r#"{"items":[{"name":"product_minecraft"},{"name":"game_minecraft"}]}"#).unwrap();
+        let empty: Entitlements = serde_json::from_str(r#"{"items":[]}"#).unwrap();
+        assert!(has_java_entitlement(&owned));
+        assert!(!has_java_entitlement(&empty));
     }
 }
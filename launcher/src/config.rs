@@ -1,14 +1,19 @@
+use crate::http::{DownloadLimits, ProxySettings};
 use crate::paths::Paths;
 use anyhow::{Context, Result};
 use keyring::{Entry, Error as KeyringError};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
 
 const KEYRING_SERVICE: &str = "shard";
 const MSA_CLIENT_SECRET_KEY: &str = "config:msa_client_secret";
 const CURSEFORGE_API_KEY: &str = "config:curseforge_api_key";
 
+/// Startup watchdog timeout used when the config does not set one.
+pub const DEFAULT_STARTUP_TIMEOUT_SECS: u64 = 180;
+
 /// Microsoft Client ID baked in at compile time (for release builds)
 const BUILTIN_MS_CLIENT_ID: Option<&str> = option_env!("SHARD_MS_CLIENT_ID");
 
@@ -26,6 +31,77 @@ pub struct Config {
     /// Whether to automatically check for content updates on launcher start
     #[serde(default = "default_auto_update")]
     pub auto_update_enabled: bool,
+    /// Whether launcher diagnostics are logged at debug level by default
+    #[serde(default)]
+    pub debug_logging: bool,
+    /// Mirror base URLs tried (fastest first) before upstream hosts for large downloads.
+    /// A mirror serves each file under its upstream host: `<mirror>/<host>/<path>`.
+    #[serde(default)]
+    pub download_mirrors: Vec<String>,
+    /// Self-hosted mirror of the Adoptium API (https), used instead of api.adoptium.net
+    #[serde(default)]
+    pub adoptium_base_url: Option<String>,
+    /// Proxy URL used instead of HTTPS_PROXY/HTTP_PROXY
+    #[serde(default)]
+    pub proxy_url: Option<String>,
+    /// Connect directly even when a proxy is configured or set in the environment
+    #[serde(default)]
+    pub disable_proxy: bool,
+    /// How the desktop window's title bar is drawn on Linux
+    #[serde(default)]
+    pub titlebar: TitlebarMode,
+    /// Seconds to wait for the game window before offering to stop the game
+    /// (unset: 180, 0: never)
+    #[serde(default)]
+    pub startup_timeout_secs: Option<u64>,
+    /// Keep a console window open for the game on Windows (java.exe instead of javaw.exe)
+    #[serde(default)]
+    pub show_game_console: bool,
+    /// Downloads running at once across the launcher (unset: no global cap)
+    #[serde(default)]
+    pub max_concurrent_downloads: Option<usize>,
+    /// Combined download speed cap in bytes per second (unset: unlimited)
+    #[serde(default)]
+    pub download_bytes_per_sec: Option<u64>,
+}
+
+/// Title bar style for the desktop window on Linux.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TitlebarMode {
+    /// Draw our own title bar when the desktop does not decorate the window
+    #[default]
+    Auto,
+    /// Always use the window manager's decorations
+    Native,
+    /// Always draw our own title bar and window controls
+    Custom,
+}
+
+impl Config {
+    /// Proxy settings to hand to [`crate::http::set_proxy_settings`].
+    pub fn proxy_settings(&self) -> ProxySettings {
+        ProxySettings {
+            url: self.proxy_url.clone(),
+            disabled: self.disable_proxy,
+        }
+    }
+
+    /// Download limits to hand to [`crate::http::set_download_limits`].
+    pub fn download_limits(&self) -> DownloadLimits {
+        DownloadLimits {
+            max_concurrent: self.max_concurrent_downloads,
+            bytes_per_sec: self.download_bytes_per_sec,
+        }
+    }
+
+    /// Startup watchdog timeout for [`crate::minecraft::LaunchOptions::startup_timeout`].
+    pub fn startup_timeout(&self) -> Option<Duration> {
+        match self.startup_timeout_secs.unwrap_or(DEFAULT_STARTUP_TIMEOUT_SECS) {
+            0 => None,
+            secs => Some(Duration::from_secs(secs)),
+        }
+    }
 }
 
 fn default_auto_update() -> bool {
@@ -168,6 +244,16 @@ pub fn save_config(paths: &Paths, config: &Config) -> Result<()> {
         msa_client_secret: None,
         curseforge_api_key: None,
         auto_update_enabled: config.auto_update_enabled,
+        debug_logging: config.debug_logging,
+        download_mirrors: config.download_mirrors.clone(),
+        adoptium_base_url: config.adoptium_base_url.clone(),
+        proxy_url: config.proxy_url.clone(),
+        disable_proxy: config.disable_proxy,
+        titlebar: config.titlebar,
+        startup_timeout_secs: config.startup_timeout_secs,
+        show_game_console: config.show_game_console,
+        max_concurrent_downloads: config.max_concurrent_downloads,
+        download_bytes_per_sec: config.download_bytes_per_sec,
     };
     let data = serde_json::to_string_pretty(&scrubbed).context("failed to serialize config")?;
     fs::write(&paths.config, data)
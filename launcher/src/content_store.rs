@@ -4,7 +4,7 @@
 //! content from multiple sources.
 
 use crate::curseforge::{self, CurseForgeClient, ModLoaderType};
-use crate::modrinth::{ModrinthClient, ProjectType, SearchFacets};
+use crate::modrinth::{ModrinthClient, ProjectType, SearchFacets, Version as ModrinthVersion};
 use crate::paths::Paths;
 use crate::store::store_from_url;
 use anyhow::{Context, Result};
@@ -165,19 +165,19 @@ pub struct ContentStore {
 
 impl ContentStore {
     /// Create a new content store
-    pub fn new(curseforge_api_key: Option<&str>) -> Self {
-        Self {
-            modrinth: ModrinthClient::new(),
-            curseforge: curseforge_api_key.map(CurseForgeClient::new),
-        }
+    pub fn new(curseforge_api_key: Option<&str>) -> Result<Self> {
+        Ok(Self {
+            modrinth: ModrinthClient::new()?,
+            curseforge: curseforge_api_key.map(CurseForgeClient::new).transpose()?,
+        })
     }
 
     /// Create with only Modrinth (no API key required)
-    pub fn modrinth_only() -> Self {
-        Self {
-            modrinth: ModrinthClient::new(),
+    pub fn modrinth_only() -> Result<Self> {
+        Ok(Self {
+            modrinth: ModrinthClient::new()?,
             curseforge: None,
-        }
+        })
     }
 
     /// Search across all available platforms
@@ -396,37 +396,7 @@ impl ContentStore {
                     .modrinth
                     .get_compatible_versions(id, game_version, loader)?;
 
-                Ok(versions
-                    .into_iter()
-                    .filter_map(|v| {
-                        let file = ModrinthClient::get_primary_file(&v)?.clone();
-                        Some(ContentVersion {
-                            id: v.id,
-                            project_id: v.project_id,
-                            name: v.name,
-                            version: v.version_number,
-                            download_url: file.url,
-                            filename: file.filename,
-                            size: file.size,
-                            sha256: None,
-                            sha1: Some(file.hashes.sha1),
-                            platform: Platform::Modrinth,
-                            game_versions: v.game_versions,
-                            loaders: v.loaders,
-                            release_type: v.version_type,
-                            dependencies: v
-                                .dependencies
-                                .into_iter()
-                                .filter_map(|d| {
-                                    Some(ContentDependency {
-                                        project_id: d.project_id?,
-                                        dependency_type: d.dependency_type,
-                                    })
-                                })
-                                .collect(),
-                        })
-                    })
-                    .collect())
+                Ok(versions.into_iter().filter_map(modrinth_content_version).collect())
             }
             Platform::CurseForge => {
                 let cf = self
@@ -536,12 +506,42 @@ impl ContentStore {
     }
 }
 
+/// Convert a Modrinth version into a [`ContentVersion`] using its primary file.
+pub fn modrinth_content_version(v: ModrinthVersion) -> Option<ContentVersion> {
+    let file = ModrinthClient::get_primary_file(&v)?.clone();
+    Some(ContentVersion {
+        id: v.id,
+        project_id: v.project_id,
+        name: v.name,
+        version: v.version_number,
+        download_url: file.url,
+        filename: file.filename,
+        size: file.size,
+        sha256: None,
+        sha1: Some(file.hashes.sha1),
+        platform: Platform::Modrinth,
+        game_versions: v.game_versions,
+        loaders: v.loaders,
+        release_type: v.version_type,
+        dependencies: v
+            .dependencies
+            .into_iter()
+            .filter_map(|d| {
+                Some(ContentDependency {
+                    project_id: d.project_id?,
+                    dependency_type: d.dependency_type,
+                })
+            })
+            .collect(),
+    })
+}
+
 /// Convenience functions for direct Modrinth access
 pub mod modrinth_helpers {
     use super::*;
 
     pub fn search_mods(query: &str, game_version: Option<&str>, loader: Option<&str>) -> Result<Vec<ContentItem>> {
-        let store = ContentStore::modrinth_only();
+        let store = ContentStore::modrinth_only()?;
         store.search_modrinth(&SearchOptions {
             query: query.to_string(),
             content_type: Some(ContentType::Mod),
@@ -553,7 +553,7 @@ pub mod modrinth_helpers {
     }
 
     pub fn search_shaders(query: &str, game_version: Option<&str>) -> Result<Vec<ContentItem>> {
-        let store = ContentStore::modrinth_only();
+        let store = ContentStore::modrinth_only()?;
         store.search_modrinth(&SearchOptions {
             query: query.to_string(),
             content_type: Some(ContentType::ShaderPack),
@@ -565,7 +565,7 @@ pub mod modrinth_helpers {
     }
 
     pub fn search_resourcepacks(query: &str, game_version: Option<&str>) -> Result<Vec<ContentItem>> {
-        let store = ContentStore::modrinth_only();
+        let store = ContentStore::modrinth_only()?;
         store.search_modrinth(&SearchOptions {
             query: query.to_string(),
             content_type: Some(ContentType::ResourcePack),
@@ -577,7 +577,7 @@ pub mod modrinth_helpers {
     }
 
     pub fn get_project(id_or_slug: &str) -> Result<ContentItem> {
-        let store = ContentStore::modrinth_only();
+        let store = ContentStore::modrinth_only()?;
         store.get_project(Platform::Modrinth, id_or_slug)
     }
 
@@ -586,7 +586,7 @@ pub mod modrinth_helpers {
         game_version: Option<&str>,
         loader: Option<&str>,
     ) -> Result<ContentVersion> {
-        let store = ContentStore::modrinth_only();
+        let store = ContentStore::modrinth_only()?;
         store.get_latest_version(Platform::Modrinth, id_or_slug, game_version, loader)
     }
 }
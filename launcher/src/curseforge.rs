@@ -262,20 +262,22 @@ pub struct CurseForgeClient {
 }
 
 impl CurseForgeClient {
-    pub fn new(api_key: &str) -> Self {
+    /// Fails when the API key is not a valid header value or the configured
+    /// proxy URL is malformed.
+    pub fn new(api_key: &str) -> Result<Self> {
         let mut headers = HeaderMap::new();
         headers.insert(USER_AGENT, HeaderValue::from_static(USER_AGENT_VALUE));
         headers.insert(
             "x-api-key",
-            HeaderValue::from_str(api_key).expect("invalid API key"),
+            HeaderValue::from_str(api_key).context("invalid CurseForge API key")?,
         );
 
-        let client = Client::builder()
+        let client = crate::http::client_builder()?
             .default_headers(headers)
             .build()
-            .expect("failed to build HTTP client");
+            .context("failed to build HTTP client")?;
 
-        Self { client }
+        Ok(Self { client })
     }
 
     /// Search for mods
@@ -427,6 +429,40 @@ impl CurseForgeClient {
         Ok(response.data)
     }
 
+    /// Get multiple files by IDs; unknown IDs are left out of the result
+    pub fn get_files(&self, file_ids: &[u32]) -> Result<Vec<File>> {
+        if file_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct GetFilesBody {
+            file_ids: Vec<u32>,
+        }
+
+        let url = format!("{}/mods/files", API_BASE);
+
+        let resp = self
+            .client
+            .post(&url)
+            .json(&GetFilesBody {
+                file_ids: file_ids.to_vec(),
+            })
+            .send()
+            .context("failed to fetch files")?
+            .error_for_status()
+            .context("CurseForge request failed")?;
+
+        #[derive(Deserialize)]
+        struct FilesByIdResponse {
+            data: Vec<File>,
+        }
+
+        let response: FilesByIdResponse = resp.json().context("failed to parse files")?;
+        Ok(response.data)
+    }
+
     /// Get the latest file for a mod
     pub fn get_latest_file(
         &self,
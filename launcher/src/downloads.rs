@@ -0,0 +1,334 @@
+//! Concurrent download of many small files (libraries, asset objects)
+//!
+//! [`download_all`] fetches a batch of [`DownloadEntry`]s over a bounded pool of
+//! worker threads sharing one HTTP client. Each file is written to a `.part`
+//! file next to its destination and moved into place only once its size and
+//! sha1 check out, so a destination never holds a partial file. A `.part` left
+//! by an interrupted run is resumed with a range request. Files already present
+//! with the right hash are skipped, and configured mirrors are tried first. A
+//! download that arrives corrupt is fetched once more before failing with
+//! [`ChecksumMismatch`]. Each transfer also holds an [`http::download_slot`] and
+//! is throttled by [`http::throttle_download`], so the process-wide limits apply
+//! on top of the worker count.
+
+use crate::http;
+use anyhow::{Context, Result};
+use reqwest::StatusCode;
+use reqwest::blocking::Client;
+use serde::Serialize;
+use sha1::{Digest, Sha1};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+/// Workers used when the caller has no reason to pick another number.
+pub const DEFAULT_CONCURRENCY: usize = 8;
+
+/// A file to download.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadEntry {
+    pub url: String,
+    pub dest: PathBuf,
+    /// Expected sha1 (hex); unchecked when absent.
+    pub sha1: Option<String>,
+    /// Expected size in bytes; unchecked when absent.
+    pub size: Option<u64>,
+}
+
+/// What happened to one entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryStatus {
+    Downloaded,
+    /// The destination already held the expected file.
+    AlreadyPresent,
+}
+
+/// Error returned when a downloaded file does not match its expected size or sha1,
+/// even after downloading it again.
+#[derive(Debug, Clone)]
+pub struct ChecksumMismatch {
+    pub path: PathBuf,
+}
+
+impl std::fmt::Display for ChecksumMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "size or sha1 mismatch for {}", self.path.display())
+    }
+}
+
+impl std::error::Error for ChecksumMismatch {}
+
+/// Progress across a whole batch, reported after each entry finishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BatchProgress {
+    /// Entries finished so far, successfully or not.
+    pub finished: usize,
+    pub total: usize,
+    /// Bytes of finished entries, including ones already present.
+    pub bytes: u64,
+    /// Sum of the expected sizes that are known.
+    pub total_bytes: u64,
+}
+
+/// Download every entry using up to `concurrency` workers.
+///
+/// Returns one result per entry, in the order given. A failed entry does not
+/// stop the others; callers decide whether any failure is fatal.
+pub fn download_all(
+    entries: &[DownloadEntry],
+    concurrency: usize,
+    on_progress: Option<&(dyn Fn(BatchProgress) + Sync)>,
+) -> Vec<Result<EntryStatus>> {
+    if entries.is_empty() {
+        return Vec::new();
+    }
+    let client = match http::download_client() {
+        Ok(client) => client,
+        Err(err) => return entries.iter().map(|_| Err(anyhow::anyhow!("{err:#}"))).collect(),
+    };
+    let mirrors = http::ordered_mirrors();
+    let total_bytes = entries.iter().filter_map(|entry| entry.size).sum();
+
+    let next = AtomicUsize::new(0);
+    let finished = AtomicUsize::new(0);
+    let bytes = AtomicU64::new(0);
+    let results: Mutex<Vec<Option<Result<EntryStatus>>>> = Mutex::new(entries.iter().map(|_| None).collect());
+    let workers = concurrency.max(1).min(entries.len());
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                let Some(entry) = entries.get(idx) else { break };
+                let result = download_entry(&client, entry, &mirrors);
+                let len = fs::metadata(&entry.dest).map(|m| m.len()).unwrap_or(0);
+                let progress = BatchProgress {
+                    finished: finished.fetch_add(1, Ordering::Relaxed) + 1,
+                    total: entries.len(),
+                    bytes: bytes.fetch_add(len, Ordering::Relaxed) + len,
+                    total_bytes,
+                };
+                if let Ok(mut results) = results.lock() {
+                    results[idx] = Some(result);
+                }
+                if let Some(on_progress) = on_progress {
+                    on_progress(progress);
+                }
+            });
+        }
+    });
+
+    results
+        .into_inner()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|result| result.unwrap_or_else(|| Err(anyhow::anyhow!("download worker stopped"))))
+        .collect()
+}
+
+fn download_entry(client: &Client, entry: &DownloadEntry, mirrors: &[String]) -> Result<EntryStatus> {
+    if is_intact(&entry.dest, entry.sha1.as_deref(), entry.size) {
+        return Ok(EntryStatus::AlreadyPresent);
+    }
+    if let Some(parent) = entry.dest.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("failed to create dir: {}", parent.display()))?;
+    }
+    let _slot = http::download_slot();
+    let fetch = || http::with_mirror_failover(&entry.url, mirrors, |url| fetch_to_part(client, url, entry));
+    let ((), served_by) = match fetch() {
+        // A corrupt transfer is usually a one-off; the bad .part is already gone
+        Err(err) if err.chain().any(|cause| cause.is::<ChecksumMismatch>()) => {
+            tracing::warn!("{err:#}; downloading {} again", entry.dest.display());
+            fetch()?
+        }
+        result => result?,
+    };
+    tracing::debug!("{} served by {served_by}", entry.dest.display());
+    fs::rename(part_path(&entry.dest), &entry.dest)
+        .with_context(|| format!("failed to move file into place: {}", entry.dest.display()))?;
+    Ok(EntryStatus::Downloaded)
+}
+
+/// Whether `path` exists and matches the expected size and sha1.
+fn is_intact(path: &Path, sha1: Option<&str>, size: Option<u64>) -> bool {
+    let Ok(metadata) = fs::metadata(path) else {
+        return false;
+    };
+    if size.is_some_and(|size| metadata.len() != size) {
+        return false;
+    }
+    match sha1 {
+        Some(expected) => sha1_file(path).is_ok_and(|actual| actual.eq_ignore_ascii_case(expected)),
+        None => metadata.len() > 0,
+    }
+}
+
+fn part_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+/// Download `url` into the entry's `.part` file, resuming a partial one, and
+/// check the result. A `.part` that fails the check is removed.
+fn fetch_to_part(client: &Client, url: &str, entry: &DownloadEntry) -> Result<()> {
+    let part = part_path(&entry.dest);
+    let mut existing = fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+    if entry.size.is_some_and(|size| existing > size) {
+        let _ = fs::remove_file(&part);
+        existing = 0;
+    }
+
+    let mut request = client.get(url);
+    if existing > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={existing}-"));
+    }
+    let mut resp = http::send_with_retry(request, http::DEFAULT_RETRY_ATTEMPTS)
+        .with_context(|| format!("failed to download: {url}"))?;
+    if existing > 0 && resp.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial file cannot be continued; start over
+        resp = http::send_with_retry(client.get(url), http::DEFAULT_RETRY_ATTEMPTS)
+            .with_context(|| format!("failed to download: {url}"))?;
+    }
+    let mut resp = http::check_status(resp).with_context(|| format!("download failed: {url}"))?;
+    let resumed = existing > 0 && resp.status() == StatusCode::PARTIAL_CONTENT;
+
+    let mut out = fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resumed)
+        .truncate(!resumed)
+        .open(&part)
+        .with_context(|| format!("failed to create file: {}", part.display()))?;
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let read = resp.read(&mut buf).map_err(|err| http::read_error(url, err))?;
+        if read == 0 {
+            break;
+        }
+        http::throttle_download(read);
+        out.write_all(&buf[..read]).context("failed to write download")?;
+    }
+    drop(out);
+
+    if !is_intact(&part, entry.sha1.as_deref(), entry.size) {
+        let _ = fs::remove_file(&part);
+        return Err(ChecksumMismatch { path: entry.dest.clone() }.into());
+    }
+    Ok(())
+}
+
+fn sha1_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path).with_context(|| format!("failed to open file for sha1: {}", path.display()))?;
+    let mut hasher = Sha1::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buf).context("failed to hash file")?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buf[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::net::TcpListener;
+    use std::sync::Arc;
+
+    /// Serve `files` by path, honouring `Range: bytes=N-`; records each request line.
+    fn serve(files: HashMap<String, Vec<u8>>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let seen = log.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut buf = [0u8; 4096];
+                let n = stream.read(&mut buf).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                let path = request.split_whitespace().nth(1).unwrap_or("").to_string();
+                let from = request
+                    .lines()
+                    .find_map(|line| line.to_ascii_lowercase().strip_prefix("range: bytes=").map(str::to_string))
+                    .and_then(|range| range.trim_end_matches('-').parse::<usize>().ok());
+                seen.lock().unwrap().push(format!("{path} {}", from.unwrap_or(0)));
+                let response = match (files.get(&path), from) {
+                    (Some(body), Some(from)) => {
+                        let mut out = format!(
+                            "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                            body.len() - from
+                        )
+                        .into_bytes();
+                        out.extend_from_slice(&body[from..]);
+                        out
+                    }
+                    (Some(body), None) => {
+                        let mut out = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len())
+                            .into_bytes();
+                        out.extend_from_slice(body);
+                        out
+                    }
+                    (None, _) => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+                };
+                let _ = stream.write_all(&response);
+            }
+        });
+        (base, log)
+    }
+
+    #[test]
+    fn test_downloads_skips_resumes_and_verifies() {
+        let dir = std::env::temp_dir().join(format!("shard-downloads-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let bodies: Vec<Vec<u8>> = (0..4).map(|i| format!("library body number {i}").into_bytes()).collect();
+        let files: HashMap<String, Vec<u8>> =
+            bodies.iter().enumerate().map(|(i, body)| (format!("/lib{i}.jar"), body.clone())).collect();
+        let (base, log) = serve(files);
+        let mut entries: Vec<DownloadEntry> = bodies
+            .iter()
+            .enumerate()
+            .map(|(i, body)| DownloadEntry {
+                url: format!("{base}/lib{i}.jar"),
+                dest: dir.join(format!("lib{i}.jar")),
+                sha1: Some(hex::encode(Sha1::digest(body))),
+                size: Some(body.len() as u64),
+            })
+            .collect();
+        // lib0 is already in place, lib1 was interrupted after 7 bytes
+        fs::write(&entries[0].dest, &bodies[0]).unwrap();
+        fs::write(part_path(&entries[1].dest), &bodies[1][..7]).unwrap();
+        // lib3's recorded hash is wrong
+        entries[3].sha1 = Some("0000000000000000000000000000000000000000".to_string());
+
+        let last = Mutex::new(None);
+        let report = |progress: BatchProgress| *last.lock().unwrap() = Some(progress);
+        let results = download_all(&entries, 3, Some(&report));
+
+        assert_eq!(results[0].as_ref().unwrap(), &EntryStatus::AlreadyPresent);
+        assert_eq!(results[1].as_ref().unwrap(), &EntryStatus::Downloaded);
+        assert_eq!(results[2].as_ref().unwrap(), &EntryStatus::Downloaded);
+        assert!(results[3].as_ref().unwrap_err().chain().any(|cause| cause.is::<ChecksumMismatch>()));
+        for i in 0..3 {
+            assert_eq!(fs::read(&entries[i].dest).unwrap(), bodies[i]);
+        }
+        assert!(!entries[3].dest.exists());
+        assert!(!part_path(&entries[3].dest).exists());
+
+        let mut requests = log.lock().unwrap().clone();
+        requests.sort();
+        // The corrupt lib3 is fetched a second time before giving up
+        assert_eq!(requests, ["/lib1.jar 7", "/lib2.jar 0", "/lib3.jar 0", "/lib3.jar 0"]);
+        let last = last.lock().unwrap().unwrap();
+        assert_eq!((last.finished, last.total), (4, 4));
+        assert_eq!(last.total_bytes, bodies.iter().map(|b| b.len() as u64).sum::<u64>());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,217 @@
+//! Fabric loader resolution against the Fabric Meta API
+//!
+//! Fetches the loader profile for a Minecraft + loader version pair, downloads
+//! the loader, intermediary and support libraries into the shared libraries
+//! directory, and writes the profile as a version json that inherits from the
+//! vanilla version. Meta responses are cached so a known loader can be
+//! prepared again without network access.
+
+use crate::http::{DEFAULT_RETRY_ATTEMPTS, check_status, client as http_client, send_with_retry};
+use crate::minecraft::{IntegrityCheck, join_url, maven_path_from_name};
+use crate::paths::Paths;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+const FABRIC_META_BASE: &str = "https://meta.fabricmc.net/v2";
+const FABRIC_MAVEN: &str = "https://maven.fabricmc.net/";
+/// How long the loader version list is trusted before it is fetched again.
+const LOADER_LIST_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// A library entry from a Fabric loader profile.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FabricLibrary {
+    /// Maven coordinate, e.g. `net.fabricmc:intermediary:1.20.1`.
+    pub name: String,
+    /// Maven repository the artifact is served from.
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub sha1: Option<String>,
+    #[serde(default)]
+    pub size: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct FabricProfile {
+    id: String,
+    #[serde(default)]
+    libraries: Vec<FabricLibrary>,
+}
+
+/// Resolve the newest stable Fabric loader version.
+pub fn latest_loader_version(paths: &Paths) -> Result<String> {
+    let url = format!("{FABRIC_META_BASE}/versions/loader");
+    let json = cached_meta(paths, "fabric-loader-versions.json", &url, Some(LOADER_LIST_TTL))?;
+    let versions = json.as_array().context("fabric loader versions not an array")?;
+
+    versions
+        .iter()
+        .find(|entry| entry.get("stable").and_then(|v| v.as_bool()).unwrap_or(false))
+        .or_else(|| versions.first())
+        .and_then(|entry| entry.get("version"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .context("no fabric loader versions found")
+}
+
+/// Prepare a Fabric loader for `mc_version`. `loader_version` may be `latest`.
+///
+/// Downloads every loader library that `integrity` finds missing or invalid
+/// (checking sha1 and size when the profile lists them) and writes the version
+/// json used by the launch planner. Returns its id, e.g.
+/// `fabric-loader-0.15.11-1.20.1`.
+pub(crate) fn prepare(
+    paths: &Paths,
+    mc_version: &str,
+    loader_version: &str,
+    integrity: &mut IntegrityCheck,
+) -> Result<String> {
+    let loader_version = if loader_version.eq_ignore_ascii_case("latest") {
+        latest_loader_version(paths)?
+    } else {
+        loader_version.to_string()
+    };
+
+    let url = format!("{FABRIC_META_BASE}/versions/loader/{mc_version}/{loader_version}/profile/json");
+    let cache_name = format!("fabric-{mc_version}-{loader_version}.json");
+    // A loader profile never changes once published, so the cache never expires.
+    let profile_json = cached_meta(paths, &cache_name, &url, None)?;
+    let profile: FabricProfile = serde_json::from_value(profile_json.clone())
+        .context("failed to parse fabric loader profile")?;
+
+    for library in &profile.libraries {
+        ensure_library(paths, library, integrity)?;
+    }
+
+    let target = paths.minecraft_version_json(&profile.id);
+    if !target.exists() {
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create version dir: {}", parent.display()))?;
+        }
+        fs::write(&target, serde_json::to_string_pretty(&profile_json)?).with_context(|| {
+            format!("failed to write fabric version json: {}", target.display())
+        })?;
+    }
+
+    Ok(profile.id)
+}
+
+/// Download a loader library into the libraries dir unless an intact copy exists.
+fn ensure_library(paths: &Paths, library: &FabricLibrary, integrity: &mut IntegrityCheck) -> Result<()> {
+    let maven_path = maven_path_from_name(&library.name)
+        .with_context(|| format!("invalid fabric library name: {}", library.name))?;
+    let path = paths.minecraft_library_path(&maven_path);
+    let url = join_url(library.url.as_deref().unwrap_or(FABRIC_MAVEN), &maven_path);
+    integrity
+        .ensure(&url, &path, library.sha1.as_deref(), library.size)
+        .with_context(|| format!("failed to download fabric library {}", library.name))
+}
+
+/// Fetch a meta document, serving it from the manifest cache while it is fresh.
+/// When the network fails, a stale cached copy is used instead.
+fn cached_meta(paths: &Paths, cache_name: &str, url: &str, ttl: Option<Duration>) -> Result<Value> {
+    let cache_path = paths.cache_manifest(cache_name);
+    let cached = fs::read_to_string(&cache_path)
+        .ok()
+        .and_then(|data| serde_json::from_str::<Value>(&data).ok());
+
+    if let Some(value) = &cached
+        && is_fresh(&cache_path, ttl)
+    {
+        return Ok(value.clone());
+    }
+
+    match fetch_text(url) {
+        Ok(data) => {
+            let value: Value = serde_json::from_str(&data)
+                .with_context(|| format!("failed to parse fabric meta response: {url}"))?;
+            if let Some(parent) = cache_path.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("failed to create manifest dir: {}", parent.display()))?;
+            }
+            fs::write(&cache_path, &data)
+                .with_context(|| format!("failed to write fabric meta cache: {}", cache_path.display()))?;
+            Ok(value)
+        }
+        Err(err) => match cached {
+            Some(value) => {
+                tracing::warn!("using cached fabric meta for {url}: {err:#}");
+                Ok(value)
+            }
+            None => Err(err),
+        },
+    }
+}
+
+fn is_fresh(path: &Path, ttl: Option<Duration>) -> bool {
+    let Some(ttl) = ttl else {
+        return true;
+    };
+    path.metadata()
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .map(|age| age < ttl)
+        .unwrap_or(false)
+}
+
+fn fetch_text(url: &str) -> Result<String> {
+    let client = http_client()?;
+    let resp = send_with_retry(client.get(url), DEFAULT_RETRY_ATTEMPTS)
+        .with_context(|| format!("failed to reach fabric meta: {url}"))?;
+    let resp = check_status(resp).with_context(|| format!("fabric meta request failed: {url}"))?;
+    resp.text().context("failed to read fabric meta response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_profile_is_used_without_network() {
+        let base = std::env::temp_dir().join(format!("shard-fabric-cache-{}", std::process::id()));
+        let paths = Paths::from_base(&base);
+        let cache = paths.cache_manifest("fabric-1.20.1-0.15.11.json");
+        fs::create_dir_all(cache.parent().unwrap()).unwrap();
+        fs::write(
+            &cache,
+            r#"{"id":"fabric-loader-0.15.11-1.20.1","inheritsFrom":"1.20.1","mainClass":"net.fabricmc.loader.impl.launch.knot.KnotClient","libraries":[]}"#,
+        )
+        .unwrap();
+
+        let version_id = prepare(&paths, "1.20.1", "0.15.11", &mut IntegrityCheck::new(true)).unwrap();
+        assert_eq!(version_id, "fabric-loader-0.15.11-1.20.1");
+        assert!(paths.minecraft_version_json(&version_id).exists());
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_libraries_follow_the_integrity_mode() {
+        let base = std::env::temp_dir().join(format!("shard-fabric-verify-{}", std::process::id()));
+        let paths = Paths::from_base(&base);
+        let cache = paths.cache_manifest("fabric-1.20.1-0.15.11.json");
+        fs::create_dir_all(cache.parent().unwrap()).unwrap();
+        // The listed hash does not match the jar on disk, and the repo is unreachable
+        fs::write(
+            &cache,
+            r#"{"id":"fabric-loader-0.15.11-1.20.1","inheritsFrom":"1.20.1","libraries":[{"name":"net.fabricmc:intermediary:1.20.1","url":"http://127.0.0.1:9/","sha1":"0000000000000000000000000000000000000000","size":4}]}"#,
+        )
+        .unwrap();
+        let jar = paths.minecraft_library_path("net/fabricmc/intermediary/1.20.1/intermediary-1.20.1.jar");
+        fs::create_dir_all(jar.parent().unwrap()).unwrap();
+        fs::write(&jar, b"1234").unwrap();
+
+        // A fast check trusts the size; a full one hashes the jar and drops it
+        // (the failed refetch is reported when the check finishes)
+        assert!(prepare(&paths, "1.20.1", "0.15.11", &mut IntegrityCheck::fast()).is_ok());
+        assert!(jar.exists());
+        assert!(prepare(&paths, "1.20.1", "0.15.11", &mut IntegrityCheck::new(true)).is_ok());
+        assert!(!jar.exists());
+        let _ = fs::remove_dir_all(&base);
+    }
+}
@@ -0,0 +1,297 @@
+//! Legacy Forge (Minecraft 1.12.2 and older) installed without running its installer
+//!
+//! Installers from before 1.13 have no processors: they only unpack the
+//! universal jar and write a version json that launches through LaunchWrapper.
+//! That is emulated here. The install profile is read from the installer jar,
+//! the universal jar it carries is placed in the shared libraries directory,
+//! and the version json is rewritten to inherit from the vanilla version with
+//! library urls and checksums the launch planner understands. The remaining
+//! libraries are then downloaded like any other version's.
+
+use crate::java::compare_mc_versions;
+use crate::minecraft::{download_with_sha1, maven_path_from_name};
+use crate::paths::Paths;
+use anyhow::{Context, Result, bail};
+use serde_json::{Value, json};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+const FORGE_MAVEN: &str = "https://maven.minecraftforge.net/";
+/// Maven urls older install profiles point at; the same artifacts are served from [`FORGE_MAVEN`].
+const LEGACY_FORGE_MAVENS: &[&str] = &[
+    "http://files.minecraftforge.net/maven/",
+    "https://files.minecraftforge.net/maven/",
+];
+const LAUNCHWRAPPER_MAIN: &str = "net.minecraft.launchwrapper.Launch";
+/// FML's LaunchWrapper tweaker, which moved packages in 1.8.
+const FML_TWEAKER: &str = "net.minecraftforge.fml.common.launcher.FMLTweaker";
+const FML_TWEAKER_PRE_1_8: &str = "cpw.mods.fml.common.launcher.FMLTweaker";
+
+/// Newest Java legacy Forge runs on: LaunchWrapper casts the system class
+/// loader to `URLClassLoader`, which fails from Java 9 on.
+pub const LEGACY_MAX_JAVA: u32 = 8;
+
+/// Whether Forge for this Minecraft version uses the legacy LaunchWrapper
+/// layout (everything before 1.13).
+pub fn is_legacy(mc_version: &str) -> bool {
+    compare_mc_versions(mc_version, "1.13") < 0
+}
+
+/// What a legacy installer carries: its version json and the universal jar.
+struct LegacyInstall {
+    version: Value,
+    /// Maven coordinate the universal jar is installed as.
+    coordinate: String,
+    /// Path of the universal jar inside the installer.
+    universal_entry: String,
+}
+
+/// Install legacy Forge `version_id` (e.g. `1.12.2-14.23.5.2860`) for
+/// `mc_version` and return the id of the version json written.
+pub fn prepare_legacy(paths: &Paths, mc_version: &str, version_id: &str) -> Result<String> {
+    let id = format!("forge-{version_id}");
+    let target = paths.minecraft_version_json(&id);
+    let universal = maven_path_from_name(&format!("net.minecraftforge:forge:{version_id}"))
+        .map(|path| paths.minecraft_library_path(&path));
+    if target.exists() && universal.is_some_and(|path| path.exists()) {
+        return Ok(id);
+    }
+
+    let installer_url =
+        format!("{FORGE_MAVEN}net/minecraftforge/forge/{version_id}/forge-{version_id}-installer.jar");
+    let installer_path = paths.cache_downloads.join(format!("forge-{version_id}-installer.jar"));
+    download_with_sha1(&installer_url, &installer_path, None)?;
+
+    let file = fs::File::open(&installer_path)
+        .with_context(|| format!("failed to open installer jar: {}", installer_path.display()))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .with_context(|| format!("failed to read installer jar: {}", installer_path.display()))?;
+    let install = read_install(&mut archive)?;
+
+    let maven_path = maven_path_from_name(&install.coordinate)
+        .with_context(|| format!("invalid forge coordinate: {}", install.coordinate))?;
+    extract_entry(&mut archive, &install.universal_entry, &paths.minecraft_library_path(&maven_path))?;
+
+    let version = legacy_version_json(install.version, &id, mc_version);
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create version dir: {}", parent.display()))?;
+    }
+    fs::write(&target, serde_json::to_string_pretty(&version)?)
+        .with_context(|| format!("failed to write forge version json: {}", target.display()))?;
+    Ok(id)
+}
+
+/// Read `install_profile.json`, in either the original layout (`install` +
+/// `versionInfo`) or the spec 0 layout late 1.12.2 builds shipped.
+fn read_install<R: Read + std::io::Seek>(archive: &mut zip::ZipArchive<R>) -> Result<LegacyInstall> {
+    let profile: Value = serde_json::from_str(&read_entry(archive, "install_profile.json")?)
+        .context("failed to parse forge install profile")?;
+
+    if let Some(install) = profile.get("install") {
+        let field = |key: &str| {
+            install
+                .get(key)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .with_context(|| format!("forge install profile is missing install.{key}"))
+        };
+        let version = profile
+            .get("versionInfo")
+            .cloned()
+            .context("forge install profile is missing versionInfo")?;
+        return Ok(LegacyInstall {
+            version,
+            coordinate: field("path")?,
+            universal_entry: field("filePath")?,
+        });
+    }
+
+    if profile.get("processors").and_then(Value::as_array).is_some_and(|p| !p.is_empty()) {
+        bail!("this forge installer needs its processors run, so it is not a legacy installer");
+    }
+    let coordinate = profile
+        .get("path")
+        .and_then(Value::as_str)
+        .context("forge install profile is missing path")?
+        .to_string();
+    let json_entry = profile.get("json").and_then(Value::as_str).unwrap_or("/version.json");
+    let version: Value = serde_json::from_str(&read_entry(archive, json_entry.trim_start_matches('/'))?)
+        .context("failed to parse forge version json")?;
+    let maven_path =
+        maven_path_from_name(&coordinate).with_context(|| format!("invalid forge coordinate: {coordinate}"))?;
+    Ok(LegacyInstall {
+        version,
+        coordinate,
+        universal_entry: format!("maven/{maven_path}"),
+    })
+}
+
+/// Rewrite an installer's version json for the launch planner: our id, the
+/// vanilla parent, current maven urls, single checksums as `sha1`, and exactly
+/// one FML tweaker.
+fn legacy_version_json(mut version: Value, id: &str, mc_version: &str) -> Value {
+    version["id"] = json!(id);
+    if version.get("inheritsFrom").and_then(Value::as_str).is_none() {
+        version["inheritsFrom"] = json!(mc_version);
+    }
+
+    if let Some(libraries) = version.get_mut("libraries").and_then(Value::as_array_mut) {
+        for library in libraries {
+            if let Some(url) = library.get("url").and_then(Value::as_str)
+                && LEGACY_FORGE_MAVENS.contains(&url)
+            {
+                library["url"] = json!(FORGE_MAVEN);
+            }
+            // Some entries list checksums for a packed variant too; only a lone one is the jar's
+            let checksum = match library.get("checksums").and_then(Value::as_array) {
+                Some(sums) if sums.len() == 1 => sums[0].as_str().map(str::to_string),
+                _ => None,
+            };
+            if let Some(sha1) = checksum
+                && library.get("sha1").is_none()
+            {
+                library["sha1"] = json!(sha1);
+            }
+        }
+    }
+
+    if version.get("mainClass").and_then(Value::as_str) == Some(LAUNCHWRAPPER_MAIN) {
+        let args = version.get("minecraftArguments").and_then(Value::as_str).unwrap_or_default();
+        version["minecraftArguments"] = json!(with_fml_tweaker(args, mc_version));
+    }
+    version
+}
+
+/// `args` with FML's tweaker passed exactly once, keeping any other tweakers.
+fn with_fml_tweaker(args: &str, mc_version: &str) -> String {
+    let tweaker = if compare_mc_versions(mc_version, "1.8") < 0 {
+        FML_TWEAKER_PRE_1_8
+    } else {
+        FML_TWEAKER
+    };
+
+    let mut out: Vec<&str> = Vec::new();
+    let mut tweakers: Vec<&str> = Vec::new();
+    let mut tokens = args.split_whitespace();
+    while let Some(token) = tokens.next() {
+        if token == "--tweakClass" {
+            if let Some(class) = tokens.next()
+                && !tweakers.contains(&class)
+            {
+                tweakers.push(class);
+            }
+        } else {
+            out.push(token);
+        }
+    }
+    if !tweakers.iter().any(|class| class.ends_with(".FMLTweaker")) {
+        tweakers.push(tweaker);
+    }
+    for class in tweakers {
+        out.push("--tweakClass");
+        out.push(class);
+    }
+    out.join(" ")
+}
+
+fn read_entry<R: Read + std::io::Seek>(archive: &mut zip::ZipArchive<R>, name: &str) -> Result<String> {
+    let mut entry = archive
+        .by_name(name)
+        .with_context(|| format!("{name} not found in forge installer"))?;
+    let mut contents = String::new();
+    entry
+        .read_to_string(&mut contents)
+        .with_context(|| format!("failed to read {name} from forge installer"))?;
+    Ok(contents)
+}
+
+fn extract_entry<R: Read + std::io::Seek>(archive: &mut zip::ZipArchive<R>, name: &str, dest: &Path) -> Result<()> {
+    let mut entry = archive
+        .by_name(name)
+        .with_context(|| format!("{name} not found in forge installer"))?;
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).with_context(|| format!("failed to create dir: {}", parent.display()))?;
+    }
+    let tmp_path = dest.with_extension("tmp");
+    let mut out = fs::File::create(&tmp_path)
+        .with_context(|| format!("failed to create file: {}", tmp_path.display()))?;
+    std::io::copy(&mut entry, &mut out).with_context(|| format!("failed to extract {name}"))?;
+    fs::rename(&tmp_path, dest).with_context(|| format!("failed to move file into place: {}", dest.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_installer(path: &Path, profile: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut zip = zip::ZipWriter::new(fs::File::create(path).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("install_profile.json", options).unwrap();
+        zip.write_all(profile.as_bytes()).unwrap();
+        zip.start_file("forge-1.12.2-14.23.5.2847-universal.jar", options).unwrap();
+        zip.write_all(b"universal").unwrap();
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_legacy_installer_is_unpacked_without_running_it() {
+        let base = std::env::temp_dir().join(format!("shard-forge-legacy-{}", std::process::id()));
+        let paths = Paths::from_base(&base);
+        let version_id = "1.12.2-14.23.5.2847";
+        write_installer(
+            &paths.cache_downloads.join(format!("forge-{version_id}-installer.jar")),
+            r#"{
+                "install": {
+                    "path": "net.minecraftforge:forge:1.12.2-14.23.5.2847",
+                    "filePath": "forge-1.12.2-14.23.5.2847-universal.jar"
+                },
+                "versionInfo": {
+                    "id": "1.12.2-forge1.12.2-14.23.5.2847",
+                    "mainClass": "net.minecraft.launchwrapper.Launch",
+                    "minecraftArguments": "--username ${auth_player_name} --tweakClass net.minecraftforge.fml.common.launcher.FMLTweaker --versionType Forge",
+                    "libraries": [
+                        {"name": "net.minecraftforge:forge:1.12.2-14.23.5.2847", "url": "http://files.minecraftforge.net/maven/"},
+                        {"name": "org.scala-lang:scala-library:2.11.1", "url": "http://files.minecraftforge.net/maven/", "checksums": ["0e4ad0"]},
+                        {"name": "lzma:lzma:0.0.1"}
+                    ]
+                }
+            }"#,
+        );
+
+        let id = prepare_legacy(&paths, "1.12.2", version_id).unwrap();
+        assert_eq!(id, "forge-1.12.2-14.23.5.2847");
+        let universal = paths.minecraft_library_path(
+            "net/minecraftforge/forge/1.12.2-14.23.5.2847/forge-1.12.2-14.23.5.2847.jar",
+        );
+        assert_eq!(fs::read(&universal).unwrap(), b"universal");
+
+        let version: Value = serde_json::from_str(&fs::read_to_string(paths.minecraft_version_json(&id)).unwrap()).unwrap();
+        assert_eq!(version["id"], id);
+        assert_eq!(version["inheritsFrom"], "1.12.2");
+        assert_eq!(version["libraries"][0]["url"], FORGE_MAVEN);
+        assert_eq!(version["libraries"][1]["sha1"], "0e4ad0");
+        assert!(version["libraries"][2].get("url").is_none());
+        let args = version["minecraftArguments"].as_str().unwrap();
+        assert_eq!(args.matches("--tweakClass").count(), 1);
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_fml_tweaker_is_added_once() {
+        assert_eq!(
+            with_fml_tweaker("--version ${version_name}", "1.7.10"),
+            "--version ${version_name} --tweakClass cpw.mods.fml.common.launcher.FMLTweaker"
+        );
+        assert_eq!(
+            with_fml_tweaker("--tweakClass optifine.OptiFineForgeTweaker --tweakClass optifine.OptiFineForgeTweaker", "1.12.2"),
+            "--tweakClass optifine.OptiFineForgeTweaker --tweakClass net.minecraftforge.fml.common.launcher.FMLTweaker"
+        );
+        assert!(is_legacy("1.12.2"));
+        assert!(!is_legacy("1.13"));
+    }
+}
@@ -0,0 +1,967 @@
+//! Shared HTTP client construction, proxy authentication and mirror failover.
+//!
+//! Requests go through [`client`] so that the proxy (the config override or
+//! `HTTPS_PROXY`/`HTTP_PROXY`, minus `NO_PROXY` hosts) and any credentials
+//! supplied by the user are applied consistently, and through [`check_status`] so that a proxy asking
+//! for authentication (HTTP 407) surfaces as a distinct [`ProxyAuthRequired`] error.
+//! Large downloads can be routed through user-configured mirrors with
+//! [`with_mirror_failover`]; a download aborted by the user surfaces as
+//! [`DownloadCancelled`]. Transient failures (network errors, 5xx, 429) are
+//! retried with backoff by [`send_with_retry`]. Every client has a connect
+//! timeout; a stalled request surfaces as [`RequestTimedOut`]. Downloads take a
+//! [`download_slot`] and report what they read to [`throttle_download`], so the
+//! configured [`DownloadLimits`] hold across all of them together.
+//! [`connectivity_check`] probes the services a launch depends on, to tell a
+//! service outage apart from a broken network.
+
+use anyhow::{Context, Result};
+use keyring::{Entry, Error as KeyringError};
+use reqwest::StatusCode;
+use reqwest::blocking::{Client, ClientBuilder, RequestBuilder, Response};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+const USER_AGENT: &str = "Shard-Launcher";
+const KEYRING_SERVICE: &str = "shard";
+const PROXY_CREDENTIALS_KEY: &str = "config:proxy_credentials";
+const MIRROR_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+const CONNECTIVITY_TIMEOUT: Duration = Duration::from_secs(5);
+/// Hosts checked by [`connectivity_check`], besides the Adoptium API.
+const REQUIRED_SERVICES: &[(&str, &str)] = &[
+    ("Microsoft sign-in", "https://login.microsoftonline.com/"),
+    ("Xbox Live", "https://user.auth.xboxlive.com/"),
+    ("Minecraft services", "https://api.minecraftservices.com/"),
+    ("Mojang session server", "https://sessionserver.mojang.com/"),
+    ("Version metadata", "https://piston-meta.mojang.com/"),
+    ("Libraries", "https://libraries.minecraft.net/"),
+    ("Assets", "https://resources.download.minecraft.net/"),
+];
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+/// Limit for metadata requests, covering the response and each body read.
+const METADATA_TIMEOUT: Duration = Duration::from_secs(30);
+/// Limit for each read of a download, so slow but steady transfers of big files still finish.
+const DOWNLOAD_READ_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Attempts made by [`send_with_retry`] callers that have no reason to differ.
+pub const DEFAULT_RETRY_ATTEMPTS: u32 = 3;
+
+/// Username and password for an authenticating proxy.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProxyCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl fmt::Debug for ProxyCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProxyCredentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Error returned when a proxy rejects a request with HTTP 407.
+#[derive(Debug, Clone)]
+pub struct ProxyAuthRequired {
+    /// URL of the request that was rejected.
+    pub url: String,
+    /// Whether credentials were attached to the rejected request.
+    pub had_credentials: bool,
+}
+
+impl fmt::Display for ProxyAuthRequired {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.had_credentials {
+            write!(f, "proxy rejected the supplied credentials (HTTP 407) for {}", self.url)
+        } else {
+            write!(f, "proxy authentication required (HTTP 407) for {}", self.url)
+        }
+    }
+}
+
+impl std::error::Error for ProxyAuthRequired {}
+
+/// Error returned when a download is aborted through its cancellation flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadCancelled;
+
+impl fmt::Display for DownloadCancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "download cancelled")
+    }
+}
+
+impl std::error::Error for DownloadCancelled {}
+
+/// Error returned when a server stops responding within the client's timeout.
+#[derive(Debug, Clone)]
+pub struct RequestTimedOut {
+    /// URL of the request that stalled.
+    pub url: String,
+}
+
+impl fmt::Display for RequestTimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "timed out waiting for {} (the server stopped responding)", self.url)
+    }
+}
+
+impl std::error::Error for RequestTimedOut {}
+
+/// Proxy choice from the launcher config, applied on top of the environment.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxySettings {
+    /// Proxy URL used instead of `HTTPS_PROXY`/`HTTP_PROXY`.
+    pub url: Option<String>,
+    /// Connect directly, ignoring both the override and the environment.
+    pub disabled: bool,
+}
+
+static PROXY_CREDENTIALS: Mutex<Option<ProxyCredentials>> = Mutex::new(None);
+static PROXY_SETTINGS: Mutex<ProxySettings> = Mutex::new(ProxySettings { url: None, disabled: false });
+
+/// Set the proxy override used by [`client_builder`].
+pub fn set_proxy_settings(settings: ProxySettings) {
+    let settings = ProxySettings {
+        url: settings.url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty()),
+        ..settings
+    };
+    if let Ok(mut guard) = PROXY_SETTINGS.lock() {
+        *guard = settings;
+    }
+}
+
+/// Current proxy override.
+pub fn proxy_settings() -> ProxySettings {
+    PROXY_SETTINGS.lock().map(|guard| guard.clone()).unwrap_or_default()
+}
+
+/// Set (or clear) the in-memory proxy credentials used by [`client`].
+pub fn set_proxy_credentials(credentials: Option<ProxyCredentials>) {
+    if let Ok(mut guard) = PROXY_CREDENTIALS.lock() {
+        *guard = credentials;
+    }
+}
+
+/// Current in-memory proxy credentials, if any.
+pub fn proxy_credentials() -> Option<ProxyCredentials> {
+    PROXY_CREDENTIALS.lock().ok().and_then(|guard| guard.clone())
+}
+
+/// Persist proxy credentials in the system keychain (or remove them with `None`).
+pub fn save_proxy_credentials(credentials: Option<&ProxyCredentials>) -> Result<()> {
+    let entry = Entry::new(KEYRING_SERVICE, PROXY_CREDENTIALS_KEY)
+        .context("failed to open keyring entry for proxy credentials")?;
+    match credentials {
+        Some(credentials) => {
+            let data = serde_json::to_string(credentials)
+                .context("failed to serialize proxy credentials")?;
+            entry
+                .set_password(&data)
+                .context("failed to store proxy credentials in keyring")?;
+        }
+        None => match entry.delete_password() {
+            Ok(()) | Err(KeyringError::NoEntry) => {}
+            Err(err) => return Err(err).context("failed to delete proxy credentials from keyring"),
+        },
+    }
+    Ok(())
+}
+
+/// Load proxy credentials saved in the keychain into memory.
+/// Returns whether credentials were found.
+pub fn restore_saved_proxy_credentials() -> Result<bool> {
+    let entry = Entry::new(KEYRING_SERVICE, PROXY_CREDENTIALS_KEY)
+        .context("failed to open keyring entry for proxy credentials")?;
+    match entry.get_password() {
+        Ok(data) => {
+            let credentials: ProxyCredentials = serde_json::from_str(&data)
+                .context("failed to parse saved proxy credentials")?;
+            set_proxy_credentials(Some(credentials));
+            Ok(true)
+        }
+        Err(KeyringError::NoEntry) => Ok(false),
+        Err(err) => Err(err).context("failed to read proxy credentials from keyring"),
+    }
+}
+
+/// Proxy URL configured through the standard environment variables.
+pub fn system_proxy_url() -> Option<String> {
+    ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"]
+        .iter()
+        .filter_map(|key| std::env::var(key).ok())
+        .map(|value| value.trim().to_string())
+        .find(|value| !value.is_empty())
+}
+
+/// Build a blocking HTTP client with the launcher user agent and any proxy credentials applied.
+/// Suited to API and metadata requests, which time out after 30 seconds.
+pub fn client() -> Result<Client> {
+    client_builder()?.build().context("failed to create HTTP client")
+}
+
+/// Client for large file downloads: the timeout applies to each read rather
+/// than to the whole transfer.
+pub fn download_client() -> Result<Client> {
+    client_builder()?
+        .timeout(DOWNLOAD_READ_TIMEOUT)
+        .build()
+        .context("failed to create HTTP client")
+}
+
+/// Proxy URL requests go through: the config override, then the environment.
+/// `None` when proxies are disabled in the config.
+pub fn effective_proxy_url() -> Option<String> {
+    resolve_proxy_url(proxy_settings(), system_proxy_url)
+}
+
+fn resolve_proxy_url(settings: ProxySettings, system: impl FnOnce() -> Option<String>) -> Option<String> {
+    if settings.disabled {
+        return None;
+    }
+    settings.url.or_else(system)
+}
+
+/// Client builder with the proxy (and its credentials) applied, for callers that
+/// need extra options. Hosts in `NO_PROXY` bypass the proxy.
+pub fn client_builder() -> Result<ClientBuilder> {
+    if proxy_settings().disabled {
+        return Ok(builder_for_proxy(None, None)?.no_proxy());
+    }
+    builder_for_proxy(effective_proxy_url().as_deref(), proxy_credentials().as_ref())
+}
+
+fn builder_for_proxy(proxy_url: Option<&str>, credentials: Option<&ProxyCredentials>) -> Result<ClientBuilder> {
+    let mut builder = Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(METADATA_TIMEOUT);
+    if let Some(url) = proxy_url {
+        let mut proxy = reqwest::Proxy::all(url)
+            .with_context(|| format!("invalid proxy url: {url}"))?
+            .no_proxy(reqwest::NoProxy::from_env());
+        if let Some(credentials) = credentials {
+            proxy = proxy.basic_auth(&credentials.username, &credentials.password);
+        }
+        builder = builder.proxy(proxy);
+    }
+    Ok(builder)
+}
+
+/// Turn non-success responses into errors, mapping HTTP 407 to [`ProxyAuthRequired`].
+pub fn check_status(resp: Response) -> Result<Response> {
+    if resp.status() == StatusCode::PROXY_AUTHENTICATION_REQUIRED {
+        return Err(ProxyAuthRequired {
+            url: resp.url().to_string(),
+            had_credentials: proxy_credentials().is_some(),
+        }
+        .into());
+    }
+    resp.error_for_status().map_err(Into::into)
+}
+
+/// Send a request, retrying network errors and 5xx/429 responses up to
+/// `attempts` times in total with exponential backoff plus jitter.
+///
+/// Other responses (including 4xx) are returned on the first attempt; the
+/// final response is returned as-is, so callers still run [`check_status`].
+/// Requests with a streaming body cannot be cloned and are sent once.
+pub fn send_with_retry(mut request: RequestBuilder, attempts: u32) -> Result<Response> {
+    let attempts = attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        // Keep a copy to resend, unless this is the last attempt
+        let spare = if attempt < attempts { request.try_clone() } else { None };
+        let outcome = request.send();
+        let transient = match &outcome {
+            Ok(resp) => is_transient_status(resp.status()),
+            Err(err) => !is_tunnel_auth_rejection(err),
+        };
+        let Some(spare) = spare.filter(|_| transient) else {
+            return match outcome {
+                Err(err) if err.is_timeout() => Err(timed_out(err)),
+                Err(err) if is_tunnel_auth_rejection(&err) => Err(tunnel_auth_required(err)),
+                outcome => outcome.context("request failed"),
+            };
+        };
+
+        let delay = backoff_delay(attempt);
+        match &outcome {
+            Ok(resp) => tracing::debug!("{} returned {}, retrying in {delay:?}", resp.url(), resp.status()),
+            Err(err) => tracing::debug!("request failed ({err}), retrying in {delay:?}"),
+        }
+        std::thread::sleep(delay);
+        request = spare;
+        attempt += 1;
+    }
+}
+
+/// Message of the error hyper returns when a proxy answers an HTTPS `CONNECT`
+/// with 407; the error type itself is private.
+const TUNNEL_AUTH_MESSAGE: &str = "proxy authorization required";
+
+/// Whether an error (or one of its sources) is a proxy rejecting a `CONNECT`
+/// tunnel for lack of credentials. Such a request never gets a response, so
+/// [`check_status`] cannot see the 407.
+fn is_tunnel_auth_rejection(err: &(dyn std::error::Error + 'static)) -> bool {
+    let mut cause = Some(err);
+    while let Some(err) = cause {
+        if err.to_string().contains(TUNNEL_AUTH_MESSAGE) {
+            return true;
+        }
+        cause = err.source();
+    }
+    false
+}
+
+fn tunnel_auth_required(err: reqwest::Error) -> anyhow::Error {
+    let url = err.url().map(|u| u.to_string()).unwrap_or_else(|| "proxy".to_string());
+    tracing::debug!("proxy refused the tunnel: {err}");
+    ProxyAuthRequired { url, had_credentials: proxy_credentials().is_some() }.into()
+}
+
+fn timed_out(err: reqwest::Error) -> anyhow::Error {
+    let url = err.url().map(|u| u.to_string()).unwrap_or_else(|| "server".to_string());
+    tracing::debug!("request timed out: {err}");
+    RequestTimedOut { url }.into()
+}
+
+/// Convert an error from reading a response body, turning a stalled read into
+/// [`RequestTimedOut`].
+pub fn read_error(url: &str, err: std::io::Error) -> anyhow::Error {
+    let stalled = err.kind() == std::io::ErrorKind::TimedOut
+        || err
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<reqwest::Error>())
+            .is_some_and(reqwest::Error::is_timeout);
+    if stalled {
+        RequestTimedOut { url: url.to_string() }.into()
+    } else {
+        anyhow::Error::new(err).context("failed to read from download stream")
+    }
+}
+
+fn is_transient_status(status: StatusCode) -> bool {
+    status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+}
+
+/// Exponential backoff (`base * 2^(attempt-1)`) plus up to 50% jitter.
+fn backoff_delay(attempt: u32) -> Duration {
+    let base = RETRY_BASE_DELAY * 2u32.saturating_pow(attempt.saturating_sub(1));
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    base + base.mul_f64(f64::from(nanos % 1000) / 2000.0)
+}
+
+/// Configured download mirrors, and the probed order once computed.
+struct MirrorState {
+    mirrors: Vec<String>,
+    ordered: Option<Vec<String>>,
+}
+
+static MIRRORS: Mutex<MirrorState> = Mutex::new(MirrorState { mirrors: Vec::new(), ordered: None });
+
+/// Set the download mirrors (base URLs) tried before the upstream host.
+pub fn set_mirrors(mirrors: Vec<String>) {
+    let mirrors = mirrors
+        .into_iter()
+        .map(|m| m.trim().trim_end_matches('/').to_string())
+        .filter(|m| reqwest::Url::parse(m).is_ok())
+        .collect();
+    if let Ok(mut state) = MIRRORS.lock() {
+        *state = MirrorState { mirrors, ordered: None };
+    }
+}
+
+/// Configured mirrors ordered fastest-first by a quick health probe.
+/// The probe runs once per mirror configuration; unreachable mirrors go last.
+pub fn ordered_mirrors() -> Vec<String> {
+    let mirrors = match MIRRORS.lock() {
+        Ok(state) => match &state.ordered {
+            Some(ordered) => return ordered.clone(),
+            None => state.mirrors.clone(),
+        },
+        Err(_) => return Vec::new(),
+    };
+    if mirrors.is_empty() {
+        return mirrors;
+    }
+
+    let ordered = probe_mirrors(&mirrors);
+    if let Ok(mut state) = MIRRORS.lock()
+        && state.mirrors == mirrors {
+            state.ordered = Some(ordered.clone());
+        }
+    ordered
+}
+
+/// Order mirrors by response time of a HEAD request to their base URL.
+pub fn probe_mirrors(mirrors: &[String]) -> Vec<String> {
+    let client = match client_builder().and_then(|b| b.timeout(MIRROR_PROBE_TIMEOUT).build().map_err(Into::into)) {
+        Ok(client) => client,
+        Err(_) => return mirrors.to_vec(),
+    };
+    let mut timed: Vec<(Option<Duration>, &String)> = mirrors
+        .iter()
+        .map(|mirror| {
+            let started = Instant::now();
+            // Any HTTP answer (even 404 for the bare base URL) means the host is up
+            let latency = client.head(mirror.as_str()).send().ok().map(|_| started.elapsed());
+            (latency, mirror)
+        })
+        .collect();
+    // Stable sort keeps configured order among equally healthy mirrors
+    timed.sort_by_key(|(latency, _)| latency.unwrap_or(Duration::MAX));
+    timed.into_iter().map(|(_, mirror)| mirror.clone()).collect()
+}
+
+/// Rewrite `url` onto a mirror base, keeping its host (and port) as the first
+/// path segment, then its path and query, so files from different upstream
+/// hosts cannot collide on the mirror:
+/// `https://libraries.minecraft.net/a.jar` -> `<mirror>/libraries.minecraft.net/a.jar`.
+pub fn mirror_url(url: &str, mirror: &str) -> Option<String> {
+    let parsed = reqwest::Url::parse(url).ok()?;
+    let mut host = parsed.host_str()?.to_string();
+    if let Some(port) = parsed.port() {
+        host.push_str(&format!(":{port}"));
+    }
+    let mut path = parsed.path().to_string();
+    if let Some(query) = parsed.query() {
+        path.push('?');
+        path.push_str(query);
+    }
+    Some(format!("{}/{host}{path}", mirror.trim_end_matches('/')))
+}
+
+/// Run `op` against each mirror in turn, then the upstream URL, returning the
+/// first success together with the URL that served it.
+pub fn with_mirror_failover<T>(
+    url: &str,
+    mirrors: &[String],
+    mut op: impl FnMut(&str) -> Result<T>,
+) -> Result<(T, String)> {
+    let mut candidates: Vec<String> = mirrors.iter().filter_map(|m| mirror_url(url, m)).collect();
+    candidates.push(url.to_string());
+
+    let mut last_err = None;
+    for candidate in candidates {
+        match op(&candidate) {
+            Ok(value) => return Ok((value, candidate)),
+            Err(err) => {
+                // A proxy asking for credentials fails every mirror the same way,
+                // and a cancelled download must not move on to the next source
+                if is_proxy_auth_required(&err) || is_download_cancelled(&err) {
+                    return Err(err);
+                }
+                tracing::warn!("download from {candidate} failed: {err:#}");
+                last_err = Some(err);
+            }
+        }
+    }
+    Err(last_err.unwrap_or_else(|| anyhow::anyhow!("no download source for {url}")))
+}
+
+/// Process-wide caps on downloads, from the launcher config.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DownloadLimits {
+    /// Transfers running at once, across every download; unlimited when unset.
+    pub max_concurrent: Option<usize>,
+    /// Combined rate of all transfers in bytes per second; unlimited when unset.
+    pub bytes_per_sec: Option<u64>,
+}
+
+/// Enforces [`DownloadLimits`] across every download that shares it.
+pub struct DownloadCoordinator {
+    state: Mutex<CoordinatorState>,
+    slot_freed: Condvar,
+}
+
+struct CoordinatorState {
+    limits: DownloadLimits,
+    active: usize,
+    /// Bytes that may still be read right away; negative when in debt.
+    allowance: f64,
+    refilled: Option<Instant>,
+}
+
+/// A transfer slot from [`DownloadCoordinator::acquire`], freed on drop.
+pub struct DownloadSlot<'a> {
+    coordinator: &'a DownloadCoordinator,
+}
+
+impl DownloadCoordinator {
+    pub const fn new() -> Self {
+        Self {
+            state: Mutex::new(CoordinatorState {
+                limits: DownloadLimits { max_concurrent: None, bytes_per_sec: None },
+                active: 0,
+                allowance: 0.0,
+                refilled: None,
+            }),
+            slot_freed: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CoordinatorState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Replace the limits; transfers already running keep their slots.
+    pub fn set_limits(&self, limits: DownloadLimits) {
+        let mut state = self.lock();
+        state.limits = limits;
+        state.allowance = 0.0;
+        state.refilled = None;
+        drop(state);
+        self.slot_freed.notify_all();
+    }
+
+    pub fn limits(&self) -> DownloadLimits {
+        self.lock().limits
+    }
+
+    /// Wait until fewer than `max_concurrent` transfers are running, then
+    /// take a slot until the returned guard is dropped.
+    pub fn acquire(&self) -> DownloadSlot<'_> {
+        let mut state = self.lock();
+        while state.limits.max_concurrent.is_some_and(|max| state.active >= max.max(1)) {
+            state = self.slot_freed.wait(state).unwrap_or_else(PoisonError::into_inner);
+        }
+        state.active += 1;
+        DownloadSlot { coordinator: self }
+    }
+
+    /// Count `bytes` just read by any transfer, sleeping as long as it takes
+    /// for all transfers together to stay under `bytes_per_sec`.
+    pub fn throttle(&self, bytes: usize) {
+        let wait = {
+            let mut state = self.lock();
+            let Some(rate) = state.limits.bytes_per_sec.filter(|rate| *rate > 0) else {
+                return;
+            };
+            let rate = rate as f64;
+            let now = Instant::now();
+            let elapsed = state.refilled.map_or(0.0, |refilled| now.duration_since(refilled).as_secs_f64());
+            state.refilled = Some(now);
+            // At most a second's worth of unused rate is banked for bursts
+            state.allowance = (state.allowance + elapsed * rate).min(rate) - bytes as f64;
+            (state.allowance < 0.0).then(|| Duration::from_secs_f64(-state.allowance / rate))
+        };
+        if let Some(wait) = wait {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+impl Default for DownloadCoordinator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for DownloadSlot<'_> {
+    fn drop(&mut self) {
+        let mut state = self.coordinator.lock();
+        state.active = state.active.saturating_sub(1);
+        drop(state);
+        self.coordinator.slot_freed.notify_one();
+    }
+}
+
+static DOWNLOADS: DownloadCoordinator = DownloadCoordinator::new();
+
+/// Set the limits shared by every download in the process.
+pub fn set_download_limits(limits: DownloadLimits) {
+    DOWNLOADS.set_limits(limits);
+}
+
+/// Current process-wide download limits.
+pub fn download_limits() -> DownloadLimits {
+    DOWNLOADS.limits()
+}
+
+/// Take a process-wide transfer slot (see [`DownloadCoordinator::acquire`]).
+pub fn download_slot() -> DownloadSlot<'static> {
+    DOWNLOADS.acquire()
+}
+
+/// Count bytes read by a download against the process-wide rate cap
+/// (see [`DownloadCoordinator::throttle`]).
+pub fn throttle_download(bytes: usize) {
+    DOWNLOADS.throttle(bytes);
+}
+
+/// Outcome of probing one service in [`connectivity_check`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceStatus {
+    pub name: String,
+    pub url: String,
+    /// Whether the host answered at all; any HTTP status counts.
+    pub reachable: bool,
+    /// Time until the response headers arrived.
+    pub latency_ms: Option<u64>,
+    /// HTTP status of the answer.
+    pub status: Option<u16>,
+    /// Why the host could not be reached.
+    pub error: Option<String>,
+}
+
+/// Probe every service a launch needs (sign-in, session, metadata, libraries,
+/// assets and the Adoptium API) with a short HEAD request, in parallel and
+/// through the configured proxy.
+pub fn connectivity_check() -> Vec<ServiceStatus> {
+    let adoptium = format!("{}/", crate::java::adoptium_base_url());
+    let services: Vec<(&str, &str)> = REQUIRED_SERVICES
+        .iter()
+        .copied()
+        .chain(std::iter::once(("Adoptium (Java downloads)", adoptium.as_str())))
+        .collect();
+    let client = match client_builder().and_then(|b| b.timeout(CONNECTIVITY_TIMEOUT).build().map_err(Into::into)) {
+        Ok(client) => client,
+        Err(err) => {
+            return services
+                .iter()
+                .map(|(name, url)| ServiceStatus {
+                    name: name.to_string(),
+                    url: url.to_string(),
+                    reachable: false,
+                    latency_ms: None,
+                    status: None,
+                    error: Some(format!("{err:#}")),
+                })
+                .collect();
+        }
+    };
+    std::thread::scope(|scope| {
+        let probes: Vec<_> = services
+            .iter()
+            .map(|(name, url)| scope.spawn(|| probe_service(&client, name, url)))
+            .collect();
+        probes.into_iter().filter_map(|probe| probe.join().ok()).collect()
+    })
+}
+
+fn probe_service(client: &Client, name: &str, url: &str) -> ServiceStatus {
+    let started = Instant::now();
+    let result = client.head(url).send();
+    let latency_ms = started.elapsed().as_millis() as u64;
+    let mut status = ServiceStatus {
+        name: name.to_string(),
+        url: url.to_string(),
+        reachable: false,
+        latency_ms: None,
+        status: None,
+        error: None,
+    };
+    match result {
+        Ok(resp) if resp.status() == StatusCode::PROXY_AUTHENTICATION_REQUIRED => {
+            status.status = Some(resp.status().as_u16());
+            status.error = Some("the proxy requires authentication".to_string());
+        }
+        // Any answer, even 404 or 405 for a bare HEAD, means the host is up
+        Ok(resp) => {
+            status.reachable = true;
+            status.latency_ms = Some(latency_ms);
+            status.status = Some(resp.status().as_u16());
+        }
+        Err(err) if err.is_timeout() => {
+            status.error = Some(format!("no answer within {}s", CONNECTIVITY_TIMEOUT.as_secs()));
+        }
+        Err(err) => status.error = Some(format!("{:#}", anyhow::Error::from(err))),
+    }
+    status
+}
+
+/// Whether an error (or any of its causes) is a [`ProxyAuthRequired`], or a
+/// proxy refusing an HTTPS tunnel for lack of credentials.
+pub fn is_proxy_auth_required(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| cause.is::<ProxyAuthRequired>() || is_tunnel_auth_rejection(cause))
+}
+
+/// Whether an error (or any of its causes) is a [`RequestTimedOut`].
+pub fn is_timed_out(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| cause.is::<RequestTimedOut>())
+}
+
+/// Whether an error (or any of its causes) is a [`DownloadCancelled`].
+pub fn is_download_cancelled(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| cause.is::<DownloadCancelled>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::Engine;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    fn read_request(stream: &mut std::net::TcpStream) -> String {
+        let mut data = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !data.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = stream.read(&mut buf).unwrap();
+            if n == 0 {
+                break;
+            }
+            data.extend_from_slice(&buf[..n]);
+        }
+        String::from_utf8_lossy(&data).to_string()
+    }
+
+    /// Serve one canned response per connection, returning how many were served.
+    fn serve_sequence(responses: Vec<&'static str>) -> (String, std::thread::JoinHandle<usize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/release", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let mut served = 0;
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                read_request(&mut stream);
+                stream.write_all(response.as_bytes()).unwrap();
+                served += 1;
+            }
+            served
+        });
+        (url, server)
+    }
+
+    #[test]
+    fn test_download_coordinator_caps_concurrency() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let coordinator = DownloadCoordinator::new();
+        coordinator.set_limits(DownloadLimits { max_concurrent: Some(2), bytes_per_sec: None });
+        let (active, peak) = (AtomicUsize::new(0), AtomicUsize::new(0));
+        std::thread::scope(|scope| {
+            for _ in 0..6 {
+                scope.spawn(|| {
+                    let _slot = coordinator.acquire();
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(20));
+                    active.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_download_coordinator_rate_is_shared() {
+        let coordinator = DownloadCoordinator::new();
+        coordinator.set_limits(DownloadLimits { max_concurrent: None, bytes_per_sec: Some(1_000_000) });
+        // 4 transfers x 50 KB against a 1 MB/s cap take at least 0.2 s together
+        let started = Instant::now();
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    for _ in 0..10 {
+                        coordinator.throttle(5_000);
+                    }
+                });
+            }
+        });
+        assert!(started.elapsed() >= Duration::from_millis(190), "{:?}", started.elapsed());
+
+        coordinator.set_limits(DownloadLimits::default());
+        let started = Instant::now();
+        coordinator.throttle(100_000_000);
+        assert!(started.elapsed() < Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_probe_service_reports_status_and_errors() {
+        let (url, server) = serve_sequence(vec!["HTTP/1.1 405 Method Not Allowed\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"]);
+        let client = Client::new();
+        let up = probe_service(&client, "up", &url);
+        assert!(up.reachable && up.latency_ms.is_some() && up.error.is_none(), "{up:?}");
+        assert_eq!(up.status, Some(405));
+        server.join().unwrap();
+
+        // Nothing listens on a port freed right after binding it
+        let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let down = probe_service(&client, "down", &format!("http://127.0.0.1:{port}/"));
+        assert!(!down.reachable && down.status.is_none(), "{down:?}");
+        assert!(down.error.is_some());
+    }
+
+    #[test]
+    fn test_send_with_retry_recovers_from_5xx() {
+        let (url, server) = serve_sequence(vec![
+            "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+        ]);
+        let client = Client::new();
+        let resp = check_status(send_with_retry(client.get(&url), 2).unwrap()).unwrap();
+        assert_eq!(resp.text().unwrap(), "ok");
+        assert_eq!(server.join().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_send_with_retry_passes_4xx_and_respects_attempts() {
+        let (url, server) = serve_sequence(vec![
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        ]);
+        let client = Client::new();
+        // 4xx is final even with attempts left
+        let resp = send_with_retry(client.get(&url), 3).unwrap();
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+        // A single attempt hands back the 5xx without retrying
+        let resp = send_with_retry(client.get(&url), 1).unwrap();
+        assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(server.join().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_read_timeout_surfaces_as_timed_out() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/slow", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            read_request(&mut stream);
+            // Promise a body, then stall
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nhi").unwrap();
+            std::thread::sleep(Duration::from_millis(800));
+        });
+
+        let client = Client::builder().timeout(Duration::from_millis(200)).build().unwrap();
+        let mut resp = send_with_retry(client.get(&url), 1).unwrap();
+        let mut body = Vec::new();
+        let err = resp.read_to_end(&mut body).unwrap_err();
+        let err = read_error(&url, err);
+        assert!(is_timed_out(&err), "{err:#}");
+        assert!(err.to_string().contains("timed out"));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_backoff_delay_grows() {
+        assert!(backoff_delay(1) >= RETRY_BASE_DELAY && backoff_delay(1) < RETRY_BASE_DELAY * 2);
+        assert!(backoff_delay(3) >= RETRY_BASE_DELAY * 4);
+    }
+
+    #[test]
+    fn test_proxy_auth_retry_with_credentials() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy_url = format!("http://{}", listener.local_addr().unwrap());
+        let expected = format!(
+            "Basic {}",
+            base64::engine::general_purpose::STANDARD.encode("alice:hunter2")
+        );
+
+        let server = std::thread::spawn(move || {
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let request = read_request(&mut stream);
+                let authorized = request.lines().any(|line| {
+                    line.to_ascii_lowercase().starts_with("proxy-authorization:")
+                        && line.ends_with(&expected)
+                });
+                let response = if authorized {
+                    "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok"
+                } else {
+                    "HTTP/1.1 407 Proxy Authentication Required\r\nProxy-Authenticate: Basic realm=\"corp\"\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        let url = "http://downloads.example.invalid/file.jar";
+
+        let anonymous = builder_for_proxy(Some(&proxy_url), None).unwrap().build().unwrap();
+        let err = check_status(anonymous.get(url).send().unwrap()).unwrap_err();
+        assert!(is_proxy_auth_required(&err));
+
+        let credentials = ProxyCredentials {
+            username: "alice".to_string(),
+            password: "hunter2".to_string(),
+        };
+        let authed = builder_for_proxy(Some(&proxy_url), Some(&credentials)).unwrap().build().unwrap();
+        let resp = check_status(authed.get(url).send().unwrap()).unwrap();
+        assert_eq!(resp.text().unwrap(), "ok");
+
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_proxy_auth_required_for_https_tunnel() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let request = read_request(&mut stream);
+            stream
+                .write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\nProxy-Authenticate: Basic realm=\"corp\"\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            request
+        });
+
+        let client = builder_for_proxy(Some(&proxy_url), None).unwrap().build().unwrap();
+        let err = send_with_retry(client.get("https://downloads.example.invalid/file.jar"), 3).unwrap_err();
+        assert!(err.is::<ProxyAuthRequired>());
+        assert!(is_proxy_auth_required(&err));
+
+        // One CONNECT, not retried
+        assert!(server.join().unwrap().starts_with("CONNECT downloads.example.invalid:443"));
+    }
+
+    #[test]
+    fn test_mirror_failover() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let good = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let request = read_request(&mut stream);
+            assert!(request.starts_with("GET /upstream.example.invalid/v1/file.bin?x=1 "));
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 7\r\nConnection: close\r\n\r\npayload")
+                .unwrap();
+        });
+
+        // Nothing listens on the discard port, so the first mirror errors
+        let mirrors = vec!["http://127.0.0.1:9".to_string(), good.clone()];
+        let client = builder_for_proxy(None, None).unwrap().build().unwrap();
+        let (body, served_by) = with_mirror_failover(
+            "https://upstream.example.invalid/v1/file.bin?x=1",
+            &mirrors,
+            |url| Ok(check_status(client.get(url).send()?)?.text()?),
+        )
+        .unwrap();
+
+        assert_eq!(body, "payload");
+        assert_eq!(served_by, format!("{good}/upstream.example.invalid/v1/file.bin?x=1"));
+        server.join().unwrap();
+
+        assert_eq!(
+            mirror_url("https://libraries.minecraft.net/a/b.jar", "https://mirror.example.com/cache/").unwrap(),
+            "https://mirror.example.com/cache/libraries.minecraft.net/a/b.jar"
+        );
+        assert_eq!(
+            mirror_url("http://maven.example.com:8080/c.jar", "https://mirror.example.com").unwrap(),
+            "https://mirror.example.com/maven.example.com:8080/c.jar"
+        );
+    }
+
+    #[test]
+    fn test_proxy_settings_override_environment() {
+        let system = || Some("http://env-proxy:3128".to_string());
+        let default = ProxySettings::default();
+        assert_eq!(resolve_proxy_url(default, system).as_deref(), Some("http://env-proxy:3128"));
+
+        let custom = ProxySettings {
+            url: Some("http://corp-proxy:8080".to_string()),
+            disabled: false,
+        };
+        assert_eq!(resolve_proxy_url(custom.clone(), system).as_deref(), Some("http://corp-proxy:8080"));
+
+        let disabled = ProxySettings { disabled: true, ..custom };
+        assert_eq!(resolve_proxy_url(disabled, system), None);
+    }
+}
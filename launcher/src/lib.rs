@@ -3,14 +3,22 @@ pub mod auth;
 pub mod config;
 pub mod content_store;
 pub mod curseforge;
+pub mod downloads;
+pub mod fabric;
+pub mod forge;
+pub mod http;
 pub mod instance;
+pub mod instance_archive;
 pub mod java;
 pub mod library;
+pub mod logging;
 pub mod logs;
 pub mod minecraft;
+pub mod mod_scan;
 pub mod modpack;
 pub mod modrinth;
 pub mod ops;
+pub mod options_txt;
 pub mod paths;
 pub mod profile;
 pub mod skin;
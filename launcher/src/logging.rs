@@ -0,0 +1,161 @@
+//! Launcher diagnostics logging.
+//!
+//! Sets up a `tracing` subscriber that writes to stderr and to a launcher log file.
+//! The level is controlled by `RUST_LOG` when set; otherwise it defaults to `info`,
+//! or `debug` when verbose output is requested (CLI flag or persisted setting).
+//! The log file is rotated once it reaches [`MAX_LOG_FILE_BYTES`], keeping one
+//! previous file, so debug logging cannot fill the disk.
+
+use anyhow::{Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// File name of the launcher log inside the logs directory.
+pub const LAUNCHER_LOG_FILE: &str = "launcher.log";
+/// Size at which the launcher log is moved to `launcher.log.1` and started afresh.
+pub const MAX_LOG_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Append-only log file that rotates itself past a size limit.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    len: u64,
+    max_len: u64,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, max_len: u64) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let len = file.metadata()?.len();
+        let mut rotating = Self { path, file, len, max_len };
+        if rotating.len >= max_len {
+            rotating.rotate()?;
+        }
+        Ok(rotating)
+    }
+
+    /// Keep the current contents as `<name>.1` (replacing an older copy) and
+    /// empty the file. Copying rather than renaming works while the file is
+    /// open on Windows.
+    fn rotate(&mut self) -> std::io::Result<()> {
+        let mut previous = self.path.clone().into_os_string();
+        previous.push(".1");
+        std::fs::copy(&self.path, previous)?;
+        self.file.set_len(0)?;
+        self.len = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.len > 0 && self.len + buf.len() as u64 > self.max_len {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.len += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Build the level filter from `RUST_LOG`-style directives.
+///
+/// When `directives` is `None` or empty the default level is used: `debug` if
+/// `verbose` is set, `info` otherwise. Invalid directives are ignored.
+pub fn build_filter(directives: Option<&str>, verbose: bool) -> EnvFilter {
+    let default_level = if verbose { LevelFilter::DEBUG } else { LevelFilter::INFO };
+    let builder = EnvFilter::builder().with_default_directive(default_level.into());
+    match directives.map(str::trim).filter(|d| !d.is_empty()) {
+        Some(directives) => builder.parse_lossy(directives),
+        None => builder.parse_lossy(""),
+    }
+}
+
+/// Initialize the global subscriber.
+///
+/// The same filter applies to the console (stderr) and, when `log_dir` is given,
+/// to `launcher.log` in that directory. Calling this more than once is a no-op.
+pub fn init_logging(log_dir: Option<&Path>, verbose: bool) -> Result<()> {
+    let env = std::env::var("RUST_LOG").ok();
+    let filter = build_filter(env.as_deref(), verbose);
+
+    let console = tracing_subscriber::fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_target(false)
+        .without_time();
+
+    let file = match log_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create log dir: {}", dir.display()))?;
+            let path = dir.join(LAUNCHER_LOG_FILE);
+            let file = RotatingFile::open(path.clone(), MAX_LOG_FILE_BYTES)
+                .with_context(|| format!("failed to open log file: {}", path.display()))?;
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_writer(Mutex::new(file))
+                    .with_ansi(false),
+            )
+        }
+        None => None,
+    };
+
+    let _ = tracing_subscriber::registry()
+        .with(filter)
+        .with(console)
+        .with(file)
+        .try_init();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_filter_parses_env_directive() {
+        let filter = build_filter(Some("warn,shard::java=trace"), false);
+        assert_eq!(filter.max_level_hint(), Some(LevelFilter::TRACE));
+        let rendered = filter.to_string();
+        assert!(rendered.contains("shard::java=trace"));
+        assert!(rendered.contains("warn"));
+
+        assert_eq!(build_filter(None, false).max_level_hint(), Some(LevelFilter::INFO));
+        assert_eq!(build_filter(Some("  "), true).max_level_hint(), Some(LevelFilter::DEBUG));
+        assert_eq!(build_filter(Some("error"), true).max_level_hint(), Some(LevelFilter::ERROR));
+    }
+
+    #[test]
+    fn test_log_file_rotates_past_the_limit() {
+        let dir = std::env::temp_dir().join(format!("shard-log-rotate-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(LAUNCHER_LOG_FILE);
+        let previous = dir.join(format!("{LAUNCHER_LOG_FILE}.1"));
+
+        let mut file = RotatingFile::open(path.clone(), 16).unwrap();
+        file.write_all(b"first line\n").unwrap();
+        file.write_all(b"second line\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&previous).unwrap(), "first line\n");
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second line\n");
+
+        // An oversized file left by an earlier run is rotated on open
+        drop(file);
+        std::fs::write(&path, "x".repeat(32)).unwrap();
+        RotatingFile::open(path.clone(), 16).unwrap();
+        assert_eq!(std::fs::read_to_string(&previous).unwrap().len(), 32);
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
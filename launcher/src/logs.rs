@@ -3,11 +3,13 @@
 //! Handles reading logs from running and past game sessions.
 
 use crate::paths::Paths;
+use crate::profile::{ContentRef, Profile};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader, Seek, SeekFrom};
 use std::path::PathBuf;
+use std::process::ExitStatus;
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
 use std::time::Duration;
@@ -84,9 +86,19 @@ pub struct LogSession {
 }
 
 impl Paths {
+    /// Directory the game of a profile runs in, which holds its logs and crash
+    /// reports (see [`crate::instance::game_dir`]). Falls back to the instance
+    /// folder when the profile cannot be read.
+    pub fn instance_game_dir(&self, profile_id: &str) -> PathBuf {
+        match crate::profile::load_profile(self, profile_id) {
+            Ok(profile) => crate::instance::game_dir(self, &profile),
+            Err(_) => self.instance_dir(profile_id),
+        }
+    }
+
     /// Get the logs directory for a profile instance
     pub fn instance_logs_dir(&self, profile_id: &str) -> PathBuf {
-        self.instance_dir(profile_id).join("logs")
+        self.instance_game_dir(profile_id).join("logs")
     }
 
     /// Get the current log file path for a profile instance
@@ -96,7 +108,7 @@ impl Paths {
 
     /// Get the crash reports directory for a profile instance
     pub fn instance_crash_reports(&self, profile_id: &str) -> PathBuf {
-        self.instance_dir(profile_id).join("crash-reports")
+        self.instance_game_dir(profile_id).join("crash-reports")
     }
 }
 
@@ -281,6 +293,181 @@ pub fn list_crash_reports(paths: &Paths, profile_id: &str) -> Result<Vec<LogFile
     Ok(files)
 }
 
+/// Suggest which mods a crash report implicates, by name or file name.
+///
+/// This is a heuristic: it returns the names of mods whose name or jar file
+/// name (without version suffix) appears in the report text.
+pub fn suggest_suspect_mods(report: &str, mods: &[ContentRef]) -> Vec<String> {
+    let haystack = report.to_lowercase();
+    let mut suspects = Vec::new();
+
+    for item in mods {
+        let mut needles = vec![item.name.to_lowercase()];
+        if let Some(file_name) = &item.file_name {
+            let stem = file_name.trim_end_matches(".jar").to_lowercase();
+            // "sodium-fabric-0.5.8+mc1.20.4.jar" -> "sodium-fabric"
+            let base = stem
+                .split(['-', '_', '+'])
+                .take_while(|part| !part.starts_with(|c: char| c.is_ascii_digit()))
+                .collect::<Vec<_>>()
+                .join("-");
+            needles.push(stem);
+            needles.push(base);
+        }
+        let hit = needles
+            .iter()
+            .filter(|needle| needle.len() >= 3)
+            .any(|needle| haystack.contains(needle.as_str()));
+        if hit && !suspects.contains(&item.name) {
+            suspects.push(item.name.clone());
+        }
+    }
+
+    suspects
+}
+
+/// Suggest suspect mods from a profile's most recent crash report.
+pub fn suggest_suspect_mods_from_latest_crash(paths: &Paths, profile: &Profile) -> Result<Vec<String>> {
+    let reports = list_crash_reports(paths, &profile.id)?;
+    let Some(latest) = reports.first() else {
+        return Ok(Vec::new());
+    };
+    let report = fs::read_to_string(&latest.path)
+        .with_context(|| format!("failed to read crash report: {}", latest.path.display()))?;
+    Ok(suggest_suspect_mods(&report, &profile.mods))
+}
+
+/// Recognized cause of a failed game run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CrashKind {
+    OutOfMemory,
+    /// The game or a mod needs a newer Java than the one it was launched with.
+    WrongJava { required: u32, launched: Option<u32> },
+    MissingNatives,
+    ModConflict,
+    Unknown,
+}
+
+/// Short explanation of why the game exited, with a suggested fix.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CrashDiagnosis {
+    pub kind: CrashKind,
+    pub explanation: String,
+    pub suggestion: String,
+}
+
+/// Offset between a class file major version and the Java release that introduced it.
+const CLASS_FILE_VERSION_OFFSET: u32 = 44;
+
+const NATIVES_SIGNATURES: &[&str] = &[
+    "UnsatisfiedLinkError",
+    "no lwjgl in java.library.path",
+    "Failed to locate library:",
+    "Could not load library",
+];
+
+const MOD_CONFLICT_SIGNATURES: &[&str] = &[
+    "Incompatible mods found",
+    "Mod resolution failed",
+    "ModResolutionException",
+    "DuplicateModsFoundException",
+    "Found duplicate mods",
+    "Mod Sorting failed",
+    "is incompatible with",
+];
+
+/// Whether the game was killed with SIGKILL, which on Linux usually means the
+/// OOM killer stepped in. A shell wrapper reports the same as exit code 137.
+fn killed_by_sigkill(status: &ExitStatus) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if status.signal() == Some(9) {
+            return true;
+        }
+    }
+    status.code() == Some(137)
+}
+
+/// Explain a non-zero exit from the exit status and the last lines of game output.
+///
+/// Signatures are checked from most to least specific. For a wrong Java major the
+/// required version is the newer of what Minecraft `mc_version` needs (see
+/// [`get_required_java_version`](crate::java::get_required_java_version)) and
+/// what the rejected class file asks for.
+pub fn diagnose_exit(status: &ExitStatus, last_log_lines: &[String], mc_version: &str) -> CrashDiagnosis {
+    let has = |needles: &[&str]| {
+        last_log_lines
+            .iter()
+            .any(|line| needles.iter().any(|needle| line.contains(needle)))
+    };
+
+    if let Some(line) = last_log_lines.iter().find(|line| line.contains("UnsupportedClassVersionError")) {
+        let (class_needs, runtime_supports) = parse_class_file_versions(line);
+        let required = crate::java::get_required_java_version(mc_version).max(class_needs.unwrap_or(0));
+        let explanation = match runtime_supports {
+            Some(launched) => format!("This needs Java {}, but it was launched with Java {}.", required, launched),
+            None => format!("This needs Java {} or newer, but it was launched with an older Java.", required),
+        };
+        return CrashDiagnosis {
+            kind: CrashKind::WrongJava { required, launched: runtime_supports },
+            explanation,
+            suggestion: format!("Select or download Java {} for this instance.", required),
+        };
+    }
+
+    if has(&["java.lang.OutOfMemoryError"]) || killed_by_sigkill(status) {
+        return CrashDiagnosis {
+            kind: CrashKind::OutOfMemory,
+            explanation: "The game ran out of memory.".to_string(),
+            suggestion: "Increase the instance's memory, or remove heavy mods and resource packs.".to_string(),
+        };
+    }
+
+    if has(NATIVES_SIGNATURES) {
+        return CrashDiagnosis {
+            kind: CrashKind::MissingNatives,
+            explanation: "Native libraries (LWJGL) could not be loaded.".to_string(),
+            suggestion: "Launch again with full verification to re-download the game libraries.".to_string(),
+        };
+    }
+
+    if has(MOD_CONFLICT_SIGNATURES) {
+        return CrashDiagnosis {
+            kind: CrashKind::ModConflict,
+            explanation: "The mod loader found conflicting, duplicate or missing mods.".to_string(),
+            suggestion: "Check the log for the mods named, update or remove them, or launch in safe mode.".to_string(),
+        };
+    }
+
+    let explanation = match status.code() {
+        Some(code) => format!("Minecraft exited with code {}.", code),
+        None => "Minecraft was terminated by a signal.".to_string(),
+    };
+    CrashDiagnosis {
+        kind: CrashKind::Unknown,
+        explanation,
+        suggestion: "Open the latest log or crash report for details.".to_string(),
+    }
+}
+
+/// Java majors from an `UnsupportedClassVersionError` message: the one the class
+/// was compiled for and the newest the running JVM supports.
+///
+/// "... compiled by a more recent version of the Java Runtime (class file version 65.0),
+/// this version of the Java Runtime only recognizes class file versions up to 61.0"
+/// gives `(Some(21), Some(17))`.
+fn parse_class_file_versions(line: &str) -> (Option<u32>, Option<u32>) {
+    let major_after = |marker: &str| {
+        let rest = &line[line.find(marker)? + marker.len()..];
+        let digits: String = rest.trim_start().chars().take_while(|c| c.is_ascii_digit()).collect();
+        let class_version: u32 = digits.parse().ok()?;
+        class_version.checked_sub(CLASS_FILE_VERSION_OFFSET)
+    };
+    (major_after("class file version"), major_after("versions up to"))
+}
+
 /// Log watcher for real-time log streaming
 pub struct LogWatcher {
     path: PathBuf,
@@ -439,3 +626,104 @@ pub fn format_entry(entry: &LogEntry, colored: bool) -> String {
         entry.message.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    fn exit_status(raw: i32) -> ExitStatus {
+        std::os::unix::process::ExitStatusExt::from_raw(raw)
+    }
+
+    #[cfg(windows)]
+    fn exit_status(raw: i32) -> ExitStatus {
+        std::os::windows::process::ExitStatusExt::from_raw(raw as u32)
+    }
+
+    /// A normal exit with `code` (wait statuses keep the code in the second byte on Unix).
+    fn exited(code: i32) -> ExitStatus {
+        exit_status(if cfg!(unix) { code << 8 } else { code })
+    }
+
+    fn mod_ref(name: &str, file_name: &str) -> ContentRef {
+        serde_json::from_value(serde_json::json!({
+            "name": name,
+            "hash": "00",
+            "file_name": file_name,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_suggest_suspect_mods() {
+        let mods = vec![
+            mod_ref("Sodium", "sodium-fabric-0.5.8+mc1.20.4.jar"),
+            mod_ref("Lithium", "lithium-fabric-mc1.20.4-0.12.1.jar"),
+        ];
+        let report = "---- Minecraft Crash Report ----\n\
+            Description: Mod loading failed\n\
+            java.lang.NoSuchMethodError at me.jellysquid.mods.sodium.client.SodiumClientMod\n\
+            \tFile: sodium-fabric-0.5.8+mc1.20.4.jar";
+        assert_eq!(suggest_suspect_mods(report, &mods), vec!["Sodium".to_string()]);
+        assert!(suggest_suspect_mods("nothing relevant", &mods).is_empty());
+    }
+
+    #[test]
+    fn test_diagnose_exit_reports_required_and_launched_java() {
+        let lines = vec![
+            "Exception in thread \"main\" java.lang.UnsupportedClassVersionError: net/minecraft/client/main/Main \
+             has been compiled by a more recent version of the Java Runtime (class file version 65.0), \
+             this version of the Java Runtime only recognizes class file versions up to 61.0"
+                .to_string(),
+        ];
+        let diagnosis = diagnose_exit(&exited(1), &lines, "1.20.6");
+        assert_eq!(diagnosis.kind, CrashKind::WrongJava { required: 21, launched: Some(17) });
+        assert_eq!(diagnosis.explanation, "This needs Java 21, but it was launched with Java 17.");
+    }
+
+    #[test]
+    fn test_diagnose_exit_recognizes_common_signatures() {
+        let diagnose = |line: &str| diagnose_exit(&exited(1), &[line.to_string()], "1.20.1").kind;
+        assert_eq!(diagnose("java.lang.OutOfMemoryError: Java heap space"), CrashKind::OutOfMemory);
+        assert_eq!(
+            diagnose("java.lang.UnsatisfiedLinkError: Failed to locate library: liblwjgl.so"),
+            CrashKind::MissingNatives
+        );
+        assert_eq!(diagnose("net.fabricmc.loader.impl.FormattedException: Incompatible mods found!"), CrashKind::ModConflict);
+        assert_eq!(diagnose("[main/INFO]: Stopping!"), CrashKind::Unknown);
+        // Through a shell wrapper, which turns the signal into exit code 137
+        assert_eq!(diagnose_exit(&exited(137), &[], "1.20.1").kind, CrashKind::OutOfMemory);
+        assert_eq!(diagnose_exit(&exited(9), &[], "1.20.1").kind, CrashKind::Unknown);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_diagnose_exit_treats_sigkill_as_out_of_memory() {
+        // Killed by signal 9: no exit code at all
+        let killed = exit_status(9);
+        assert_eq!(killed.code(), None);
+        assert_eq!(diagnose_exit(&killed, &[], "1.20.1").kind, CrashKind::OutOfMemory);
+        // SIGTERM is a plain stop, not memory
+        assert_eq!(diagnose_exit(&exit_status(15), &[], "1.20.1").kind, CrashKind::Unknown);
+    }
+
+    #[test]
+    fn test_logs_follow_a_custom_game_dir() {
+        use crate::profile::{Runtime, create_profile};
+
+        let base = std::env::temp_dir().join(format!("shard-logs-game-dir-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&base);
+        let paths = Paths::from_base(&base);
+        paths.ensure().unwrap();
+        let runtime = Runtime { game_dir: Some("shared".to_string()), ..Runtime::default() };
+        create_profile(&paths, "custom", "1.20.1", None, runtime).unwrap();
+
+        let game_dir = paths.instance_dir("custom").join("shared");
+        assert_eq!(paths.instance_latest_log("custom"), game_dir.join("logs").join("latest.log"));
+        assert_eq!(paths.instance_crash_reports("custom"), game_dir.join("crash-reports"));
+        assert_eq!(paths.instance_logs_dir("missing"), paths.instance_dir("missing").join("logs"));
+
+        let _ = fs::remove_dir_all(&base);
+    }
+}
@@ -1,6 +1,5 @@
 use anyhow::{Context, Result, bail};
 use clap::{Parser, Subcommand, ValueEnum};
-use reqwest::blocking::Client;
 use reqwest::header::CONTENT_TYPE;
 use semver::Version;
 use serde::Deserialize;
@@ -8,15 +7,21 @@ use shard::accounts::{delete_account_tokens, load_accounts, remove_account, save
 use shard::auth::request_device_code;
 use shard::config::{load_config, save_config};
 use shard::content_store::{ContentStore, ContentType, Platform, SearchOptions};
+use shard::curseforge::CurseForgeClient;
 use shard::library::{
     Library, LibraryContentType, LibraryFilter, LibraryItemInput,
 };
 use shard::logs::{
     filter_by_level, format_entry, list_crash_reports, list_log_files, read_log_file,
-    read_log_tail, search_logs, watch_log, LogLevel,
+    read_log_tail, search_logs, suggest_suspect_mods_from_latest_crash, watch_log, LogLevel,
 };
-use shard::minecraft::{launch, prepare};
-use shard::modpack::import_mrpack;
+use shard::minecraft::{LaunchOptions, QuickPlay, launch_with_options, prepare_with_options, test_launch, verify_instance_integrity};
+use shard::http::{set_download_limits, set_mirrors, set_proxy_settings};
+use shard::java::{migrate_legacy_runtime_dirs, parse_adoptium_base_url, set_adoptium_base_url};
+use shard::logging::init_logging;
+use shard::instance::duplicate_instance;
+use shard::instance_archive::{export_instance, import_instance_archive};
+use shard::modpack::{import_curseforge_zip, import_mrpack, is_curseforge_pack};
 use shard::ops::{finish_device_code_flow, parse_loader, resolve_input, resolve_launch_account};
 use shard::paths::Paths;
 use shard::profile::{
@@ -41,6 +46,9 @@ use std::time::Duration;
 #[derive(Parser, Debug)]
 #[command(name = "shard", version, about = "Minimal Minecraft launcher")]
 struct Cli {
+    /// Enable debug logging (RUST_LOG takes precedence when set)
+    #[arg(short, long, global = true)]
+    verbose: bool,
     #[command(subcommand)]
     command: Command,
 }
@@ -121,6 +129,8 @@ enum Command {
         #[command(subcommand)]
         command: ConfigCommand,
     },
+    /// Check that the services a launch needs (sign-in, Mojang, Adoptium) are reachable
+    Connectivity,
     /// Desktop app update checks
     AppUpdate {
         #[command(subcommand)]
@@ -133,6 +143,39 @@ enum Command {
         account: Option<String>,
         #[arg(long)]
         prepare_only: bool,
+        /// Start the game, wait until it reaches the main menu, then close it
+        #[arg(long, conflicts_with = "prepare_only")]
+        test: bool,
+        /// Seconds to wait for a test launch before giving up
+        #[arg(long, default_value_t = 180)]
+        timeout: u64,
+        /// Re-hash every library and asset before launching
+        #[arg(long)]
+        full_verify: bool,
+        /// Trust game files whose size matches instead of re-hashing them
+        #[arg(long, conflicts_with = "full_verify")]
+        fast_verify: bool,
+        /// Do not pass _JAVA_OPTIONS, JAVA_TOOL_OPTIONS, etc. to the game
+        #[arg(long)]
+        clear_java_env: bool,
+        /// Launch with all mods disabled (the loader still runs)
+        #[arg(long)]
+        safe_mode: bool,
+        /// Copy mods and packs into the instance instead of linking them
+        #[arg(long)]
+        copy_files: bool,
+        /// Run the game with java.exe instead of javaw.exe so it keeps a console (Windows)
+        #[arg(long)]
+        console: bool,
+        /// Join this server on start (Minecraft 1.20+)
+        #[arg(long, value_name = "ADDRESS", conflicts_with_all = ["world", "realm"])]
+        server: Option<String>,
+        /// Open this singleplayer world on start (Minecraft 1.20+)
+        #[arg(long, value_name = "FOLDER", conflicts_with = "realm")]
+        world: Option<String>,
+        /// Join this realm on start (Minecraft 1.20+)
+        #[arg(long, value_name = "ID")]
+        realm: Option<String>,
     },
 }
 
@@ -157,6 +200,34 @@ enum ProfileCommand {
     },
     /// Clone an existing profile
     Clone { src: String, dst: String },
+    /// Duplicate a profile together with its instance files
+    Duplicate {
+        src: String,
+        /// Name for the copy (defaults to "<src> (copy)")
+        #[arg(long)]
+        name: Option<String>,
+        /// Also copy worlds (saves)
+        #[arg(long)]
+        saves: bool,
+    },
+    /// Re-check every library and asset of a profile, re-downloading corrupt files
+    Repair { id: String },
+    /// Export a profile and its instance files as a portable archive
+    Export {
+        id: String,
+        /// Output .zip path
+        out: PathBuf,
+        /// Include worlds (saves)
+        #[arg(long)]
+        worlds: bool,
+    },
+    /// Import a profile from an archive made by `profile export`
+    ImportArchive {
+        path: PathBuf,
+        /// Optional profile id (defaults to the exported id)
+        #[arg(long)]
+        id: Option<String>,
+    },
     /// Rename a profile
     Rename {
         /// Current profile ID
@@ -210,9 +281,9 @@ enum PackCommand {
 
 #[derive(Subcommand, Debug)]
 enum ModpackCommand {
-    /// Import a Modrinth .mrpack into a new profile
+    /// Import a Modrinth .mrpack or CurseForge modpack .zip into a new profile
     Import {
-        /// Path to .mrpack file
+        /// Path to the .mrpack or .zip file
         path: PathBuf,
         /// Optional profile id (defaults to pack name)
         #[arg(long)]
@@ -491,6 +562,29 @@ enum ConfigCommand {
     SetClientSecret { client_secret: String },
     /// Set CurseForge API key
     SetCurseforgeKey { api_key: String },
+    /// Set download mirror base URLs (pass none to clear)
+    SetMirrors { mirrors: Vec<String> },
+    /// Set an https mirror of the Adoptium API for Java downloads (omit to clear)
+    SetAdoptiumUrl { url: Option<String> },
+    /// Set how many seconds the desktop app waits for the game window before
+    /// offering to stop it (0 disables, omit for the default)
+    SetStartupTimeout { secs: Option<u64> },
+    /// Cap downloads across the whole launcher (omit a flag to lift that limit)
+    SetDownloadLimits {
+        /// Downloads running at once
+        #[arg(long)]
+        max_concurrent: Option<usize>,
+        /// Combined speed in bytes per second
+        #[arg(long)]
+        bytes_per_sec: Option<u64>,
+    },
+    /// Set the proxy used instead of HTTPS_PROXY/HTTP_PROXY (omit to use the environment)
+    SetProxy {
+        url: Option<String>,
+        /// Connect directly, ignoring any configured or environment proxy
+        #[arg(long, conflicts_with = "url")]
+        disable: bool,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -625,6 +719,13 @@ fn run() -> Result<()> {
     let cli = Cli::parse();
     let paths = Paths::new()?;
     paths.ensure()?;
+    let startup_config = load_config(&paths).unwrap_or_default();
+    init_logging(Some(&paths.logs), cli.verbose || startup_config.debug_logging)?;
+    set_proxy_settings(startup_config.proxy_settings());
+    set_download_limits(startup_config.download_limits());
+    set_mirrors(startup_config.download_mirrors);
+    set_adoptium_base_url(startup_config.adoptium_base_url);
+    migrate_legacy_runtime_dirs(&paths);
 
     match cli.command {
         Command::List => {
@@ -656,7 +757,20 @@ fn run() -> Result<()> {
                         Some(value) => Some(parse_loader(&value)?),
                         None => None,
                     };
-                    let runtime = Runtime { java, memory, args };
+                    let runtime = Runtime {
+                        java,
+                        memory,
+                        args,
+                        wrapper_command: Vec::new(),
+                        pre_launch: None,
+                        post_exit: None,
+                        env: HashMap::new(),
+                        width: None,
+                        height: None,
+                        fullscreen: false,
+                        game_dir: None,
+                        gc_preset: None,
+                    };
                     create_profile(&paths, &id, &mc_version, loader, runtime)?;
                     println!("created profile {id}");
                 }
@@ -665,6 +779,40 @@ fn run() -> Result<()> {
                 clone_profile(&paths, &src, &dst)?;
                 println!("cloned profile {src} -> {dst}");
             }
+            ProfileCommand::Duplicate { src, name, saves } => {
+                let copy = duplicate_instance(&paths, &src, name.as_deref(), saves)?;
+                println!("duplicated profile {src} -> {}", copy.id);
+            }
+            ProfileCommand::Repair { id } => {
+                let profile = load_profile(&paths, &id)?;
+                let summary = verify_instance_integrity(&paths, &profile)?;
+                for path in &summary.repaired_files {
+                    println!("repaired {}", path.display());
+                }
+                println!(
+                    "checked {} files: {} downloaded, {} repaired",
+                    summary.checked,
+                    summary.downloaded,
+                    summary.repaired_files.len()
+                );
+            }
+            ProfileCommand::Export { id, out, worlds } => {
+                let profile = load_profile(&paths, &id)?;
+                let manifest = export_instance(&paths, &profile, &out, worlds)?;
+                println!(
+                    "exported {id} to {} ({} downloads referenced, {} files bundled)",
+                    out.display(),
+                    manifest.downloads.len(),
+                    manifest.bundled.len()
+                );
+            }
+            ProfileCommand::ImportArchive { path, id } => {
+                let import = import_instance_archive(&paths, &path, id.as_deref())?;
+                println!("imported instance into profile {}", import.profile.id);
+                for failed in &import.failed {
+                    eprintln!("warning: failed to download {}: {}", failed.path, failed.error);
+                }
+            }
             ProfileCommand::Diff { a, b } => {
                 let profile_a = load_profile(&paths, &a)?;
                 let profile_b = load_profile(&paths, &b)?;
@@ -805,25 +953,177 @@ fn run() -> Result<()> {
                 save_config(&paths, &config)?;
                 println!("saved CurseForge API key");
             }
+            ConfigCommand::SetMirrors { mirrors } => {
+                let mut config = load_config(&paths)?;
+                config.download_mirrors = mirrors.clone();
+                save_config(&paths, &config)?;
+                if mirrors.is_empty() {
+                    println!("cleared download mirrors");
+                } else {
+                    println!("saved {} download mirror(s)", mirrors.len());
+                }
+            }
+            ConfigCommand::SetAdoptiumUrl { url } => {
+                let mut config = load_config(&paths)?;
+                config.adoptium_base_url = url.as_deref().map(parse_adoptium_base_url).transpose()?;
+                save_config(&paths, &config)?;
+                match &config.adoptium_base_url {
+                    Some(url) => println!("saved Adoptium mirror {url}"),
+                    None => println!("cleared Adoptium mirror"),
+                }
+            }
+            ConfigCommand::SetStartupTimeout { secs } => {
+                let mut config = load_config(&paths)?;
+                config.startup_timeout_secs = secs;
+                save_config(&paths, &config)?;
+                match config.startup_timeout() {
+                    Some(timeout) => println!("startup timeout set to {}s", timeout.as_secs()),
+                    None => println!("startup timeout disabled"),
+                }
+            }
+            ConfigCommand::SetDownloadLimits { max_concurrent, bytes_per_sec } => {
+                let mut config = load_config(&paths)?;
+                config.max_concurrent_downloads = max_concurrent.filter(|max| *max > 0);
+                config.download_bytes_per_sec = bytes_per_sec.filter(|rate| *rate > 0);
+                save_config(&paths, &config)?;
+                match config.max_concurrent_downloads {
+                    Some(max) => println!("at most {max} downloads at once"),
+                    None => println!("no limit on concurrent downloads"),
+                }
+                match config.download_bytes_per_sec {
+                    Some(rate) => println!("download speed capped at {rate} bytes/s"),
+                    None => println!("no download speed cap"),
+                }
+            }
+            ConfigCommand::SetProxy { url, disable } => {
+                if let Some(url) = &url {
+                    reqwest::Proxy::all(url.as_str()).with_context(|| format!("invalid proxy url: {url}"))?;
+                }
+                let mut config = load_config(&paths)?;
+                config.proxy_url = url;
+                config.disable_proxy = disable;
+                save_config(&paths, &config)?;
+                match (&config.proxy_url, disable) {
+                    (_, true) => println!("proxy disabled"),
+                    (Some(url), false) => println!("saved proxy {url}"),
+                    (None, false) => println!("using the proxy from the environment"),
+                }
+            }
         },
+        Command::Connectivity => {
+            for service in shard::http::connectivity_check() {
+                match (service.latency_ms, &service.error) {
+                    (Some(ms), _) => println!("ok    {} ({}) {ms} ms", service.name, service.url),
+                    (None, Some(error)) => println!("FAIL  {} ({}): {error}", service.name, service.url),
+                    (None, None) => println!("FAIL  {} ({})", service.name, service.url),
+                }
+            }
+        }
         Command::AppUpdate { command } => handle_app_update_command(command)?,
         Command::Launch {
             profile,
             account,
             prepare_only,
+            test,
+            timeout,
+            full_verify,
+            fast_verify,
+            clear_java_env,
+            safe_mode,
+            copy_files,
+            console,
+            server,
+            world,
+            realm,
         } => {
             let profile_data = load_profile(&paths, &profile)?;
             let launch_account = resolve_launch_account(&paths, account)?;
+            let options = LaunchOptions {
+                full_verify,
+                fast_verify,
+                clear_java_env,
+                safe_mode,
+                copy_instead_of_link: copy_files,
+                show_console: console,
+                quick_play: server
+                    .map(QuickPlay::Multiplayer)
+                    .or(world.map(QuickPlay::Singleplayer))
+                    .or(realm.map(QuickPlay::Realms)),
+                screen_size: None,
+                startup_timeout: None,
+            };
             if prepare_only {
-                let plan = prepare(&paths, &profile_data, &launch_account)?;
+                let plan = prepare_with_options(&paths, &profile_data, &launch_account, &options)?;
+                for warning in &plan.warnings {
+                    eprintln!("warning: {}", warning);
+                }
                 println!("prepared instance: {}", plan.instance_dir.display());
+                if !plan.wrapper.is_empty() {
+                    println!("wrapper: {}", plan.wrapper.join(" "));
+                }
+                if let Some(hook) = &plan.pre_launch {
+                    println!("pre-launch hook: {}", hook);
+                }
+                if let Some(hook) = &plan.post_exit {
+                    println!("post-exit hook: {}", hook);
+                }
+                let mut env: Vec<_> = plan.env.iter().collect();
+                env.sort();
+                for (name, value) in env {
+                    if value.is_empty() {
+                        println!("env: unset {}", name);
+                    } else {
+                        println!("env: {}={}", name, value);
+                    }
+                }
                 println!("java: {}", plan.java_exec);
                 println!("main class: {}", plan.main_class);
                 println!("classpath: {}", plan.classpath);
                 println!("jvm args: {}", plan.jvm_args.join(" "));
-                println!("game args: {}", plan.game_args.join(" "));
-            } else {
-                launch(&paths, &profile_data, &launch_account)?;
+                println!("game args: {}", plan.redact(&plan.game_args.join(" ")));
+                println!("working dir: {}", plan.instance_dir.display());
+                println!("command: {}", plan.preview(&options).command_line());
+                if full_verify {
+                    println!(
+                        "verified {} files ({} repaired)",
+                        plan.verification.checked, plan.verification.repaired
+                    );
+                }
+            } else if test {
+                let report = test_launch(
+                    &paths,
+                    &profile_data,
+                    &launch_account,
+                    &options,
+                    Duration::from_secs(timeout),
+                )?;
+                if report.success {
+                    println!(
+                        "test launch succeeded in {:.1}s ({})",
+                        report.elapsed.as_secs_f64(),
+                        report.marker.as_deref().unwrap_or("started")
+                    );
+                } else {
+                    for line in &report.output {
+                        eprintln!("{line}");
+                    }
+                    if report.timed_out {
+                        bail!("test launch timed out after {timeout}s");
+                    }
+                    bail!(
+                        "test launch failed: game exited with {}",
+                        report.exit_status.as_deref().unwrap_or("unknown status")
+                    );
+                }
+            } else if let Err(err) = launch_with_options(&paths, &profile_data, &launch_account, &options) {
+                if !safe_mode && !profile_data.mods.is_empty() {
+                    let suspects = suggest_suspect_mods_from_latest_crash(&paths, &profile_data).unwrap_or_default();
+                    if !suspects.is_empty() {
+                        eprintln!("crash report mentions: {}", suspects.join(", "));
+                    }
+                    eprintln!("try `shard launch {profile} --safe-mode` to check whether a mod is at fault");
+                }
+                return Err(err);
             }
         }
     }
@@ -846,7 +1146,7 @@ fn handle_app_update_command(command: AppUpdateCommand) -> Result<()> {
                     .context("unsupported OS/arch for updater target; use --platform to override")?,
             };
 
-            let client = Client::builder()
+            let client = shard::http::client_builder()?
                 .user_agent(format!("ShardCLI/{}", env!("CARGO_PKG_VERSION")))
                 .build()?;
 
@@ -1205,7 +1505,7 @@ fn handle_skin_command(paths: &Paths, command: SkinCommand) -> Result<()> {
             println!("skin url: {skin_url}");
             println!("normalized: {normalized_url}");
 
-            let client = Client::builder()
+            let client = shard::http::client_builder()?
                 .user_agent(format!("ShardCLI/{}", env!("CARGO_PKG_VERSION")))
                 .build()?;
             let response = client
@@ -1382,7 +1682,7 @@ fn handle_template_command(paths: &Paths, command: TemplateCommand) -> Result<()
 
 fn handle_store_command(paths: &Paths, command: StoreCommand) -> Result<()> {
     let config = load_config(paths)?;
-    let store = ContentStore::new(config.curseforge_api_key.as_deref());
+    let store = ContentStore::new(config.curseforge_api_key.as_deref())?;
 
     match command {
         StoreCommand::Search {
@@ -1690,6 +1990,15 @@ fn create_profile_from_template(
         } else {
             args
         },
+        wrapper_command: Vec::new(),
+        pre_launch: None,
+        post_exit: None,
+        env: HashMap::new(),
+        width: None,
+        height: None,
+        fullscreen: false,
+        game_dir: None,
+        gc_preset: None,
     };
 
     // Create the profile
@@ -1700,7 +2009,7 @@ fn create_profile_from_template(
     println!("downloading content from template...");
 
     // Download mods from template
-    let store = ContentStore::modrinth_only();
+    let store = ContentStore::modrinth_only()?;
     let loader_type = loader.as_ref().map(|l| l.loader_type.as_str());
 
     for mod_content in &template.mods {
@@ -2132,8 +2441,29 @@ fn handle_library_command(paths: &Paths, command: LibraryCommand) -> Result<()>
 fn handle_modpack_command(paths: &Paths, command: ModpackCommand) -> Result<()> {
     match command {
         ModpackCommand::Import { path, id } => {
-            let profile = import_mrpack(paths, &path, id.as_deref())?;
+            let (profile, failed) = if is_curseforge_pack(&path)? {
+                let config = load_config(paths)?;
+                let api_key = config
+                    .curseforge_api_key
+                    .context("CurseForge modpacks need an API key; set one with `shard config set-curseforge-key`")?;
+                let import = import_curseforge_zip(paths, &CurseForgeClient::new(&api_key)?, &path, id.as_deref())?;
+                for blocked in &import.blocked {
+                    let kind = if blocked.required { "required" } else { "optional" };
+                    eprintln!(
+                        "warning: {} ({kind}) can't be downloaded by launchers; get {} from {} and add it to the profile",
+                        blocked.name, blocked.file_name, blocked.url
+                    );
+                }
+                (import.profile, import.failed)
+            } else {
+                let import = import_mrpack(paths, &path, id.as_deref())?;
+                (import.profile, import.failed)
+            };
             println!("imported modpack into profile {}", profile.id);
+            for failed in &failed {
+                let kind = if failed.optional { "optional" } else { "required" };
+                eprintln!("warning: failed to download {kind} file {}: {}", failed.path, failed.error);
+            }
         }
     }
     Ok(())
@@ -1,11 +1,18 @@
-use crate::instance::materialize_instance;
-use crate::java::{detect_installations, get_required_java_version, is_java_compatible};
+use crate::downloads::{DownloadEntry, download_all};
+use crate::fabric;
+use crate::forge;
+use crate::http::{check_status, client as http_client};
+use crate::instance::{SafeModeGuard, game_dir, locked_worlds, materialize_instance};
+use crate::java::{
+    detect_installations, get_required_java_version, is_java_compatible_with_loader, max_java_version,
+    recommend_max_heap_mb, validate_java_path,
+};
+use crate::options_txt;
 use crate::paths::Paths;
-use crate::profile::{Loader, Profile};
-use crate::util::normalize_path_separator;
+use crate::profile::{GcPreset, Loader, Profile, Runtime, forbidden_jvm_arg};
+use crate::util::{find_executable, normalize_path_separator};
 use anyhow::{Context, Result, bail};
-use reqwest::blocking::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sha1::{Digest, Sha1};
 use shell_words::split;
@@ -13,11 +20,33 @@ use std::collections::HashMap;
 use std::fs;
 use std::io::Read;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant, SystemTime};
 
 const VERSION_MANIFEST_URL: &str =
     "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
 const LIBRARIES_BASE: &str = "https://libraries.minecraft.net/";
+const ASSET_BASE: &str = "https://resources.download.minecraft.net";
+const ASSET_DOWNLOAD_WORKERS: usize = crate::downloads::DEFAULT_CONCURRENCY;
+
+/// Log markers that indicate the client got far enough to create its window.
+const LAUNCH_SUCCESS_MARKERS: &[&str] = &[
+    "Setting user:",
+    "LWJGL Version:",
+    "Backend library: LWJGL",
+    "Created: 1024x512",
+];
+
+/// Number of output lines kept for a failed test launch report.
+const TEST_LAUNCH_TAIL_LINES: usize = 200;
+
+/// Number of output lines added to the crash report after a failed run.
+const CRASH_OUTPUT_TAIL_LINES: usize = 1000;
+
+/// How often a running game is checked for a stalled start or a kill request.
+const WATCHDOG_TICK: Duration = Duration::from_millis(250);
 
 #[derive(Debug, Clone)]
 pub struct LaunchAccount {
@@ -29,53 +58,524 @@ pub struct LaunchAccount {
 
 #[derive(Debug, Clone)]
 pub struct LaunchPlan {
+    /// Game directory (`--gameDir`), also the process working directory: the
+    /// instance folder unless the profile sets `runtime.game_dir`.
     pub instance_dir: PathBuf,
+    /// Wrapper program and its arguments placed before `java_exec`, if any.
+    pub wrapper: Vec<String>,
+    /// Shell command run before the game starts (see [`run_pre_launch_hook`]).
+    pub pre_launch: Option<String>,
+    /// Shell command run after the game exits (see [`run_post_exit_hook`]).
+    pub post_exit: Option<String>,
+    /// Environment overrides for the game process; empty values unset the variable.
+    pub env: HashMap<String, String>,
     pub java_exec: String,
     pub jvm_args: Vec<String>,
     pub classpath: String,
     pub main_class: String,
     pub game_args: Vec<String>,
+    /// `options.txt` keys set when the game starts, for window options the
+    /// version has no arguments for.
+    pub game_options: Vec<(&'static str, String)>,
+    /// Whether a missing `options.txt` is created to hold [`Self::game_options`].
+    pub create_game_options: bool,
+    pub verification: VerifySummary,
+    /// Non-fatal problems found while preparing, e.g. an unusable Java override.
+    pub warnings: Vec<String>,
+    /// Values hidden when the plan is shown (the access token).
+    pub secrets: Vec<String>,
 }
 
-pub fn prepare(paths: &Paths, profile: &Profile, account: &LaunchAccount) -> Result<LaunchPlan> {
-    let instance_dir = materialize_instance(paths, profile)?;
+/// The exact process a plan starts, for showing the user what launching will do.
+/// Secrets are already redacted.
+#[derive(Debug, Clone, Serialize)]
+pub struct LaunchPreview {
+    /// Program that is started: the wrapper, or Java itself.
+    pub program: String,
+    /// Arguments after the program, including Java when a wrapper is used.
+    pub args: Vec<String>,
+    pub working_dir: PathBuf,
+    /// Changes to the inherited environment; `None` removes the variable.
+    pub env: std::collections::BTreeMap<String, Option<String>>,
+}
 
-    let java_path = profile.runtime.java.as_deref();
-    let version_id = resolve_version_id(paths, &profile.mc_version, profile.loader.as_ref(), java_path)?;
-    let resolved = resolve_version(paths, &version_id)?;
-    let version = resolved.merged;
+impl LaunchPreview {
+    /// The command as a single shell-quoted line.
+    pub fn command_line(&self) -> String {
+        shell_words::join(std::iter::once(&self.program).chain(&self.args))
+    }
+}
 
-    // Collect client JARs from versions in the chain.
-    // Forge/NeoForge handle the client JAR internally via their processed JARs,
-    // so we shouldn't add the vanilla client JAR to the classpath for those loaders.
-    let is_forge_loader = profile.loader.as_ref().map_or(false, |l| {
-        l.loader_type == "forge" || l.loader_type == "neoforge"
-    });
+/// Placeholder shown instead of a secret.
+const REDACTED: &str = "<redacted>";
 
-    let mut client_jars = Vec::new();
-    for entry in &resolved.chain {
-        if entry.downloads.is_some() {
-            let jar_path = ensure_client_jar(paths, entry)?;
-            // For Forge/NeoForge, download the client JAR (needed for processing)
-            // but don't add it to the classpath - they handle it internally
-            if !is_forge_loader {
-                client_jars.push(jar_path);
+impl LaunchPlan {
+    /// `value` with every secret replaced by a placeholder.
+    pub fn redact(&self, value: &str) -> String {
+        self.secrets
+            .iter()
+            .filter(|secret| !secret.is_empty())
+            .fold(value.to_string(), |value, secret| value.replace(secret.as_str(), REDACTED))
+    }
+
+    /// Describe the process [`launch_command`] would start, without starting it.
+    pub fn preview(&self, options: &LaunchOptions) -> LaunchPreview {
+        let cmd = launch_command(self, options);
+        let text = |s: &std::ffi::OsStr| self.redact(&s.to_string_lossy());
+        LaunchPreview {
+            program: text(cmd.get_program()),
+            args: cmd.get_args().map(text).collect(),
+            working_dir: cmd.get_current_dir().map(Path::to_path_buf).unwrap_or_else(|| self.instance_dir.clone()),
+            env: cmd
+                .get_envs()
+                .map(|(name, value)| (name.to_string_lossy().to_string(), value.map(text)))
+                .collect(),
+        }
+    }
+}
+
+/// Options controlling how a launch is prepared.
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOptions {
+    /// Re-hash every library and asset against its recorded hash, attempt
+    /// every repair and report them all; launch is refused if any mismatch
+    /// cannot be repaired.
+    pub full_verify: bool,
+    /// Trust existing files whose size matches instead of re-hashing them.
+    /// Quicker, but a corrupt file of the right size goes unnoticed. Ignored
+    /// with `full_verify`.
+    pub fast_verify: bool,
+    /// Remove JVM option variables such as `_JAVA_OPTIONS` from the game's
+    /// environment. The launcher's own environment is left untouched.
+    pub clear_java_env: bool,
+    /// Start with an empty `mods` directory; the instance's mods are moved
+    /// aside for the duration of the game and restored afterwards.
+    pub safe_mode: bool,
+    /// Copy mods and packs into the instance instead of linking them from the
+    /// store. Always the case on Windows.
+    pub copy_instead_of_link: bool,
+    /// Run the game with `java.exe` even when a sibling `javaw.exe` exists, so
+    /// Windows opens a console window for it. Only affects the game process.
+    pub show_console: bool,
+    /// Join a server, world or realm straight from launch.
+    pub quick_play: Option<QuickPlay>,
+    /// Primary monitor size in pixels, if known. A configured window larger
+    /// than this is launched anyway, with a warning.
+    pub screen_size: Option<(u32, u32)>,
+    /// Report the game as not responding when it logs none of the startup
+    /// markers (see [`find_launch_marker`]) within this time. Only used by
+    /// [`run_with_watchdog`]; `None` disables the check.
+    pub startup_timeout: Option<Duration>,
+}
+
+/// Where the game should go right after starting, via `--quickPlay*` arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "target", rename_all = "lowercase")]
+pub enum QuickPlay {
+    /// Server address, optionally with `:port`.
+    Multiplayer(String),
+    /// Folder name of a world in the instance's `saves` directory.
+    Singleplayer(String),
+    /// Realm id.
+    Realms(String),
+}
+
+impl QuickPlay {
+    /// Version json feature that enables this target's arguments.
+    fn feature(&self) -> &'static str {
+        match self {
+            QuickPlay::Multiplayer(_) => "is_quick_play_multiplayer",
+            QuickPlay::Singleplayer(_) => "is_quick_play_singleplayer",
+            QuickPlay::Realms(_) => "is_quick_play_realms",
+        }
+    }
+
+    fn var(&self) -> (&'static str, &str) {
+        match self {
+            QuickPlay::Multiplayer(addr) => ("quick_play_multiplayer", addr),
+            QuickPlay::Singleplayer(world) => ("quick_play_singleplayer", world),
+            QuickPlay::Realms(id) => ("quick_play_realms", id),
+        }
+    }
+}
+
+/// Window size the game uses when none is given.
+const DEFAULT_WINDOW_WIDTH: u32 = 854;
+const DEFAULT_WINDOW_HEIGHT: u32 = 480;
+
+/// Environment variables the JVM reads options from. When set, they override or
+/// extend the launcher's JVM arguments (heap size, GC, agents).
+pub const JAVA_OPTION_ENV_VARS: &[&str] = &[
+    "_JAVA_OPTIONS",
+    "JAVA_TOOL_OPTIONS",
+    "JDK_JAVA_OPTIONS",
+    "JAVA_OPTS",
+];
+
+/// JVM option variables set in the current environment, as (name, value) pairs.
+pub fn detect_java_env_overrides() -> Vec<(String, String)> {
+    JAVA_OPTION_ENV_VARS
+        .iter()
+        .filter_map(|name| {
+            std::env::var(name)
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+                .map(|value| (name.to_string(), value))
+        })
+        .collect()
+}
+
+/// Build the command that starts the game for a prepared plan.
+///
+/// With a wrapper the command is `<wrapper...> <java> <args...>`; the working
+/// directory and environment are set on the wrapper, which passes them on.
+pub fn launch_command(plan: &LaunchPlan, options: &LaunchOptions) -> Command {
+    // Only Windows has a separate windowless launcher; skip the lookup elsewhere
+    let javaw = (cfg!(windows) && !options.show_console)
+        .then(|| windowless_java(&plan.java_exec))
+        .flatten();
+    let java_exec = javaw.unwrap_or_else(|| PathBuf::from(&plan.java_exec));
+    let mut cmd = match plan.wrapper.split_first() {
+        Some((program, wrapper_args)) => {
+            let mut cmd = Command::new(program);
+            cmd.args(wrapper_args).arg(&java_exec);
+            cmd
+        }
+        None => Command::new(&java_exec),
+    };
+    cmd.args(&plan.jvm_args)
+        .arg("-cp")
+        .arg(&plan.classpath)
+        .arg(&plan.main_class)
+        .args(&plan.game_args)
+        .current_dir(&plan.instance_dir);
+    apply_java_env_policy(&mut cmd, options.clear_java_env);
+    apply_env_overrides(&mut cmd, &plan.env);
+    cmd
+}
+
+/// The `javaw.exe` next to a `java.exe`, which starts without a console window.
+/// Version probing keeps using `java.exe`, since `javaw` prints nothing.
+fn windowless_java(java_exec: &str) -> Option<PathBuf> {
+    let path = Path::new(java_exec);
+    let name = path.file_name()?.to_str()?;
+    if !name.eq_ignore_ascii_case("java.exe") && !name.eq_ignore_ascii_case("java") {
+        return None;
+    }
+    if path.parent().is_none_or(|dir| dir.as_os_str().is_empty()) {
+        return find_executable("javaw");
+    }
+    let javaw = path.with_file_name("javaw.exe");
+    javaw.is_file().then_some(javaw)
+}
+
+/// Merge the profile's variables onto the inherited environment. They are
+/// applied last, so they win over both inherited values and the Java env
+/// policy; an empty value removes the variable from the game's environment.
+fn apply_env_overrides(cmd: &mut Command, env: &HashMap<String, String>) {
+    for (name, value) in env {
+        if value.is_empty() {
+            cmd.env_remove(name);
+        } else {
+            cmd.env(name, value);
+        }
+    }
+}
+
+/// Error returned when the pre-launch hook exits non-zero.
+#[derive(Debug, Clone)]
+pub struct HookFailed {
+    pub command: String,
+    pub code: Option<i32>,
+    pub stderr: String,
+}
+
+impl std::fmt::Display for HookFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.code {
+            Some(code) => write!(f, "pre-launch hook `{}` exited with code {}", self.command, code)?,
+            None => write!(f, "pre-launch hook `{}` was terminated", self.command)?,
+        }
+        if !self.stderr.is_empty() {
+            write!(f, ":\n{}", self.stderr)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for HookFailed {}
+
+fn non_empty_hook(hook: &Option<String>) -> Option<String> {
+    hook.as_deref().map(str::trim).filter(|h| !h.is_empty()).map(String::from)
+}
+
+fn hook_command(command: &str, instance_dir: &Path) -> Command {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    cmd.current_dir(instance_dir)
+        .env("SHARD_INSTANCE_DIR", instance_dir)
+        .stdin(Stdio::null());
+    cmd
+}
+
+/// Run the plan's pre-launch hook, if any, and wait for it. A non-zero exit
+/// fails with [`HookFailed`] carrying the hook's stderr.
+pub fn run_pre_launch_hook(plan: &LaunchPlan) -> Result<()> {
+    let Some(command) = &plan.pre_launch else {
+        return Ok(());
+    };
+    tracing::info!("running pre-launch hook: {command}");
+    let output = hook_command(command, &plan.instance_dir)
+        .output()
+        .with_context(|| format!("failed to run pre-launch hook `{command}`"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !stdout.trim().is_empty() {
+        tracing::info!("pre-launch hook output: {}", stdout.trim());
+    }
+    if !output.status.success() {
+        return Err(HookFailed {
+            command: command.clone(),
+            code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Run the plan's post-exit hook, if any, with the game's exit code in
+/// `SHARD_EXIT_CODE`. The game has already ended, so failures are only logged.
+pub fn run_post_exit_hook(plan: &LaunchPlan, status: &ExitStatus) {
+    let Some(command) = &plan.post_exit else {
+        return;
+    };
+    tracing::info!("running post-exit hook: {command}");
+    let code = status.code().map(|c| c.to_string()).unwrap_or_default();
+    match hook_command(command, &plan.instance_dir).env("SHARD_EXIT_CODE", code).output() {
+        Ok(output) if output.status.success() => {}
+        Ok(output) => tracing::warn!(
+            "post-exit hook `{command}` exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(err) => tracing::warn!("failed to run post-exit hook `{command}`: {err}"),
+    }
+}
+
+fn apply_java_env_policy(cmd: &mut Command, clear: bool) {
+    if clear {
+        for name in JAVA_OPTION_ENV_VARS {
+            cmd.env_remove(name);
+        }
+        return;
+    }
+    for (name, value) in detect_java_env_overrides() {
+        tracing::warn!(
+            "{name}={value} is set and will affect the game's JVM options; \
+             enable clearing Java environment options to ignore it"
+        );
+    }
+}
+
+/// How existing game files are checked before launch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VerifyMode {
+    /// Trust existing files whose size matches the manifest. Opt-in.
+    Fast,
+    /// Re-hash every existing file against its recorded sha1.
+    Full,
+}
+
+/// Result of checking game files while preparing a launch.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerifySummary {
+    pub full_verify: bool,
+    /// Files checked (libraries, assets, client jars).
+    pub checked: usize,
+    /// Files fetched because they were missing or invalid.
+    pub downloaded: usize,
+    /// Existing files that failed verification and were downloaded again.
+    pub repaired: usize,
+    /// Files that could not be repaired, with the reason.
+    pub failed: Vec<String>,
+    /// Existing files that failed verification and were replaced by a good copy.
+    pub repaired_files: Vec<PathBuf>,
+}
+
+pub(crate) struct IntegrityCheck {
+    mode: VerifyMode,
+    summary: VerifySummary,
+    /// Existing files found invalid, awaiting their re-download.
+    invalid: std::collections::HashSet<PathBuf>,
+}
+
+impl IntegrityCheck {
+    /// Check that re-hashes existing files. With `full_verify`, failed
+    /// downloads are collected and refuse the launch in `finish` rather than
+    /// aborting at the first one.
+    pub(crate) fn new(full_verify: bool) -> Self {
+        Self {
+            mode: VerifyMode::Full,
+            summary: VerifySummary {
+                full_verify,
+                ..VerifySummary::default()
+            },
+            invalid: std::collections::HashSet::new(),
+        }
+    }
+
+    /// Check that trusts existing files whose size matches.
+    pub(crate) fn fast() -> Self {
+        Self { mode: VerifyMode::Fast, ..Self::new(false) }
+    }
+
+    fn for_launch(options: &LaunchOptions) -> Self {
+        if options.fast_verify && !options.full_verify {
+            Self::fast()
+        } else {
+            Self::new(options.full_verify)
+        }
+    }
+
+    /// Make sure `path` holds the expected file, downloading or repairing it as needed.
+    pub(crate) fn ensure(&mut self, url: &str, path: &Path, expected_sha1: Option<&str>, expected_size: Option<u64>) -> Result<()> {
+        if !self.needs_fetch(path, expected_sha1, expected_size) {
+            return Ok(());
+        }
+        let entry = DownloadEntry {
+            url: url.to_string(),
+            dest: path.to_path_buf(),
+            sha1: expected_sha1.map(str::to_string),
+            size: expected_size,
+        };
+        self.fetch_all(&[entry], 1)
+    }
+
+    /// Check an existing file. Returns true if it must be (re)downloaded;
+    /// invalid files are removed so a download always starts clean.
+    fn needs_fetch(&mut self, path: &Path, expected_sha1: Option<&str>, expected_size: Option<u64>) -> bool {
+        self.summary.checked += 1;
+
+        if path.exists() {
+            let intact = match self.mode {
+                VerifyMode::Fast => {
+                    let len = path.metadata().map(|m| m.len()).unwrap_or(0);
+                    match expected_size {
+                        Some(size) => len == size,
+                        None => len > 0,
+                    }
+                }
+                VerifyMode::Full => match expected_sha1 {
+                    Some(expected) => sha1_file(path)
+                        .map(|actual| actual.eq_ignore_ascii_case(expected))
+                        .unwrap_or(false),
+                    // Nothing recorded to verify against
+                    None => path.metadata().map(|m| m.len()).unwrap_or(0) > 0,
+                },
+            };
+            if intact {
+                return false;
             }
+            self.summary.repaired += 1;
+            self.invalid.insert(path.to_path_buf());
+            let _ = fs::remove_file(path);
         }
+        true
     }
 
-    let asset_index_id = ensure_assets(paths, &version)?;
-    let (classpath, natives_dir) = ensure_libraries(paths, &version, &instance_dir, &client_jars)?;
+    /// Download `entries` (already checked with `needs_fetch`) concurrently and
+    /// record each outcome.
+    fn fetch_all(&mut self, entries: &[DownloadEntry], concurrency: usize) -> Result<()> {
+        let results = download_all(entries, concurrency, None);
+        for (entry, result) in entries.iter().zip(results) {
+            self.record_fetch(&entry.dest, result.map(|_| ()))?;
+        }
+        Ok(())
+    }
+
+    /// Record the outcome of a download. In full-verify mode failures are collected
+    /// and reported by `finish`; otherwise they abort immediately.
+    fn record_fetch(&mut self, path: &Path, result: Result<()>) -> Result<()> {
+        match result {
+            Ok(()) => {
+                self.summary.downloaded += 1;
+                if self.invalid.remove(path) {
+                    tracing::info!("repaired {}", path.display());
+                    self.summary.repaired_files.push(path.to_path_buf());
+                }
+                Ok(())
+            }
+            Err(err) if self.summary.full_verify => {
+                self.summary.failed.push(format!("{}: {err:#}", path.display()));
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn finish(self) -> Result<VerifySummary> {
+        if !self.summary.failed.is_empty() {
+            bail!(
+                "full verification failed for {} of {} files:\n{}",
+                self.summary.failed.len(),
+                self.summary.checked,
+                self.summary.failed.join("\n")
+            );
+        }
+        Ok(self.summary)
+    }
+}
+
+pub fn prepare(paths: &Paths, profile: &Profile, account: &LaunchAccount) -> Result<LaunchPlan> {
+    prepare_with_options(paths, profile, account, &LaunchOptions::default())
+}
+
+pub fn prepare_with_options(
+    paths: &Paths,
+    profile: &Profile,
+    account: &LaunchAccount,
+    options: &LaunchOptions,
+) -> Result<LaunchPlan> {
+    let wrapper = resolve_wrapper(&profile.runtime.wrapper_command)?;
+    let mut warnings = Vec::new();
+    // Checked before content is synced into a directory another game may be using
+    let shared_dir = game_dir(paths, profile);
+    let worlds_in_use = locked_worlds(&shared_dir);
+    if !worlds_in_use.is_empty() {
+        let warning = format!(
+            "Another running game has {} open in {}; launching a second game there can corrupt saves",
+            worlds_in_use.join(", "),
+            shared_dir.display()
+        );
+        tracing::warn!("{}", warning);
+        warnings.push(warning);
+    }
+    let game_dir = materialize_instance(paths, profile, options.copy_instead_of_link)?;
+    // Natives stay in the instance folder, which is never shared
+    let instance_dir = paths.instance_dir(&profile.id);
+    let mut integrity = IntegrityCheck::for_launch(options);
+
+    let java_path = checked_java_override(profile.runtime.java.as_deref(), max_java(profile), &mut warnings);
+    let GameFiles { version, asset_index_id, classpath, natives_dir } =
+        ensure_game_files(paths, profile, &instance_dir, java_path.as_deref(), &mut integrity)?;
+    let verification = integrity.finish()?;
 
-    let java_exec = resolve_java(profile.runtime.java.as_deref(), &profile.mc_version);
+    let java_exec = resolve_java(java_path.as_deref(), &profile.mc_version, loader_type(profile));
     let assets_root = paths
         .minecraft_assets_objects
         .parent()
         .context("assets root missing")?
         .to_path_buf();
 
-    let vars = build_var_map(
-        &instance_dir,
+    let mut vars = build_var_map(
+        &game_dir,
         &assets_root,
         &asset_index_id,
         &classpath,
@@ -85,15 +585,81 @@ pub fn prepare(paths: &Paths, profile: &Profile, account: &LaunchAccount) -> Res
         account,
     );
 
-    let (mut jvm_args, game_args) = build_args(&version, &vars)?;
+    let mut features = Vec::new();
+    if let Some(quick_play) = &options.quick_play {
+        if supports_quick_play(&version) {
+            let (key, value) = quick_play.var();
+            vars.insert(key.to_string(), value.to_string());
+            features.push(quick_play.feature());
+        } else {
+            let warning = format!(
+                "Minecraft {} does not support Quick Play (1.20+); starting at the main menu",
+                profile.mc_version
+            );
+            tracing::warn!("{}", warning);
+            warnings.push(warning);
+        }
+    }
+
+    let resolution = window_resolution(&profile.runtime, options.screen_size, &mut warnings)?;
+    let mut extra_game_args = Vec::new();
+    let mut game_options = Vec::new();
+    if let Some((width, height)) = resolution {
+        if declares_feature(&version, "has_custom_resolution") {
+            vars.insert("resolution_width".to_string(), width.to_string());
+            vars.insert("resolution_height".to_string(), height.to_string());
+            features.push("has_custom_resolution");
+        } else if accepts_named_game_args(&version) {
+            extra_game_args.extend(["--width".to_string(), width.to_string()]);
+            extra_game_args.extend(["--height".to_string(), height.to_string()]);
+        } else {
+            game_options.push(("overrideWidth", width.to_string()));
+            game_options.push(("overrideHeight", height.to_string()));
+        }
+    }
+    if profile.runtime.fullscreen {
+        if accepts_named_game_args(&version) {
+            extra_game_args.push("--fullscreen".to_string());
+            if let (Some((width, height)), Some(_)) = (resolution, &version.arguments) {
+                extra_game_args.extend(["--fullscreenWidth".to_string(), width.to_string()]);
+                extra_game_args.extend(["--fullscreenHeight".to_string(), height.to_string()]);
+            }
+        } else {
+            game_options.push(("fullscreen", "true".to_string()));
+        }
+    } else if resolution.is_some() {
+        // options.txt remembers fullscreen from the last session and would
+        // otherwise override the requested window.
+        game_options.push(("fullscreen", "false".to_string()));
+    }
+
+    let (mut jvm_args, mut game_args) = build_args(&version, &vars, &features)?;
+    game_args.extend(extra_game_args);
 
     if let Some(memory) = &profile.runtime.memory
         && !jvm_args.iter().any(|arg| arg.starts_with("-Xmx")) {
             jvm_args.push(format!("-Xmx{memory}"));
         }
 
-    if !profile.runtime.args.is_empty() {
-        jvm_args.extend(profile.runtime.args.iter().cloned());
+    jvm_args.extend(instance_jvm_args(&profile.runtime.args, &mut warnings));
+
+    // The JVM honours the last -Xmx it is given
+    let xmx_mb = jvm_args.iter().rev().find_map(|arg| parse_memory_mb(arg.strip_prefix("-Xmx")?));
+    if let Some(warning) = xmx_mb.and_then(|xmx_mb| check_memory_adequacy(profile, xmx_mb)) {
+        tracing::warn!("{}", warning);
+        warnings.push(warning);
+    }
+
+    if let Some(preset) = profile.runtime.gc_preset
+        && let Some(preset) = checked_gc_preset(preset, &profile.runtime.args, &java_exec, &mut warnings)
+    {
+        // The preset is sized for a heap, so pin one when none is set
+        let heap_mb = xmx_mb.unwrap_or_else(|| {
+            let heap_mb = recommend_max_heap_mb(&profile.mc_version, profile.mods.len());
+            jvm_args.push(format!("-Xmx{heap_mb}M"));
+            heap_mb
+        });
+        jvm_args.extend(gc_preset_args(preset, heap_mb));
     }
 
     ensure_jvm_flag(&mut jvm_args, "-Djava.library.path", &natives_dir)?;
@@ -105,40 +671,550 @@ pub fn prepare(paths: &Paths, profile: &Profile, account: &LaunchAccount) -> Res
         .context("mainClass missing from version JSON")?;
 
     Ok(LaunchPlan {
-        instance_dir,
+        instance_dir: game_dir,
+        wrapper,
+        pre_launch: non_empty_hook(&profile.runtime.pre_launch),
+        post_exit: non_empty_hook(&profile.runtime.post_exit),
+        env: profile.runtime.env.clone(),
         java_exec,
         jvm_args,
         classpath,
         main_class,
         game_args,
+        create_game_options: !accepts_named_game_args(&version),
+        game_options,
+        verification,
+        warnings,
+        secrets: vec![account.access_token.clone()],
     })
 }
 
+/// Re-check every library, asset and client jar of an instance against its
+/// recorded sha1, downloading missing or corrupt files again. Files that were
+/// replaced are listed in [`VerifySummary::repaired_files`]; fails if any file
+/// could not be repaired.
+pub fn verify_instance_integrity(paths: &Paths, profile: &Profile) -> Result<VerifySummary> {
+    let instance_dir = paths.instance_dir(&profile.id);
+    let mut integrity = IntegrityCheck::new(true);
+    let mut warnings = Vec::new();
+    let java_path = checked_java_override(profile.runtime.java.as_deref(), max_java(profile), &mut warnings);
+    ensure_game_files(paths, profile, &instance_dir, java_path.as_deref(), &mut integrity)?;
+    integrity.finish()
+}
+
+/// The game files of a prepared version.
+struct GameFiles {
+    version: VersionJson,
+    asset_index_id: String,
+    classpath: String,
+    natives_dir: PathBuf,
+}
+
+/// Resolve the profile's version and make sure its client jars, assets and
+/// libraries are present and intact. Natives are extracted into `instance_dir`.
+fn ensure_game_files(
+    paths: &Paths,
+    profile: &Profile,
+    instance_dir: &Path,
+    java_path: Option<&str>,
+    integrity: &mut IntegrityCheck,
+) -> Result<GameFiles> {
+    let version_id = resolve_version_id(paths, &profile.mc_version, profile.loader.as_ref(), java_path, integrity)?;
+    let resolved = resolve_version(paths, &version_id)?;
+    let version = resolved.merged;
+
+    // Collect client JARs from versions in the chain.
+    // Forge/NeoForge handle the client JAR internally via their processed JARs,
+    // so we shouldn't add the vanilla client JAR to the classpath for those loaders.
+    // Legacy Forge has no processed JARs and loads the vanilla one through LaunchWrapper.
+    let is_forge_loader = profile.loader.as_ref().is_some_and(|l| {
+        (l.loader_type == "forge" && !forge::is_legacy(&profile.mc_version)) || l.loader_type == "neoforge"
+    });
+
+    let mut client_jars = Vec::new();
+    for entry in &resolved.chain {
+        if entry.downloads.is_some() {
+            let jar_path = ensure_client_jar(paths, entry, integrity)?;
+            // For Forge/NeoForge, download the client JAR (needed for processing)
+            // but don't add it to the classpath - they handle it internally
+            if !is_forge_loader {
+                client_jars.push(jar_path);
+            }
+        }
+    }
+
+    let asset_index_id = ensure_assets(paths, &version, integrity)?;
+    let (classpath, natives_dir) = ensure_libraries(paths, &version, instance_dir, &client_jars, integrity)?;
+    Ok(GameFiles { version, asset_index_id, classpath, natives_dir })
+}
+
 pub fn launch(paths: &Paths, profile: &Profile, account: &LaunchAccount) -> Result<()> {
-    let plan = prepare(paths, profile, account)?;
+    launch_with_options(paths, profile, account, &LaunchOptions::default())
+}
 
-    let status = Command::new(&plan.java_exec)
-        .args(&plan.jvm_args)
-        .arg("-cp")
-        .arg(&plan.classpath)
-        .arg(&plan.main_class)
-        .args(&plan.game_args)
-        .current_dir(&plan.instance_dir)
+pub fn launch_with_options(
+    paths: &Paths,
+    profile: &Profile,
+    account: &LaunchAccount,
+    options: &LaunchOptions,
+) -> Result<()> {
+    let plan = prepare_with_options(paths, profile, account, options)?;
+    if options.full_verify {
+        tracing::info!(
+            "verified {} files ({} repaired)",
+            plan.verification.checked,
+            plan.verification.repaired
+        );
+    }
+
+    write_game_options(&plan)?;
+    run_pre_launch_hook(&plan)?;
+
+    let safe_mode = if options.safe_mode {
+        Some(SafeModeGuard::engage(&plan.instance_dir)?)
+    } else {
+        None
+    };
+
+    let status = launch_command(&plan, options)
         .status()
         .context("failed to launch java")?;
 
-    if !status.success() {
-        bail!("minecraft exited with status {status}");
+    if let Some(guard) = safe_mode {
+        guard.restore()?;
+    }
+    run_post_exit_hook(&plan, &status);
+
+    if !status.success() {
+        bail!("minecraft exited with status {status}");
+    }
+
+    Ok(())
+}
+
+/// Apply the plan's `options.txt` changes. Done when the game starts rather
+/// than in [`prepare`], so previewing a launch leaves the instance untouched.
+fn write_game_options(plan: &LaunchPlan) -> Result<()> {
+    if plan.game_options.is_empty() {
+        return Ok(());
+    }
+    options_txt::update(&plan.instance_dir, &plan.game_options, plan.create_game_options)
+}
+
+/// Stream a line of game output was written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+/// One line of output from the running game.
+#[derive(Debug, Clone, Serialize)]
+pub struct GameOutputLine {
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+/// Startup progress reported by [`run_with_watchdog`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum StartupEvent {
+    /// No startup marker within [`LaunchOptions::startup_timeout`]; the game
+    /// may be stuck (shader compile stall, deadlock). Reported once.
+    NotResponding { waited_secs: u64 },
+    /// The game logged a startup marker. After `NotResponding` this means it
+    /// recovered on its own.
+    Started,
+}
+
+/// How a game run started by [`run_with_output`] ended.
+#[derive(Debug)]
+pub struct GameRun {
+    pub status: ExitStatus,
+    /// Whether the game was stopped through the `kill` flag of [`run_with_watchdog`].
+    pub killed: bool,
+    /// Crash report that received the captured output, if the game failed on its own.
+    pub crash_report: Option<PathBuf>,
+    /// Last lines of output, for diagnosing a failed run.
+    pub output_tail: Vec<GameOutputLine>,
+}
+
+/// Run the game for a prepared plan, forwarding each output line to `on_line`.
+/// The plan's pre-launch and post-exit hooks run around the game.
+///
+/// Stdout and stderr are read on background threads and delivered in arrival
+/// order. If the game exits non-zero, the tail of its output is appended to the
+/// crash report it wrote during this run, or saved as a new report in the
+/// instance's `crash-reports` directory when it wrote none.
+pub fn run_with_output(
+    plan: &LaunchPlan,
+    options: &LaunchOptions,
+    on_line: impl FnMut(&GameOutputLine),
+) -> Result<GameRun> {
+    run_with_watchdog(plan, options, None, on_line, |_| {})
+}
+
+/// Like [`run_with_output`], but also watches the output for a startup marker.
+/// With [`LaunchOptions::startup_timeout`] set, `on_startup` hears about a game
+/// that has not started in time and about the marker once it shows up.
+/// Setting `kill` stops the game; the run then ends as [`GameRun::killed`],
+/// without a crash report.
+pub fn run_with_watchdog(
+    plan: &LaunchPlan,
+    options: &LaunchOptions,
+    kill: Option<&AtomicBool>,
+    mut on_line: impl FnMut(&GameOutputLine),
+    mut on_startup: impl FnMut(StartupEvent),
+) -> Result<GameRun> {
+    write_game_options(plan)?;
+    run_pre_launch_hook(plan)?;
+    let started = SystemTime::now();
+    let launched = Instant::now();
+    let mut command = launch_command(plan, options);
+    if kill.is_some() {
+        ProcessTree::isolate(&mut command);
+    }
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to launch java")?;
+    let tree = kill.is_some().then(|| ProcessTree::attach(&child));
+
+    let (tx, rx) = mpsc::channel::<GameOutputLine>();
+    if let Some(stdout) = child.stdout.take() {
+        spawn_line_reader(stdout, tx.clone(), |line| GameOutputLine { stream: OutputStream::Stdout, line });
+    }
+    if let Some(stderr) = child.stderr.take() {
+        spawn_line_reader(stderr, tx.clone(), |line| GameOutputLine { stream: OutputStream::Stderr, line });
+    }
+    drop(tx);
+
+    let mut tail = std::collections::VecDeque::new();
+    let mut awaiting_start = options.startup_timeout.is_some();
+    let mut reported_hang = false;
+    let mut killed = false;
+    loop {
+        let line = match rx.recv_timeout(WATCHDOG_TICK) {
+            Ok(line) => Some(line),
+            Err(mpsc::RecvTimeoutError::Timeout) => None,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+
+        if !killed && kill.is_some_and(|kill| kill.load(Ordering::Relaxed)) {
+            tracing::info!("stopping the game on request");
+            match &tree {
+                Some(tree) => tree.kill(&mut child),
+                None => {
+                    let _ = child.kill();
+                }
+            }
+            killed = true;
+        }
+
+        if let Some(line) = line {
+            if awaiting_start && find_launch_marker(&line.line).is_some() {
+                awaiting_start = false;
+                on_startup(StartupEvent::Started);
+            }
+            on_line(&line);
+            tail.push_back(line);
+            if tail.len() > CRASH_OUTPUT_TAIL_LINES {
+                tail.pop_front();
+            }
+        }
+
+        if awaiting_start
+            && !reported_hang
+            && let Some(timeout) = options.startup_timeout
+            && launched.elapsed() >= timeout
+        {
+            reported_hang = true;
+            tracing::warn!("no startup marker after {}s", timeout.as_secs());
+            on_startup(StartupEvent::NotResponding { waited_secs: timeout.as_secs() });
+        }
+    }
+
+    let status = child.wait().context("failed to wait for java")?;
+    run_post_exit_hook(plan, &status);
+    let crash_report = if status.success() || killed {
+        None
+    } else {
+        let crash_dir = plan.instance_dir.join("crash-reports");
+        Some(record_output_in_crash_report(&crash_dir, started, &status, tail.make_contiguous())?)
+    };
+
+    Ok(GameRun {
+        status,
+        killed,
+        crash_report,
+        output_tail: tail.into(),
+    })
+}
+
+/// Everything a game started, so stopping it also stops the JVM behind a
+/// wrapper command such as `gamemoderun`. On unix the game leads its own
+/// process group; on Windows it is put in a job object.
+struct ProcessTree {
+    #[cfg(windows)]
+    job: windows_sys::Win32::Foundation::HANDLE,
+}
+
+impl ProcessTree {
+    /// Set up `command` so its children can be found again.
+    fn isolate(command: &mut Command) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+        #[cfg(not(unix))]
+        let _ = command;
+    }
+
+    fn attach(child: &Child) -> Self {
+        #[cfg(windows)]
+        {
+            use std::os::windows::io::AsRawHandle;
+            use windows_sys::Win32::System::JobObjects::{AssignProcessToJobObject, CreateJobObjectW};
+
+            // SAFETY: a fresh anonymous job, and the handle of a child we own
+            let job = unsafe { CreateJobObjectW(std::ptr::null(), std::ptr::null()) };
+            if !job.is_null() && unsafe { AssignProcessToJobObject(job, child.as_raw_handle() as _) } == 0 {
+                tracing::warn!("failed to put the game in a job object");
+            }
+            Self { job }
+        }
+        #[cfg(not(windows))]
+        {
+            let _ = child;
+            Self {}
+        }
+    }
+
+    /// Kill the game and every process it started, falling back to the
+    /// direct child alone.
+    fn kill(&self, child: &mut Child) {
+        #[cfg(unix)]
+        {
+            // SAFETY: plain syscall; the group was created by `isolate`
+            if unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) } == 0 {
+                return;
+            }
+        }
+        #[cfg(windows)]
+        {
+            use windows_sys::Win32::System::JobObjects::TerminateJobObject;
+
+            // SAFETY: `job` stays open until drop
+            if !self.job.is_null() && unsafe { TerminateJobObject(self.job, 1) } != 0 {
+                return;
+            }
+        }
+        let _ = child.kill();
+    }
+}
+
+#[cfg(windows)]
+impl Drop for ProcessTree {
+    fn drop(&mut self) {
+        if !self.job.is_null() {
+            // SAFETY: the handle came from `CreateJobObjectW` and is closed once
+            unsafe { windows_sys::Win32::Foundation::CloseHandle(self.job) };
+        }
+    }
+}
+
+/// Append captured output to the newest crash report modified since `started`,
+/// or write a new launcher report if the game produced none.
+fn record_output_in_crash_report(
+    crash_dir: &Path,
+    started: SystemTime,
+    status: &ExitStatus,
+    lines: &[GameOutputLine],
+) -> Result<PathBuf> {
+    use std::io::Write;
+
+    let game_report = fs::read_dir(crash_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "txt"))
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            (modified >= started).then(|| (modified, entry.path()))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path);
+
+    let path = match game_report {
+        Some(path) => path,
+        None => {
+            fs::create_dir_all(crash_dir)
+                .with_context(|| format!("failed to create {}", crash_dir.display()))?;
+            let secs = started.duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+            crash_dir.join(format!("crash-{}-launcher.txt", secs))
+        }
+    };
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("failed to open crash report {}", path.display()))?;
+    let mut report = format!("\n---- Launcher: game output (exited with {}) ----\n", status);
+    for line in lines {
+        let tag = match line.stream {
+            OutputStream::Stdout => "stdout",
+            OutputStream::Stderr => "stderr",
+        };
+        report.push_str(&format!("[{}] {}\n", tag, line.line));
+    }
+    file.write_all(report.as_bytes())
+        .with_context(|| format!("failed to write crash report {}", path.display()))?;
+
+    Ok(path)
+}
+
+/// Outcome of a headless test launch.
+#[derive(Debug, Clone)]
+pub struct TestLaunchReport {
+    pub success: bool,
+    /// Marker line that confirmed a successful start.
+    pub marker: Option<String>,
+    /// Exit status if the game exited on its own before reaching a marker.
+    pub exit_status: Option<String>,
+    pub timed_out: bool,
+    pub elapsed: Duration,
+    /// Last lines of game output, useful when the launch failed.
+    pub output: Vec<String>,
+}
+
+/// Return the success marker contained in a game output line, if any.
+pub fn find_launch_marker(line: &str) -> Option<&'static str> {
+    LAUNCH_SUCCESS_MARKERS
+        .iter()
+        .copied()
+        .find(|marker| line.contains(marker))
+}
+
+/// Launch the game in demo mode, wait until it reports a successful start,
+/// then terminate it. Fails the report if the game exits or the timeout expires first.
+///
+/// `options` apply as for a real launch (e.g. clearing `_JAVA_OPTIONS`, safe
+/// mode), so the test runs the game the way launching would.
+pub fn test_launch(
+    paths: &Paths,
+    profile: &Profile,
+    account: &LaunchAccount,
+    options: &LaunchOptions,
+    timeout: Duration,
+) -> Result<TestLaunchReport> {
+    let mut plan = prepare_with_options(paths, profile, account, options)?;
+    if !plan.game_args.iter().any(|arg| arg == "--demo") {
+        plan.game_args.push("--demo".to_string());
+    }
+
+    write_game_options(&plan)?;
+    // Restored when dropped, once the game has been stopped
+    let _safe_mode = if options.safe_mode {
+        Some(SafeModeGuard::engage(&plan.instance_dir)?)
+    } else {
+        None
+    };
+    let mut child = launch_command(&plan, options)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to launch java")?;
+
+    let (tx, rx) = mpsc::channel::<String>();
+    if let Some(stdout) = child.stdout.take() {
+        spawn_line_reader(stdout, tx.clone(), |line| line);
+    }
+    if let Some(stderr) = child.stderr.take() {
+        spawn_line_reader(stderr, tx.clone(), |line| line);
+    }
+    drop(tx);
+
+    let started = Instant::now();
+    let mut output = Vec::new();
+    let mut marker = None;
+    let mut exit_status = None;
+    let mut timed_out = false;
+
+    loop {
+        let remaining = timeout.saturating_sub(started.elapsed());
+        if remaining.is_zero() {
+            timed_out = true;
+            break;
+        }
+        match rx.recv_timeout(remaining.min(Duration::from_millis(250))) {
+            Ok(line) => {
+                if let Some(found) = find_launch_marker(&line) {
+                    marker = Some(found.to_string());
+                    output.push(line);
+                    break;
+                }
+                output.push(line);
+                if output.len() > TEST_LAUNCH_TAIL_LINES {
+                    output.remove(0);
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                let status = child.wait().context("failed to wait for java")?;
+                exit_status = Some(status.to_string());
+                break;
+            }
+        }
+    }
+
+    if exit_status.is_none() {
+        let _ = child.kill();
+        let _ = child.wait();
     }
 
-    Ok(())
+    Ok(TestLaunchReport {
+        success: marker.is_some(),
+        marker,
+        exit_status,
+        timed_out,
+        elapsed: started.elapsed(),
+        output,
+    })
+}
+
+fn spawn_line_reader<R, T>(reader: R, tx: mpsc::Sender<T>, wrap: fn(String) -> T)
+where
+    R: Read + Send + 'static,
+    T: Send + 'static,
+{
+    std::thread::spawn(move || {
+        use std::io::BufRead;
+        for line in std::io::BufReader::new(reader).lines() {
+            let Ok(line) = line else { break };
+            if tx.send(wrap(line)).is_err() {
+                break;
+            }
+        }
+    });
 }
 
-fn resolve_version_id(paths: &Paths, mc_version: &str, loader: Option<&Loader>, java: Option<&str>) -> Result<String> {
+fn resolve_version_id(
+    paths: &Paths,
+    mc_version: &str,
+    loader: Option<&Loader>,
+    java: Option<&str>,
+    integrity: &mut IntegrityCheck,
+) -> Result<String> {
     match loader {
         None => Ok(mc_version.to_string()),
         Some(loader) => match loader.loader_type.as_str() {
-            "fabric" => ensure_fabric_profile(paths, mc_version, &loader.version),
+            "fabric" => fabric::prepare(paths, mc_version, &loader.version, integrity),
             "quilt" => ensure_quilt_profile(paths, mc_version, &loader.version),
             "neoforge" => ensure_neoforge_profile(paths, mc_version, &loader.version, java),
             "forge" => ensure_forge_profile(paths, mc_version, &loader.version, java),
@@ -147,58 +1223,6 @@ fn resolve_version_id(paths: &Paths, mc_version: &str, loader: Option<&Loader>,
     }
 }
 
-/// Fetch the latest stable Fabric loader version from the Fabric Meta API
-fn resolve_fabric_latest_version() -> Result<String> {
-    let url = "https://meta.fabricmc.net/v2/versions/loader";
-    let json = download_json(url)?;
-    let versions = json.as_array().context("fabric loader versions not an array")?;
-
-    // Find the first stable version
-    for entry in versions {
-        if entry.get("stable").and_then(|v| v.as_bool()).unwrap_or(false) {
-            if let Some(version) = entry.get("version").and_then(|v| v.as_str()) {
-                return Ok(version.to_string());
-            }
-        }
-    }
-
-    // Fallback to first version if no stable found
-    versions.first()
-        .and_then(|v| v.get("version"))
-        .and_then(|v| v.as_str())
-        .map(|s| s.to_string())
-        .context("no fabric loader versions found")
-}
-
-fn ensure_fabric_profile(paths: &Paths, mc_version: &str, loader_version: &str) -> Result<String> {
-    // Resolve "latest" to actual version number
-    let resolved_version = if loader_version.eq_ignore_ascii_case("latest") {
-        resolve_fabric_latest_version()?
-    } else {
-        loader_version.to_string()
-    };
-
-    let url = format!(
-        "https://meta.fabricmc.net/v2/versions/loader/{mc_version}/{resolved_version}/profile/json"
-    );
-    let profile_json = download_json(&url)?;
-    let id = profile_json
-        .get("id")
-        .and_then(|v| v.as_str())
-        .context("fabric profile missing id")?;
-    let target = paths.minecraft_version_json(id);
-    if !target.exists() {
-        if let Some(parent) = target.parent() {
-            fs::create_dir_all(parent)
-                .with_context(|| format!("failed to create version dir: {}", parent.display()))?;
-        }
-        fs::write(&target, serde_json::to_string_pretty(&profile_json)?).with_context(|| {
-            format!("failed to write fabric version json: {}", target.display())
-        })?;
-    }
-    Ok(id.to_string())
-}
-
 /// Fetch the latest Quilt loader version from the Quilt Meta API
 fn resolve_quilt_latest_version() -> Result<String> {
     let url = "https://meta.quiltmc.org/v3/versions/loader";
@@ -338,6 +1362,11 @@ fn ensure_forge_profile(paths: &Paths, mc_version: &str, loader_version: &str, j
         format!("{mc_version}-{resolved_loader}")
     };
 
+    // Legacy installers only unpack files, so they are emulated instead of run
+    if forge::is_legacy(mc_version) {
+        return forge::prepare_legacy(paths, mc_version, &version_id);
+    }
+
     let id = format!("forge-{version_id}");
     let target = paths.minecraft_version_json(&id);
 
@@ -403,7 +1432,7 @@ fn extract_version_json_from_jar(jar_path: &Path, json_name: &str) -> Result<Str
 /// Run the Forge/NeoForge installer to process libraries and generate SRG jars.
 /// The installer creates the necessary processed artifacts that aren't available via Maven.
 fn run_forge_installer(paths: &Paths, installer_path: &Path, mc_version: &str, java: Option<&str>) -> Result<()> {
-    let java = resolve_java(java, mc_version);
+    let java = resolve_java(java, mc_version, None);
 
     // Derive minecraft_dir from minecraft_versions path
     let minecraft_dir = paths
@@ -420,9 +1449,7 @@ fn run_forge_installer(paths: &Paths, installer_path: &Path, mc_version: &str, j
             .context("failed to create launcher_profiles.json for Forge installer")?;
     }
 
-    eprintln!(
-        "Running installer to process libraries (this may take a minute)..."
-    );
+    tracing::info!("Running installer to process libraries (this may take a minute)...");
 
     // Run the installer with the working directory set to cache_downloads.
     // This ensures the installer can write its log file (installer.jar.log) without
@@ -552,7 +1579,7 @@ fn load_version_manifest(paths: &Paths) -> Result<VersionManifest> {
     Ok(manifest)
 }
 
-fn ensure_client_jar(paths: &Paths, version: &VersionJson) -> Result<PathBuf> {
+fn ensure_client_jar(paths: &Paths, version: &VersionJson, integrity: &mut IntegrityCheck) -> Result<PathBuf> {
     let downloads = version
         .downloads
         .as_ref()
@@ -562,11 +1589,11 @@ fn ensure_client_jar(paths: &Paths, version: &VersionJson) -> Result<PathBuf> {
         .as_ref()
         .context("client download missing from version json")?;
     let jar_path = paths.minecraft_version_jar(&version.id);
-    download_with_sha1(&client.url, &jar_path, Some(&client.sha1))?;
+    integrity.ensure(&client.url, &jar_path, Some(&client.sha1), client.size)?;
     Ok(jar_path)
 }
 
-fn ensure_assets(paths: &Paths, version: &VersionJson) -> Result<String> {
+fn ensure_assets(paths: &Paths, version: &VersionJson, integrity: &mut IntegrityCheck) -> Result<String> {
     let asset_index = version
         .asset_index
         .as_ref()
@@ -579,23 +1606,45 @@ fn ensure_assets(paths: &Paths, version: &VersionJson) -> Result<String> {
         .with_context(|| format!("failed to read asset index: {}", index_path.display()))?;
     let index: AssetIndex = serde_json::from_str(&data).context("failed to parse asset index")?;
 
-    for (name, object) in index.objects {
-        let _ = name; // reserved for future logging
-        if object.hash.len() < 2 {
+    let objects: Vec<AssetObject> = index.objects.into_values().collect();
+    download_asset_objects(paths, &objects, ASSET_BASE, integrity)?;
+
+    Ok(asset_index.id.clone())
+}
+
+/// Download every asset object that is missing or invalid, in parallel.
+///
+/// Objects are content-addressed and written via a temp file + rename, so the
+/// store never holds partial files and nothing else needs to be recorded: resuming
+/// an interrupted download is simply running this again. Returns the number of
+/// objects fetched.
+fn download_asset_objects(
+    paths: &Paths,
+    objects: &[AssetObject],
+    base_url: &str,
+    integrity: &mut IntegrityCheck,
+) -> Result<usize> {
+    let mut pending = Vec::new();
+    // Objects with the same content share a hash, and so a file in the store
+    let mut seen = std::collections::HashSet::new();
+    for object in objects {
+        if object.hash.len() < 2 || !seen.insert(object.hash.as_str()) {
             continue;
         }
         let object_path = paths.minecraft_asset_object(&object.hash);
-        let url = object.url.clone().unwrap_or_else(|| {
-            format!(
-                "https://resources.download.minecraft.net/{}/{}",
-                &object.hash[0..2],
-                object.hash
-            )
-        });
-        download_with_sha1(&url, &object_path, Some(&object.hash))?;
+        if integrity.needs_fetch(&object_path, Some(&object.hash), object.size) {
+            let url = object.url.clone().unwrap_or_else(|| {
+                format!("{}/{}/{}", base_url.trim_end_matches('/'), &object.hash[0..2], object.hash)
+            });
+            pending.push(DownloadEntry { url, dest: object_path, sha1: Some(object.hash.clone()), size: object.size });
+        }
     }
-
-    Ok(asset_index.id.clone())
+    if pending.is_empty() {
+        return Ok(0);
+    }
+    tracing::debug!("downloading {} of {} asset objects", pending.len(), objects.len());
+    integrity.fetch_all(&pending, ASSET_DOWNLOAD_WORKERS)?;
+    Ok(pending.len())
 }
 
 fn ensure_libraries(
@@ -603,6 +1652,7 @@ fn ensure_libraries(
     version: &VersionJson,
     instance_dir: &Path,
     client_jars: &[PathBuf],
+    integrity: &mut IntegrityCheck,
 ) -> Result<(String, PathBuf)> {
     let mut classpath = Vec::new();
     let natives_dir = instance_dir.join("natives");
@@ -617,6 +1667,9 @@ fn ensure_libraries(
     fs::create_dir_all(&natives_dir)
         .with_context(|| format!("failed to create natives dir: {}", natives_dir.display()))?;
 
+    // Work out every file first, so missing ones can be fetched together
+    let mut files = Vec::new();
+    let mut natives = Vec::new();
     for library in &version.libraries {
         if !library_allowed(library) {
             continue;
@@ -628,18 +1681,27 @@ fn ensure_libraries(
             .and_then(|downloads| downloads.artifact.as_ref())
         {
             let lib_path = paths.minecraft_library_path(&artifact.path);
-            download_with_sha1(&artifact.url, &lib_path, Some(&artifact.sha1))?;
+            files.push(DownloadEntry {
+                url: artifact.url.clone(),
+                dest: lib_path.clone(),
+                sha1: Some(artifact.sha1.clone()),
+                size: artifact.size,
+            });
             classpath.push(lib_path);
         } else if let Some(path) = maven_path_from_name(&library.name) {
             let base_url = library.url.as_deref().unwrap_or(LIBRARIES_BASE);
-            let url = join_url(base_url, &path);
             let lib_path = paths.minecraft_library_path(&path);
-            download_with_sha1(&url, &lib_path, None)?;
+            files.push(DownloadEntry {
+                url: join_url(base_url, &path),
+                dest: lib_path.clone(),
+                sha1: library.sha1.clone(),
+                size: library.size,
+            });
             classpath.push(lib_path);
         }
 
-        if let Some(natives) = library.natives.as_ref()
-            && let Some(classifier) = natives.get(&os_key()) {
+        if let Some(classifiers) = library.natives.as_ref()
+            && let Some(classifier) = classifiers.get(&os_key()) {
                 let classifier = classifier.replace("${arch}", arch_marker());
                 if let Some(native_artifact) = library
                     .downloads
@@ -648,24 +1710,42 @@ fn ensure_libraries(
                     .and_then(|classifiers| classifiers.get(&classifier))
                 {
                     let jar_path = paths.minecraft_library_path(&native_artifact.path);
-                    download_with_sha1(
-                        &native_artifact.url,
-                        &jar_path,
-                        Some(&native_artifact.sha1),
-                    )?;
-                    extract_natives(&jar_path, &natives_dir, library.extract.as_ref())?;
+                    files.push(DownloadEntry {
+                        url: native_artifact.url.clone(),
+                        dest: jar_path.clone(),
+                        sha1: Some(native_artifact.sha1.clone()),
+                        size: native_artifact.size,
+                    });
+                    natives.push((jar_path, library.extract.as_ref()));
                 } else if let Some(path) =
                     maven_path_from_name_with_classifier(&library.name, &classifier)
                 {
                     let base_url = library.url.as_deref().unwrap_or(LIBRARIES_BASE);
-                    let url = join_url(base_url, &path);
                     let jar_path = paths.minecraft_library_path(&path);
-                    download_with_sha1(&url, &jar_path, None)?;
-                    extract_natives(&jar_path, &natives_dir, library.extract.as_ref())?;
+                    files.push(DownloadEntry {
+                        url: join_url(base_url, &path),
+                        dest: jar_path.clone(),
+                        sha1: None,
+                        size: None,
+                    });
+                    natives.push((jar_path, library.extract.as_ref()));
                 }
             }
     }
 
+    // The same jar can be listed twice (e.g. by a loader and by vanilla)
+    let mut seen = std::collections::HashSet::new();
+    files.retain(|entry| seen.insert(entry.dest.clone()));
+    let pending: Vec<DownloadEntry> = files
+        .into_iter()
+        .filter(|entry| integrity.needs_fetch(&entry.dest, entry.sha1.as_deref(), entry.size))
+        .collect();
+    integrity.fetch_all(&pending, crate::downloads::DEFAULT_CONCURRENCY)?;
+
+    for (jar_path, extract) in natives {
+        extract_natives(&jar_path, &natives_dir, extract)?;
+    }
+
     for jar in client_jars {
         classpath.push(jar.to_path_buf());
     }
@@ -678,16 +1758,24 @@ fn ensure_libraries(
     Ok((classpath, natives_dir))
 }
 
+/// Build JVM and game arguments. `features` are rule features (e.g.
+/// `is_quick_play_multiplayer`) to enable on top of the defaults.
 fn build_args(
     version: &VersionJson,
     vars: &HashMap<String, String>,
+    features: &[&str],
 ) -> Result<(Vec<String>, Vec<String>)> {
     let mut jvm_args = Vec::new();
     let mut game_args = Vec::new();
 
+    let mut ctx = RuleContext::new();
+    for feature in features {
+        ctx.features.insert(feature.to_string(), true);
+    }
+
     if let Some(arguments) = &version.arguments {
-        jvm_args.extend(collect_args(&arguments.jvm, vars));
-        game_args.extend(collect_args(&arguments.game, vars));
+        jvm_args.extend(collect_args(&arguments.jvm, vars, &ctx));
+        game_args.extend(collect_args(&arguments.game, vars, &ctx));
     } else if let Some(raw) = &version.minecraft_arguments {
         let parts = split(raw).context("failed to parse minecraftArguments")?;
         game_args.extend(parts.into_iter().map(|arg| substitute_vars(&arg, vars)));
@@ -696,14 +1784,70 @@ fn build_args(
     Ok((jvm_args, game_args))
 }
 
-fn collect_args(list: &[Argument], vars: &HashMap<String, String>) -> Vec<String> {
-    let ctx = RuleContext::new();
+/// Whether the version json declares the Quick Play arguments (1.20 and later).
+fn supports_quick_play(version: &VersionJson) -> bool {
+    declares_feature(version, "is_quick_play_")
+}
+
+/// Whether any game argument rule uses a feature whose name starts with `prefix`.
+fn declares_feature(version: &VersionJson, prefix: &str) -> bool {
+    let Some(arguments) = &version.arguments else {
+        return false;
+    };
+    arguments.game.iter().any(|arg| match arg {
+        Argument::WithRules { rules, .. } => rules.iter().any(|rule| {
+            rule.features
+                .as_ref()
+                .is_some_and(|f| f.keys().any(|k| k.starts_with(prefix)))
+        }),
+        Argument::Simple(_) => false,
+    })
+}
+
+/// Whether the game parses named options such as `--width` and `--fullscreen`
+/// (1.6 and later). Older versions only take a positional user name and session.
+fn accepts_named_game_args(version: &VersionJson) -> bool {
+    version.arguments.is_some()
+        || version
+            .minecraft_arguments
+            .as_deref()
+            .is_some_and(|raw| raw.contains("--username"))
+}
+
+/// The configured window size, if any. Zero is rejected; a size larger than
+/// the screen only produces a warning.
+fn window_resolution(
+    runtime: &Runtime,
+    screen_size: Option<(u32, u32)>,
+    warnings: &mut Vec<String>,
+) -> Result<Option<(u32, u32)>> {
+    if runtime.width.is_none() && runtime.height.is_none() {
+        return Ok(None);
+    }
+    let width = runtime.width.unwrap_or(DEFAULT_WINDOW_WIDTH);
+    let height = runtime.height.unwrap_or(DEFAULT_WINDOW_HEIGHT);
+    if width == 0 || height == 0 {
+        bail!("window size must be positive, got {width}x{height}");
+    }
+    if let Some((screen_width, screen_height)) = screen_size
+        && (width > screen_width || height > screen_height)
+    {
+        let warning = format!(
+            "window size {width}x{height} is larger than the primary monitor ({screen_width}x{screen_height})"
+        );
+        tracing::warn!("{}", warning);
+        warnings.push(warning);
+    }
+    Ok(Some((width, height)))
+}
+
+fn collect_args(list: &[Argument], vars: &HashMap<String, String>, ctx: &RuleContext) -> Vec<String> {
     let mut out = Vec::new();
     for arg in list {
         match arg {
             Argument::Simple(value) => out.push(substitute_vars(value, vars)),
             Argument::WithRules { rules, value } => {
-                if rules_allow(rules, &ctx) {
+                if rules_allow(rules, ctx) {
                     match value {
                         ArgValue::Single(value) => out.push(substitute_vars(value, vars)),
                         ArgValue::Multiple(values) => {
@@ -717,11 +1861,30 @@ fn collect_args(list: &[Argument], vars: &HashMap<String, String>) -> Vec<String
     out
 }
 
+/// Replace `${token}` placeholders in a single pass so substituted values
+/// (paths, tokens) are never re-expanded. Unknown tokens are left untouched.
 fn substitute_vars(value: &str, vars: &HashMap<String, String>) -> String {
-    let mut out = value.to_string();
-    for (key, val) in vars {
-        out = out.replace(&format!("${{{key}}}"), val);
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find('}') {
+            Some(end) => {
+                let key = &after[..end];
+                match vars.get(key) {
+                    Some(val) => out.push_str(val),
+                    None => out.push_str(&rest[start..start + 3 + end]),
+                }
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
     }
+    out.push_str(rest);
     out
 }
 
@@ -809,20 +1972,261 @@ fn strip_classpath_args(args: &mut Vec<String>) {
     }
 }
 
-fn resolve_java(override_java: Option<&str>, mc_version: &str) -> String {
+/// Check that the wrapper program exists, returning the command with the
+/// program resolved to its full path.
+fn resolve_wrapper(wrapper: &[String]) -> Result<Vec<String>> {
+    let Some((program, args)) = wrapper.split_first() else {
+        return Ok(Vec::new());
+    };
+    let Some(resolved) = find_executable(program) else {
+        bail!("wrapper command '{}' was not found on PATH; install it or remove it from the instance settings", program);
+    };
+    let mut command = vec![resolved.to_string_lossy().to_string()];
+    command.extend(args.iter().cloned());
+    Ok(command)
+}
+
+/// The instance's own JVM arguments, minus any that would replace the
+/// classpath or main class (a warning is recorded for each one dropped).
+fn instance_jvm_args(args: &[String], warnings: &mut Vec<String>) -> Vec<String> {
+    let mut kept = Vec::with_capacity(args.len());
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let Some(flag) = forbidden_jvm_arg(arg) else {
+            kept.push(arg.clone());
+            continue;
+        };
+        if arg == flag {
+            // The value follows as a separate argument
+            iter.next();
+        }
+        let warning = format!("Ignoring JVM argument {}: the launcher sets the classpath and main class itself", flag);
+        tracing::warn!("{}", warning);
+        warnings.push(warning);
+    }
+    kept
+}
+
+/// Parse an instance memory setting such as "4G" or "3072M" into megabytes.
+pub fn parse_memory_mb(memory: &str) -> Option<u64> {
+    let memory = memory.trim();
+    let (number, unit) = memory.split_at(memory.len().checked_sub(1)?);
+    let value: u64 = number.parse().ok()?;
+    match unit {
+        "g" | "G" => Some(value * 1024),
+        "m" | "M" => Some(value),
+        "k" | "K" => Some(value / 1024),
+        _ => None,
+    }
+}
+
+/// Compare a configured max heap (`-Xmx`, in MB) against what the instance's
+/// mods are likely to need. Returns a warning with a recommended value (from
+/// [`recommend_max_heap_mb`]) when it looks too small for the pack.
+pub fn check_memory_adequacy(profile: &Profile, xmx_mb: u64) -> Option<String> {
+    let mods = profile.mods.len();
+    memory_warning(
+        &profile.mc_version,
+        profile.loader.as_ref().map(|loader| loader.loader_type.as_str()),
+        mods,
+        xmx_mb,
+        recommend_max_heap_mb(&profile.mc_version, mods),
+    )
+}
+
+fn memory_warning(
+    mc_version: &str,
+    loader_type: Option<&str>,
+    mods: usize,
+    xmx_mb: u64,
+    recommended_mb: u64,
+) -> Option<String> {
+    if mods == 0 {
+        return None;
+    }
+    let needed = required_heap_mb(mc_version, loader_type, mods);
+    if xmx_mb >= needed {
+        return None;
+    }
+
+    let advice = if recommended_mb > xmx_mb {
+        format!("raise the max memory to {} MB", recommended_mb)
+    } else {
+        "this machine has little memory to spare, so consider removing some mods".to_string()
+    };
+    Some(format!(
+        "Max memory is {} MB, but {} mods likely need about {} MB and the game may run out of memory; {}",
+        xmx_mb, mods, needed, advice
+    ))
+}
+
+/// Rough heap a modded instance needs: the vanilla baseline plus an allowance
+/// per mod, larger for Forge-family loaders whose mods tend to be heavier.
+/// Rounded up to a multiple of 256 MB.
+fn required_heap_mb(mc_version: &str, loader_type: Option<&str>, mods: usize) -> u64 {
+    let base = if get_required_java_version(mc_version) >= 17 { 2048 } else { 1024 };
+    let per_mod = match loader_type {
+        Some("forge" | "neoforge") => 24,
+        _ => 12,
+    };
+    (base + mods as u64 * per_mod).div_ceil(256) * 256
+}
+
+/// Heap size above which Aikar's flags switch to their large-heap tuning.
+const AIKAR_LARGE_HEAP_MB: u64 = 12 * 1024;
+
+/// Aikar's G1 tuning flags (<https://docs.papermc.io/paper/aikars-flags>) for a heap of `heap_mb`.
+pub fn aikar_flags(heap_mb: u64) -> Vec<String> {
+    let large = heap_mb > AIKAR_LARGE_HEAP_MB;
+    let (new_size, max_new_size, region_size, reserve, occupancy) = if large {
+        (40, 50, "16M", 15, 20)
+    } else {
+        (30, 40, "8M", 20, 15)
+    };
+    vec![
+        format!("-Xms{}M", heap_mb),
+        "-XX:+UseG1GC".to_string(),
+        "-XX:+ParallelRefProcEnabled".to_string(),
+        "-XX:MaxGCPauseMillis=200".to_string(),
+        "-XX:+UnlockExperimentalVMOptions".to_string(),
+        "-XX:+DisableExplicitGC".to_string(),
+        "-XX:+AlwaysPreTouch".to_string(),
+        format!("-XX:G1NewSizePercent={}", new_size),
+        format!("-XX:G1MaxNewSizePercent={}", max_new_size),
+        format!("-XX:G1HeapRegionSize={}", region_size),
+        format!("-XX:G1ReservePercent={}", reserve),
+        "-XX:G1HeapWastePercent=5".to_string(),
+        "-XX:G1MixedGCCountTarget=4".to_string(),
+        format!("-XX:InitiatingHeapOccupancyPercent={}", occupancy),
+        "-XX:G1MixedGCLiveThresholdPercent=90".to_string(),
+        "-XX:SurvivorRatio=32".to_string(),
+        "-XX:+PerfDisableSharedMem".to_string(),
+        "-XX:MaxTenuringThreshold=1".to_string(),
+    ]
+}
+
+/// Replace the `-Xms` and `-XX:` options in `args` with Aikar's flags for `heap_mb`,
+/// keeping other arguments.
+pub fn apply_aikar_flags(args: &[String], heap_mb: u64) -> Vec<String> {
+    let mut result: Vec<String> = args
+        .iter()
+        .filter(|arg| !arg.starts_with("-XX:") && !arg.starts_with("-Xms"))
+        .cloned()
+        .collect();
+    result.extend(aikar_flags(heap_mb));
+    result
+}
+
+/// JVM arguments for a GC preset sized for a heap of `heap_mb`.
+pub fn gc_preset_args(preset: GcPreset, heap_mb: u64) -> Vec<String> {
+    match preset {
+        GcPreset::G1 => vec!["-XX:+UseG1GC".to_string()],
+        GcPreset::Zgc => vec![
+            format!("-Xms{}M", heap_mb),
+            "-XX:+UseZGC".to_string(),
+            "-XX:+AlwaysPreTouch".to_string(),
+            "-XX:+DisableExplicitGC".to_string(),
+        ],
+        GcPreset::Aikar => aikar_flags(heap_mb),
+    }
+}
+
+/// The preset to apply, given the instance's own JVM arguments and Java.
+///
+/// A preset is skipped when the arguments already pick a collector, since the
+/// JVM refuses to start with two. A collector the Java is too old for falls
+/// back to G1. Both cases record a warning.
+fn checked_gc_preset(preset: GcPreset, args: &[String], java: &str, warnings: &mut Vec<String>) -> Option<GcPreset> {
+    let selects_gc = |arg: &String| arg.starts_with("-XX:+Use") && arg.ends_with("GC");
+    if let Some(arg) = args.iter().find(|arg| selects_gc(arg)) {
+        let warning = format!(
+            "Ignoring the {} GC preset: the instance's JVM arguments already select a collector ({})",
+            preset.as_str(),
+            arg
+        );
+        tracing::warn!("{}", warning);
+        warnings.push(warning);
+        return None;
+    }
+
+    let Some(min_java) = preset.min_java() else {
+        return Some(preset);
+    };
+    let major = validate_java_path(java).major;
+    if major.is_some_and(|major| major >= min_java) {
+        return Some(preset);
+    }
+    let found = major.map_or_else(|| "an unknown version".to_string(), |major| format!("Java {}", major));
+    let warning = format!(
+        "The {} GC preset needs Java {} or newer, but '{}' is {}; using G1 instead",
+        preset.as_str(),
+        min_java,
+        java,
+        found
+    );
+    tracing::warn!("{}", warning);
+    warnings.push(warning);
+    Some(GcPreset::G1)
+}
+
+fn loader_type(profile: &Profile) -> Option<&str> {
+    profile.loader.as_ref().map(|loader| loader.loader_type.as_str())
+}
+
+fn max_java(profile: &Profile) -> Option<u32> {
+    max_java_version(&profile.mc_version, loader_type(profile))
+}
+
+/// Validate the instance's pinned Java, if any.
+///
+/// An override that is missing, does not run or is newer than `max_major` is
+/// dropped so the launch falls back to auto-detection, and a warning explaining
+/// why is recorded. A Java home folder is replaced by the executable found
+/// inside it.
+fn checked_java_override(
+    override_java: Option<&str>,
+    max_major: Option<u32>,
+    warnings: &mut Vec<String>,
+) -> Option<String> {
+    let java = override_java?;
+    let validation = validate_java_path(java);
+    if validation.is_valid {
+        if let (Some(major), Some(max)) = (validation.major, max_major)
+            && major > max
+        {
+            let warning = format!(
+                "Java override '{}' is Java {}, but this loader only runs on Java {} or older; using auto-detected Java instead",
+                java, major, max
+            );
+            tracing::warn!("{}", warning);
+            warnings.push(warning);
+            return None;
+        }
+        return Some(validation.resolved_path.unwrap_or_else(|| java.to_string()));
+    }
+
+    let reason = validation.error.unwrap_or_else(|| "it could not be run".to_string());
+    let warning = format!("Java override '{}' is unusable ({}); using auto-detected Java instead", java, reason);
+    tracing::warn!("{}", warning);
+    warnings.push(warning);
+    None
+}
+
+fn resolve_java(override_java: Option<&str>, mc_version: &str, loader_type: Option<&str>) -> String {
     // If user explicitly set a Java path, use it (they know what they're doing)
     if let Some(java) = override_java {
         return java.to_string();
     }
 
     let required_java = get_required_java_version(mc_version);
+    let max_java = max_java_version(mc_version, loader_type);
 
     // Try to find a compatible Java installation
     let installations = detect_installations();
     for install in &installations {
         if let Some(major) = install.major {
-            if is_java_compatible(major, mc_version) {
-                eprintln!(
+            if is_java_compatible_with_loader(major, mc_version, loader_type) {
+                tracing::info!(
                     "Auto-selected Java {} ({}) for Minecraft {}",
                     major,
                     install.vendor.as_deref().unwrap_or("Unknown"),
@@ -840,32 +2244,41 @@ fn resolve_java(override_java: Option<&str>, mc_version: &str) -> String {
         "java".to_string()
     };
 
-    eprintln!(
-        "Warning: Could not find Java {} or newer for Minecraft {}. \
-         Using '{}' which may not be compatible.",
-        required_java, mc_version, fallback
-    );
+    match max_java {
+        Some(max) => tracing::warn!(
+            "Could not find Java {} to {} for Minecraft {}. \
+             Using '{}' which may not be compatible.",
+            required_java, max, mc_version, fallback
+        ),
+        None => tracing::warn!(
+            "Could not find Java {} or newer for Minecraft {}. \
+             Using '{}' which may not be compatible.",
+            required_java, mc_version, fallback
+        ),
+    }
 
     fallback
 }
 
 fn download_text(url: &str) -> Result<String> {
-    let client = Client::new();
+    let client = http_client()?;
     let resp = client.get(url).send().context("failed to download")?;
-    let resp = resp.error_for_status().context("download failed")?;
+    let resp = check_status(resp).context("download failed")?;
     let text = resp.text().context("failed to read response")?;
     Ok(text)
 }
 
 fn download_json(url: &str) -> Result<Value> {
-    let client = Client::new();
+    let client = http_client()?;
     let resp = client.get(url).send().context("failed to download json")?;
-    let resp = resp.error_for_status().context("json download failed")?;
+    let resp = check_status(resp).context("json download failed")?;
     let json: Value = resp.json().context("failed to parse json")?;
     Ok(json)
 }
 
-fn download_with_sha1(url: &str, path: &Path, expected_sha1: Option<&str>) -> Result<()> {
+pub(crate) fn download_with_sha1(url: &str, path: &Path, expected_sha1: Option<&str>) -> Result<()> {
+    use std::io::Write;
+
     if path.exists() {
         if let Some(expected) = expected_sha1 {
             if let Ok(actual) = sha1_file(path)
@@ -883,17 +2296,26 @@ fn download_with_sha1(url: &str, path: &Path, expected_sha1: Option<&str>) -> Re
     }
 
     let tmp_path = path.with_extension("tmp");
-    let client = Client::new();
-    let mut resp = client
+    tracing::debug!("downloading {url} -> {}", path.display());
+    let client = http_client()?;
+    let _slot = crate::http::download_slot();
+    let resp = client
         .get(url)
         .send()
-        .with_context(|| format!("failed to download: {url}"))?
-        .error_for_status()
-        .with_context(|| format!("download failed: {url}"))?;
+        .with_context(|| format!("failed to download: {url}"))?;
+    let mut resp = check_status(resp).with_context(|| format!("download failed: {url}"))?;
 
     let mut out = fs::File::create(&tmp_path)
         .with_context(|| format!("failed to create file: {}", tmp_path.display()))?;
-    std::io::copy(&mut resp, &mut out).context("failed to write download")?;
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let read = resp.read(&mut buf).map_err(|err| crate::http::read_error(url, err))?;
+        if read == 0 {
+            break;
+        }
+        crate::http::throttle_download(read);
+        out.write_all(&buf[..read]).context("failed to write download")?;
+    }
 
     if let Some(expected) = expected_sha1 {
         let actual = sha1_file(&tmp_path)?;
@@ -1008,7 +2430,7 @@ fn arch_marker() -> &'static str {
     }
 }
 
-fn maven_path_from_name(name: &str) -> Option<String> {
+pub(crate) fn maven_path_from_name(name: &str) -> Option<String> {
     let parts: Vec<&str> = name.split(':').collect();
     if parts.len() < 3 {
         return None;
@@ -1044,7 +2466,7 @@ fn maven_path_from_name_with_classifier(name: &str, classifier: &str) -> Option<
     Some(format!("{group}/{artifact}/{version}/{file}"))
 }
 
-fn join_url(base: &str, path: &str) -> String {
+pub(crate) fn join_url(base: &str, path: &str) -> String {
     if base.ends_with('/') {
         format!("{base}{path}")
     } else {
@@ -1182,7 +2604,6 @@ struct Downloads {
 struct DownloadInfo {
     sha1: String,
     url: String,
-    #[allow(dead_code)]
     #[serde(default)]
     size: Option<u64>,
 }
@@ -1203,6 +2624,8 @@ struct AssetIndex {
 struct AssetObject {
     hash: String,
     #[serde(default)]
+    size: Option<u64>,
+    #[serde(default)]
     url: Option<String>,
 }
 
@@ -1240,6 +2663,11 @@ struct Library {
     extract: Option<Extract>,
     #[serde(default)]
     url: Option<String>,
+    /// Checksum and size for maven-style entries (Fabric and Quilt list these).
+    #[serde(default)]
+    sha1: Option<String>,
+    #[serde(default)]
+    size: Option<u64>,
 }
 
 #[derive(Clone, Deserialize)]
@@ -1253,6 +2681,8 @@ struct LibraryArtifact {
     path: String,
     sha1: String,
     url: String,
+    #[serde(default)]
+    size: Option<u64>,
 }
 
 #[derive(Clone, Deserialize)]
@@ -1327,3 +2757,820 @@ fn merge_versions(mut parent: VersionJson, mut child: VersionJson) -> VersionJso
 
     child
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_find_launch_marker() {
+        let vanilla = [
+            "[12:00:01] [main/INFO]: Loaded 7 recipes",
+            "[12:00:01] [Render thread/INFO]: Setting user: Player",
+            "[12:00:02] [Render thread/INFO]: Backend library: LWJGL version 3.3.1 build 7",
+        ];
+        let legacy = [
+            "[12:00:00] [Client thread/INFO]: Setting user: Player",
+            "[12:00:00] [Client thread/INFO]: LWJGL Version: 2.9.4",
+        ];
+        let crashed = [
+            "[12:00:00] [main/ERROR]: Failed to start the minecraft server",
+            "java.lang.UnsupportedClassVersionError: net/minecraft/client/main/Main",
+        ];
+
+        assert_eq!(vanilla.iter().find_map(|l| find_launch_marker(l)), Some("Setting user:"));
+        assert_eq!(find_launch_marker(vanilla[2]), Some("Backend library: LWJGL"));
+        assert_eq!(find_launch_marker(legacy[1]), Some("LWJGL Version:"));
+        assert!(crashed.iter().all(|l| find_launch_marker(l).is_none()));
+    }
+
+    #[test]
+    fn test_full_verify_blocks_tampered_file() {
+        let dir = std::env::temp_dir().join(format!("shard-verify-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("library.jar");
+        fs::write(&path, b"original").unwrap();
+        let expected = sha1_file(&path).unwrap();
+        // Same size, different content
+        fs::write(&path, b"tampered").unwrap();
+        let unreachable = "http://127.0.0.1:9/library.jar";
+
+        let mut fast = IntegrityCheck::fast();
+        fast.ensure(unreachable, &path, Some(&expected), Some(8)).unwrap();
+        let summary = fast.finish().unwrap();
+        assert_eq!(summary.checked, 1);
+        assert_eq!(summary.repaired, 0);
+
+        // By default the file is hashed too, and the failed repair aborts at once
+        let mut default = IntegrityCheck::for_launch(&LaunchOptions::default());
+        assert!(default.ensure(unreachable, &path, Some(&expected), Some(8)).is_err());
+        assert_eq!(default.summary.repaired, 1);
+        assert!(!path.exists());
+        fs::write(&path, b"tampered").unwrap();
+
+        let mut full = IntegrityCheck::new(true);
+        full.ensure(unreachable, &path, Some(&expected), Some(8)).unwrap();
+        assert_eq!(full.summary.repaired, 1);
+        assert_eq!(full.summary.failed.len(), 1);
+        assert!(full.finish().is_err());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_clear_java_env_omits_java_options() {
+        let run = |clear: bool| {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c").arg("env");
+            // Simulate a polluted parent environment
+            cmd.env("_JAVA_OPTIONS", "-Xmx256m").env("JAVA_TOOL_OPTIONS", "-XX:+UseSerialGC");
+            apply_java_env_policy(&mut cmd, clear);
+            String::from_utf8(cmd.output().unwrap().stdout).unwrap()
+        };
+
+        let kept = run(false);
+        assert!(kept.lines().any(|l| l == "_JAVA_OPTIONS=-Xmx256m"));
+
+        let cleared = run(true);
+        assert!(!cleared.lines().any(|l| l.starts_with("_JAVA_OPTIONS=")));
+        assert!(!cleared.lines().any(|l| l.starts_with("JAVA_TOOL_OPTIONS=")));
+    }
+
+    /// Serve `files` (path -> body) over HTTP, counting requests per path.
+    fn serve_files(files: HashMap<String, Vec<u8>>) -> (String, std::sync::Arc<Mutex<Vec<String>>>) {
+        use std::io::Write;
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let requests = std::sync::Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut buf = [0u8; 4096];
+                let n = stream.read(&mut buf).unwrap_or(0);
+                let request = String::from_utf8_lossy(&buf[..n]).to_string();
+                let path = request.split_whitespace().nth(1).unwrap_or("").to_string();
+                log.lock().unwrap().push(path.clone());
+                let response = match files.get(&path) {
+                    Some(body) => {
+                        let mut out = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", body.len()).into_bytes();
+                        out.extend_from_slice(body);
+                        out
+                    }
+                    None => b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_vec(),
+                };
+                let _ = stream.write_all(&response);
+            }
+        });
+        (base, requests)
+    }
+
+    #[test]
+    fn test_asset_download_resumes_partial_store() {
+        let root = std::env::temp_dir().join(format!("shard-assets-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let paths = Paths::from_base(&root);
+
+        let mut files = HashMap::new();
+        let mut objects = Vec::new();
+        for i in 0..5 {
+            let body = format!("asset object {i}").into_bytes();
+            let hash = hex::encode(Sha1::digest(&body));
+            files.insert(format!("/{}/{}", &hash[0..2], hash), body.clone());
+            objects.push(AssetObject { hash, size: Some(body.len() as u64), url: None });
+        }
+        // Two index entries with the same content are fetched once
+        objects.push(objects[4].clone());
+        // Simulate an interrupted earlier session that fetched 3 of 5 objects.
+        for object in &objects[..3] {
+            let path = paths.minecraft_asset_object(&object.hash);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            let key = format!("/{}/{}", &object.hash[0..2], object.hash);
+            fs::write(&path, &files[&key]).unwrap();
+        }
+
+        let (base, requests) = serve_files(files);
+
+        let mut integrity = IntegrityCheck::new(false);
+        assert_eq!(download_asset_objects(&paths, &objects, &base, &mut integrity).unwrap(), 2);
+        let mut fetched = requests.lock().unwrap().clone();
+        fetched.sort();
+        let mut expected: Vec<_> = objects[3..5].iter().map(|o| format!("/{}/{}", &o.hash[0..2], o.hash)).collect();
+        expected.sort();
+        assert_eq!(fetched, expected);
+
+        let mut integrity = IntegrityCheck::new(false);
+        assert_eq!(download_asset_objects(&paths, &objects, &base, &mut integrity).unwrap(), 0);
+        assert_eq!(requests.lock().unwrap().len(), 2);
+        assert!(objects.iter().all(|o| paths.minecraft_asset_object(&o.hash).exists()));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_full_verify_lists_repaired_files() {
+        let root = std::env::temp_dir().join(format!("shard-assets-repair-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        let paths = Paths::from_base(&root);
+
+        let mut files = HashMap::new();
+        let mut objects = Vec::new();
+        for i in 0..2 {
+            let body = format!("repairable object {i}").into_bytes();
+            let hash = hex::encode(Sha1::digest(&body));
+            let path = paths.minecraft_asset_object(&hash);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            // Same size, different bytes: only a full verify notices
+            let stored = if i == 0 { body.iter().map(|b| b ^ 1).collect() } else { body.clone() };
+            fs::write(&path, stored).unwrap();
+            files.insert(format!("/{}/{}", &hash[0..2], hash), body.clone());
+            objects.push(AssetObject { hash, size: Some(body.len() as u64), url: None });
+        }
+        let (base, _) = serve_files(files);
+
+        let mut integrity = IntegrityCheck::new(true);
+        assert_eq!(download_asset_objects(&paths, &objects, &base, &mut integrity).unwrap(), 1);
+        let summary = integrity.finish().unwrap();
+        assert_eq!(summary.repaired_files, vec![paths.minecraft_asset_object(&objects[0].hash)]);
+        assert_eq!(sha1_file(&summary.repaired_files[0]).unwrap(), objects[0].hash);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_build_args_modern_schema() {
+        let json = r#"{
+            "id": "1.20.1",
+            "type": "release",
+            "mainClass": "net.minecraft.client.main.Main",
+            "arguments": {
+                "game": [
+                    "--username", "${auth_player_name}",
+                    "--version", "${version_name}",
+                    "--gameDir", "${game_directory}",
+                    "--assetsDir", "${assets_root}",
+                    "--assetIndex", "${assets_index_name}",
+                    "--uuid", "${auth_uuid}",
+                    "--accessToken", "${auth_access_token}",
+                    "--clientId", "${clientid}",
+                    "--xuid", "${auth_xuid}",
+                    "--userType", "${user_type}",
+                    "--versionType", "${version_type}",
+                    {"rules": [{"action": "allow", "features": {"is_demo_user": true}}], "value": "--demo"},
+                    {"rules": [{"action": "allow", "features": {"has_custom_resolution": true}}],
+                     "value": ["--width", "${resolution_width}", "--height", "${resolution_height}"]}
+                ],
+                "jvm": [
+                    {"rules": [{"action": "allow", "os": {"name": "osx"}}], "value": ["-XstartOnFirstThread"]},
+                    "-Djava.library.path=${natives_directory}",
+                    "-Djna.tmpdir=${natives_directory}",
+                    "-Dminecraft.launcher.brand=${launcher_name}",
+                    "-Dminecraft.launcher.version=${launcher_version}",
+                    "-cp",
+                    "${classpath}"
+                ]
+            }
+        }"#;
+        let version: VersionJson = serde_json::from_str(json).unwrap();
+        let account = LaunchAccount {
+            uuid: "0123456789abcdef0123456789abcdef".to_string(),
+            username: "Player".to_string(),
+            access_token: "token-${classpath}".to_string(),
+            xuid: None,
+        };
+        let vars = build_var_map(
+            Path::new("/game"),
+            Path::new("/assets"),
+            "5",
+            "/libs/a.jar:/libs/b.jar",
+            Path::new("/natives"),
+            Path::new("/libs"),
+            &version,
+            &account,
+        );
+
+        let (mut jvm_args, game_args) = build_args(&version, &vars, &[]).unwrap();
+        assert!(jvm_args.contains(&"-Djava.library.path=/natives".to_string()));
+        assert!(jvm_args.contains(&"-Djna.tmpdir=/natives".to_string()));
+        assert!(jvm_args.contains(&"-Dminecraft.launcher.brand=shard".to_string()));
+        assert!(jvm_args.iter().all(|arg| !arg.contains("${")));
+        let cp_idx = jvm_args.iter().position(|arg| arg == "-cp").unwrap();
+        assert_eq!(jvm_args[cp_idx + 1], "/libs/a.jar:/libs/b.jar");
+        strip_classpath_args(&mut jvm_args);
+        assert!(!jvm_args.iter().any(|arg| arg == "-cp"));
+
+        let value_of = |flag: &str| {
+            let idx = game_args.iter().position(|arg| arg == flag).unwrap();
+            game_args[idx + 1].clone()
+        };
+        assert_eq!(value_of("--username"), "Player");
+        assert_eq!(value_of("--gameDir"), "/game");
+        assert_eq!(value_of("--assetIndex"), "5");
+        assert_eq!(value_of("--xuid"), "");
+        // Substituted values must not be expanded a second time.
+        assert_eq!(value_of("--accessToken"), "token-${classpath}");
+        assert!(!game_args.iter().any(|arg| arg == "--demo" || arg == "--width"));
+        assert_eq!(version.main_class.as_deref(), Some("net.minecraft.client.main.Main"));
+    }
+
+    #[test]
+    fn test_missing_java_override_falls_back_with_warning() {
+        let mut warnings = Vec::new();
+        assert_eq!(checked_java_override(None, None, &mut warnings), None);
+        assert!(warnings.is_empty());
+
+        let missing = std::env::temp_dir().join("shard-no-such-java").join("java");
+        let missing = missing.to_string_lossy().to_string();
+        assert_eq!(checked_java_override(Some(&missing), None, &mut warnings), None);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains(&missing));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_java_override_folder_resolves_to_the_executable() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let home = std::env::temp_dir().join(format!("shard-java-override-home-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&home);
+        let java = home.join("bin").join("java");
+        fs::create_dir_all(java.parent().unwrap()).unwrap();
+        fs::write(&java, "#!/bin/sh\necho 'openjdk version \"17.0.10\" 2024-01-16' >&2\n").unwrap();
+        fs::set_permissions(&java, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut warnings = Vec::new();
+        let folder = home.to_string_lossy().to_string();
+        let resolved = checked_java_override(Some(&folder), None, &mut warnings);
+        assert_eq!(resolved.as_deref(), Some(java.to_string_lossy().as_ref()));
+        assert!(warnings.is_empty());
+
+        // Legacy Forge caps Java at 8
+        assert_eq!(checked_java_override(Some(&folder), Some(8), &mut warnings), None);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("Java 17"), "{}", warnings[0]);
+
+        let _ = fs::remove_dir_all(&home);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_with_output_tags_streams_and_records_crash_output() {
+        let instance_dir = std::env::temp_dir().join(format!("shard-run-output-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&instance_dir);
+        fs::create_dir_all(&instance_dir).unwrap();
+        // `sh -c <script> <$0...>` swallows the trailing classpath/main class arguments
+        let plan = LaunchPlan {
+            instance_dir: instance_dir.clone(),
+            wrapper: Vec::new(),
+            pre_launch: None,
+            post_exit: None,
+            env: HashMap::new(),
+            java_exec: "sh".to_string(),
+            jvm_args: vec!["-c".to_string(), "echo hello; echo boom >&2; exit 3".to_string()],
+            classpath: String::new(),
+            main_class: String::new(),
+            game_args: Vec::new(),
+            game_options: Vec::new(),
+            create_game_options: false,
+            verification: VerifySummary::default(),
+            warnings: Vec::new(),
+            secrets: Vec::new(),
+        };
+
+        let mut seen = Vec::new();
+        let run = run_with_output(&plan, &LaunchOptions::default(), |line| seen.push(line.clone())).unwrap();
+
+        assert!(!run.status.success());
+        seen.sort_by_key(|line| line.line.clone());
+        assert_eq!(seen.len(), 2);
+        assert_eq!((seen[0].stream, seen[0].line.as_str()), (OutputStream::Stderr, "boom"));
+        assert_eq!((seen[1].stream, seen[1].line.as_str()), (OutputStream::Stdout, "hello"));
+
+        let report = fs::read_to_string(run.crash_report.unwrap()).unwrap();
+        assert!(report.contains("[stdout] hello"));
+        assert!(report.contains("[stderr] boom"));
+        fs::remove_dir_all(&instance_dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_game_options_are_written_when_the_game_starts() {
+        let instance_dir = std::env::temp_dir().join(format!("shard-run-options-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&instance_dir);
+        fs::create_dir_all(&instance_dir).unwrap();
+        let plan = LaunchPlan {
+            instance_dir: instance_dir.clone(),
+            wrapper: Vec::new(),
+            pre_launch: None,
+            post_exit: None,
+            env: HashMap::new(),
+            java_exec: "sh".to_string(),
+            jvm_args: vec!["-c".to_string(), "cat options.txt".to_string()],
+            classpath: String::new(),
+            main_class: String::new(),
+            game_args: Vec::new(),
+            game_options: vec![("overrideWidth", "1280".to_string()), ("fullscreen", "false".to_string())],
+            create_game_options: true,
+            verification: VerifySummary::default(),
+            warnings: Vec::new(),
+            secrets: Vec::new(),
+        };
+
+        let mut seen = Vec::new();
+        let run = run_with_output(&plan, &LaunchOptions::default(), |line| seen.push(line.line.clone())).unwrap();
+
+        assert!(run.status.success());
+        assert_eq!(seen, vec!["overrideWidth:1280", "fullscreen:false"]);
+        fs::remove_dir_all(&instance_dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_watchdog_reports_a_late_start() {
+        let instance_dir = std::env::temp_dir().join(format!("shard-watchdog-start-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&instance_dir);
+        fs::create_dir_all(&instance_dir).unwrap();
+        let plan = LaunchPlan {
+            instance_dir: instance_dir.clone(),
+            wrapper: Vec::new(),
+            pre_launch: None,
+            post_exit: None,
+            env: HashMap::new(),
+            java_exec: "sh".to_string(),
+            jvm_args: vec!["-c".to_string(), "sleep 1; echo '[Render thread/INFO]: Setting user: Player'".to_string()],
+            classpath: String::new(),
+            main_class: String::new(),
+            game_args: Vec::new(),
+            game_options: Vec::new(),
+            create_game_options: false,
+            verification: VerifySummary::default(),
+            warnings: Vec::new(),
+            secrets: Vec::new(),
+        };
+        let options = LaunchOptions { startup_timeout: Some(Duration::from_millis(300)), ..LaunchOptions::default() };
+
+        let mut events = Vec::new();
+        let run = run_with_watchdog(&plan, &options, None, |_| {}, |event| events.push(event)).unwrap();
+
+        assert!(run.status.success());
+        assert_eq!(events, [StartupEvent::NotResponding { waited_secs: 0 }, StartupEvent::Started]);
+        fs::remove_dir_all(&instance_dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_watchdog_kill_stops_a_hung_game() {
+        let instance_dir = std::env::temp_dir().join(format!("shard-watchdog-kill-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&instance_dir);
+        fs::create_dir_all(&instance_dir).unwrap();
+        // `exec` so the kill reaches the sleeping process itself
+        let plan = LaunchPlan {
+            instance_dir: instance_dir.clone(),
+            wrapper: Vec::new(),
+            pre_launch: None,
+            post_exit: None,
+            env: HashMap::new(),
+            java_exec: "sh".to_string(),
+            jvm_args: vec!["-c".to_string(), "echo loading; exec sleep 30".to_string()],
+            classpath: String::new(),
+            main_class: String::new(),
+            game_args: Vec::new(),
+            game_options: Vec::new(),
+            create_game_options: false,
+            verification: VerifySummary::default(),
+            warnings: Vec::new(),
+            secrets: Vec::new(),
+        };
+        let options = LaunchOptions { startup_timeout: Some(Duration::from_millis(200)), ..LaunchOptions::default() };
+
+        let kill = AtomicBool::new(false);
+        let begun = Instant::now();
+        let run = run_with_watchdog(&plan, &options, Some(&kill), |_| {}, |event| {
+            if matches!(event, StartupEvent::NotResponding { .. }) {
+                kill.store(true, Ordering::Relaxed);
+            }
+        })
+        .unwrap();
+
+        assert!(run.killed && !run.status.success());
+        assert!(begun.elapsed() < Duration::from_secs(10));
+        assert!(run.crash_report.is_none());
+        assert!(!instance_dir.join("crash-reports").exists());
+        fs::remove_dir_all(&instance_dir).unwrap();
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_watchdog_kill_reaches_the_game_behind_a_wrapper() {
+        let instance_dir = std::env::temp_dir().join(format!("shard-watchdog-wrapper-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&instance_dir);
+        fs::create_dir_all(&instance_dir).unwrap();
+        // The wrapper shell waits on a separate "JVM" process, like gamemoderun
+        let plan = LaunchPlan {
+            instance_dir: instance_dir.clone(),
+            wrapper: vec!["sh".to_string(), "-c".to_string(), "\"$@\"; echo wrapper done".to_string(), "wrapper".to_string()],
+            pre_launch: None,
+            post_exit: None,
+            env: HashMap::new(),
+            java_exec: "sh".to_string(),
+            jvm_args: vec!["-c".to_string(), "echo $$ > game.pid; echo loading; exec sleep 30".to_string()],
+            classpath: String::new(),
+            main_class: String::new(),
+            game_args: Vec::new(),
+            game_options: Vec::new(),
+            create_game_options: false,
+            verification: VerifySummary::default(),
+            warnings: Vec::new(),
+            secrets: Vec::new(),
+        };
+        let options = LaunchOptions { startup_timeout: Some(Duration::from_millis(200)), ..LaunchOptions::default() };
+
+        let kill = AtomicBool::new(false);
+        let begun = Instant::now();
+        let run = run_with_watchdog(&plan, &options, Some(&kill), |_| {}, |event| {
+            if matches!(event, StartupEvent::NotResponding { .. }) {
+                kill.store(true, Ordering::Relaxed);
+            }
+        })
+        .unwrap();
+
+        // The game kept the output pipes open, so the run only ends once it is gone too
+        assert!(run.killed);
+        assert!(begun.elapsed() < Duration::from_secs(10));
+        let pid = fs::read_to_string(instance_dir.join("game.pid")).unwrap();
+        // Gone, or a zombie waiting for a reaper; it may still be tearing down
+        let exited = || {
+            let stat = fs::read_to_string(format!("/proc/{}/stat", pid.trim())).unwrap_or_default();
+            stat.is_empty() || stat.split_whitespace().nth(2) == Some("Z")
+        };
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while !exited() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(exited());
+        fs::remove_dir_all(&instance_dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_crash_output_is_appended_to_the_games_own_report() {
+        let crash_dir = std::env::temp_dir().join(format!("shard-crash-append-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&crash_dir);
+        fs::create_dir_all(&crash_dir).unwrap();
+        let started = SystemTime::now() - Duration::from_secs(5);
+        let game_report = crash_dir.join("crash-2024-01-01_00.00.00-client.txt");
+        fs::write(&game_report, "---- Minecraft Crash Report ----\n").unwrap();
+
+        let status = std::os::unix::process::ExitStatusExt::from_raw(1 << 8);
+        let lines = [GameOutputLine { stream: OutputStream::Stderr, line: "Exception".to_string() }];
+        let path = record_output_in_crash_report(&crash_dir, started, &status, &lines).unwrap();
+
+        assert_eq!(path, game_report);
+        let report = fs::read_to_string(&path).unwrap();
+        assert!(report.starts_with("---- Minecraft Crash Report ----"));
+        assert!(report.contains("[stderr] Exception"));
+        fs::remove_dir_all(&crash_dir).unwrap();
+    }
+
+    #[test]
+    fn test_instance_jvm_args_drop_classpath_overrides() {
+        let args: Vec<String> = ["-XX:+UseG1GC", "-cp", "evil.jar", "--class-path=x.jar", "-jar", "a.jar", "-Dfoo=bar"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut warnings = Vec::new();
+        assert_eq!(instance_jvm_args(&args, &mut warnings), vec!["-XX:+UseG1GC", "-Dfoo=bar"]);
+        assert_eq!(warnings.len(), 3);
+        assert!(crate::profile::validate_jvm_args(&args).is_err());
+        assert!(crate::profile::validate_jvm_args(&args[..1]).is_ok());
+    }
+
+    #[test]
+    fn test_aikar_flags_follow_allocated_heap() {
+        assert_eq!(parse_memory_mb("4G"), Some(4096));
+        assert_eq!(parse_memory_mb("3072m"), Some(3072));
+        assert_eq!(parse_memory_mb("4096"), None);
+
+        let normal = aikar_flags(4096);
+        assert_eq!(normal[0], "-Xms4096M");
+        assert!(normal.contains(&"-XX:G1HeapRegionSize=8M".to_string()));
+        assert!(aikar_flags(16384).contains(&"-XX:G1HeapRegionSize=16M".to_string()));
+
+        let existing = vec!["-XX:+UseZGC".to_string(), "-Dfml.ignoreInvalidMinecraftCertificates=true".to_string()];
+        let applied = apply_aikar_flags(&existing, 4096);
+        assert_eq!(applied[0], "-Dfml.ignoreInvalidMinecraftCertificates=true");
+        assert!(!applied.contains(&"-XX:+UseZGC".to_string()));
+        assert_eq!(applied.len(), 1 + normal.len());
+    }
+
+    #[test]
+    fn test_small_heap_for_a_large_pack_is_flagged() {
+        let warning = memory_warning("1.20.1", Some("forge"), 150, 2048, 6144).unwrap();
+        assert!(warning.contains("raise the max memory to 6144 MB"));
+        assert!(warning.contains("about 5888 MB"));
+        assert!(memory_warning("1.20.1", Some("fabric"), 40, 4096, 6144).is_none());
+        assert!(memory_warning("1.20.1", None, 0, 1024, 4096).is_none());
+        let warning = memory_warning("1.12.2", Some("forge"), 200, 4096, 4096).unwrap();
+        assert!(warning.contains("little memory to spare"));
+    }
+
+    #[test]
+    fn test_gc_presets_expand_and_respect_the_instance_args() {
+        assert_eq!(gc_preset_args(GcPreset::G1, 4096), vec!["-XX:+UseG1GC"]);
+        assert!(gc_preset_args(GcPreset::Zgc, 16384).contains(&"-XX:+UseZGC".to_string()));
+        assert!(gc_preset_args(GcPreset::Aikar, 16384).contains(&"-XX:G1HeapRegionSize=16M".to_string()));
+        assert!(gc_preset_args(GcPreset::Aikar, 8192).contains(&"-XX:G1HeapRegionSize=8M".to_string()));
+
+        let mut warnings = Vec::new();
+        let args = vec!["-XX:+UseShenandoahGC".to_string()];
+        assert_eq!(checked_gc_preset(GcPreset::Aikar, &args, "java", &mut warnings), None);
+        assert_eq!(checked_gc_preset(GcPreset::G1, &[], "java", &mut warnings), Some(GcPreset::G1));
+        let missing = std::env::temp_dir().join("shard-no-such-java").join("java");
+        let missing = missing.to_string_lossy().to_string();
+        assert_eq!(checked_gc_preset(GcPreset::Zgc, &[], &missing, &mut warnings), Some(GcPreset::G1));
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[1].contains("Java 15"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_wrapper_runs_java_in_the_instance_dir() {
+        assert!(resolve_wrapper(&["shard-no-such-wrapper".to_string()]).is_err());
+        let wrapper = resolve_wrapper(&["env".to_string(), "SHARD_WRAPPED=1".to_string()]).unwrap();
+        assert!(wrapper[0].ends_with("env"));
+
+        let instance_dir = std::env::temp_dir().join(format!("shard-wrapper-{}", std::process::id()));
+        fs::create_dir_all(&instance_dir).unwrap();
+        let plan = LaunchPlan {
+            instance_dir: instance_dir.clone(),
+            wrapper,
+            pre_launch: None,
+            post_exit: None,
+            env: HashMap::new(),
+            java_exec: "sh".to_string(),
+            jvm_args: vec!["-c".to_string(), "echo \"$SHARD_WRAPPED $(pwd)\"".to_string()],
+            classpath: String::new(),
+            main_class: String::new(),
+            game_args: Vec::new(),
+            game_options: Vec::new(),
+            create_game_options: false,
+            verification: VerifySummary::default(),
+            warnings: Vec::new(),
+            secrets: Vec::new(),
+        };
+        let output = launch_command(&plan, &LaunchOptions::default()).output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let expected = format!("1 {}", instance_dir.canonicalize().unwrap().display());
+        assert_eq!(stdout.trim(), expected);
+        fs::remove_dir_all(&instance_dir).unwrap();
+    }
+
+    #[test]
+    fn test_quick_play_arguments_follow_version_support() {
+        let json = r#"{
+            "id": "1.20.4",
+            "mainClass": "net.minecraft.client.main.Main",
+            "arguments": {
+                "game": [
+                    "--username", "${auth_player_name}",
+                    {"rules": [{"action": "allow", "features": {"is_quick_play_multiplayer": true}}],
+                     "value": ["--quickPlayMultiplayer", "${quick_play_multiplayer}"]},
+                    {"rules": [{"action": "allow", "features": {"is_quick_play_singleplayer": true}}],
+                     "value": ["--quickPlaySingleplayer", "${quick_play_singleplayer}"]}
+                ],
+                "jvm": []
+            }
+        }"#;
+        let version: VersionJson = serde_json::from_str(json).unwrap();
+        assert!(supports_quick_play(&version));
+
+        let quick_play = QuickPlay::Multiplayer("play.example.net:25566".to_string());
+        let mut vars = HashMap::new();
+        let (key, value) = quick_play.var();
+        vars.insert(key.to_string(), value.to_string());
+        let (_, game_args) = build_args(&version, &vars, &[quick_play.feature()]).unwrap();
+        assert!(game_args.ends_with(&["--quickPlayMultiplayer".to_string(), "play.example.net:25566".to_string()]));
+        assert!(!game_args.iter().any(|arg| arg == "--quickPlaySingleplayer"));
+
+        let legacy: VersionJson = serde_json::from_str(
+            r#"{"id": "1.12.2", "minecraftArguments": "--username ${auth_player_name}"}"#,
+        )
+        .unwrap();
+        assert!(!supports_quick_play(&legacy));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hooks_run_in_the_instance_dir_and_failing_pre_hook_aborts() {
+        let instance_dir = std::env::temp_dir().join(format!("shard-hooks-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&instance_dir);
+        fs::create_dir_all(&instance_dir).unwrap();
+        let mut plan = LaunchPlan {
+            instance_dir: instance_dir.clone(),
+            wrapper: Vec::new(),
+            pre_launch: Some("echo pre > pre.txt".to_string()),
+            post_exit: Some("echo \"$SHARD_EXIT_CODE\" > post.txt".to_string()),
+            env: HashMap::new(),
+            java_exec: "sh".to_string(),
+            jvm_args: vec!["-c".to_string(), "test -f pre.txt && exit 4".to_string()],
+            classpath: String::new(),
+            main_class: String::new(),
+            game_args: Vec::new(),
+            game_options: Vec::new(),
+            create_game_options: false,
+            verification: VerifySummary::default(),
+            warnings: Vec::new(),
+            secrets: Vec::new(),
+        };
+
+        let run = run_with_output(&plan, &LaunchOptions::default(), |_| {}).unwrap();
+        assert_eq!(run.status.code(), Some(4));
+        let post = fs::read_to_string(instance_dir.join("post.txt")).unwrap();
+        assert_eq!(post.trim(), "4");
+
+        plan.pre_launch = Some("echo not ready >&2; exit 2".to_string());
+        let err = run_with_output(&plan, &LaunchOptions::default(), |_| {}).unwrap_err();
+        let failed = err.downcast_ref::<HookFailed>().unwrap();
+        assert_eq!(failed.code, Some(2));
+        assert_eq!(failed.stderr, "not ready");
+        assert!(err.to_string().contains("not ready"));
+        let _ = fs::remove_dir_all(&instance_dir);
+    }
+
+    #[test]
+    fn test_env_overrides_set_and_unset_variables() {
+        let plan = LaunchPlan {
+            instance_dir: std::env::temp_dir(),
+            wrapper: Vec::new(),
+            pre_launch: None,
+            post_exit: None,
+            env: HashMap::from([
+                ("__GL_THREADED_OPTIMIZATIONS".to_string(), "1".to_string()),
+                ("MESA_GL_VERSION_OVERRIDE".to_string(), String::new()),
+            ]),
+            java_exec: "java".to_string(),
+            jvm_args: Vec::new(),
+            classpath: String::new(),
+            main_class: String::new(),
+            game_args: Vec::new(),
+            game_options: Vec::new(),
+            create_game_options: false,
+            verification: VerifySummary::default(),
+            warnings: Vec::new(),
+            secrets: Vec::new(),
+        };
+        let cmd = launch_command(&plan, &LaunchOptions::default());
+        let envs: HashMap<_, _> = cmd.get_envs().collect();
+        assert_eq!(
+            envs.get(std::ffi::OsStr::new("__GL_THREADED_OPTIMIZATIONS")),
+            Some(&Some(std::ffi::OsStr::new("1")))
+        );
+        assert_eq!(envs.get(std::ffi::OsStr::new("MESA_GL_VERSION_OVERRIDE")), Some(&None));
+    }
+
+    #[test]
+    fn test_window_resolution_validates_and_warns() {
+        let mut warnings = Vec::new();
+        let runtime = Runtime::default();
+        assert_eq!(window_resolution(&runtime, None, &mut warnings).unwrap(), None);
+
+        let runtime = Runtime { width: Some(1280), ..Runtime::default() };
+        assert_eq!(window_resolution(&runtime, Some((1920, 1080)), &mut warnings).unwrap(), Some((1280, 480)));
+        assert!(warnings.is_empty());
+
+        let runtime = Runtime { width: Some(2560), height: Some(1440), ..Runtime::default() };
+        assert_eq!(window_resolution(&runtime, Some((1920, 1080)), &mut warnings).unwrap(), Some((2560, 1440)));
+        assert_eq!(warnings.len(), 1);
+
+        let runtime = Runtime { width: Some(0), height: Some(720), ..Runtime::default() };
+        assert!(window_resolution(&runtime, None, &mut warnings).is_err());
+    }
+
+    #[test]
+    fn test_resolution_support_follows_version_format() {
+        let modern: VersionJson = serde_json::from_value(serde_json::json!({
+            "id": "1.20.1",
+            "arguments": {
+                "game": [
+                    {"rules": [{"action": "allow", "features": {"has_custom_resolution": true}}],
+                     "value": ["--width", "${resolution_width}", "--height", "${resolution_height}"]}
+                ],
+                "jvm": []
+            }
+        }))
+        .unwrap();
+        assert!(declares_feature(&modern, "has_custom_resolution"));
+        assert!(accepts_named_game_args(&modern));
+
+        let legacy: VersionJson = serde_json::from_value(serde_json::json!({
+            "id": "1.12.2",
+            "minecraftArguments": "--username ${auth_player_name} --version ${version_name}"
+        }))
+        .unwrap();
+        assert!(!declares_feature(&legacy, "has_custom_resolution"));
+        assert!(accepts_named_game_args(&legacy));
+
+        let ancient: VersionJson = serde_json::from_value(serde_json::json!({
+            "id": "1.5.2",
+            "minecraftArguments": "${auth_player_name} ${auth_session} --gameDir ${game_directory}"
+        }))
+        .unwrap();
+        assert!(!accepts_named_game_args(&ancient));
+    }
+
+    #[test]
+    fn test_javaw_is_found_next_to_java() {
+        let dir = std::env::temp_dir().join(format!("shard-javaw-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let java = dir.join("java.exe");
+        fs::write(&java, b"").unwrap();
+        assert_eq!(windowless_java(&java.to_string_lossy()), None);
+
+        fs::write(dir.join("javaw.exe"), b"").unwrap();
+        assert_eq!(windowless_java(&java.to_string_lossy()), Some(dir.join("javaw.exe")));
+        assert_eq!(windowless_java(&dir.join("javaw.exe").to_string_lossy()), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_preview_shows_the_command_with_the_token_redacted() {
+        let plan = LaunchPlan {
+            instance_dir: PathBuf::from("/instances/demo"),
+            wrapper: vec!["gamemoderun".to_string()],
+            pre_launch: None,
+            post_exit: None,
+            env: HashMap::from([("MESA_GL_VERSION_OVERRIDE".to_string(), String::new())]),
+            java_exec: "/opt/java/bin/java".to_string(),
+            jvm_args: vec!["-Xmx4G".to_string()],
+            classpath: "a.jar:b.jar".to_string(),
+            main_class: "net.minecraft.client.main.Main".to_string(),
+            game_args: vec!["--accessToken".to_string(), "secret-token".to_string(), "--session".to_string(), "token:secret-token:uuid".to_string()],
+            game_options: Vec::new(),
+            create_game_options: false,
+            verification: VerifySummary::default(),
+            warnings: Vec::new(),
+            secrets: vec!["secret-token".to_string()],
+        };
+        let options = LaunchOptions { clear_java_env: true, show_console: true, ..LaunchOptions::default() };
+        let preview = plan.preview(&options);
+
+        assert_eq!(preview.program, "gamemoderun");
+        assert_eq!(
+            preview.args,
+            [
+                "/opt/java/bin/java", "-Xmx4G", "-cp", "a.jar:b.jar", "net.minecraft.client.main.Main",
+                "--accessToken", "<redacted>", "--session", "token:<redacted>:uuid",
+            ]
+        );
+        assert_eq!(preview.working_dir, PathBuf::from("/instances/demo"));
+        assert_eq!(preview.env.get("MESA_GL_VERSION_OVERRIDE"), Some(&None));
+        assert_eq!(preview.env.get("_JAVA_OPTIONS"), Some(&None));
+        assert!(!preview.command_line().contains("secret-token"));
+        assert!(preview.command_line().starts_with("gamemoderun /opt/java/bin/java -Xmx4G"));
+    }
+}
@@ -0,0 +1,425 @@
+//! Mod jar inspection and conflict detection
+//!
+//! Reads the loader metadata (`fabric.mod.json`, `quilt.mod.json` or
+//! `META-INF/mods.toml` / `neoforge.mods.toml`) from each mod in a profile to
+//! find the same mod installed twice and mods that declare each other
+//! incompatible. Jars without loader metadata (e.g. resource packs dropped in
+//! the mods folder) and unreadable jars are reported, never fatal.
+
+use crate::paths::Paths;
+use crate::profile::{Profile, remove_mod};
+use crate::store::{ContentKind, content_store_path};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use zip::ZipArchive;
+
+/// Mod pairs that crash together but do not declare it in their metadata.
+const KNOWN_INCOMPATIBLE: &[(&str, &str)] = &[
+    ("sodium", "optifabric"),
+    ("sodium", "optifine"),
+    ("iris", "optifabric"),
+    ("embeddium", "rubidium"),
+];
+
+/// Loader metadata read from a mod jar.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModMetadata {
+    pub id: String,
+    pub version: String,
+    pub name: Option<String>,
+    /// Mod ids this mod declares it cannot run alongside.
+    pub incompatible: Vec<String>,
+}
+
+/// A jar found in the profile or the instance `mods/` folder.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModJar {
+    /// Name of the profile entry, or `None` for a file placed in `mods/` by hand.
+    pub content_name: Option<String>,
+    pub file_name: String,
+    pub path: PathBuf,
+}
+
+/// One jar involved in a conflict.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConflictingJar {
+    pub jar: ModJar,
+    pub mod_id: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModConflictKind {
+    /// The same mod id is provided by more than one jar.
+    Duplicate,
+    /// Two mods that cannot be loaded together.
+    Incompatible,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModConflict {
+    pub kind: ModConflictKind,
+    pub message: String,
+    pub jars: Vec<ConflictingJar>,
+    /// For duplicates, the jar with the newest version. Removing the others resolves the conflict.
+    pub keep: Option<ModJar>,
+}
+
+/// Result of [`scan_mod_conflicts`].
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ModScan {
+    pub conflicts: Vec<ModConflict>,
+    /// Jars without loader metadata, such as resource packs in the mods folder.
+    pub not_mods: Vec<ModJar>,
+    /// Jars that could not be read, with the reason.
+    pub unreadable: Vec<(ModJar, String)>,
+}
+
+/// Inspect the enabled mods of a profile plus any extra jars in its instance `mods/` folder.
+pub fn scan_mod_conflicts(paths: &Paths, profile: &Profile) -> ModScan {
+    scan_jars(collect_jars(paths, profile))
+}
+
+/// Resolve a duplicate by removing every jar except `conflict.keep`.
+/// Profile entries are dropped from `profile` (the caller saves it); loose
+/// files in `mods/` are deleted. Returns the number of jars removed.
+pub fn keep_newest(profile: &mut Profile, conflict: &ModConflict) -> Result<usize> {
+    let Some(keep) = &conflict.keep else {
+        return Ok(0);
+    };
+    let mut removed = 0;
+    for entry in conflict.jars.iter().filter(|j| j.jar.path != keep.path) {
+        match &entry.jar.content_name {
+            Some(name) => {
+                if remove_mod(profile, name) {
+                    removed += 1;
+                }
+            }
+            None => {
+                fs::remove_file(&entry.jar.path)
+                    .with_context(|| format!("failed to remove {}", entry.jar.path.display()))?;
+                removed += 1;
+            }
+        }
+    }
+    Ok(removed)
+}
+
+fn collect_jars(paths: &Paths, profile: &Profile) -> Vec<ModJar> {
+    let mut jars = Vec::new();
+    let mut known_files = HashSet::new();
+    for content in profile.mods.iter().filter(|m| m.enabled) {
+        let file_name = content.file_name.clone().unwrap_or_else(|| format!("{}.jar", content.name));
+        known_files.insert(file_name.clone());
+        jars.push(ModJar {
+            content_name: Some(content.name.clone()),
+            file_name,
+            path: content_store_path(paths, ContentKind::Mod, &content.hash),
+        });
+    }
+
+    let mods_dir = crate::instance::game_dir(paths, profile).join("mods");
+    if let Ok(entries) = fs::read_dir(&mods_dir) {
+        let mut extra: Vec<_> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("jar")))
+            .filter_map(|p| {
+                let file_name = p.file_name()?.to_string_lossy().to_string();
+                (!known_files.contains(&file_name)).then_some(ModJar { content_name: None, file_name, path: p })
+            })
+            .collect();
+        extra.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+        jars.extend(extra);
+    }
+    jars
+}
+
+fn scan_jars(jars: Vec<ModJar>) -> ModScan {
+    let mut scan = ModScan::default();
+    let mut by_id: BTreeMap<String, Vec<(ModJar, ModMetadata)>> = BTreeMap::new();
+
+    for jar in jars {
+        match read_mod_metadata(&jar.path) {
+            Ok(Some(meta)) => by_id.entry(meta.id.clone()).or_default().push((jar, meta)),
+            Ok(None) => scan.not_mods.push(jar),
+            Err(err) => scan.unreadable.push((jar, format!("{err:#}"))),
+        }
+    }
+
+    for (id, entries) in by_id.iter().filter(|(_, entries)| entries.len() > 1) {
+        let keep = entries
+            .iter()
+            .max_by(|a, b| compare_versions(&a.1.version, &b.1.version))
+            .map(|(jar, _)| jar.clone());
+        let versions: Vec<_> = entries.iter().map(|(_, m)| m.version.as_str()).collect();
+        scan.conflicts.push(ModConflict {
+            kind: ModConflictKind::Duplicate,
+            message: format!("{id} is installed {} times ({})", entries.len(), versions.join(", ")),
+            jars: entries.iter().map(|(jar, meta)| conflicting(jar, meta)).collect(),
+            keep,
+        });
+    }
+
+    let mut reported = HashSet::new();
+    for (id, entries) in &by_id {
+        let (jar, meta) = &entries[0];
+        let declared = meta.incompatible.iter().map(String::as_str);
+        let known = KNOWN_INCOMPATIBLE.iter().filter_map(|(a, b)| {
+            if a == id {
+                Some(*b)
+            } else if b == id {
+                Some(*a)
+            } else {
+                None
+            }
+        });
+        for other_id in declared.chain(known) {
+            let Some((other_jar, other_meta)) = by_id.get(other_id).and_then(|e| e.first()) else {
+                continue;
+            };
+            let pair = if id.as_str() < other_id { (id.clone(), other_id.to_string()) } else { (other_id.to_string(), id.clone()) };
+            if !reported.insert(pair) {
+                continue;
+            }
+            scan.conflicts.push(ModConflict {
+                kind: ModConflictKind::Incompatible,
+                message: format!("{id} is incompatible with {other_id}"),
+                jars: vec![conflicting(jar, meta), conflicting(other_jar, other_meta)],
+                keep: None,
+            });
+        }
+    }
+
+    scan
+}
+
+fn conflicting(jar: &ModJar, meta: &ModMetadata) -> ConflictingJar {
+    ConflictingJar {
+        jar: jar.clone(),
+        mod_id: meta.id.clone(),
+        version: meta.version.clone(),
+    }
+}
+
+/// Read loader metadata from a jar. Returns `Ok(None)` for a valid zip that
+/// is not a mod and an error when the file cannot be read as a zip.
+pub fn read_mod_metadata(path: &Path) -> Result<Option<ModMetadata>> {
+    let file = fs::File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+    let mut zip = ZipArchive::new(file).with_context(|| format!("not a valid jar: {}", path.display()))?;
+
+    for name in ["fabric.mod.json", "quilt.mod.json"] {
+        if let Some(text) = read_entry(&mut zip, name) {
+            let json: Value = serde_json::from_str(&text).with_context(|| format!("invalid {name}"))?;
+            return Ok(if name == "fabric.mod.json" { parse_fabric(&json) } else { parse_quilt(&json) });
+        }
+    }
+    for name in ["META-INF/neoforge.mods.toml", "META-INF/mods.toml"] {
+        if let Some(text) = read_entry(&mut zip, name) {
+            let manifest_version = read_entry(&mut zip, "META-INF/MANIFEST.MF")
+                .and_then(|m| manifest_value(&m, "Implementation-Version"));
+            return Ok(parse_mods_toml(&text, manifest_version.as_deref()));
+        }
+    }
+    Ok(None)
+}
+
+fn read_entry<R: Read + std::io::Seek>(zip: &mut ZipArchive<R>, name: &str) -> Option<String> {
+    let mut entry = zip.by_name(name).ok()?;
+    let mut text = String::new();
+    entry.read_to_string(&mut text).ok()?;
+    Some(text)
+}
+
+fn parse_fabric(json: &Value) -> Option<ModMetadata> {
+    let str_field = |key: &str| json.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    let mut incompatible: Vec<String> = ["breaks", "conflicts"]
+        .iter()
+        .filter_map(|key| json.get(*key).and_then(|v| v.as_object()))
+        .flat_map(|map| map.keys().cloned())
+        .collect();
+    incompatible.sort();
+    incompatible.dedup();
+    Some(ModMetadata {
+        id: str_field("id")?,
+        version: str_field("version").unwrap_or_default(),
+        name: str_field("name"),
+        incompatible,
+    })
+}
+
+fn parse_quilt(json: &Value) -> Option<ModMetadata> {
+    let loader = json.get("quilt_loader")?;
+    let meta = loader.get("metadata");
+    let incompatible = loader
+        .get("breaks")
+        .and_then(|v| v.as_array())
+        .map(|list| {
+            list.iter()
+                .filter_map(|b| b.as_str().or_else(|| b.get("id").and_then(|id| id.as_str())))
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    Some(ModMetadata {
+        id: loader.get("id")?.as_str()?.to_string(),
+        version: loader.get("version").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+        name: meta.and_then(|m| m.get("name")).and_then(|v| v.as_str()).map(str::to_string),
+        incompatible,
+    })
+}
+
+/// Minimal reader for the first `[[mods]]` entry and its `incompatible` dependencies.
+fn parse_mods_toml(text: &str, manifest_version: Option<&str>) -> Option<ModMetadata> {
+    let mut section = String::new();
+    let mut id = None;
+    let mut version = None;
+    let mut name = None;
+    let mut dep_id: Option<String> = None;
+    let mut incompatible = Vec::new();
+    let mut in_first_mod = false;
+    let mut seen_mod = false;
+
+    let mut flush_dep = |dep_id: &mut Option<String>, is_incompatible: bool| {
+        if let (Some(dep), true) = (dep_id.take(), is_incompatible) {
+            incompatible.push(dep);
+        }
+    };
+    let mut dep_incompatible = false;
+
+    for raw in text.lines() {
+        let line = raw.split('#').next().unwrap_or("").trim();
+        if line.starts_with('[') {
+            flush_dep(&mut dep_id, dep_incompatible);
+            dep_incompatible = false;
+            section = line.trim_matches(|c| c == '[' || c == ']').trim().to_string();
+            in_first_mod = section == "mods" && !seen_mod;
+            seen_mod |= section == "mods";
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim();
+        let value = value.trim().trim_matches(|c| c == '"' || c == '\'').to_string();
+        if in_first_mod {
+            match key {
+                "modId" => id = Some(value),
+                "version" => version = Some(value),
+                "displayName" => name = Some(value),
+                _ => {}
+            }
+        } else if section.starts_with("dependencies") {
+            match key {
+                "modId" => dep_id = Some(value),
+                "type" => dep_incompatible = value.eq_ignore_ascii_case("incompatible"),
+                _ => {}
+            }
+        }
+    }
+    flush_dep(&mut dep_id, dep_incompatible);
+
+    let version = match version {
+        Some(v) if v.contains("${") => manifest_version.unwrap_or(&v).to_string(),
+        Some(v) => v,
+        None => manifest_version.unwrap_or_default().to_string(),
+    };
+    Some(ModMetadata { id: id?, version, name, incompatible })
+}
+
+fn manifest_value(manifest: &str, key: &str) -> Option<String> {
+    manifest.lines().find_map(|line| {
+        let (k, v) = line.split_once(':')?;
+        (k.trim() == key).then(|| v.trim().to_string())
+    })
+}
+
+/// Order mod versions, using semver when both parse and numeric segments otherwise.
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    if let (Ok(a), Ok(b)) = (semver::Version::parse(a), semver::Version::parse(b)) {
+        return a.cmp(&b);
+    }
+    let numbers = |s: &str| -> Vec<u64> {
+        s.split(|c: char| !c.is_ascii_digit())
+            .filter(|p| !p.is_empty())
+            .filter_map(|p| p.parse().ok())
+            .collect()
+    };
+    numbers(a).cmp(&numbers(b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn write_jar(dir: &Path, name: &str, entries: &[(&str, &str)]) -> ModJar {
+        let path = dir.join(name);
+        let mut zip = zip::ZipWriter::new(fs::File::create(&path).unwrap());
+        for (entry, body) in entries {
+            zip.start_file(*entry, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(body.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+        ModJar { content_name: Some(name.to_string()), file_name: name.to_string(), path }
+    }
+
+    #[test]
+    fn test_duplicates_keep_newest_and_bad_jars_are_reported() {
+        let dir = std::env::temp_dir().join(format!("shard-mod-scan-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let old = write_jar(&dir, "sodium-0.5.3.jar", &[("fabric.mod.json", r#"{"id":"sodium","version":"0.5.3"}"#)]);
+        let new = write_jar(&dir, "sodium-0.5.11.jar", &[("fabric.mod.json", r#"{"id":"sodium","version":"0.5.11"}"#)]);
+        let optifabric = write_jar(&dir, "optifabric.jar", &[("fabric.mod.json", r#"{"id":"optifabric","version":"1.0"}"#)]);
+        let pack = write_jar(&dir, "pack.jar", &[("pack.mcmeta", "{}")]);
+        let corrupt = dir.join("broken.jar");
+        fs::write(&corrupt, b"not a zip").unwrap();
+        let corrupt = ModJar { content_name: None, file_name: "broken.jar".to_string(), path: corrupt };
+
+        let scan = scan_jars(vec![old, new, optifabric, pack, corrupt]);
+        let duplicate = scan.conflicts.iter().find(|c| c.kind == ModConflictKind::Duplicate).unwrap();
+        assert_eq!(duplicate.jars.len(), 2);
+        assert_eq!(duplicate.keep.as_ref().unwrap().file_name, "sodium-0.5.11.jar");
+        assert!(scan.conflicts.iter().any(|c| c.kind == ModConflictKind::Incompatible));
+        assert_eq!(scan.not_mods.len(), 1);
+        assert_eq!(scan.unreadable.len(), 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_mods_toml_reads_first_mod_and_incompatibilities() {
+        let toml = r#"
+modLoader="javafml"
+[[mods]]
+modId="create"
+version="${file.jarVersion}"
+displayName="Create"
+[[mods]]
+modId="other"
+[[dependencies.create]]
+    modId="flywheel"
+    type="required"
+[[dependencies.create]]
+    modId="rubidium"
+    type="incompatible"
+"#;
+        let meta = parse_mods_toml(toml, Some("0.5.1.f")).unwrap();
+        assert_eq!(meta.id, "create");
+        assert_eq!(meta.version, "0.5.1.f");
+        assert_eq!(meta.name.as_deref(), Some("Create"));
+        assert_eq!(meta.incompatible, ["rubidium"]);
+    }
+
+    #[test]
+    fn test_versions_compare_numerically() {
+        assert_eq!(compare_versions("0.5.11", "0.5.3"), Ordering::Greater);
+        assert_eq!(compare_versions("1.20.1-47.2.0", "1.20.1-47.10.0"), Ordering::Less);
+    }
+}
@@ -1,9 +1,11 @@
+use crate::curseforge::{self, CurseForgeClient};
 use crate::paths::Paths;
 use crate::profile::{ContentRef, Loader, Profile, Runtime, create_profile, load_profile, save_profile, upsert_mod, upsert_resourcepack, upsert_shaderpack};
 use crate::store::{ContentKind, store_content, store_from_url};
 use anyhow::{Context, Result, bail};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sha1::{Sha1, Digest};
+use sha2::Sha512;
 use std::collections::HashMap;
 use std::fs;
 use std::io::{Read, Write};
@@ -37,7 +39,6 @@ struct ModrinthFile {
 }
 
 #[derive(Debug, Deserialize)]
-#[allow(dead_code)]
 struct ModrinthHashes {
     sha1: String,
     sha512: String,
@@ -50,7 +51,45 @@ struct ModrinthEnv {
     server: Option<String>,
 }
 
-pub fn import_mrpack(paths: &Paths, pack_path: &Path, profile_id: Option<&str>) -> Result<Profile> {
+/// How a pack file is treated on the client side (`env.client`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ClientEnv {
+    Required,
+    Optional,
+    Unsupported,
+}
+
+/// A pack file that could not be downloaded or verified.
+#[derive(Debug, Clone, Serialize)]
+pub struct FailedFile {
+    pub path: String,
+    /// Whether the pack marks the file as optional on the client.
+    pub optional: bool,
+    pub error: String,
+}
+
+/// Outcome of a modpack import. The profile is created even when some
+/// files fail; those are listed in `failed` so the caller can report them.
+#[derive(Debug, Clone, Serialize)]
+pub struct MrpackImport {
+    pub profile: Profile,
+    pub failed: Vec<FailedFile>,
+}
+
+impl MrpackImport {
+    /// Failed files the pack declares as required on the client.
+    pub fn failed_required(&self) -> impl Iterator<Item = &FailedFile> {
+        self.failed.iter().filter(|f| !f.optional)
+    }
+}
+
+/// Import a Modrinth `.mrpack` into a new profile.
+///
+/// The declared Minecraft and loader versions become the profile's version
+/// and loader, so Java selection at launch matches the pack. Files the client
+/// does not support are skipped; optional files are included. A file that
+/// fails to download is recorded in the result instead of aborting the import.
+pub fn import_mrpack(paths: &Paths, pack_path: &Path, profile_id: Option<&str>) -> Result<MrpackImport> {
     let file = fs::File::open(pack_path)
         .with_context(|| format!("failed to open modpack: {}", pack_path.display()))?;
     let mut zip = ZipArchive::new(file).context("failed to read modpack zip")?;
@@ -68,15 +107,28 @@ pub fn import_mrpack(paths: &Paths, pack_path: &Path, profile_id: Option<&str>)
     create_profile(paths, &profile_id, &mc_version, loader, Runtime::default())?;
 
     let overrides_dir = paths.profile_overrides(&profile_id);
-    extract_overrides(&mut zip, &overrides_dir)?;
+    // client-overrides are applied after overrides so they win on conflicts.
+    extract_overrides(&mut zip, &["overrides/", "client-overrides/"], &overrides_dir)?;
 
     let mut profile = load_profile(paths, &profile_id)?;
+    let mut failed = Vec::new();
     for file in &index.files {
-        if !is_client_allowed(&file.env) {
+        let env = client_env(&file.env);
+        if env == ClientEnv::Unsupported {
             continue;
         }
         let rel_path = sanitize_rel_path(&file.path)?;
-        let (download_path, download_url) = download_with_hash(paths, file)?;
+        let (download_path, download_url) = match download_with_hash(paths, file) {
+            Ok(found) => found,
+            Err(err) => {
+                failed.push(FailedFile {
+                    path: file.path.clone(),
+                    optional: env == ClientEnv::Optional,
+                    error: format!("{err:#}"),
+                });
+                continue;
+            }
+        };
 
         match content_kind_for_path(&file.path) {
             Some(kind) => {
@@ -103,12 +155,7 @@ pub fn import_mrpack(paths: &Paths, pack_path: &Path, profile_id: Option<&str>)
                     enabled: true,
                     pinned: false,
                 };
-                match kind {
-                    ContentKind::Mod => { upsert_mod(&mut profile, content_ref); }
-                    ContentKind::ResourcePack => { upsert_resourcepack(&mut profile, content_ref); }
-                    ContentKind::ShaderPack => { upsert_shaderpack(&mut profile, content_ref); }
-                    ContentKind::Skin => {}
-                }
+                add_content(&mut profile, kind, content_ref);
             }
             None => {
                 write_override_file(&overrides_dir, &rel_path, &download_path)?;
@@ -117,7 +164,16 @@ pub fn import_mrpack(paths: &Paths, pack_path: &Path, profile_id: Option<&str>)
     }
 
     save_profile(paths, &profile)?;
-    Ok(profile)
+    Ok(MrpackImport { profile, failed })
+}
+
+fn add_content(profile: &mut Profile, kind: ContentKind, content_ref: ContentRef) {
+    match kind {
+        ContentKind::Mod => { upsert_mod(profile, content_ref); }
+        ContentKind::ResourcePack => { upsert_resourcepack(profile, content_ref); }
+        ContentKind::ShaderPack => { upsert_shaderpack(profile, content_ref); }
+        ContentKind::Skin => {}
+    }
 }
 
 fn read_modrinth_index<R: Read + Seekable>(zip: &mut ZipArchive<R>) -> Result<ModrinthIndex> {
@@ -149,22 +205,24 @@ fn resolve_dependencies(deps: &HashMap<String, String>) -> Result<(String, Optio
         .cloned()
         .ok_or_else(|| anyhow::anyhow!("modpack missing minecraft dependency"))?;
 
-    if deps.contains_key("forge") || deps.contains_key("neoforge") {
-        bail!("Forge/NeoForge modpacks are not supported yet");
-    }
-
-    let loader = if let Some(version) = deps.get("fabric-loader") {
-        Some(Loader { loader_type: "fabric".to_string(), version: version.clone() })
-    } else if let Some(version) = deps.get("quilt-loader") {
-        Some(Loader { loader_type: "quilt".to_string(), version: version.clone() })
-    } else {
-        None
-    };
+    let loader = [
+        ("fabric-loader", "fabric"),
+        ("quilt-loader", "quilt"),
+        ("neoforge", "neoforge"),
+        ("forge", "forge"),
+    ]
+    .iter()
+    .find_map(|(key, loader_type)| {
+        deps.get(*key).map(|version| Loader {
+            loader_type: loader_type.to_string(),
+            version: version.clone(),
+        })
+    });
 
     Ok((mc_version, loader))
 }
 
-fn resolve_profile_id(paths: &Paths, name: &str, requested: Option<&str>) -> Result<String> {
+pub(crate) fn resolve_profile_id(paths: &Paths, name: &str, requested: Option<&str>) -> Result<String> {
     if let Some(id) = requested {
         let trimmed = id.trim();
         if trimmed.is_empty() {
@@ -214,14 +272,15 @@ fn content_kind_for_path(path: &str) -> Option<ContentKind> {
     }
 }
 
-fn is_client_allowed(env: &Option<ModrinthEnv>) -> bool {
-    match env.as_ref().and_then(|e| e.client.as_ref()) {
-        Some(flag) if flag == "unsupported" => false,
-        _ => true,
+fn client_env(env: &Option<ModrinthEnv>) -> ClientEnv {
+    match env.as_ref().and_then(|e| e.client.as_deref()) {
+        Some("unsupported") => ClientEnv::Unsupported,
+        Some("optional") => ClientEnv::Optional,
+        _ => ClientEnv::Required,
     }
 }
 
-fn sanitize_rel_path(path: &str) -> Result<PathBuf> {
+pub(crate) fn sanitize_rel_path(path: &str) -> Result<PathBuf> {
     let mut out = PathBuf::new();
     for comp in Path::new(path).components() {
         match comp {
@@ -242,10 +301,19 @@ fn download_with_hash(paths: &Paths, file: &ModrinthFile) -> Result<(PathBuf, St
     }
 
     let expected_sha1 = file.hashes.sha1.to_lowercase();
+    let expected_sha512 = file.hashes.sha512.to_lowercase();
+    let mut last_err = None;
     for url in &file.downloads {
-        let (download_path, _file_name) = store_from_url(paths, url)?;
+        let download_path = match store_from_url(paths, url) {
+            Ok((path, _file_name)) => path,
+            Err(err) => {
+                last_err = Some(err);
+                continue;
+            }
+        };
         let actual = sha1_file(&download_path)?;
-        if actual == expected_sha1 {
+        let sha512_ok = expected_sha512.is_empty() || sha512_file(&download_path)? == expected_sha512;
+        if actual == expected_sha1 && sha512_ok {
             if let Some(expected_size) = file.file_size {
                 let actual_size = fs::metadata(&download_path)?.len();
                 if actual_size != expected_size {
@@ -256,7 +324,10 @@ fn download_with_hash(paths: &Paths, file: &ModrinthFile) -> Result<(PathBuf, St
         }
     }
 
-    bail!("hash mismatch for {}", file.path)
+    match last_err {
+        Some(err) => Err(err.context(format!("failed to download {}", file.path))),
+        None => bail!("hash mismatch for {}", file.path),
+    }
 }
 
 fn sha1_file(path: &Path) -> Result<String> {
@@ -274,34 +345,41 @@ fn sha1_file(path: &Path) -> Result<String> {
     Ok(hex::encode(hasher.finalize()))
 }
 
-fn extract_overrides<R: Read + Seekable>(zip: &mut ZipArchive<R>, overrides_dir: &Path) -> Result<()> {
-    for i in 0..zip.len() {
-        let mut file = zip.by_index(i).context("failed to read zip entry")?;
-        if file.is_dir() {
-            continue;
-        }
-        let name = file.name().to_string();
-        let (prefix, rest) = if name.starts_with("overrides/") {
-            ("overrides/", &name["overrides/".len()..])
-        } else if name.starts_with("client-overrides/") {
-            ("client-overrides/", &name["client-overrides/".len()..])
-        } else {
-            continue;
-        };
-        let _ = prefix; // prefix reserved for clarity
-        if rest.is_empty() {
-            continue;
-        }
-        let rel = sanitize_rel_path(rest)?;
-        let target = overrides_dir.join(rel);
-        if let Some(parent) = target.parent() {
-            fs::create_dir_all(parent)?;
+fn sha512_file(path: &Path) -> Result<String> {
+    let mut file = fs::File::open(path)
+        .with_context(|| format!("failed to open file for hashing: {}", path.display()))?;
+    let mut hasher = Sha512::new();
+    std::io::copy(&mut file, &mut hasher).context("failed to read file")?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Extract the entries under each of `prefixes` into `overrides_dir`, in
+/// order, so a later prefix wins on conflicts.
+fn extract_overrides<R: Read + Seekable>(zip: &mut ZipArchive<R>, prefixes: &[&str], overrides_dir: &Path) -> Result<()> {
+    for prefix in prefixes {
+        for i in 0..zip.len() {
+            let mut file = zip.by_index(i).context("failed to read zip entry")?;
+            if file.is_dir() {
+                continue;
+            }
+            let name = file.name().to_string();
+            let Some(rest) = name.strip_prefix(prefix) else {
+                continue;
+            };
+            if rest.is_empty() {
+                continue;
+            }
+            let rel = sanitize_rel_path(rest)?;
+            let target = overrides_dir.join(rel);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut out = fs::File::create(&target)
+                .with_context(|| format!("failed to write override file: {}", target.display()))?;
+            std::io::copy(&mut file, &mut out)
+                .with_context(|| format!("failed to extract override file: {}", name))?;
+            out.flush().ok();
         }
-        let mut out = fs::File::create(&target)
-            .with_context(|| format!("failed to write override file: {}", target.display()))?;
-        std::io::copy(&mut file, &mut out)
-            .with_context(|| format!("failed to extract override file: {}", name))?;
-        out.flush().ok();
     }
     Ok(())
 }
@@ -320,6 +398,383 @@ fn write_override_file(overrides_dir: &Path, rel_path: &Path, src: &Path) -> Res
     Ok(())
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurseForgeManifest {
+    manifest_type: String,
+    manifest_version: u32,
+    name: String,
+    minecraft: CurseForgeMinecraft,
+    files: Vec<CurseForgeManifestFile>,
+    #[serde(default = "default_overrides")]
+    overrides: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CurseForgeMinecraft {
+    version: String,
+    #[serde(default)]
+    mod_loaders: Vec<CurseForgeModLoader>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CurseForgeModLoader {
+    /// `<loader>-<version>`, e.g. `forge-47.2.0`.
+    id: String,
+    #[serde(default)]
+    primary: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct CurseForgeManifestFile {
+    #[serde(rename = "projectID")]
+    project_id: u32,
+    #[serde(rename = "fileID")]
+    file_id: u32,
+    #[serde(default = "default_required")]
+    required: bool,
+}
+
+fn default_overrides() -> String {
+    "overrides".to_string()
+}
+
+fn default_required() -> bool {
+    true
+}
+
+/// A pack file whose author does not allow downloads from other launchers.
+/// It has to be downloaded from `url` by hand and added to the profile.
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockedFile {
+    pub project_id: u32,
+    pub file_id: u32,
+    /// Project name, e.g. "OptiFine".
+    pub name: String,
+    pub file_name: String,
+    /// Page on curseforge.com the file can be downloaded from.
+    pub url: String,
+    /// Whether the pack marks the file as required.
+    pub required: bool,
+}
+
+/// Outcome of a CurseForge modpack import. Like [`MrpackImport`], the profile
+/// is created even when some files could not be fetched.
+#[derive(Debug, Clone, Serialize)]
+pub struct CurseForgeImport {
+    pub profile: Profile,
+    pub failed: Vec<FailedFile>,
+    /// Files that need a manual download (see [`BlockedFile`]).
+    pub blocked: Vec<BlockedFile>,
+}
+
+/// Whether a pack zip is in the CurseForge format (`manifest.json`) rather
+/// than Modrinth's (`modrinth.index.json`).
+pub fn is_curseforge_pack(pack_path: &Path) -> Result<bool> {
+    let file = fs::File::open(pack_path)
+        .with_context(|| format!("failed to open modpack: {}", pack_path.display()))?;
+    let zip = ZipArchive::new(file).context("failed to read modpack zip")?;
+    let has = |name: &str| zip.file_names().any(|entry| entry == name);
+    Ok(has("manifest.json") && !has("modrinth.index.json"))
+}
+
+/// Import a CurseForge modpack `.zip` into a new profile.
+///
+/// The manifest's Minecraft version and primary loader become the profile's
+/// version and loader, and its overrides folder is extracted into the profile
+/// overrides. Each `projectID`/`fileID` is resolved through the CurseForge API,
+/// downloaded, checked against its SHA-1 and added as a mod, resource pack or
+/// shader pack depending on the project's class. Files the author only allows
+/// on curseforge.com are listed in `blocked`; other failures in `failed`.
+pub fn import_curseforge_zip(
+    paths: &Paths,
+    client: &CurseForgeClient,
+    pack_path: &Path,
+    profile_id: Option<&str>,
+) -> Result<CurseForgeImport> {
+    let file = fs::File::open(pack_path)
+        .with_context(|| format!("failed to open modpack: {}", pack_path.display()))?;
+    let mut zip = ZipArchive::new(file).context("failed to read modpack zip")?;
+
+    let manifest = read_curseforge_manifest(&mut zip)?;
+    let loader = curseforge_loader(&manifest.minecraft.mod_loaders)?;
+
+    let profile_id = resolve_profile_id(paths, &manifest.name, profile_id)?;
+    if paths.is_profile_present(&profile_id) {
+        bail!("profile already exists: {}", profile_id);
+    }
+
+    // Resolve everything before creating the profile, so a bad key or an
+    // outage does not leave a half-imported pack behind
+    let file_ids: Vec<u32> = manifest.files.iter().map(|f| f.file_id).collect();
+    let files: HashMap<u32, curseforge::File> = client
+        .get_files(&file_ids)
+        .context("failed to resolve modpack files on CurseForge")?
+        .into_iter()
+        .map(|file| (file.id, file))
+        .collect();
+    let project_ids: Vec<u32> = manifest.files.iter().map(|f| f.project_id).collect();
+    let projects: HashMap<u32, curseforge::Mod> = client
+        .get_mods(&project_ids)
+        .context("failed to resolve modpack projects on CurseForge")?
+        .into_iter()
+        .map(|project| (project.id, project))
+        .collect();
+
+    create_profile(paths, &profile_id, &manifest.minecraft.version, loader, Runtime::default())?;
+
+    let overrides_dir = paths.profile_overrides(&profile_id);
+    let overrides_prefix = format!("{}/", manifest.overrides.trim_matches('/'));
+    extract_overrides(&mut zip, &[overrides_prefix.as_str()], &overrides_dir)?;
+
+    let mut profile = load_profile(paths, &profile_id)?;
+    let mut failed = Vec::new();
+    let mut blocked = Vec::new();
+    for entry in &manifest.files {
+        let project = projects.get(&entry.project_id);
+        let label = project.map_or_else(|| format!("project {}", entry.project_id), |p| p.name.clone());
+        let fail = |error: String| FailedFile { path: label.clone(), optional: !entry.required, error };
+
+        let Some(file) = files.get(&entry.file_id) else {
+            failed.push(fail(format!("file {} not found on CurseForge", entry.file_id)));
+            continue;
+        };
+        let Some(kind) = curseforge_content_kind(project.and_then(|p| p.class_id)) else {
+            failed.push(fail("unsupported CurseForge project type".to_string()));
+            continue;
+        };
+        let Some(url) = file.download_url.as_deref() else {
+            blocked.push(BlockedFile {
+                project_id: entry.project_id,
+                file_id: entry.file_id,
+                name: label.clone(),
+                file_name: file.file_name.clone(),
+                url: manual_download_url(project, entry.project_id, entry.file_id),
+                required: entry.required,
+            });
+            continue;
+        };
+
+        let download_path = match download_curseforge_file(paths, file, url) {
+            Ok(path) => path,
+            Err(err) => {
+                failed.push(fail(format!("{err:#}")));
+                continue;
+            }
+        };
+        let stored = store_content(paths, kind, &download_path, Some(url.to_string()), Some(file.file_name.clone()))?;
+        let content_ref = ContentRef {
+            name: project.map_or(stored.name, |p| p.name.clone()),
+            hash: stored.hash,
+            version: Some(file.display_name.clone()),
+            source: stored.source,
+            file_name: Some(stored.file_name),
+            platform: Some("curseforge".to_string()),
+            project_id: Some(entry.project_id.to_string()),
+            version_id: Some(entry.file_id.to_string()),
+            enabled: true,
+            pinned: false,
+        };
+        add_content(&mut profile, kind, content_ref);
+    }
+
+    save_profile(paths, &profile)?;
+    Ok(CurseForgeImport { profile, failed, blocked })
+}
+
+fn read_curseforge_manifest<R: Read + Seekable>(zip: &mut ZipArchive<R>) -> Result<CurseForgeManifest> {
+    let mut manifest_file = zip
+        .by_name("manifest.json")
+        .context("manifest.json not found in modpack")?;
+    let mut data = String::new();
+    manifest_file
+        .read_to_string(&mut data)
+        .context("failed to read manifest.json")?;
+    parse_curseforge_manifest(&data)
+}
+
+fn parse_curseforge_manifest(data: &str) -> Result<CurseForgeManifest> {
+    let manifest: CurseForgeManifest = serde_json::from_str(data)
+        .context("failed to parse manifest.json")?;
+    if manifest.manifest_type != "minecraftModpack" {
+        bail!("unsupported modpack manifest type: {}", manifest.manifest_type);
+    }
+    if manifest.manifest_version != 1 {
+        bail!("unsupported modpack manifest version: {}", manifest.manifest_version);
+    }
+    Ok(manifest)
+}
+
+/// The profile loader for a manifest's `modLoaders` (the primary one, else the first).
+fn curseforge_loader(loaders: &[CurseForgeModLoader]) -> Result<Option<Loader>> {
+    let Some(loader) = loaders.iter().find(|l| l.primary).or_else(|| loaders.first()) else {
+        return Ok(None);
+    };
+    let (loader_type, version) = loader
+        .id
+        .split_once('-')
+        .with_context(|| format!("invalid mod loader in modpack: {}", loader.id))?;
+    match loader_type {
+        "forge" | "neoforge" | "fabric" | "quilt" => Ok(Some(Loader {
+            loader_type: loader_type.to_string(),
+            version: version.to_string(),
+        })),
+        other => bail!("unsupported mod loader in modpack: {}", other),
+    }
+}
+
+fn curseforge_content_kind(class_id: Option<u32>) -> Option<ContentKind> {
+    match class_id {
+        None | Some(curseforge::CLASS_MODS) => Some(ContentKind::Mod),
+        Some(curseforge::CLASS_RESOURCEPACKS) => Some(ContentKind::ResourcePack),
+        Some(curseforge::CLASS_SHADERS) => Some(ContentKind::ShaderPack),
+        Some(_) => None,
+    }
+}
+
+/// The curseforge.com page of a file, for downloading it by hand.
+fn manual_download_url(project: Option<&curseforge::Mod>, project_id: u32, file_id: u32) -> String {
+    match project.and_then(|p| p.links.website_url.as_deref()) {
+        Some(website) => format!("{}/files/{}", website.trim_end_matches('/'), file_id),
+        None => format!("https://www.curseforge.com/projects/{}", project_id),
+    }
+}
+
+fn download_curseforge_file(paths: &Paths, file: &curseforge::File, url: &str) -> Result<PathBuf> {
+    let (download_path, _file_name) = store_from_url(paths, url)
+        .with_context(|| format!("failed to download {}", file.file_name))?;
+    if let Some(expected) = curseforge::get_sha1_hash(file)
+        && sha1_file(&download_path)? != expected.to_lowercase()
+    {
+        bail!("hash mismatch for {}", file.file_name);
+    }
+    Ok(download_path)
+}
+
 // Trait alias workaround to keep ZipArchive generic bounds tidy
 trait Seekable: std::io::Seek {}
 impl<T: std::io::Seek> Seekable for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(client: &str) -> Option<ModrinthEnv> {
+        Some(ModrinthEnv { client: Some(client.to_string()), server: None })
+    }
+
+    /// Scratch directory removed when dropped, so a failed assertion does not leave it behind.
+    struct ScratchDir(PathBuf);
+
+    impl ScratchDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for ScratchDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_client_env_defaults_to_required() {
+        assert_eq!(client_env(&None), ClientEnv::Required);
+        assert_eq!(client_env(&env("required")), ClientEnv::Required);
+        assert_eq!(client_env(&env("optional")), ClientEnv::Optional);
+        assert_eq!(client_env(&env("unsupported")), ClientEnv::Unsupported);
+    }
+
+    #[test]
+    fn test_dependencies_record_loader_versions() {
+        let deps = HashMap::from([
+            ("minecraft".to_string(), "1.20.1".to_string()),
+            ("forge".to_string(), "47.2.0".to_string()),
+        ]);
+        let (mc, loader) = resolve_dependencies(&deps).unwrap();
+        assert_eq!(mc, "1.20.1");
+        let loader = loader.unwrap();
+        assert_eq!(loader.loader_type, "forge");
+        assert_eq!(loader.version, "47.2.0");
+
+        let deps = HashMap::from([("minecraft".to_string(), "1.21".to_string())]);
+        assert!(resolve_dependencies(&deps).unwrap().1.is_none());
+    }
+
+    #[test]
+    fn test_curseforge_manifest_records_versions() {
+        let manifest = parse_curseforge_manifest(r#"{
+            "minecraft": {
+                "version": "1.20.1",
+                "modLoaders": [{"id": "fabric-0.14.21", "primary": false}, {"id": "forge-47.2.0", "primary": true}]
+            },
+            "manifestType": "minecraftModpack",
+            "manifestVersion": 1,
+            "name": "All the Mods 9",
+            "version": "0.2.44",
+            "author": "ATMTeam",
+            "files": [
+                {"projectID": 238222, "fileID": 4712189, "required": true},
+                {"projectID": 250398, "fileID": 4653326}
+            ],
+            "overrides": "overrides"
+        }"#).unwrap();
+        assert_eq!(manifest.minecraft.version, "1.20.1");
+        assert_eq!(manifest.files.len(), 2);
+        assert!(manifest.files.iter().all(|f| f.required));
+        let loader = curseforge_loader(&manifest.minecraft.mod_loaders).unwrap().unwrap();
+        assert_eq!((loader.loader_type.as_str(), loader.version.as_str()), ("forge", "47.2.0"));
+
+        let no_loader = r#"{"minecraft": {"version": "1.21"}, "manifestType": "minecraftModpack", "manifestVersion": 1, "name": "Vanilla+", "files": []}"#;
+        let manifest = parse_curseforge_manifest(no_loader).unwrap();
+        assert!(curseforge_loader(&manifest.minecraft.mod_loaders).unwrap().is_none());
+        assert_eq!(manifest.overrides, "overrides");
+
+        let loaders = [CurseForgeModLoader { id: "liteloader-1.12.2".to_string(), primary: true }];
+        assert!(curseforge_loader(&loaders).is_err());
+    }
+
+    #[test]
+    fn test_blocked_files_link_to_the_file_page() {
+        let project: curseforge::Mod = serde_json::from_value(serde_json::json!({
+            "id": 238222, "gameId": 432, "name": "Just Enough Items (JEI)", "slug": "jei", "summary": "",
+            "links": {"websiteUrl": "https://www.curseforge.com/minecraft/mc-mods/jei"},
+            "status": 4, "downloadCount": 1, "isFeatured": false, "primaryCategoryId": 421, "classId": 6,
+            "mainFileId": 4712189, "dateCreated": "", "dateModified": "", "dateReleased": "",
+            "allowModDistribution": false, "gamePopularityRank": 1, "isAvailable": true, "thumbsUpCount": 0
+        })).unwrap();
+        assert_eq!(
+            manual_download_url(Some(&project), 238222, 4712189),
+            "https://www.curseforge.com/minecraft/mc-mods/jei/files/4712189"
+        );
+        assert_eq!(manual_download_url(None, 238222, 4712189), "https://www.curseforge.com/projects/238222");
+        assert!(matches!(curseforge_content_kind(project.class_id), Some(ContentKind::Mod)));
+        assert!(matches!(curseforge_content_kind(Some(curseforge::CLASS_SHADERS)), Some(ContentKind::ShaderPack)));
+        assert!(curseforge_content_kind(Some(17)).is_none());
+    }
+
+    #[test]
+    fn test_client_overrides_win_over_overrides() {
+        let scratch = ScratchDir::new("shard-mrpack-overrides");
+        let dir = &scratch.0;
+        let pack = dir.join("pack.zip");
+        {
+            let mut zip = zip::ZipWriter::new(fs::File::create(&pack).unwrap());
+            let opts = zip::write::SimpleFileOptions::default();
+            zip.start_file("client-overrides/config/a.txt", opts).unwrap();
+            zip.write_all(b"client").unwrap();
+            zip.start_file("overrides/config/a.txt", opts).unwrap();
+            zip.write_all(b"common").unwrap();
+            zip.finish().unwrap();
+        }
+        let mut archive = ZipArchive::new(fs::File::open(&pack).unwrap()).unwrap();
+        let out = dir.join("out");
+        extract_overrides(&mut archive, &["overrides/", "client-overrides/"], &out).unwrap();
+        assert_eq!(fs::read_to_string(out.join("config/a.txt")).unwrap(), "client");
+    }
+}
@@ -2,6 +2,7 @@ use anyhow::{Context, Result, bail};
 use reqwest::blocking::Client;
 use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
 
 const API_BASE: &str = "https://api.modrinth.com/v2";
 const USER_AGENT_VALUE: &str = "shard-launcher/1.0 (https://github.com/th0rgal/shard)";
@@ -167,23 +168,18 @@ pub struct ModrinthClient {
     client: Client,
 }
 
-impl Default for ModrinthClient {
-    fn default() -> Self {
-        Self::new()
-    }
-}
-
 impl ModrinthClient {
-    pub fn new() -> Self {
+    /// Fails when the configured proxy URL is malformed.
+    pub fn new() -> Result<Self> {
         let mut headers = HeaderMap::new();
         headers.insert(USER_AGENT, HeaderValue::from_static(USER_AGENT_VALUE));
 
-        let client = Client::builder()
+        let client = crate::http::client_builder()?
             .default_headers(headers)
             .build()
-            .expect("failed to build HTTP client");
+            .context("failed to build HTTP client")?;
 
-        Self { client }
+        Ok(Self { client })
     }
 
     /// Search for projects
@@ -363,6 +359,120 @@ impl ModrinthClient {
         Ok(())
     }
 
+    /// Identify files by hash. Returns the version each known hash belongs to.
+    pub fn get_versions_by_hashes(&self, hashes: &[&str], algorithm: &str) -> Result<HashMap<String, Version>> {
+        if hashes.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let url = format!("{}/version_files", API_BASE);
+        let body = serde_json::json!({ "hashes": hashes, "algorithm": algorithm });
+
+        let resp = self
+            .client
+            .post(&url)
+            .json(&body)
+            .send()
+            .context("failed to look up file hashes")?
+            .error_for_status()
+            .context("Modrinth request failed")?;
+
+        resp.json().context("failed to parse versions")
+    }
+
+    /// Find the latest version matching `game_version` and `loader` for each
+    /// project a hash belongs to, keyed by the hash.
+    pub fn get_latest_versions_by_hashes(
+        &self,
+        hashes: &[&str],
+        algorithm: &str,
+        game_version: &str,
+        loader: Option<&str>,
+    ) -> Result<HashMap<String, Version>> {
+        if hashes.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let url = format!("{}/version_files/update", API_BASE);
+        let mut body = serde_json::json!({
+            "hashes": hashes,
+            "algorithm": algorithm,
+            "game_versions": [game_version],
+        });
+        if let Some(loader) = loader {
+            body["loaders"] = serde_json::json!([loader]);
+        }
+
+        let resp = self
+            .client
+            .post(&url)
+            .json(&body)
+            .send()
+            .context("failed to check file hashes for updates")?
+            .error_for_status()
+            .context("Modrinth request failed")?;
+
+        resp.json().context("failed to parse versions")
+    }
+
+    /// Get multiple projects by IDs
+    pub fn get_projects(&self, ids: &[&str]) -> Result<Vec<Project>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids_json = serde_json::to_string(ids).context("failed to serialize project IDs")?;
+        let url = format!("{}/projects?ids={}", API_BASE, urlencoding::encode(&ids_json));
+
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .context("failed to fetch projects")?
+            .error_for_status()
+            .context("Modrinth request failed")?;
+
+        resp.json().context("failed to parse projects")
+    }
+
+    /// Plan installing the latest compatible version of a project together with
+    /// every required dependency.
+    pub fn resolve_dependencies(
+        &self,
+        project_id: &str,
+        game_version: &str,
+        loader: Option<&str>,
+    ) -> Result<InstallPlan> {
+        let root = self.get_latest_version(project_id, Some(game_version), loader)?;
+        self.resolve_version_dependencies(root, game_version, loader)
+    }
+
+    /// Plan installing a specific version together with every required dependency.
+    pub fn resolve_version_dependencies(
+        &self,
+        root: Version,
+        game_version: &str,
+        loader: Option<&str>,
+    ) -> Result<InstallPlan> {
+        let mut plan = plan_install(
+            root,
+            |project| self.get_latest_version(project, Some(game_version), loader),
+            |version_id| self.get_version(version_id),
+        )?;
+
+        // Titles are only cosmetic; keep the project ids if the lookup fails.
+        let ids: Vec<&str> = plan.entries().map(|e| e.project_id.as_str()).collect();
+        if let Ok(projects) = self.get_projects(&ids) {
+            let titles: HashMap<_, _> = projects.into_iter().map(|p| (p.id, p.title)).collect();
+            for entry in std::iter::once(&mut plan.root).chain(plan.dependencies.iter_mut()) {
+                if let Some(title) = titles.get(&entry.project_id) {
+                    entry.title = title.clone();
+                }
+            }
+        }
+        Ok(plan)
+    }
+
     /// Get categories (for browsing)
     pub fn get_categories(&self) -> Result<Vec<Category>> {
         let url = format!("{}/tag/category", API_BASE);
@@ -409,6 +519,162 @@ impl ModrinthClient {
     }
 }
 
+/// A version selected by [`ModrinthClient::resolve_dependencies`].
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedVersion {
+    pub project_id: String,
+    /// Project title, or the project id when it could not be looked up.
+    pub title: String,
+    pub version_id: String,
+    pub version_number: String,
+    /// Project whose dependency pulled this version in; `None` for the requested project.
+    pub required_by: Option<String>,
+}
+
+/// Two parts of the dependency graph that cannot be satisfied together.
+#[derive(Debug, Clone, Serialize)]
+pub struct DependencyConflict {
+    pub project_id: String,
+    pub message: String,
+}
+
+/// The requested version plus everything it needs, in install order.
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallPlan {
+    pub root: PlannedVersion,
+    pub dependencies: Vec<PlannedVersion>,
+    pub conflicts: Vec<DependencyConflict>,
+}
+
+impl InstallPlan {
+    /// The requested version followed by its dependencies.
+    pub fn entries(&self) -> impl Iterator<Item = &PlannedVersion> {
+        std::iter::once(&self.root).chain(self.dependencies.iter())
+    }
+
+    /// Drop dependencies whose project is already installed.
+    pub fn skip_installed<'a>(&mut self, installed: impl IntoIterator<Item = &'a str>) {
+        let installed: HashSet<&str> = installed.into_iter().collect();
+        self.dependencies.retain(|d| !installed.contains(d.project_id.as_str()));
+    }
+}
+
+/// Walk the required dependencies of `root` breadth-first.
+///
+/// Unpinned dependencies take the latest compatible version. A dependency pinned
+/// to a specific version replaces an unpinned pick, while two different pins
+/// on the same project, a missing dependency, or an `incompatible` declaration
+/// between selected projects are reported as conflicts.
+fn plan_install(
+    root: Version,
+    mut latest: impl FnMut(&str) -> Result<Version>,
+    mut by_id: impl FnMut(&str) -> Result<Version>,
+) -> Result<InstallPlan> {
+    struct Selected {
+        version: Version,
+        required_by: Option<String>,
+        pinned: bool,
+    }
+
+    let root_project = root.project_id.clone();
+    let mut selected: HashMap<String, Selected> = HashMap::new();
+    let mut order = vec![root_project.clone()];
+    let mut conflicts = Vec::new();
+    let mut queue = VecDeque::from([root_project.clone()]);
+    selected.insert(root_project.clone(), Selected { version: root, required_by: None, pinned: true });
+
+    while let Some(project) = queue.pop_front() {
+        let dependencies = selected[&project].version.dependencies.clone();
+        for dep in dependencies.iter().filter(|d| d.dependency_type == "required") {
+            let candidate = match (&dep.version_id, &dep.project_id) {
+                (Some(version_id), _) => {
+                    let existing = dep.project_id.as_ref().and_then(|p| selected.get(p));
+                    match existing {
+                        Some(existing) if &existing.version.id == version_id => continue,
+                        Some(existing) if existing.pinned => {
+                            conflicts.push(DependencyConflict {
+                                project_id: existing.version.project_id.clone(),
+                                message: format!(
+                                    "{} requires version {} of {}, but version {} is already required",
+                                    project, version_id, existing.version.project_id, existing.version.version_number
+                                ),
+                            });
+                            continue;
+                        }
+                        _ => by_id(version_id).map(|v| (v, true)),
+                    }
+                }
+                (None, Some(dep_project)) => {
+                    if selected.contains_key(dep_project) {
+                        continue;
+                    }
+                    latest(dep_project).map(|v| (v, false))
+                }
+                (None, None) => continue,
+            };
+
+            let (version, pinned) = match candidate {
+                Ok(found) => found,
+                Err(err) => {
+                    let dep_project = dep.project_id.clone().or_else(|| dep.version_id.clone()).unwrap_or_default();
+                    conflicts.push(DependencyConflict {
+                        message: format!("{} requires {}, but no compatible version was found: {err:#}", project, dep_project),
+                        project_id: dep_project,
+                    });
+                    continue;
+                }
+            };
+
+            let dep_project = version.project_id.clone();
+            match selected.get(&dep_project) {
+                Some(existing) if existing.version.id == version.id => continue,
+                Some(existing) if existing.pinned => {
+                    conflicts.push(DependencyConflict {
+                        project_id: dep_project.clone(),
+                        message: format!(
+                            "{} requires version {} of {}, but version {} is already required",
+                            project, version.version_number, dep_project, existing.version.version_number
+                        ),
+                    });
+                    continue;
+                }
+                Some(_) => {}
+                None => order.push(dep_project.clone()),
+            }
+            selected.insert(dep_project.clone(), Selected { version, required_by: Some(project.clone()), pinned });
+            queue.push_back(dep_project);
+        }
+    }
+
+    for project in &order {
+        for dep in selected[project].version.dependencies.iter().filter(|d| d.dependency_type == "incompatible") {
+            let clash = selected.values().find(|other| {
+                dep.project_id.as_deref() == Some(other.version.project_id.as_str())
+                    || dep.version_id.as_deref() == Some(other.version.id.as_str())
+            });
+            if let Some(other) = clash {
+                conflicts.push(DependencyConflict {
+                    project_id: other.version.project_id.clone(),
+                    message: format!("{} is incompatible with {}", project, other.version.project_id),
+                });
+            }
+        }
+    }
+
+    let mut entries = order.into_iter().map(|project| {
+        let entry = selected.remove(&project).expect("ordered project is selected");
+        PlannedVersion {
+            title: entry.version.project_id.clone(),
+            project_id: entry.version.project_id,
+            version_id: entry.version.id,
+            version_number: entry.version.version_number,
+            required_by: entry.required_by,
+        }
+    });
+    let root = entries.next().context("install plan has no root")?;
+    Ok(InstallPlan { root, dependencies: entries.collect(), conflicts })
+}
+
 /// Category tag
 #[derive(Debug, Clone, Deserialize)]
 pub struct Category {
@@ -436,3 +702,101 @@ pub struct Loader {
     pub supported_project_types: Vec<String>,
     pub icon: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(project: &str, id: &str, deps: &[(&str, Option<&str>, &str)]) -> Version {
+        Version {
+            id: id.to_string(),
+            project_id: project.to_string(),
+            name: id.to_string(),
+            version_number: id.to_string(),
+            changelog: String::new(),
+            date_published: String::new(),
+            downloads: 0,
+            version_type: "release".to_string(),
+            loaders: vec!["fabric".to_string()],
+            game_versions: vec!["1.21".to_string()],
+            files: Vec::new(),
+            dependencies: deps
+                .iter()
+                .map(|(project, version_id, kind)| Dependency {
+                    version_id: version_id.map(|v| v.to_string()),
+                    project_id: Some(project.to_string()),
+                    file_name: None,
+                    dependency_type: kind.to_string(),
+                })
+                .collect(),
+        }
+    }
+
+    fn catalog() -> Vec<Version> {
+        vec![
+            version("fabric-api", "api-0.100.1", &[]),
+            version("fabric-api", "api-0.90.0", &[]),
+            version("cloth", "cloth-15", &[("fabric-api", None, "required")]),
+            version("lib-a", "lib-a-1", &[("fabric-api", Some("api-0.90.0"), "required")]),
+            version("lib-b", "lib-b-1", &[("fabric-api", Some("api-0.100.1"), "required")]),
+            version("optifine", "of-1", &[]),
+        ]
+    }
+
+    fn plan(root: Version) -> InstallPlan {
+        let all = catalog();
+        plan_install(
+            root,
+            |project| all.iter().find(|v| v.project_id == project).cloned().context("not found"),
+            |id| all.iter().find(|v| v.id == id).cloned().context("not found"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_transitive_dependencies_are_planned_once() {
+        let root = version("sodium-extra", "se-1", &[
+            ("cloth", None, "required"),
+            ("fabric-api", None, "required"),
+            ("modmenu", None, "optional"),
+        ]);
+        let plan = plan(root);
+        let deps: Vec<_> = plan.dependencies.iter().map(|d| d.version_id.as_str()).collect();
+        assert_eq!(deps, ["cloth-15", "api-0.100.1"]);
+        assert_eq!(plan.dependencies[0].required_by.as_deref(), Some("sodium-extra"));
+        assert!(plan.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_pinned_dependency_replaces_unpinned_pick() {
+        let root = version("mod", "mod-1", &[("cloth", None, "required"), ("lib-a", None, "required")]);
+        let plan = plan(root);
+        let api = plan.dependencies.iter().find(|d| d.project_id == "fabric-api").unwrap();
+        assert_eq!(api.version_id, "api-0.90.0");
+        assert!(plan.conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_conflicting_pins_and_incompatibilities_are_reported() {
+        let root = version("mod", "mod-1", &[
+            ("lib-a", None, "required"),
+            ("lib-b", None, "required"),
+            ("optifine", None, "required"),
+            ("optifine", None, "incompatible"),
+        ]);
+        let plan = plan(root);
+        assert_eq!(plan.conflicts.len(), 2);
+        assert!(plan.conflicts.iter().any(|c| c.project_id == "fabric-api"));
+        assert!(plan.conflicts.iter().any(|c| c.project_id == "optifine"));
+    }
+
+    #[test]
+    fn test_missing_dependency_is_a_conflict_and_installed_are_skipped() {
+        let root = version("mod", "mod-1", &[("gone", None, "required"), ("fabric-api", None, "required")]);
+        let mut plan = plan(root);
+        assert_eq!(plan.conflicts.len(), 1);
+        assert_eq!(plan.conflicts[0].project_id, "gone");
+        plan.skip_installed(["fabric-api"]);
+        assert!(plan.dependencies.is_empty());
+    }
+}
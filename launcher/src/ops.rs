@@ -72,9 +72,6 @@ pub fn finish_device_code_flow(
     };
 
     let mut accounts = load_accounts(paths)?;
-    if accounts.active.is_none() {
-        accounts.active = Some(account.uuid.clone());
-    }
     upsert_account(&mut accounts, account.clone());
     save_accounts(paths, &accounts)?;
 
@@ -0,0 +1,178 @@
+//! Reading and updating the game's `options.txt`
+//!
+//! The game writes its settings as `key:value` lines. The launcher only ever
+//! changes the few keys it controls (window size, fullscreen); every other
+//! line, including ones that do not parse, is written back verbatim and in its
+//! original order, with the file's own line endings.
+
+use anyhow::{Context, Result};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+pub const FILE_NAME: &str = "options.txt";
+
+/// One line of the file. `key` is set for well-formed `key:value` lines.
+#[derive(Debug, Clone)]
+struct Line {
+    raw: String,
+    key: Option<String>,
+}
+
+impl Line {
+    fn parse(raw: &str) -> Self {
+        let key = raw
+            .split_once(':')
+            .map(|(key, _)| key)
+            .filter(|key| !key.is_empty() && !key.contains(char::is_whitespace))
+            .map(str::to_string);
+        Self { raw: raw.to_string(), key }
+    }
+
+    fn value(&self) -> Option<&str> {
+        self.key.as_ref().map(|key| &self.raw[key.len() + 1..])
+    }
+}
+
+/// A parsed `options.txt`.
+#[derive(Debug, Clone)]
+pub struct OptionsTxt {
+    lines: Vec<Line>,
+    newline: &'static str,
+    trailing_newline: bool,
+}
+
+impl Default for OptionsTxt {
+    fn default() -> Self {
+        Self { lines: Vec::new(), newline: "\n", trailing_newline: true }
+    }
+}
+
+impl OptionsTxt {
+    pub fn parse(data: &str) -> Self {
+        if data.is_empty() {
+            return Self::default();
+        }
+        let newline = if data.contains("\r\n") { "\r\n" } else { "\n" };
+        let trailing_newline = data.ends_with('\n');
+        let lines = data.lines().map(Line::parse).collect();
+        Self { lines, newline, trailing_newline }
+    }
+
+    /// Read the file at `path`; `None` if it does not exist yet.
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        match fs::read_to_string(path) {
+            Ok(data) => Ok(Some(Self::parse(&data))),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    /// The value of `key`; the last one wins if it is repeated, as in the game.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines
+            .iter()
+            .rev()
+            .find(|line| line.key.as_deref() == Some(key))
+            .and_then(Line::value)
+    }
+
+    /// Well-formed entries in file order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.lines
+            .iter()
+            .filter_map(|line| Some((line.key.as_deref()?, line.value()?)))
+    }
+
+    /// Set `key` wherever it appears, or append it if it does not.
+    pub fn set(&mut self, key: &str, value: &str) {
+        let mut found = false;
+        for line in self.lines.iter_mut().filter(|line| line.key.as_deref() == Some(key)) {
+            line.raw = format!("{key}:{value}");
+            found = true;
+        }
+        if !found {
+            self.lines.push(Line::parse(&format!("{key}:{value}")));
+        }
+    }
+
+    /// Write the file through a temporary file, so a crash never leaves it half written.
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let tmp_path = path.with_extension("txt.tmp");
+        fs::write(&tmp_path, self.to_string())
+            .with_context(|| format!("failed to write {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, path).with_context(|| format!("failed to write {}", path.display()))
+    }
+}
+
+impl fmt::Display for OptionsTxt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                f.write_str(self.newline)?;
+            }
+            f.write_str(&line.raw)?;
+        }
+        if self.trailing_newline && !self.lines.is_empty() {
+            f.write_str(self.newline)?;
+        }
+        Ok(())
+    }
+}
+
+/// Set `options` in the `options.txt` of `game_dir`, leaving every other line
+/// alone. A missing file is only created, holding just these keys, when
+/// `create` is set; otherwise it is left for the game to write with its defaults.
+pub fn update(game_dir: &Path, options: &[(&str, String)], create: bool) -> Result<()> {
+    let path = game_dir.join(FILE_NAME);
+    let mut file = match OptionsTxt::load(&path)? {
+        Some(file) => file,
+        None if create => OptionsTxt::default(),
+        None => return Ok(()),
+    };
+    for (key, value) in options {
+        file.set(key, value);
+    }
+    file.save(&path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_and_malformed_lines_are_kept() {
+        let data = "version:3465\r\nfullscreen:true\r\nnot an option\r\nresourcePacks:[\"vanilla\",\"file/a.zip\"]\r\nfullscreen:true";
+        let mut file = OptionsTxt::parse(data);
+        assert_eq!(file.get("resourcePacks"), Some("[\"vanilla\",\"file/a.zip\"]"));
+        assert_eq!(file.entries().count(), 4);
+
+        file.set("fullscreen", "false");
+        file.set("overrideWidth", "1280");
+        assert_eq!(
+            file.to_string(),
+            "version:3465\r\nfullscreen:false\r\nnot an option\r\nresourcePacks:[\"vanilla\",\"file/a.zip\"]\r\nfullscreen:false\r\noverrideWidth:1280"
+        );
+    }
+
+    #[test]
+    fn test_update_creates_only_when_asked() {
+        let dir = std::env::temp_dir().join(format!("shard-options-txt-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        update(&dir, &[("fullscreen", "false".to_string())], false).unwrap();
+        assert!(!dir.join(FILE_NAME).exists());
+        update(&dir, &[("fullscreen", "true".to_string())], true).unwrap();
+        assert_eq!(fs::read_to_string(dir.join(FILE_NAME)).unwrap(), "fullscreen:true\n");
+
+        fs::write(dir.join(FILE_NAME), "version:3465\nfullscreen:true\nfov:0.0\n").unwrap();
+        let options = [("fullscreen", "false".to_string()), ("overrideWidth", "1280".to_string())];
+        update(&dir, &options, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(dir.join(FILE_NAME)).unwrap(),
+            "version:3465\nfullscreen:false\nfov:0.0\noverrideWidth:1280\n"
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,6 +1,6 @@
 use anyhow::{Context, Result};
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone)]
 pub struct Paths {
@@ -37,6 +37,11 @@ impl Paths {
             base = cwd.join(base);
         }
 
+        Ok(Self::from_base(&base))
+    }
+
+    /// Build the directory layout rooted at `base` (normally `~/.shard` or `$SHARD_HOME`).
+    pub fn from_base(base: &Path) -> Self {
         let store_mods = base.join("store").join("mods").join("sha256");
         let store_resourcepacks = base.join("store").join("resourcepacks").join("sha256");
         let store_shaderpacks = base.join("store").join("shaderpacks").join("sha256");
@@ -59,7 +64,7 @@ impl Paths {
         let profile_organization = base.join("profile-organization.json");
         let java_runtimes = base.join("java");
 
-        Ok(Self {
+        Self {
             store_mods,
             store_resourcepacks,
             store_shaderpacks,
@@ -78,7 +83,7 @@ impl Paths {
             library_db,
             profile_organization,
             java_runtimes,
-        })
+        }
     }
 
     pub fn ensure(&self) -> Result<()> {
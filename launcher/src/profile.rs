@@ -2,6 +2,7 @@ use crate::paths::Paths;
 use crate::util::copy_dir_all;
 use anyhow::{Context, Result, bail};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -76,10 +77,102 @@ pub struct Runtime {
     pub java: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub memory: Option<String>,
-    #[serde(default)]
+    /// Extra JVM arguments, passed after the memory flags and before the main class.
+    #[serde(default, alias = "jvm_args")]
     pub args: Vec<String>,
+    /// Command the JVM is started under, e.g. `["gamemoderun"]` or `["prime-run"]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wrapper_command: Vec<String>,
+    /// Shell command run in the instance dir before the game starts; a non-zero exit aborts the launch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_launch: Option<String>,
+    /// Shell command run in the instance dir after the game exits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_exit: Option<String>,
+    /// Environment variables for the game process, e.g. `__GL_THREADED_OPTIMIZATIONS=1`.
+    /// Entries override the inherited environment; an empty value unsets the variable.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// Game window width in pixels; the game default (854) is used if only the height is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    /// Game window height in pixels; the game default (480) is used if only the width is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    /// Start in fullscreen, at `width`x`height` when those are set.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub fullscreen: bool,
+    /// Directory the game runs in (saves, options, mods) instead of the
+    /// instance folder, e.g. to share saves between instances. Relative paths
+    /// are taken from the instance folder.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_dir: Option<String>,
+    /// Garbage collector and tuning flags appended at launch; none leaves the JVM defaults.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gc_preset: Option<GcPreset>,
+}
+
+/// Named JVM garbage collector setups an instance can pick.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GcPreset {
+    /// G1 with the JVM's own tuning.
+    #[default]
+    G1,
+    /// ZGC, for large heaps; needs Java 15 or newer.
+    Zgc,
+    /// G1 with Aikar's tuning flags.
+    Aikar,
+}
+
+impl GcPreset {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GcPreset::G1 => "g1",
+            GcPreset::Zgc => "zgc",
+            GcPreset::Aikar => "aikar",
+        }
+    }
+
+    /// Oldest Java that ships the collector, when newer than any Minecraft needs.
+    pub fn min_java(self) -> Option<u32> {
+        match self {
+            GcPreset::Zgc => Some(15),
+            GcPreset::G1 | GcPreset::Aikar => None,
+        }
+    }
 }
 
+impl std::str::FromStr for GcPreset {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "g1" => Ok(GcPreset::G1),
+            "zgc" => Ok(GcPreset::Zgc),
+            "aikar" => Ok(GcPreset::Aikar),
+            other => bail!("unknown GC preset: {other} (expected g1, zgc or aikar)"),
+        }
+    }
+}
+
+/// JVM options that would replace the classpath or main class the launcher builds.
+const FORBIDDEN_JVM_ARGS: &[&str] = &["-cp", "-classpath", "--class-path", "-jar"];
+
+/// The forbidden option an argument uses, if any (`--class-path=x` counts as `--class-path`).
+pub fn forbidden_jvm_arg(arg: &str) -> Option<&'static str> {
+    FORBIDDEN_JVM_ARGS.iter().copied().find(|flag| {
+        arg == *flag || arg.strip_prefix(flag).is_some_and(|rest| rest.starts_with('='))
+    })
+}
+
+/// Reject instance JVM arguments that would break the launch command.
+pub fn validate_jvm_args(args: &[String]) -> Result<()> {
+    if let Some(flag) = args.iter().find_map(|arg| forbidden_jvm_arg(arg)) {
+        bail!("JVM argument {flag} is not allowed: the launcher sets the classpath and main class itself");
+    }
+    Ok(())
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Files {
@@ -151,6 +244,7 @@ pub fn create_profile(
     if paths.is_profile_present(id) {
         bail!("profile already exists: {id}");
     }
+    validate_jvm_args(&runtime.args)?;
     let profile = Profile {
         id: id.to_string(),
         mc_version: mc_version.to_string(),
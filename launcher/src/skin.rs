@@ -1,5 +1,5 @@
 use anyhow::{Context, Result, bail};
-use reqwest::blocking::{Client, Response};
+use reqwest::blocking::Response;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -80,7 +80,7 @@ impl std::str::FromStr for SkinVariant {
 
 /// Fetch the full Minecraft profile including skins and capes
 pub fn get_profile(access_token: &str) -> Result<MinecraftProfile> {
-    let client = Client::new();
+    let client = crate::http::client()?;
     let resp = client
         .get(MC_PROFILE_URL)
         .bearer_auth(access_token)
@@ -108,7 +108,7 @@ pub fn upload_skin(access_token: &str, skin_path: &Path, variant: SkinVariant) -
     let skin_data = fs::read(skin_path)
         .with_context(|| format!("failed to read skin file: {}", skin_path.display()))?;
 
-    let client = Client::new();
+    let client = crate::http::client()?;
     let form = reqwest::blocking::multipart::Form::new()
         .text("variant", variant.to_string())
         .part(
@@ -137,7 +137,7 @@ pub fn set_skin_url(access_token: &str, url: &str, variant: SkinVariant) -> Resu
         url: &'a str,
     }
 
-    let client = Client::new();
+    let client = crate::http::client()?;
     let body = SkinRequest {
         variant: match variant {
             SkinVariant::Classic => "classic",
@@ -158,7 +158,7 @@ pub fn set_skin_url(access_token: &str, url: &str, variant: SkinVariant) -> Resu
 
 /// Reset skin to default (Steve/Alex based on UUID)
 pub fn reset_skin(access_token: &str) -> Result<()> {
-    let client = Client::new();
+    let client = crate::http::client()?;
     let url = format!("{}/active", MC_SKINS_URL);
 
     let resp = client
@@ -178,7 +178,7 @@ pub fn set_cape(access_token: &str, cape_id: &str) -> Result<()> {
         cape_id: &'a str,
     }
 
-    let client = Client::new();
+    let client = crate::http::client()?;
     let body = CapeRequest { cape_id };
 
     let resp = client
@@ -193,7 +193,7 @@ pub fn set_cape(access_token: &str, cape_id: &str) -> Result<()> {
 
 /// Hide/remove the active cape
 pub fn hide_cape(access_token: &str) -> Result<()> {
-    let client = Client::new();
+    let client = crate::http::client()?;
 
     let resp = client
         .delete(MC_CAPES_ACTIVE_URL)
@@ -266,7 +266,7 @@ pub fn get_cape_url(uuid: &str) -> String {
 pub fn download_and_cache_skin(url: &str, store_path: &Path) -> Result<PathBuf> {
     use sha2::{Sha256, Digest};
 
-    let client = Client::new();
+    let client = crate::http::client()?;
 
     // Normalize URL (http -> https)
     let url = if let Some(stripped) = url.strip_prefix("http://") {
@@ -311,7 +311,7 @@ pub fn download_and_cache_skin(url: &str, store_path: &Path) -> Result<PathBuf>
 pub fn download_and_cache_cape(url: &str, store_path: &Path) -> Result<Option<PathBuf>> {
     use sha2::{Sha256, Digest};
 
-    let client = Client::new();
+    let client = crate::http::client()?;
 
     // Normalize URL (http -> https)
     let url = if let Some(stripped) = url.strip_prefix("http://") {
@@ -1,3 +1,4 @@
+use crate::http;
 use crate::paths::Paths;
 use crate::util::sanitize_filename;
 use anyhow::{Context, Result, bail};
@@ -101,6 +102,8 @@ pub fn store_content(
     })
 }
 
+/// Download `url` into the download cache, through the shared client and the
+/// process-wide download limits.
 pub fn store_from_url(paths: &Paths, url: &str) -> Result<(PathBuf, String)> {
     let parsed = Url::parse(url).context("invalid url")?;
     let file_name = parsed
@@ -118,14 +121,26 @@ pub fn store_from_url(paths: &Paths, url: &str) -> Result<(PathBuf, String)> {
         .cache_downloads
         .join(format!("{}-{}", timestamp, file_name));
 
-    let mut response = reqwest::blocking::get(parsed)?.error_for_status()?;
+    let client = http::download_client()?;
+    let _slot = http::download_slot();
+    let response = http::send_with_retry(client.get(parsed), http::DEFAULT_RETRY_ATTEMPTS)
+        .with_context(|| format!("failed to download: {url}"))?;
+    let mut response = http::check_status(response).with_context(|| format!("download failed: {url}"))?;
     let mut out = fs::File::create(&download_path).with_context(|| {
         format!(
             "failed to create download file: {}",
             download_path.display()
         )
     })?;
-    std::io::copy(&mut response, &mut out).context("failed to write download file")?;
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let read = response.read(&mut buf).map_err(|err| http::read_error(url, err))?;
+        if read == 0 {
+            break;
+        }
+        http::throttle_download(read);
+        out.write_all(&buf[..read]).context("failed to write download file")?;
+    }
     out.flush().context("failed to flush download file")?;
 
     Ok((download_path, file_name))
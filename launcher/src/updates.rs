@@ -5,11 +5,13 @@
 //! - Calculating storage usage statistics
 //! - Deduplication savings tracking
 
-use crate::content_store::{ContentStore, ContentType, Platform};
+use crate::content_store::{ContentStore, ContentType, ContentVersion, Platform, modrinth_content_version};
+use crate::modrinth::{ModrinthClient, Version as ModrinthVersion};
 use crate::paths::Paths;
 use crate::profile::{ContentRef, Profile, load_profile, save_profile, list_profiles};
 use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha512};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 
@@ -70,6 +72,18 @@ pub struct UpdateCheckResult {
     pub errors: Vec<String>,
 }
 
+/// An installed mod with a newer file for the profile's Minecraft version and loader
+#[derive(Debug, Clone, Serialize)]
+pub struct ModUpdate {
+    /// The installed mod
+    #[serde(rename = "mod")]
+    pub content: ContentRef,
+    /// Version number of the installed file, when the provider recognized it
+    pub current: Option<String>,
+    /// The newer version to install
+    pub latest: ContentVersion,
+}
+
 /// Calculate storage statistics
 pub fn get_storage_stats(paths: &Paths) -> Result<StorageStats> {
     let mut stats = StorageStats::default();
@@ -163,7 +177,7 @@ pub fn get_storage_stats(paths: &Paths) -> Result<StorageStats> {
 /// Check for updates for all content in all profiles
 pub fn check_all_updates(paths: &Paths, curseforge_api_key: Option<&str>) -> Result<UpdateCheckResult> {
     let mut result = UpdateCheckResult::default();
-    let store = ContentStore::new(curseforge_api_key);
+    let store = ContentStore::new(curseforge_api_key)?;
 
     let profile_ids = list_profiles(paths)?;
 
@@ -214,7 +228,7 @@ pub fn check_profile_updates(
     curseforge_api_key: Option<&str>,
 ) -> Result<UpdateCheckResult> {
     let mut result = UpdateCheckResult::default();
-    let store = ContentStore::new(curseforge_api_key);
+    let store = ContentStore::new(curseforge_api_key)?;
 
     let profile = load_profile(paths, profile_id)?;
 
@@ -252,8 +266,8 @@ fn check_content_updates(
     let loader = profile.loader.as_ref().map(|l| l.loader_type.as_str());
 
     for content in content_list {
-        // Skip pinned content
-        if content.pinned {
+        // Skip pinned and disabled content
+        if content.pinned || !content.enabled {
             result.skipped += 1;
             continue;
         }
@@ -315,6 +329,92 @@ fn check_content_updates(
     }
 }
 
+/// Check every mod in a profile for a newer file matching its Minecraft version and loader.
+///
+/// Mods are identified on Modrinth by the SHA-512 of the stored file, so files
+/// added by hand are recognized too. CurseForge mods that Modrinth does not
+/// know are checked by project id. A mod is only reported when the candidate
+/// is newer than the installed file, so a newer release that targets another
+/// Minecraft version never marks it outdated.
+pub fn check_mod_updates(
+    paths: &Paths,
+    profile: &Profile,
+    curseforge_api_key: Option<&str>,
+) -> Result<Vec<ModUpdate>> {
+    let loader = profile.loader.as_ref().map(|l| l.loader_type.as_str());
+    let candidates: Vec<(&ContentRef, String)> = profile
+        .mods
+        .iter()
+        .filter(|m| !m.pinned && m.enabled)
+        .filter_map(|m| {
+            let path = paths.store_mod_path(&normalize_hash(&m.hash));
+            sha512_file(&path).ok().map(|hash| (m, hash))
+        })
+        .collect();
+
+    let modrinth = ModrinthClient::new()?;
+    let hashes: Vec<&str> = candidates.iter().map(|(_, h)| h.as_str()).collect();
+    let current = modrinth.get_versions_by_hashes(&hashes, "sha512")?;
+    let latest = modrinth.get_latest_versions_by_hashes(&hashes, "sha512", &profile.mc_version, loader)?;
+
+    let store = ContentStore::new(curseforge_api_key)?;
+    let mut updates = Vec::new();
+    for (content, hash) in candidates {
+        if let Some(installed) = current.get(&hash) {
+            if let Some(newest) = latest.get(&hash).filter(|v| is_newer_modrinth(installed, v))
+                && let Some(version) = modrinth_content_version(newest.clone())
+            {
+                updates.push(ModUpdate {
+                    content: content.clone(),
+                    current: Some(installed.version_number.clone()),
+                    latest: version,
+                });
+            }
+            continue;
+        }
+
+        let is_curseforge = content.platform.as_deref().is_some_and(|p| p.eq_ignore_ascii_case("curseforge"));
+        let (Some(project_id), true) = (content.project_id.as_deref(), is_curseforge) else {
+            continue;
+        };
+        let Ok(newest) = store.get_latest_version(Platform::CurseForge, project_id, Some(&profile.mc_version), loader) else {
+            continue;
+        };
+        if is_newer_curseforge(content.version_id.as_deref(), &newest.id) {
+            updates.push(ModUpdate {
+                content: content.clone(),
+                current: content.version.clone(),
+                latest: newest,
+            });
+        }
+    }
+
+    Ok(updates)
+}
+
+/// Modrinth returns the newest compatible version even when the installed one is
+/// newer (e.g. built for a different Minecraft version), so compare publish dates.
+fn is_newer_modrinth(installed: &ModrinthVersion, candidate: &ModrinthVersion) -> bool {
+    candidate.id != installed.id && candidate.date_published > installed.date_published
+}
+
+/// CurseForge file ids increase with every upload.
+fn is_newer_curseforge(installed_id: Option<&str>, candidate_id: &str) -> bool {
+    match (installed_id.and_then(|id| id.parse::<u64>().ok()), candidate_id.parse::<u64>()) {
+        (Some(installed), Ok(candidate)) => candidate > installed,
+        (None, _) => true,
+        (Some(_), Err(_)) => false,
+    }
+}
+
+fn sha512_file(path: &std::path::Path) -> Result<String> {
+    let mut file = fs::File::open(path)
+        .with_context(|| format!("failed to open file for hashing: {}", path.display()))?;
+    let mut hasher = Sha512::new();
+    std::io::copy(&mut file, &mut hasher).context("failed to hash file")?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
 /// Apply a specific update to a profile
 pub fn apply_update(
     paths: &Paths,
@@ -324,7 +424,7 @@ pub fn apply_update(
     new_version_id: &str,
     curseforge_api_key: Option<&str>,
 ) -> Result<Profile> {
-    let store = ContentStore::new(curseforge_api_key);
+    let store = ContentStore::new(curseforge_api_key)?;
     let mut profile = load_profile(paths, profile_id)?;
 
     // Find the content to update
@@ -411,7 +511,8 @@ pub fn set_content_pinned(
     Ok(profile)
 }
 
-/// Set enabled state for a content item
+/// Set enabled state for a content item. Mods are renamed in the instance too
+/// (see [`crate::instance::set_mod_enabled`]).
 pub fn set_content_enabled(
     paths: &Paths,
     profile_id: &str,
@@ -419,6 +520,9 @@ pub fn set_content_enabled(
     content_type: &str,
     enabled: bool,
 ) -> Result<Profile> {
+    if content_type == "mod" {
+        return crate::instance::set_mod_enabled(paths, profile_id, content_name, enabled);
+    }
     let mut profile = load_profile(paths, profile_id)?;
 
     let content_list = match content_type {
@@ -461,3 +565,37 @@ fn dir_size(path: &std::path::Path) -> Result<u64> {
     }
     Ok(total)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(id: &str, published: &str) -> ModrinthVersion {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "project_id": "p",
+            "name": id,
+            "version_number": id,
+            "date_published": published,
+            "downloads": 0,
+            "version_type": "release",
+            "files": [],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_older_compatible_modrinth_version_is_not_an_update() {
+        let installed = version("b", "2024-06-01T00:00:00Z");
+        assert!(is_newer_modrinth(&installed, &version("c", "2024-07-01T00:00:00Z")));
+        assert!(!is_newer_modrinth(&installed, &version("a", "2024-01-01T00:00:00Z")));
+        assert!(!is_newer_modrinth(&installed, &installed.clone()));
+    }
+
+    #[test]
+    fn test_curseforge_file_ids_are_compared_numerically() {
+        assert!(is_newer_curseforge(Some("4500000"), "5100000"));
+        assert!(!is_newer_curseforge(Some("5100000"), "4500000"));
+        assert!(is_newer_curseforge(None, "4500000"));
+    }
+}
@@ -98,3 +98,22 @@ pub fn now_epoch_secs() -> u64 {
         .unwrap_or_default()
         .as_secs()
 }
+
+/// Resolve a program the way the shell would: a path is checked directly,
+/// a bare name is looked up in `PATH` (with `.exe` tried on Windows).
+pub fn find_executable(program: &str) -> Option<PathBuf> {
+    let direct = Path::new(program);
+    if direct.components().count() > 1 {
+        return direct.is_file().then(|| direct.to_path_buf());
+    }
+
+    let path_var = std::env::var_os("PATH")?;
+    std::env::split_paths(&path_var).find_map(|dir| {
+        let candidate = dir.join(program);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        let exe = candidate.with_extension("exe");
+        (cfg!(windows) && exe.is_file()).then_some(exe)
+    })
+}